@@ -4,12 +4,12 @@ use halo2_proofs::plonk::Circuit;
 use halo2_proofs::{halo2curves::bn256::Bn256, poly::kzg::commitment::ParamsKZG};
 use rand::rngs::OsRng;
 use snark_verifier_sdk::evm::{evm_verify, gen_evm_proof_shplonk, gen_evm_verifier_shplonk};
+#[cfg(feature = "halo2_lib")]
+use snark_verifier_sdk::halo2::aggregation::halo2_lib::AggregationCircuit;
+#[cfg(not(feature = "halo2_lib"))]
+use snark_verifier_sdk::halo2::aggregation::AggregationCircuit;
 use snark_verifier_sdk::halo2::gen_srs;
-use snark_verifier_sdk::{
-    gen_pk,
-    halo2::{aggregation::AggregationCircuit, gen_snark_shplonk},
-    Snark,
-};
+use snark_verifier_sdk::{gen_pk, halo2::gen_snark_shplonk, Snark};
 use snark_verifier_sdk::{CircuitExt, SHPLONK};
 use std::path::Path;
 
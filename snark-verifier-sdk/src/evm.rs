@@ -1,10 +1,13 @@
 use crate::{GWC, SHPLONK};
 
-use super::{CircuitExt, PlonkVerifier};
+use super::{AccumulatorEncodingKind, CircuitExt, PlonkSuccinctVerifier, PlonkVerifier};
 #[cfg(feature = "display")]
 use ark_std::{end_timer, start_timer};
 use halo2_proofs::{
-    halo2curves::bn256::{Bn256, Fq, Fr, G1Affine},
+    halo2curves::{
+        bn256::{Bn256, Fq, Fr, G1Affine},
+        ff::PrimeField,
+    },
     plonk::{create_proof, verify_proof, Circuit, ProvingKey, VerifyingKey},
     poly::{
         commitment::{ParamsProver, Prover, Verifier},
@@ -16,19 +19,31 @@ use halo2_proofs::{
         },
         VerificationStrategy,
     },
-    transcript::{TranscriptReadBuffer, TranscriptWriterBuffer},
+    transcript::TranscriptWrite,
 };
 use itertools::Itertools;
 use rand::{rngs::StdRng, SeedableRng};
-pub use snark_verifier::loader::evm::encode_calldata;
+pub use snark_verifier::loader::evm::{
+    encode_abi_calldata, encode_calldata, encode_calldata_unchecked,
+    encode_calldata_with_compression, encode_calldata_with_preimage, encode_ec_points,
+    PublicInputHash,
+};
 use snark_verifier::{
-    loader::evm::{compile_yul, deploy_and_call, EvmLoader},
+    cost::CostEstimation,
+    loader::{
+        evm::{
+            compile_yul, deploy_and_call, deploy_and_call_with_error_messages,
+            deploy_and_call_with_output, estimate_gas_cost, estimate_gas_cost_compressed,
+            EvmLoader, EvmLoaderConfig, GasCost,
+        },
+        native::NativeLoader,
+    },
     pcs::{
         kzg::{KzgAccumulator, KzgAsVerifyingKey, KzgDecidingKey, KzgSuccinctVerifyingKey},
-        AccumulationDecider, AccumulationScheme, PolynomialCommitmentScheme,
+        AccumulationDecider, AccumulationScheme, PolynomialCommitmentScheme, Query,
     },
     system::halo2::{compile, transcript::evm::EvmTranscript, Config},
-    verifier::SnarkVerifier,
+    verifier::{plonk::PlonkProtocol, SnarkVerifier},
 };
 use std::{fs, io, path::Path, rc::Rc};
 
@@ -49,38 +64,178 @@ where
         MSMAccumulator = DualMSM<'params, Bn256>,
     >,
 {
+    gen_evm_proof_with_compression::<C, P, V>(params, pk, circuit, instances, false)
+}
+
+/// Same as [`gen_evm_proof`], but encodes G1 points in proof as compressed if
+/// `compressed` is `true`, which should be verified by verifier generated by
+/// [`gen_evm_verifier_with_compression`] with same `compressed`.
+pub fn gen_evm_proof_with_compression<'params, C, P, V>(
+    params: &'params ParamsKZG<Bn256>,
+    pk: &'params ProvingKey<G1Affine>,
+    circuit: C,
+    instances: Vec<Vec<Fr>>,
+    compressed: bool,
+) -> Vec<u8>
+where
+    C: Circuit<Fr>,
+    P: Prover<'params, KZGCommitmentScheme<Bn256>>,
+    V: Verifier<
+        'params,
+        KZGCommitmentScheme<Bn256>,
+        Guard = GuardKZG<'params, Bn256>,
+        MSMAccumulator = DualMSM<'params, Bn256>,
+    >,
+{
+    gen_evm_proof_batch_with_compression::<C, P, V>(
+        params,
+        pk,
+        vec![circuit],
+        vec![instances],
+        compressed,
+    )
+}
+
+/// Generates a proof of several instances of the same circuit batched
+/// together for evm verification, which should be verified by verifier
+/// generated with [`EvmVerifierConfig::num_proof`] set to `circuits.len()`
+/// and calldata of concatenated instances.
+pub fn gen_evm_proof_batch<'params, C, P, V>(
+    params: &'params ParamsKZG<Bn256>,
+    pk: &'params ProvingKey<G1Affine>,
+    circuits: Vec<C>,
+    instances: Vec<Vec<Vec<Fr>>>,
+) -> Vec<u8>
+where
+    C: Circuit<Fr>,
+    P: Prover<'params, KZGCommitmentScheme<Bn256>>,
+    V: Verifier<
+        'params,
+        KZGCommitmentScheme<Bn256>,
+        Guard = GuardKZG<'params, Bn256>,
+        MSMAccumulator = DualMSM<'params, Bn256>,
+    >,
+{
+    gen_evm_proof_batch_with_compression::<C, P, V>(params, pk, circuits, instances, false)
+}
+
+/// Same as [`gen_evm_proof_batch`], but encodes G1 points in proof as
+/// compressed if `compressed` is `true`.
+pub fn gen_evm_proof_batch_with_compression<'params, C, P, V>(
+    params: &'params ParamsKZG<Bn256>,
+    pk: &'params ProvingKey<G1Affine>,
+    circuits: Vec<C>,
+    instances: Vec<Vec<Vec<Fr>>>,
+    compressed: bool,
+) -> Vec<u8>
+where
+    C: Circuit<Fr>,
+    P: Prover<'params, KZGCommitmentScheme<Bn256>>,
+    V: Verifier<
+        'params,
+        KZGCommitmentScheme<Bn256>,
+        Guard = GuardKZG<'params, Bn256>,
+        MSMAccumulator = DualMSM<'params, Bn256>,
+    >,
+{
+    gen_evm_proof_batch_with_suffix::<C, P, V>(params, pk, circuits, instances, &[], compressed)
+}
+
+/// Same as [`gen_evm_proof_batch_with_compression`], but takes instances
+/// from `circuits`, and writes their [`CircuitExt::proof_suffix`] to the
+/// transcript after the proof, e.g. limbs of accumulator for
+/// [`AccumulatorEncodingKind::Hashed`].
+pub fn gen_evm_proof_batch_with_proof_suffix<'params, C, P, V>(
+    params: &'params ParamsKZG<Bn256>,
+    pk: &'params ProvingKey<G1Affine>,
+    circuits: Vec<C>,
+    compressed: bool,
+) -> Vec<u8>
+where
+    C: CircuitExt<Fr>,
+    P: Prover<'params, KZGCommitmentScheme<Bn256>>,
+    V: Verifier<
+        'params,
+        KZGCommitmentScheme<Bn256>,
+        Guard = GuardKZG<'params, Bn256>,
+        MSMAccumulator = DualMSM<'params, Bn256>,
+    >,
+{
+    let instances = circuits
+        .iter()
+        .map(|circuit| circuit.instances())
+        .collect_vec();
+    // Suffix of each proof is read in order after the proof.
+    let suffix = circuits
+        .iter()
+        .flat_map(|circuit| circuit.proof_suffix())
+        .collect_vec();
+    gen_evm_proof_batch_with_suffix::<C, P, V>(params, pk, circuits, instances, &suffix, compressed)
+}
+
+fn gen_evm_proof_batch_with_suffix<'params, C, P, V>(
+    params: &'params ParamsKZG<Bn256>,
+    pk: &'params ProvingKey<G1Affine>,
+    circuits: Vec<C>,
+    instances: Vec<Vec<Vec<Fr>>>,
+    suffix: &[Fr],
+    compressed: bool,
+) -> Vec<u8>
+where
+    C: Circuit<Fr>,
+    P: Prover<'params, KZGCommitmentScheme<Bn256>>,
+    V: Verifier<
+        'params,
+        KZGCommitmentScheme<Bn256>,
+        Guard = GuardKZG<'params, Bn256>,
+        MSMAccumulator = DualMSM<'params, Bn256>,
+    >,
+{
+    assert!(!circuits.is_empty());
+    assert_eq!(circuits.len(), instances.len());
+
     let instances = instances
         .iter()
-        .map(|instances| instances.as_slice())
+        .map(|instances| instances.iter().map(Vec::as_slice).collect_vec())
         .collect_vec();
+    let instances = instances.iter().map(Vec::as_slice).collect_vec();
 
     #[cfg(feature = "display")]
     let proof_time = start_timer!(|| "Create EVM proof");
     let rng = StdRng::from_entropy();
     let proof = {
-        let mut transcript = TranscriptWriterBuffer::<_, G1Affine, _>::init(Vec::new());
+        let mut transcript = EvmTranscript::<G1Affine, NativeLoader, _, _>::new_with_compression(
+            Vec::new(),
+            compressed,
+        );
         create_proof::<KZGCommitmentScheme<Bn256>, P, _, _, EvmTranscript<_, _, _, _>, _>(
             params,
             pk,
-            &[circuit],
-            &[instances.as_slice()],
+            &circuits,
+            &instances,
             rng,
             &mut transcript,
         )
         .unwrap();
+        for scalar in suffix {
+            transcript.write_scalar(*scalar).unwrap();
+        }
         transcript.finalize()
     };
     #[cfg(feature = "display")]
     end_timer!(proof_time);
 
     let accept = {
-        let mut transcript = TranscriptReadBuffer::<_, G1Affine, _>::init(proof.as_slice());
+        let mut transcript = EvmTranscript::<G1Affine, NativeLoader, _, _>::new_with_compression(
+            proof.as_slice(),
+            compressed,
+        );
         VerificationStrategy::<_, V>::finalize(
             verify_proof::<_, V, _, EvmTranscript<_, _, _, _>, _>(
                 params.verifier_params(),
                 pk.get_vk(),
                 AccumulatorStrategy::new(params.verifier_params()),
-                &[instances.as_slice()],
+                &instances,
                 &mut transcript,
             )
             .unwrap(),
@@ -129,25 +284,461 @@ where
             Accumulator = KzgAccumulator<G1Affine, Rc<EvmLoader>>,
         > + AccumulationDecider<G1Affine, Rc<EvmLoader>, DecidingKey = KzgDecidingKey<Bn256>>,
 {
+    gen_evm_verifier_with_compression::<C, AS>(params, vk, num_instance, path, false)
+}
+
+/// Same as [`gen_evm_verifier`], but reads G1 points in proof as compressed if
+/// `compressed` is `true`. See [`evm_gas_cost_with_compression`] for whether
+/// it's worth it.
+pub fn gen_evm_verifier_with_compression<C, AS>(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    num_instance: Vec<usize>,
+    path: Option<&Path>,
+    compressed: bool,
+) -> Vec<u8>
+where
+    C: CircuitExt<Fr>,
+    AS: PolynomialCommitmentScheme<
+            G1Affine,
+            Rc<EvmLoader>,
+            VerifyingKey = KzgSuccinctVerifyingKey<G1Affine>,
+            Output = KzgAccumulator<G1Affine, Rc<EvmLoader>>,
+        > + AccumulationScheme<
+            G1Affine,
+            Rc<EvmLoader>,
+            VerifyingKey = KzgAsVerifyingKey,
+            Accumulator = KzgAccumulator<G1Affine, Rc<EvmLoader>>,
+        > + AccumulationDecider<G1Affine, Rc<EvmLoader>, DecidingKey = KzgDecidingKey<Bn256>>,
+{
+    gen_evm_verifier_with_config::<C, AS>(
+        params,
+        vk,
+        num_instance,
+        EvmVerifierConfig {
+            compressed,
+            ..Default::default()
+        },
+        path,
+    )
+}
+
+/// Same as [`gen_evm_verifier`], but the last instance is expected to be
+/// digest of public inputs hashed by `hash` (see [`PublicInputHash::digest`]),
+/// and verifier takes the `preimage_len` bytes preimage in calldata instead of
+/// the digest. Calldata should be encoded by [`encode_calldata_with_preimage`].
+pub fn gen_evm_verifier_with_public_input_hash<C, AS>(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    num_instance: Vec<usize>,
+    preimage_len: usize,
+    hash: PublicInputHash,
+    path: Option<&Path>,
+) -> Vec<u8>
+where
+    C: CircuitExt<Fr>,
+    AS: PolynomialCommitmentScheme<
+            G1Affine,
+            Rc<EvmLoader>,
+            VerifyingKey = KzgSuccinctVerifyingKey<G1Affine>,
+            Output = KzgAccumulator<G1Affine, Rc<EvmLoader>>,
+        > + AccumulationScheme<
+            G1Affine,
+            Rc<EvmLoader>,
+            VerifyingKey = KzgAsVerifyingKey,
+            Accumulator = KzgAccumulator<G1Affine, Rc<EvmLoader>>,
+        > + AccumulationDecider<G1Affine, Rc<EvmLoader>, DecidingKey = KzgDecidingKey<Bn256>>,
+{
+    gen_evm_verifier_with_config::<C, AS>(
+        params,
+        vk,
+        num_instance,
+        EvmVerifierConfig {
+            public_input_hash: Some((preimage_len, hash)),
+            ..Default::default()
+        },
+        path,
+    )
+}
+
+/// Options of verifier generated by [`gen_evm_verifier_with_config`].
+#[derive(Clone, Copy, Debug)]
+pub struct EvmVerifierConfig {
+    /// Whether G1 points in proof are compressed, see
+    /// [`gen_evm_verifier_with_compression`].
+    pub compressed: bool,
+    /// Length of preimage and hash function if the last instance is digest of
+    /// public inputs, see [`gen_evm_verifier_with_public_input_hash`]. It's
+    /// not supported with [`EvmVerifierConfig::abi`].
+    pub public_input_hash: Option<(usize, PublicInputHash)>,
+    /// Whether verifier exposes `verify(uint256[],bytes)` returning `bool` (see
+    /// [`VERIFIER_ABI`]) instead of taking calldata encoded by
+    /// [`encode_calldata`] and reverting on invalid proof.
+    pub abi: bool,
+    /// Whether verifier reverts at the first failing check with an error code,
+    /// see [`gen_evm_verifier_with_error_messages`]. It costs more gas so is
+    /// only meant for debugging.
+    pub debug: bool,
+    /// Encoding of accumulators in instances given by
+    /// [`CircuitExt::accumulator_indices`], which should match the one the
+    /// circuit exposes them with. Limbs of [`AccumulatorEncodingKind::Hashed`]
+    /// are expected to be written after the proof by
+    /// [`gen_evm_proof_batch_with_proof_suffix`].
+    pub accumulator_encoding: AccumulatorEncodingKind,
+    /// Number of proofs of the same circuit batched in a proof (see
+    /// [`gen_evm_proof_batch`]), where `num_instance` is of a single circuit
+    /// and instances of each proof are concatenated in calldata (also in
+    /// `instances` of [`EvmVerifierConfig::abi`]). Batching is not supported
+    /// with [`EvmVerifierConfig::public_input_hash`].
+    pub num_proof: usize,
+}
+
+impl Default for EvmVerifierConfig {
+    fn default() -> Self {
+        Self {
+            compressed: false,
+            public_input_hash: None,
+            abi: false,
+            debug: false,
+            accumulator_encoding: Default::default(),
+            num_proof: 1,
+        }
+    }
+}
+
+impl EvmVerifierConfig {
+    fn loader(&self) -> Rc<EvmLoader> {
+        EvmLoader::new_with_config::<Fq, Fr>(EvmLoaderConfig {
+            abi: self.abi,
+            debug: self.debug,
+            ..Default::default()
+        })
+    }
+}
+
+/// Generates verifier with given [`EvmVerifierConfig`].
+pub fn gen_evm_verifier_with_config<C, AS>(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    num_instance: Vec<usize>,
+    config: EvmVerifierConfig,
+    path: Option<&Path>,
+) -> Vec<u8>
+where
+    C: CircuitExt<Fr>,
+    AS: PolynomialCommitmentScheme<
+            G1Affine,
+            Rc<EvmLoader>,
+            VerifyingKey = KzgSuccinctVerifyingKey<G1Affine>,
+            Output = KzgAccumulator<G1Affine, Rc<EvmLoader>>,
+        > + AccumulationScheme<
+            G1Affine,
+            Rc<EvmLoader>,
+            VerifyingKey = KzgAsVerifyingKey,
+            Accumulator = KzgAccumulator<G1Affine, Rc<EvmLoader>>,
+        > + AccumulationDecider<G1Affine, Rc<EvmLoader>, DecidingKey = KzgDecidingKey<Bn256>>,
+{
+    gen_evm_verifier_with_error_messages::<C, AS>(params, vk, num_instance, config, path).0
+}
+
+/// Generates verifier with given [`EvmVerifierConfig`], and returns it with
+/// error messages of its checks.
+///
+/// If [`EvmVerifierConfig::debug`] is enabled, verifier reverts with the
+/// index of message of the first failing check (e.g. which instance or
+/// commitment in proof is invalid), which could be decoded by
+/// [`evm_verify_with_error_messages`]. Otherwise the error messages are empty.
+pub fn gen_evm_verifier_with_error_messages<C, AS>(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    num_instance: Vec<usize>,
+    config: EvmVerifierConfig,
+    path: Option<&Path>,
+) -> (Vec<u8>, Vec<String>)
+where
+    C: CircuitExt<Fr>,
+    AS: PolynomialCommitmentScheme<
+            G1Affine,
+            Rc<EvmLoader>,
+            VerifyingKey = KzgSuccinctVerifyingKey<G1Affine>,
+            Output = KzgAccumulator<G1Affine, Rc<EvmLoader>>,
+        > + AccumulationScheme<
+            G1Affine,
+            Rc<EvmLoader>,
+            VerifyingKey = KzgAsVerifyingKey,
+            Accumulator = KzgAccumulator<G1Affine, Rc<EvmLoader>>,
+        > + AccumulationDecider<G1Affine, Rc<EvmLoader>, DecidingKey = KzgDecidingKey<Bn256>>,
+{
+    assert!(
+        config.num_proof == 1 || config.public_input_hash.is_none(),
+        "Batched proofs with public input hash is not supported"
+    );
+    assert!(
+        !config.abi || config.public_input_hash.is_none(),
+        "Verifier with ABI and public input hash is not supported"
+    );
     let protocol = compile(
         params,
         vk,
         Config::kzg()
             .with_num_instance(num_instance.clone())
+            .with_num_proof(config.num_proof)
             .with_accumulator_indices(C::accumulator_indices()),
     );
     // deciding key
     let dk = (params.get_g()[0], params.g2(), params.s_g2()).into();
 
-    let loader = EvmLoader::new::<Fq, Fr>();
+    let loader = config.loader();
     let protocol = protocol.loaded(&loader);
-    let mut transcript = EvmTranscript::<_, Rc<EvmLoader>, _, _>::new(&loader);
+    let mut transcript =
+        EvmTranscript::<_, Rc<EvmLoader>, _, _>::new_with_compression(&loader, config.compressed);
+
+    let instances = match config.public_input_hash {
+        Some((preimage_len, hash)) => {
+            transcript.load_instances_with_digest(num_instance, preimage_len, hash)
+        }
+        None => transcript.load_instances(protocol.num_instance.clone()),
+    };
+    with_accumulator_encoding!(config.accumulator_encoding, |AE| {
+        let proof =
+            PlonkVerifier::<AS, AE>::read_proof(&dk, &protocol, &instances, &mut transcript)
+                .unwrap();
+        PlonkVerifier::<AS, AE>::verify(&dk, &protocol, &instances, &proof).unwrap();
+    });
+
+    (
+        compile_and_write_yul(&loader, path),
+        loader.error_messages(),
+    )
+}
+
+/// Generates succinct verifier, which does everything [`gen_evm_verifier`]
+/// does except the final pairing, and returns the [`KzgAccumulator`]s
+/// (including the ones in instances if [`CircuitExt::accumulator_indices`] is
+/// given) encoded by [`encode_accumulators`] instead.
+///
+/// Accepting a proof is deferred to deciding the returned accumulators, e.g.
+/// by decider generated by [`gen_evm_accumulator_decider`] with return data
+/// of several succinct verifiers, which saves a pairing per proof. So a
+/// successful call to succinct verifier alone means nothing.
+pub fn gen_evm_succinct_verifier<C, AS>(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    num_instance: Vec<usize>,
+    path: Option<&Path>,
+) -> Vec<u8>
+where
+    C: CircuitExt<Fr>,
+    AS: PolynomialCommitmentScheme<
+            G1Affine,
+            Rc<EvmLoader>,
+            VerifyingKey = KzgSuccinctVerifyingKey<G1Affine>,
+            Output = KzgAccumulator<G1Affine, Rc<EvmLoader>>,
+        > + AccumulationScheme<
+            G1Affine,
+            Rc<EvmLoader>,
+            VerifyingKey = KzgAsVerifyingKey,
+            Accumulator = KzgAccumulator<G1Affine, Rc<EvmLoader>>,
+        >,
+{
+    gen_evm_succinct_verifier_with_config::<C, AS>(
+        params,
+        vk,
+        num_instance,
+        Default::default(),
+        path,
+    )
+}
 
-    let instances = transcript.load_instances(num_instance);
-    let proof =
-        PlonkVerifier::<AS>::read_proof(&dk, &protocol, &instances, &mut transcript).unwrap();
-    PlonkVerifier::<AS>::verify(&dk, &protocol, &instances, &proof).unwrap();
+/// Same as [`gen_evm_succinct_verifier`] with given [`EvmVerifierConfig`],
+/// except [`EvmVerifierConfig::abi`] which is not supported.
+pub fn gen_evm_succinct_verifier_with_config<C, AS>(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    num_instance: Vec<usize>,
+    config: EvmVerifierConfig,
+    path: Option<&Path>,
+) -> Vec<u8>
+where
+    C: CircuitExt<Fr>,
+    AS: PolynomialCommitmentScheme<
+            G1Affine,
+            Rc<EvmLoader>,
+            VerifyingKey = KzgSuccinctVerifyingKey<G1Affine>,
+            Output = KzgAccumulator<G1Affine, Rc<EvmLoader>>,
+        > + AccumulationScheme<
+            G1Affine,
+            Rc<EvmLoader>,
+            VerifyingKey = KzgAsVerifyingKey,
+            Accumulator = KzgAccumulator<G1Affine, Rc<EvmLoader>>,
+        >,
+{
+    assert!(!config.abi, "Succinct verifier with ABI is not supported");
+    assert!(
+        config.num_proof == 1 || config.public_input_hash.is_none(),
+        "Batched proofs with public input hash is not supported"
+    );
+    let protocol = compile(
+        params,
+        vk,
+        Config::kzg()
+            .with_num_instance(num_instance.clone())
+            .with_num_proof(config.num_proof)
+            .with_accumulator_indices(C::accumulator_indices()),
+    );
+    // succinct verifying key
+    let svk = params.get_g()[0].into();
+
+    let loader = config.loader();
+    let protocol = protocol.loaded(&loader);
+    let mut transcript =
+        EvmTranscript::<_, Rc<EvmLoader>, _, _>::new_with_compression(&loader, config.compressed);
+
+    let instances = match config.public_input_hash {
+        Some((preimage_len, hash)) => {
+            transcript.load_instances_with_digest(num_instance, preimage_len, hash)
+        }
+        None => transcript.load_instances(protocol.num_instance.clone()),
+    };
+    let accumulators = with_accumulator_encoding!(config.accumulator_encoding, |AE| {
+        let proof = PlonkSuccinctVerifier::<AS, AE>::read_proof(
+            &svk,
+            &protocol,
+            &instances,
+            &mut transcript,
+        )
+        .unwrap();
+        PlonkSuccinctVerifier::<AS, AE>::verify(&svk, &protocol, &instances, &proof).unwrap()
+    });
+    loader.return_ec_points(
+        &accumulators
+            .into_iter()
+            .flat_map(|KzgAccumulator { lhs, rhs }| [lhs, rhs])
+            .collect_vec(),
+    );
+
+    compile_and_write_yul(&loader, path)
+}
+
+/// Generates decider of `num_accumulator` [`KzgAccumulator`]s with a single
+/// pairing, which takes concatenated return data of succinct verifiers
+/// generated by [`gen_evm_succinct_verifier`] as calldata, and reverts if
+/// they are not all valid.
+///
+/// Accumulators are combined by powers of a challenge derived from all of
+/// them before pairing, as [`AccumulationDecider::decide_all`] does.
+pub fn gen_evm_accumulator_decider(
+    params: &ParamsKZG<Bn256>,
+    num_accumulator: usize,
+    path: Option<&Path>,
+) -> Vec<u8> {
+    // deciding key
+    let dk = (params.get_g()[0], params.g2(), params.s_g2()).into();
+
+    let loader = EvmLoader::new::<Fq, Fr>();
+    let accumulators = (0..num_accumulator)
+        .map(|idx| {
+            let [lhs, rhs] =
+                [0, 0x40].map(|offset| loader.calldataload_ec_point(idx * 0x80 + offset));
+            KzgAccumulator::new(lhs, rhs)
+        })
+        .collect_vec();
+    <SHPLONK as AccumulationDecider<_, Rc<EvmLoader>>>::decide_all(&dk, accumulators).unwrap();
+
+    compile_and_write_yul(&loader, path)
+}
 
+/// Generates verifier of proofs of several `protocols` (e.g. [`Snark::protocol`]
+/// of different circuits), which takes calldata as concatenated calldata of
+/// each proof in order (see [`encode_multi_calldata`]).
+///
+/// It runs the succinct part of [`PlonkVerifier`] for each proof, then
+/// decides all [`KzgAccumulator`]s (including the ones in instances given by
+/// [`PlonkProtocol::accumulator_indices`]) with a single pairing as
+/// [`AccumulationDecider::decide_all`] does.
+///
+/// [`Snark::protocol`]: crate::Snark::protocol
+pub fn gen_evm_multi_verifier<AS>(
+    params: &ParamsKZG<Bn256>,
+    protocols: &[PlonkProtocol<G1Affine>],
+    path: Option<&Path>,
+) -> Vec<u8>
+where
+    AS: PolynomialCommitmentScheme<
+            G1Affine,
+            Rc<EvmLoader>,
+            VerifyingKey = KzgSuccinctVerifyingKey<G1Affine>,
+            Output = KzgAccumulator<G1Affine, Rc<EvmLoader>>,
+        > + AccumulationScheme<
+            G1Affine,
+            Rc<EvmLoader>,
+            VerifyingKey = KzgAsVerifyingKey,
+            Accumulator = KzgAccumulator<G1Affine, Rc<EvmLoader>>,
+        > + AccumulationDecider<G1Affine, Rc<EvmLoader>, DecidingKey = KzgDecidingKey<Bn256>>,
+{
+    gen_evm_multi_verifier_with_config::<AS>(params, protocols, Default::default(), path)
+}
+
+/// Same as [`gen_evm_multi_verifier`] with given [`EvmVerifierConfig`],
+/// except [`EvmVerifierConfig::abi`] and
+/// [`EvmVerifierConfig::public_input_hash`] which are not supported.
+pub fn gen_evm_multi_verifier_with_config<AS>(
+    params: &ParamsKZG<Bn256>,
+    protocols: &[PlonkProtocol<G1Affine>],
+    config: EvmVerifierConfig,
+    path: Option<&Path>,
+) -> Vec<u8>
+where
+    AS: PolynomialCommitmentScheme<
+            G1Affine,
+            Rc<EvmLoader>,
+            VerifyingKey = KzgSuccinctVerifyingKey<G1Affine>,
+            Output = KzgAccumulator<G1Affine, Rc<EvmLoader>>,
+        > + AccumulationScheme<
+            G1Affine,
+            Rc<EvmLoader>,
+            VerifyingKey = KzgAsVerifyingKey,
+            Accumulator = KzgAccumulator<G1Affine, Rc<EvmLoader>>,
+        > + AccumulationDecider<G1Affine, Rc<EvmLoader>, DecidingKey = KzgDecidingKey<Bn256>>,
+{
+    assert!(!protocols.is_empty());
+    assert!(
+        !config.abi && config.public_input_hash.is_none(),
+        "Multi-proof verifier with ABI or public input hash is not supported"
+    );
+    // deciding key
+    let dk: KzgDecidingKey<Bn256> = (params.get_g()[0], params.g2(), params.s_g2()).into();
+
+    let loader = config.loader();
+    let accumulators = protocols
+        .iter()
+        .flat_map(|protocol| {
+            let protocol = protocol.loaded(&loader);
+            // Each transcript reads the proof right after the previous one.
+            let mut transcript = EvmTranscript::<_, Rc<EvmLoader>, _, _>::new_with_compression(
+                &loader,
+                config.compressed,
+            );
+            let instances = transcript.load_instances(protocol.num_instance.clone());
+            with_accumulator_encoding!(config.accumulator_encoding, |AE| {
+                let proof = PlonkSuccinctVerifier::<AS, AE>::read_proof(
+                    dk.as_ref(),
+                    &protocol,
+                    &instances,
+                    &mut transcript,
+                )
+                .unwrap();
+                PlonkSuccinctVerifier::<AS, AE>::verify(dk.as_ref(), &protocol, &instances, &proof)
+                    .unwrap()
+            })
+        })
+        .collect_vec();
+    AS::decide_all(&dk, accumulators).unwrap();
+
+    compile_and_write_yul(&loader, path)
+}
+
+fn compile_and_write_yul(loader: &Rc<EvmLoader>, path: Option<&Path>) -> Vec<u8> {
     let yul_code = loader.yul_code();
     let byte_code = compile_yul(&yul_code);
     if let Some(path) = path {
@@ -177,16 +768,188 @@ pub fn gen_evm_verifier_shplonk<C: CircuitExt<Fr>>(
     gen_evm_verifier::<C, SHPLONK>(params, vk, num_instance, path)
 }
 
+/// Returns estimated gas cost of verifying proof on EVM without and with G1
+/// points compressed in calldata respectively.
+///
+/// Compression saves 32 bytes of calldata per commitment at the expense of a
+/// `modexp` square root to decompress it, so it's usually only worth it when
+/// calldata is priced higher than on L1 (e.g. on rollups).
+pub fn evm_gas_cost_with_compression<C, AS>(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    num_instance: Vec<usize>,
+) -> (GasCost, GasCost)
+where
+    C: CircuitExt<Fr>,
+    AS: PolynomialCommitmentScheme<G1Affine, NativeLoader, Output = AS::Accumulator>
+        + AccumulationScheme<G1Affine, NativeLoader>
+        + CostEstimation<G1Affine, Input = Vec<Query<Fr>>>,
+{
+    let protocol = compile(
+        params,
+        vk,
+        Config::kzg()
+            .with_num_instance(num_instance)
+            .with_accumulator_indices(C::accumulator_indices()),
+    );
+    let cost = PlonkVerifier::<AS>::estimate_cost(&protocol);
+    (
+        estimate_gas_cost(&cost),
+        estimate_gas_cost_compressed(&cost),
+    )
+}
+
+/// ABI JSON of verifier generated with [`EvmVerifierConfig::abi`] enabled,
+/// whose calldata could be encoded by [`encode_abi_calldata`].
+pub const VERIFIER_ABI: &str = r#"[{"type":"function","name":"verify","inputs":[{"name":"instances","type":"uint256[]","internalType":"uint256[]"},{"name":"proof","type":"bytes","internalType":"bytes"}],"outputs":[{"name":"","type":"bool","internalType":"bool"}],"stateMutability":"view"}]"#;
+
 pub fn evm_verify(deployment_code: Vec<u8>, instances: Vec<Vec<Fr>>, proof: Vec<u8>) -> u64 {
-    let calldata = encode_calldata(&instances, &proof);
+    let calldata = encode_calldata_unchecked(&instances, &proof);
     let gas_cost = deploy_and_call(deployment_code, calldata).unwrap();
     dbg!(gas_cost);
     gas_cost
 }
 
+/// Encodes instances and proof of each protocol in order into calldata for
+/// verifier generated by [`gen_evm_multi_verifier`].
+pub fn encode_multi_calldata(instances_and_proofs: &[(Vec<Vec<Fr>>, Vec<u8>)]) -> Vec<u8> {
+    instances_and_proofs
+        .iter()
+        .flat_map(|(instances, proof)| encode_calldata_unchecked(instances, proof))
+        .collect()
+}
+
+/// Deploys succinct verifier generated by [`gen_evm_succinct_verifier`] and
+/// calls it, then returns the accumulators encoded by [`encode_accumulators`]
+/// to be given to [`evm_decide`].
+pub fn evm_succinct_verify(
+    deployment_code: Vec<u8>,
+    instances: Vec<Vec<Fr>>,
+    proof: Vec<u8>,
+) -> Vec<u8> {
+    let calldata = encode_calldata_unchecked(&instances, &proof);
+    let (gas_cost, output) = deploy_and_call_with_output(deployment_code, calldata).unwrap();
+    dbg!(gas_cost);
+    output
+}
+
+/// Deploys decider generated by [`gen_evm_accumulator_decider`] and calls it
+/// with `accumulators` encoded by [`encode_accumulators`] (e.g. returned by
+/// [`evm_succinct_verify`]), then returns gas cost.
+pub fn evm_decide(deployment_code: Vec<u8>, accumulators: Vec<Vec<u8>>) -> u64 {
+    let gas_cost = deploy_and_call(deployment_code, accumulators.concat()).unwrap();
+    dbg!(gas_cost);
+    gas_cost
+}
+
+/// Encodes accumulators as concatenated `(lhs.x, lhs.y, rhs.x, rhs.y)`, which
+/// is the same as return data of succinct verifier generated by
+/// [`gen_evm_succinct_verifier`].
+pub fn encode_accumulators(accumulators: &[KzgAccumulator<G1Affine, NativeLoader>]) -> Vec<u8> {
+    encode_ec_points(
+        &accumulators
+            .iter()
+            .flat_map(|KzgAccumulator { lhs, rhs }| [*lhs, *rhs])
+            .collect_vec(),
+    )
+}
+
+/// Deploys verifier generated by [`gen_evm_verifier_with_error_messages`] and
+/// calls it with `calldata`. Returns gas cost if proof is valid, otherwise
+/// error message of the failing check.
+pub fn evm_verify_with_error_messages(
+    deployment_code: Vec<u8>,
+    calldata: Vec<u8>,
+    error_messages: &[String],
+) -> Result<u64, String> {
+    deploy_and_call_with_error_messages(deployment_code, calldata, error_messages)
+}
+
 pub fn write_calldata(instances: &[Vec<Fr>], proof: &[u8], path: &Path) -> io::Result<String> {
-    let calldata = encode_calldata(instances, proof);
+    let calldata = encode_calldata_unchecked(instances, proof);
     let calldata = hex::encode(calldata);
     fs::write(path, &calldata)?;
     Ok(calldata)
 }
+
+/// Encodes field elements as concatenated 32 bytes big-endian words, which is
+/// the same as `abi.encodePacked` of `uint256`s, to be used as preimage of
+/// [`PublicInputHash`].
+pub fn encode_public_inputs(inputs: &[Fr]) -> Vec<u8> {
+    inputs
+        .iter()
+        .flat_map(|input| input.to_repr().into_iter().rev())
+        .collect()
+}
+
+/// Returns digest of `inputs` encoded by [`encode_public_inputs`], which is
+/// what circuit should constrain its last instance to be for verifier
+/// generated by [`gen_evm_verifier_with_public_input_hash`].
+///
+/// In circuit, the same digest is computed by
+/// [`Halo2Loader::assign_public_inputs_with_digest`](snark_verifier::loader::halo2::Halo2Loader::assign_public_inputs_with_digest).
+pub fn public_input_digest(hash: PublicInputHash, inputs: &[Fr]) -> Fr {
+    hash.digest(&encode_public_inputs(inputs))
+}
+
+#[cfg(all(test, feature = "loader_halo2"))]
+mod test {
+    use super::{
+        deploy_and_call, deploy_and_call_with_output, encode_abi_calldata,
+        encode_calldata_unchecked, gen_evm_proof_batch, gen_evm_verifier_with_config,
+        EvmVerifierConfig,
+    };
+    use crate::{gen_pk, halo2::gen_srs, test::Square, CircuitExt, SHPLONK};
+    use halo2_proofs::{
+        halo2curves::bn256::Fr,
+        poly::kzg::multiopen::{ProverSHPLONK, VerifierSHPLONK},
+    };
+    use itertools::Itertools;
+
+    #[test]
+    fn test_evm_verifier_batch() {
+        let params = gen_srs(8);
+        let pk = gen_pk(&params, &Square::default(), None);
+        let circuits = vec![Square(Fr::from(2)), Square(Fr::from(3))];
+        let instances = circuits
+            .iter()
+            .map(|circuit| circuit.instances())
+            .collect_vec();
+        let proof = gen_evm_proof_batch::<_, ProverSHPLONK<_>, VerifierSHPLONK<_>>(
+            &params,
+            &pk,
+            circuits,
+            instances.clone(),
+        );
+        // Instances of each proof are concatenated in calldata.
+        let instances = instances.concat();
+        let swapped = instances.iter().rev().cloned().collect_vec();
+
+        for abi in [false, true] {
+            let deployment_code = gen_evm_verifier_with_config::<Square, SHPLONK>(
+                &params,
+                pk.get_vk(),
+                Square::default().num_instance(),
+                EvmVerifierConfig {
+                    abi,
+                    num_proof: 2,
+                    ..Default::default()
+                },
+                None,
+            );
+            let verify = |instances: &[Vec<Fr>]| {
+                if abi {
+                    let calldata = encode_abi_calldata(instances, &proof);
+                    deploy_and_call_with_output(deployment_code.clone(), calldata)
+                        .map(|(_, output)| output.last() == Some(&1))
+                        .unwrap()
+                } else {
+                    let calldata = encode_calldata_unchecked(instances, &proof);
+                    deploy_and_call(deployment_code.clone(), calldata).is_ok()
+                }
+            };
+            assert!(verify(&instances));
+            assert!(!verify(&swapped));
+        }
+    }
+}
@@ -1,11 +1,13 @@
 #![allow(unused_imports)]
-use super::{read_instances, write_instances, CircuitExt, PlonkSuccinctVerifier, Snark};
+use super::{
+    read_instances, write_instances, CircuitExt, PlonkSuccinctVerifier, PoseidonConfig, Snark,
+};
 use ark_std::{end_timer, start_timer};
 use halo2_proofs::{
     circuit::Layouter,
     halo2curves::{
         bn256::{Bn256, Fr, G1Affine},
-        group::ff::Field,
+        group::ff::{Field, PrimeField},
     },
     plonk::{
         create_proof, keygen_vk, verify_proof, Circuit, ConstraintSystem, Error, ProvingKey,
@@ -40,22 +42,49 @@ use snark_verifier::{
     verifier::plonk::PlonkProof,
 };
 use std::{
+    collections::HashMap,
     env::var,
     fs::{self, File},
     io::{BufReader, BufWriter},
     marker::PhantomData,
     path::Path,
+    sync::Mutex,
 };
 
+/// Evaluates `$body` with consts `$t` and `$rate` set to width and rate of
+/// given [`PoseidonConfig`], since they are const generics of
+/// [`PoseidonTranscript`].
+macro_rules! with_poseidon_width {
+    ($config:expr, |$t:ident, $rate:ident| $body:expr) => {
+        match $config.t {
+            3 => {
+                const $t: usize = 3;
+                const $rate: usize = 2;
+                $body
+            }
+            5 => {
+                const $t: usize = 5;
+                const $rate: usize = 4;
+                $body
+            }
+            t => panic!("Unsupported Poseidon width {t}, only 3 and 5 are supported"),
+        }
+    };
+}
+
 pub mod aggregation;
 
-// Different Poseidon parameters can be set based on usage and security level
-const T: usize = 5; // 3;
+// Parameters of default `PoseidonConfig`, different ones can be used based on
+// usage and security level.
+const T: usize = 5;
 const RATE: usize = T - 1;
 const R_F: usize = 8;
-const R_P: usize = 60; // 57;
+const R_P: usize = 60;
 
-pub type PoseidonTranscript<L, S> =
+/// Poseidon transcript with width `T` and rate `RATE`, which should be
+/// created by `from_spec` with [`PoseidonConfig::spec`] for the number of
+/// rounds to match [`PoseidonConfig`].
+pub type PoseidonTranscript<L, S, const T: usize = 5, const RATE: usize = 4> =
     snark_verifier::system::halo2::transcript::halo2::PoseidonTranscript<
         G1Affine,
         L,
@@ -70,6 +99,53 @@ lazy_static! {
     /// Poseidon spec recomputed matrix round constants each time so it is expensive to create.
     /// We use lazy_static to create it only once and then clone as needed.
     pub static ref POSEIDON_SPEC: PoseidonSpec<Fr, T, RATE> = PoseidonSpec::new(R_F, R_P);
+    static ref POSEIDON_SPECS_T3: Mutex<HashMap<(usize, usize), PoseidonSpec<Fr, 3, 2>>> =
+        Default::default();
+    static ref POSEIDON_SPECS_T5: Mutex<HashMap<(usize, usize), PoseidonSpec<Fr, 5, 4>>> =
+        Mutex::new(HashMap::from([((R_F, R_P), POSEIDON_SPEC.clone())]));
+}
+
+/// Cache of Poseidon specs of width `T` and rate `RATE` keyed by number of
+/// full and partial rounds, which is implemented by [`PoseidonSpecs`] for
+/// widths supported by [`PoseidonConfig`].
+pub trait PoseidonSpecCache<const T: usize, const RATE: usize> {
+    /// Returns the cache.
+    fn cache() -> &'static Mutex<HashMap<(usize, usize), PoseidonSpec<Fr, T, RATE>>>;
+}
+
+/// Caches of Poseidon specs created by [`PoseidonConfig::spec`].
+#[derive(Clone, Copy, Debug)]
+pub struct PoseidonSpecs;
+
+impl PoseidonSpecCache<3, 2> for PoseidonSpecs {
+    fn cache() -> &'static Mutex<HashMap<(usize, usize), PoseidonSpec<Fr, 3, 2>>> {
+        &POSEIDON_SPECS_T3
+    }
+}
+
+impl PoseidonSpecCache<5, 4> for PoseidonSpecs {
+    fn cache() -> &'static Mutex<HashMap<(usize, usize), PoseidonSpec<Fr, 5, 4>>> {
+        &POSEIDON_SPECS_T5
+    }
+}
+
+impl PoseidonConfig {
+    /// Returns Poseidon spec of this config, where `T` and `RATE` must be
+    /// equal to `self.t` and `self.t - 1`. Specs are created once and cached
+    /// in [`PoseidonSpecs`], where the default one is [`POSEIDON_SPEC`].
+    pub fn spec<const T: usize, const RATE: usize>(&self) -> PoseidonSpec<Fr, T, RATE>
+    where
+        PoseidonSpecs: PoseidonSpecCache<T, RATE>,
+    {
+        assert_eq!(self.t, T);
+        assert_eq!(self.t - 1, RATE);
+        PoseidonSpecs::cache()
+            .lock()
+            .unwrap()
+            .entry((self.r_f, self.r_p))
+            .or_insert_with(|| PoseidonSpec::new(self.r_f, self.r_p))
+            .clone()
+    }
 }
 
 /// Attempts to read the srs from a file found in `./params/kzg_bn254_{k}.srs` or `{dir}/kzg_bn254_{k}.srs` if `PARAMS_DIR` env var is specified, creates a file it if it does not exist.
@@ -130,10 +206,138 @@ where
         MSMAccumulator = DualMSM<'params, Bn256>,
     >,
 {
+    gen_proof_with_poseidon_config::<C, P, V>(
+        params,
+        pk,
+        circuit,
+        instances,
+        Default::default(),
+        path,
+    )
+}
+
+/// Same as [`gen_proof`], but uses Poseidon with given [`PoseidonConfig`] for
+/// Fiat-Shamir.
+pub fn gen_proof_with_poseidon_config<'params, C, P, V>(
+    params: &'params ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    instances: Vec<Vec<Fr>>,
+    poseidon_config: PoseidonConfig,
+    path: Option<(impl AsRef<Path>, impl AsRef<Path>)>,
+) -> Vec<u8>
+where
+    C: Circuit<Fr>,
+    P: Prover<'params, KZGCommitmentScheme<Bn256>>,
+    V: Verifier<
+        'params,
+        KZGCommitmentScheme<Bn256>,
+        Guard = GuardKZG<'params, Bn256>,
+        MSMAccumulator = DualMSM<'params, Bn256>,
+    >,
+{
+    gen_proof_batch_with_poseidon_config::<C, P, V>(
+        params,
+        pk,
+        vec![circuit],
+        vec![instances],
+        poseidon_config,
+        path,
+    )
+}
+
+/// Generates a native proof of several instances of the same circuit
+/// batched together, using either SHPLONK or GWC proving method. Uses
+/// Poseidon for Fiat-Shamir.
+///
+/// Caches the concatenated instances and proof if `path = Some(instance_path, proof_path)` is specified.
+pub fn gen_proof_batch<'params, C, P, V>(
+    params: &'params ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuits: Vec<C>,
+    instances: Vec<Vec<Vec<Fr>>>,
+    path: Option<(impl AsRef<Path>, impl AsRef<Path>)>,
+) -> Vec<u8>
+where
+    C: Circuit<Fr>,
+    P: Prover<'params, KZGCommitmentScheme<Bn256>>,
+    V: Verifier<
+        'params,
+        KZGCommitmentScheme<Bn256>,
+        Guard = GuardKZG<'params, Bn256>,
+        MSMAccumulator = DualMSM<'params, Bn256>,
+    >,
+{
+    gen_proof_batch_with_poseidon_config::<C, P, V>(
+        params,
+        pk,
+        circuits,
+        instances,
+        Default::default(),
+        path,
+    )
+}
+
+/// Same as [`gen_proof_batch`], but uses Poseidon with given
+/// [`PoseidonConfig`] for Fiat-Shamir.
+pub fn gen_proof_batch_with_poseidon_config<'params, C, P, V>(
+    params: &'params ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuits: Vec<C>,
+    instances: Vec<Vec<Vec<Fr>>>,
+    poseidon_config: PoseidonConfig,
+    path: Option<(impl AsRef<Path>, impl AsRef<Path>)>,
+) -> Vec<u8>
+where
+    C: Circuit<Fr>,
+    P: Prover<'params, KZGCommitmentScheme<Bn256>>,
+    V: Verifier<
+        'params,
+        KZGCommitmentScheme<Bn256>,
+        Guard = GuardKZG<'params, Bn256>,
+        MSMAccumulator = DualMSM<'params, Bn256>,
+    >,
+{
+    gen_proof_batch_with_suffix::<C, P, V>(
+        params,
+        pk,
+        circuits,
+        instances,
+        &[],
+        poseidon_config,
+        path,
+    )
+}
+
+/// Same as [`gen_proof_batch_with_poseidon_config`], but also writes `suffix`
+/// to the transcript after the proof (see [`CircuitExt::proof_suffix`]), so
+/// it's encoded the same way as the rest of the proof.
+fn gen_proof_batch_with_suffix<'params, C, P, V>(
+    params: &'params ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuits: Vec<C>,
+    instances: Vec<Vec<Vec<Fr>>>,
+    suffix: &[Fr],
+    poseidon_config: PoseidonConfig,
+    path: Option<(impl AsRef<Path>, impl AsRef<Path>)>,
+) -> Vec<u8>
+where
+    C: Circuit<Fr>,
+    P: Prover<'params, KZGCommitmentScheme<Bn256>>,
+    V: Verifier<
+        'params,
+        KZGCommitmentScheme<Bn256>,
+        Guard = GuardKZG<'params, Bn256>,
+        MSMAccumulator = DualMSM<'params, Bn256>,
+    >,
+{
+    assert!(!circuits.is_empty());
+    assert_eq!(circuits.len(), instances.len());
+
     if let Some((instance_path, proof_path)) = &path {
         let proof_path = proof_path.as_ref();
         let cached_instances = read_instances(instance_path.as_ref());
-        if matches!(cached_instances, Ok(tmp) if tmp == instances) && proof_path.exists() {
+        if matches!(cached_instances, Ok(tmp) if tmp == instances.concat()) && proof_path.exists() {
             #[cfg(feature = "display")]
             let read_time = start_timer!(|| format!("Reading proof from {proof_path:?}"));
 
@@ -145,39 +349,52 @@ where
         }
     }
 
+    let instances = instances
+        .iter()
+        .map(|instances| instances.iter().map(Vec::as_slice).collect_vec())
+        .collect_vec();
     let instances = instances.iter().map(Vec::as_slice).collect_vec();
 
     #[cfg(feature = "display")]
     let proof_time = start_timer!(|| "Create proof");
 
-    let mut transcript =
-        PoseidonTranscript::<NativeLoader, _>::from_spec(vec![], POSEIDON_SPEC.clone());
-    let rng = StdRng::from_entropy();
-    create_proof::<_, P, _, _, _, _>(params, pk, &[circuit], &[&instances], rng, &mut transcript)
-        .unwrap();
-    let proof = transcript.finalize();
+    let proof = with_poseidon_width!(poseidon_config, |T, RATE| {
+        let mut transcript = PoseidonTranscript::<NativeLoader, _, T, RATE>::from_spec(
+            vec![],
+            poseidon_config.spec(),
+        );
+        let rng = StdRng::from_entropy();
+        create_proof::<_, P, _, _, _, _>(params, pk, &circuits, &instances, rng, &mut transcript)
+            .unwrap();
+        for scalar in suffix {
+            transcript.write_scalar(*scalar).unwrap();
+        }
+        transcript.finalize()
+    });
 
     #[cfg(feature = "display")]
     end_timer!(proof_time);
 
     // validate proof before caching
-    assert!({
-        let mut transcript_read =
-            PoseidonTranscript::<NativeLoader, &[u8]>::from_spec(&proof[..], POSEIDON_SPEC.clone());
+    assert!(with_poseidon_width!(poseidon_config, |T, RATE| {
+        let mut transcript_read = PoseidonTranscript::<NativeLoader, &[u8], T, RATE>::from_spec(
+            &proof[..],
+            poseidon_config.spec(),
+        );
         VerificationStrategy::<_, V>::finalize(
             verify_proof::<_, V, _, _, _>(
                 params.verifier_params(),
                 pk.get_vk(),
                 AccumulatorStrategy::new(params.verifier_params()),
-                &[instances.as_slice()],
+                &instances,
                 &mut transcript_read,
             )
             .unwrap(),
         )
-    });
+    }));
 
     if let Some((instance_path, proof_path)) = path {
-        write_instances(&instances, instance_path);
+        write_instances(&instances.concat(), instance_path);
         fs::write(proof_path, &proof).unwrap();
     }
 
@@ -230,27 +447,141 @@ where
         MSMAccumulator = DualMSM<'params, Bn256>,
     >,
 {
+    gen_snark_with_poseidon_config::<ConcreteCircuit, P, V>(
+        params,
+        pk,
+        circuit,
+        Default::default(),
+        path,
+    )
+}
+
+/// Same as [`gen_snark`], but uses Poseidon with given [`PoseidonConfig`] for
+/// Fiat-Shamir, which is recorded in the SNARK so it could be aggregated
+/// with the matching transcript.
+pub fn gen_snark_with_poseidon_config<'params, ConcreteCircuit, P, V>(
+    params: &'params ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: ConcreteCircuit,
+    poseidon_config: PoseidonConfig,
+    path: Option<impl AsRef<Path>>,
+) -> Snark
+where
+    ConcreteCircuit: CircuitExt<Fr>,
+    P: Prover<'params, KZGCommitmentScheme<Bn256>>,
+    V: Verifier<
+        'params,
+        KZGCommitmentScheme<Bn256>,
+        Guard = GuardKZG<'params, Bn256>,
+        MSMAccumulator = DualMSM<'params, Bn256>,
+    >,
+{
+    gen_snark_batch_with_poseidon_config::<ConcreteCircuit, P, V>(
+        params,
+        pk,
+        vec![circuit],
+        poseidon_config,
+        path,
+    )
+}
+
+/// Generates a SNARK of several instances of the same circuit batched in a
+/// single proof, using either SHPLONK or GWC multi-open scheme. Uses Poseidon
+/// for Fiat-Shamir.
+///
+/// The protocol is compiled with `num_proof = circuits.len()`, and instances
+/// of each circuit are concatenated in order (see [`Snark::instances_per_proof`]).
+///
+/// Tries to first deserialize from / later serialize the entire SNARK into `path` if specified.
+/// Serialization is done using `bincode`.
+pub fn gen_snark_batch<'params, ConcreteCircuit, P, V>(
+    params: &'params ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuits: Vec<ConcreteCircuit>,
+    path: Option<impl AsRef<Path>>,
+) -> Snark
+where
+    ConcreteCircuit: CircuitExt<Fr>,
+    P: Prover<'params, KZGCommitmentScheme<Bn256>>,
+    V: Verifier<
+        'params,
+        KZGCommitmentScheme<Bn256>,
+        Guard = GuardKZG<'params, Bn256>,
+        MSMAccumulator = DualMSM<'params, Bn256>,
+    >,
+{
+    gen_snark_batch_with_poseidon_config::<ConcreteCircuit, P, V>(
+        params,
+        pk,
+        circuits,
+        Default::default(),
+        path,
+    )
+}
+
+/// Same as [`gen_snark_batch`], but uses Poseidon with given
+/// [`PoseidonConfig`] for Fiat-Shamir.
+pub fn gen_snark_batch_with_poseidon_config<'params, ConcreteCircuit, P, V>(
+    params: &'params ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuits: Vec<ConcreteCircuit>,
+    poseidon_config: PoseidonConfig,
+    path: Option<impl AsRef<Path>>,
+) -> Snark
+where
+    ConcreteCircuit: CircuitExt<Fr>,
+    P: Prover<'params, KZGCommitmentScheme<Bn256>>,
+    V: Verifier<
+        'params,
+        KZGCommitmentScheme<Bn256>,
+        Guard = GuardKZG<'params, Bn256>,
+        MSMAccumulator = DualMSM<'params, Bn256>,
+    >,
+{
+    assert!(!circuits.is_empty(), "trying to prove 0 circuits");
+    assert!(
+        circuits
+            .iter()
+            .map(|circuit| circuit.num_instance())
+            .all_equal(),
+        "Batched circuits should have the same number of instances"
+    );
+    let num_proof = circuits.len();
+
     #[cfg(feature = "derive_serde")]
     if let Some(path) = &path {
         if let Ok(snark) = read_snark(path) {
-            return snark;
+            if snark.poseidon_config == poseidon_config && snark.num_proof == num_proof {
+                return snark;
+            }
         }
     }
     let protocol = compile(
         params,
         pk.get_vk(),
         Config::kzg()
-            .with_num_instance(circuit.num_instance())
+            .with_num_instance(circuits[0].num_instance())
+            .with_num_proof(num_proof)
             .with_accumulator_indices(ConcreteCircuit::accumulator_indices()),
     );
 
-    let instances = circuit.instances();
+    let instances = circuits
+        .iter()
+        .map(|circuit| circuit.instances())
+        .collect_vec();
+    // Accumulators of each proof are read in order after the proof.
+    let proof_suffix = circuits
+        .iter()
+        .flat_map(|circuit| circuit.proof_suffix())
+        .collect_vec();
     #[cfg(feature = "derive_serde")]
-    let proof = gen_proof::<ConcreteCircuit, P, V>(
+    let proof = gen_proof_batch_with_suffix::<ConcreteCircuit, P, V>(
         params,
         pk,
-        circuit,
+        circuits,
         instances.clone(),
+        &proof_suffix,
+        poseidon_config,
         None::<(&str, &str)>,
     );
     // If we can't serialize the entire snark, at least serialize the proof
@@ -263,10 +594,20 @@ where
         let paths = path
             .as_ref()
             .map(|path| (Path::new(&path.0), Path::new(&path.1)));
-        gen_proof::<ConcreteCircuit, P, V>(params, pk, circuit, instances.clone(), paths)
+        gen_proof_batch_with_suffix::<ConcreteCircuit, P, V>(
+            params,
+            pk,
+            circuits,
+            instances.clone(),
+            &proof_suffix,
+            poseidon_config,
+            paths,
+        )
     };
 
-    let snark = Snark::new(protocol, instances, proof);
+    let snark =
+        Snark::new_with_poseidon_config(protocol, instances.concat(), proof, poseidon_config)
+            .with_num_proof(num_proof);
     #[cfg(feature = "derive_serde")]
     if let Some(path) = &path {
         let f = File::create(path).unwrap();
@@ -306,6 +647,30 @@ pub fn gen_snark_shplonk<ConcreteCircuit: CircuitExt<Fr>>(
     gen_snark::<ConcreteCircuit, ProverSHPLONK<_>, VerifierSHPLONK<_>>(params, pk, circuit, path)
 }
 
+/// Generates a SNARK of several instances of the same circuit using GWC
+/// multi-open scheme, see [`gen_snark_batch`].
+pub fn gen_snark_batch_gwc<ConcreteCircuit: CircuitExt<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuits: Vec<ConcreteCircuit>,
+    path: Option<impl AsRef<Path>>,
+) -> Snark {
+    gen_snark_batch::<ConcreteCircuit, ProverGWC<_>, VerifierGWC<_>>(params, pk, circuits, path)
+}
+
+/// Generates a SNARK of several instances of the same circuit using SHPLONK
+/// multi-open scheme, see [`gen_snark_batch`].
+pub fn gen_snark_batch_shplonk<ConcreteCircuit: CircuitExt<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuits: Vec<ConcreteCircuit>,
+    path: Option<impl AsRef<Path>>,
+) -> Snark {
+    gen_snark_batch::<ConcreteCircuit, ProverSHPLONK<_>, VerifierSHPLONK<_>>(
+        params, pk, circuits, path,
+    )
+}
+
 /// Tries to deserialize a SNARK from the specified `path` using `bincode`.
 ///
 /// WARNING: The user must keep track of whether the SNARK was generated using the GWC or SHPLONK multi-open scheme.
@@ -314,3 +679,96 @@ pub fn read_snark(path: impl AsRef<Path>) -> Result<Snark, bincode::Error> {
     let f = File::open(path).map_err(Box::<bincode::ErrorKind>::from)?;
     bincode::deserialize_from(f)
 }
+
+#[cfg(test)]
+mod test {
+    use super::{
+        gen_snark_batch_shplonk, gen_snark_with_poseidon_config, gen_srs, PoseidonTranscript,
+    };
+    use crate::{gen_pk, test::Square, NativeLoader, PlonkVerifier, PoseidonConfig, SHPLONK};
+    use halo2_proofs::{
+        halo2curves::bn256::{Bn256, Fr},
+        poly::{
+            commitment::ParamsProver,
+            kzg::multiopen::{ProverSHPLONK, VerifierSHPLONK},
+        },
+    };
+    use snark_verifier::{pcs::kzg::KzgDecidingKey, verifier::SnarkVerifier};
+
+    #[test]
+    fn test_poseidon_config_round_trip() {
+        let params = gen_srs(8);
+        let pk = gen_pk(&params, &Square::default(), None);
+        let dk: KzgDecidingKey<Bn256> = (params.get_g()[0], params.g2(), params.s_g2()).into();
+
+        let poseidon_config = PoseidonConfig::new(3, 8, 57);
+        let snark = gen_snark_with_poseidon_config::<_, ProverSHPLONK<_>, VerifierSHPLONK<_>>(
+            &params,
+            &pk,
+            Square(Fr::from(3)),
+            poseidon_config,
+            None::<&str>,
+        );
+        assert_eq!(snark.poseidon_config, poseidon_config);
+
+        let verify = |poseidon_config: PoseidonConfig| {
+            with_poseidon_width!(poseidon_config, |T, RATE| {
+                let mut transcript = PoseidonTranscript::<NativeLoader, _, T, RATE>::from_spec(
+                    snark.proof(),
+                    poseidon_config.spec(),
+                );
+                PlonkVerifier::<SHPLONK>::read_proof(
+                    &dk,
+                    &snark.protocol,
+                    &snark.instances,
+                    &mut transcript,
+                )
+                .and_then(|proof| {
+                    PlonkVerifier::<SHPLONK>::verify(&dk, &snark.protocol, &snark.instances, &proof)
+                })
+                .is_ok()
+            })
+        };
+        assert!(verify(poseidon_config));
+        // Spec is cached by number of rounds, so it's not confused with others.
+        assert!(!verify(PoseidonConfig::new(3, 8, 60)));
+        assert!(!verify(PoseidonConfig::default()));
+        assert!(verify(poseidon_config));
+    }
+
+    #[test]
+    fn test_gen_snark_batch() {
+        let params = gen_srs(8);
+        let pk = gen_pk(&params, &Square::default(), None);
+        let dk: KzgDecidingKey<Bn256> = (params.get_g()[0], params.g2(), params.s_g2()).into();
+
+        let snark = gen_snark_batch_shplonk(
+            &params,
+            &pk,
+            vec![Square(Fr::from(2)), Square(Fr::from(3))],
+            None::<&str>,
+        );
+        assert_eq!(snark.num_proof, 2);
+        assert_eq!(snark.protocol.num_instance, vec![1, 1]);
+        assert_eq!(
+            snark.instances_per_proof(),
+            vec![[vec![Fr::from(4)]], [vec![Fr::from(9)]]]
+        );
+
+        let verify = |instances: &[Vec<Fr>]| {
+            let mut transcript = PoseidonTranscript::<NativeLoader, _>::from_spec(
+                snark.proof(),
+                snark.poseidon_config.spec(),
+            );
+            PlonkVerifier::<SHPLONK>::read_proof(&dk, &snark.protocol, instances, &mut transcript)
+                .and_then(|proof| {
+                    PlonkVerifier::<SHPLONK>::verify(&dk, &snark.protocol, instances, &proof)
+                })
+                .is_ok()
+        };
+        assert!(verify(&snark.instances));
+        // Instances of batched proofs are bound in order.
+        let swapped = snark.instances.iter().rev().cloned().collect::<Vec<_>>();
+        assert!(!verify(&swapped));
+    }
+}
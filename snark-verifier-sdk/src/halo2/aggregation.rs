@@ -1,6 +1,6 @@
 // This is mostly a generalization of `snark_verifier::examples::evm-verifier-with-accumulators`
 use super::PlonkSuccinctVerifier;
-use crate::{SnarkWitness, BITS, LIMBS};
+use crate::{PoseidonConfig, SnarkWitness, BITS, LIMBS};
 use halo2_proofs::{
     circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
     halo2curves::bn256::{Bn256, Fr, G1Affine},
@@ -19,56 +19,278 @@ use halo2curves::{bn256::Fq, ff::PrimeField};
 use itertools::Itertools;
 use rand::{rngs::StdRng, SeedableRng};
 use serde::{Deserialize, Serialize};
-use snark_verifier::util::arithmetic::fe_to_limbs;
 use snark_verifier::{
-    loader::{self, halo2::EccInstructions, native::NativeLoader},
+    loader::{self, native::NativeLoader},
     pcs::{
         kzg::{
-            KzgAccumulator, KzgAsProvingKey, KzgAsVerifyingKey, KzgSuccinctVerifyingKey,
+            CompressedEncoding, CompressedEncodingInstructions, HashedEncoding, KzgAccumulator,
+            KzgAsProvingKey, KzgAsVerifyingKey, KzgSuccinctVerifyingKey, LimbsEncoding,
             LimbsEncodingInstructions,
         },
-        AccumulationScheme, AccumulationSchemeProver, PolynomialCommitmentScheme,
+        AccumulationScheme, AccumulationSchemeProver, AccumulatorEncoding,
+        PolynomialCommitmentScheme,
     },
+    system::halo2::transcript::halo2::NativeEncoding,
     verifier::SnarkVerifier,
 };
 use std::{fs::File, marker::PhantomData, path::Path, rc::Rc};
 
-use super::{CircuitExt, PoseidonTranscript, Snark, POSEIDON_SPEC};
+use super::{CircuitExt, PoseidonTranscript, Snark};
+
+#[cfg(feature = "halo2_lib")]
+pub mod halo2_lib;
 
 pub type Svk = KzgSuccinctVerifyingKey<G1Affine>;
 pub type BaseFieldEccChip = halo2_wrong_ecc::BaseFieldEccChip<G1Affine, LIMBS, BITS>;
 pub type Halo2Loader<'a> = loader::halo2::Halo2Loader<'a, G1Affine, BaseFieldEccChip>;
 
+/// Accumulator encoding of [`AggregationCircuit`], which decides how the
+/// accumulators in instances of snarks are decoded, and how the new
+/// accumulator is exposed as instances.
+pub trait AggregationEncoding:
+    for<'a> AccumulatorEncoding<
+        G1Affine,
+        Rc<Halo2Loader<'a>>,
+        Accumulator = KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>>,
+    > + AccumulatorEncoding<
+        G1Affine,
+        NativeLoader,
+        Accumulator = KzgAccumulator<G1Affine, NativeLoader>,
+    >
+{
+    /// Returns number of instances taken by an accumulator.
+    fn num_instance() -> usize;
+
+    /// Encodes accumulator into instances, and returns them with scalars to
+    /// be appended to the proof (see [`CircuitExt::proof_suffix`]).
+    fn encode(accumulator: &KzgAccumulator<G1Affine, NativeLoader>) -> (Vec<Fr>, Vec<Fr>);
+
+    /// Encodes assigned accumulator into cells to be exposed as instances.
+    fn assign<'a>(
+        loader: &Rc<Halo2Loader<'a>>,
+        accumulator: &KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>>,
+    ) -> Result<Vec<AssignedCell<Fr, Fr>>, plonk::Error>;
+}
+
+/// Encodes assigned accumulator into limbs of [`LimbsEncoding<L, B>`].
+fn assign_limbs<'a, const L: usize, const B: usize>(
+    loader: &Rc<Halo2Loader<'a>>,
+    accumulator: &KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>>,
+) -> Result<Vec<AssignedCell<Fr, Fr>>, plonk::Error>
+where
+    BaseFieldEccChip: LimbsEncodingInstructions<'a, G1Affine, L, B>,
+{
+    Ok([&accumulator.lhs, &accumulator.rhs]
+        .iter()
+        .map(|ec_point| {
+            LimbsEncodingInstructions::<_, L, B>::assign_ec_point_to_limbs(
+                &*loader.ecc_chip(),
+                &mut loader.ctx_mut(),
+                ec_point.assigned(),
+            )
+        })
+        .collect::<Result<Vec<_>, plonk::Error>>()?
+        .into_iter()
+        .flatten()
+        .collect())
+}
+
+impl AggregationEncoding for LimbsEncoding<LIMBS, BITS> {
+    fn num_instance() -> usize {
+        4 * LIMBS
+    }
+
+    fn encode(accumulator: &KzgAccumulator<G1Affine, NativeLoader>) -> (Vec<Fr>, Vec<Fr>) {
+        (
+            LimbsEncoding::<LIMBS, BITS>::encode(accumulator),
+            Vec::new(),
+        )
+    }
+
+    fn assign<'a>(
+        loader: &Rc<Halo2Loader<'a>>,
+        accumulator: &KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>>,
+    ) -> Result<Vec<AssignedCell<Fr, Fr>>, plonk::Error> {
+        assign_limbs::<LIMBS, BITS>(loader, accumulator)
+    }
+}
+
+/// The coordinates are re-limbed from the 4 limbs of 68 bits of
+/// [`BaseFieldEccChip`] into 3 limbs of 88 bits, which takes 12 instances
+/// instead of 16.
+impl AggregationEncoding for LimbsEncoding<3, 88> {
+    fn num_instance() -> usize {
+        4 * 3
+    }
+
+    fn encode(accumulator: &KzgAccumulator<G1Affine, NativeLoader>) -> (Vec<Fr>, Vec<Fr>) {
+        (LimbsEncoding::<3, 88>::encode(accumulator), Vec::new())
+    }
+
+    fn assign<'a>(
+        loader: &Rc<Halo2Loader<'a>>,
+        accumulator: &KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>>,
+    ) -> Result<Vec<AssignedCell<Fr, Fr>>, plonk::Error> {
+        assign_limbs::<3, 88>(loader, accumulator)
+    }
+}
+
+impl AggregationEncoding for CompressedEncoding<LIMBS, BITS> {
+    fn num_instance() -> usize {
+        2 * (LIMBS + 1)
+    }
+
+    fn encode(accumulator: &KzgAccumulator<G1Affine, NativeLoader>) -> (Vec<Fr>, Vec<Fr>) {
+        (
+            CompressedEncoding::<LIMBS, BITS>::encode(accumulator),
+            Vec::new(),
+        )
+    }
+
+    fn assign<'a>(
+        loader: &Rc<Halo2Loader<'a>>,
+        accumulator: &KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>>,
+    ) -> Result<Vec<AssignedCell<Fr, Fr>>, plonk::Error> {
+        Ok([&accumulator.lhs, &accumulator.rhs]
+            .iter()
+            .map(|ec_point| {
+                loader
+                    .ecc_chip()
+                    .assign_ec_point_to_compressed(&mut loader.ctx_mut(), ec_point.assigned())
+            })
+            .collect::<Result<Vec<_>, plonk::Error>>()?
+            .into_iter()
+            .flatten()
+            .collect())
+    }
+}
+
+impl<const T: usize, const RATE: usize, const R_F: usize, const R_P: usize> AggregationEncoding
+    for HashedEncoding<LIMBS, BITS, T, RATE, R_F, R_P>
+{
+    fn num_instance() -> usize {
+        1
+    }
+
+    fn encode(accumulator: &KzgAccumulator<G1Affine, NativeLoader>) -> (Vec<Fr>, Vec<Fr>) {
+        let (digest, limbs) = HashedEncoding::<LIMBS, BITS, T, RATE, R_F, R_P>::encode(accumulator);
+        (vec![digest], limbs)
+    }
+
+    fn assign<'a>(
+        loader: &Rc<Halo2Loader<'a>>,
+        accumulator: &KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>>,
+    ) -> Result<Vec<AssignedCell<Fr, Fr>>, plonk::Error> {
+        let limbs =
+            <LimbsEncoding<LIMBS, BITS> as AggregationEncoding>::assign(loader, accumulator)?
+                .into_iter()
+                .map(|limb| loader.scalar_from_assigned(limb))
+                .collect_vec();
+        Ok(vec![Self::hash(&limbs).into_assigned()])
+    }
+}
+
 #[allow(clippy::type_complexity)]
 /// Core function used in `synthesize` to aggregate multiple `snarks`.
 ///  
+/// Generic over the `EccChip` backend, so it can be used with either [`BaseFieldEccChip`] or,
+/// with feature `halo2_lib`, [`halo2_lib::BaseFieldEccChip`].
+///
+/// Each snark is read with the Poseidon transcript given by its own [`PoseidonConfig`], while `as_proof`
+/// is read with the default one, see [`aggregate_with_poseidon_config`] to specify it.
+///
 /// Returns the assigned instances of previous snarks and the new final pair that needs to be verified in a pairing check.
-/// For each previous snark, we concatenate all instances into a single vector. We return a vector of vectors,
-/// one vector per snark, for convenience.
+/// For each previous snark, we concatenate all instances (of all batched proofs if [`Snark::num_proof`] > 1)
+/// into a single vector. We return a vector of vectors, one vector per snark, for convenience.
 ///
 /// # Assumptions
 /// * `snarks` is not empty
-pub fn aggregate<'a, AS>(
+pub fn aggregate<'a, AS, AE, EccChip>(
     svk: &Svk,
-    loader: &Rc<Halo2Loader<'a>>,
+    loader: &Rc<loader::halo2::Halo2Loader<'a, G1Affine, EccChip>>,
     snarks: &[SnarkWitness],
     as_proof: Value<&'_ [u8]>,
 ) -> (
-    Vec<Vec<<BaseFieldEccChip as EccInstructions<'a, G1Affine>>::AssignedCell>>, // this is Vec<Vec<AssignedCell<Fr, Fr>>>, but we note what the actual trait type is for future reference
-    KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>>,
+    Vec<Vec<EccChip::AssignedScalar>>,
+    KzgAccumulator<G1Affine, Rc<loader::halo2::Halo2Loader<'a, G1Affine, EccChip>>>,
 )
 where
+    EccChip: NativeEncoding<'a, G1Affine>,
     AS: PolynomialCommitmentScheme<
             G1Affine,
-            Rc<Halo2Loader<'a>>,
+            Rc<loader::halo2::Halo2Loader<'a, G1Affine, EccChip>>,
             VerifyingKey = Svk,
-            Output = KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>>,
+            Output = KzgAccumulator<
+                G1Affine,
+                Rc<loader::halo2::Halo2Loader<'a, G1Affine, EccChip>>,
+            >,
         > + AccumulationScheme<
             G1Affine,
-            Rc<Halo2Loader<'a>>,
-            Accumulator = KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>>,
+            Rc<loader::halo2::Halo2Loader<'a, G1Affine, EccChip>>,
+            Accumulator = KzgAccumulator<
+                G1Affine,
+                Rc<loader::halo2::Halo2Loader<'a, G1Affine, EccChip>>,
+            >,
+            VerifyingKey = KzgAsVerifyingKey,
+        >,
+    AE: AccumulatorEncoding<
+        G1Affine,
+        Rc<loader::halo2::Halo2Loader<'a, G1Affine, EccChip>>,
+        Accumulator = KzgAccumulator<
+            G1Affine,
+            Rc<loader::halo2::Halo2Loader<'a, G1Affine, EccChip>>,
+        >,
+    >,
+{
+    aggregate_with_poseidon_config::<AS, AE, EccChip>(
+        svk,
+        loader,
+        snarks,
+        as_proof,
+        Default::default(),
+    )
+}
+
+#[allow(clippy::type_complexity)]
+/// Same as [`aggregate`], but reads `as_proof` with Poseidon of given
+/// [`PoseidonConfig`].
+pub fn aggregate_with_poseidon_config<'a, AS, AE, EccChip>(
+    svk: &Svk,
+    loader: &Rc<loader::halo2::Halo2Loader<'a, G1Affine, EccChip>>,
+    snarks: &[SnarkWitness],
+    as_proof: Value<&'_ [u8]>,
+    poseidon_config: PoseidonConfig,
+) -> (
+    Vec<Vec<EccChip::AssignedScalar>>,
+    KzgAccumulator<G1Affine, Rc<loader::halo2::Halo2Loader<'a, G1Affine, EccChip>>>,
+)
+where
+    EccChip: NativeEncoding<'a, G1Affine>,
+    AS: PolynomialCommitmentScheme<
+            G1Affine,
+            Rc<loader::halo2::Halo2Loader<'a, G1Affine, EccChip>>,
+            VerifyingKey = Svk,
+            Output = KzgAccumulator<
+                G1Affine,
+                Rc<loader::halo2::Halo2Loader<'a, G1Affine, EccChip>>,
+            >,
+        > + AccumulationScheme<
+            G1Affine,
+            Rc<loader::halo2::Halo2Loader<'a, G1Affine, EccChip>>,
+            Accumulator = KzgAccumulator<
+                G1Affine,
+                Rc<loader::halo2::Halo2Loader<'a, G1Affine, EccChip>>,
+            >,
             VerifyingKey = KzgAsVerifyingKey,
         >,
+    AE: AccumulatorEncoding<
+        G1Affine,
+        Rc<loader::halo2::Halo2Loader<'a, G1Affine, EccChip>>,
+        Accumulator = KzgAccumulator<
+            G1Affine,
+            Rc<loader::halo2::Halo2Loader<'a, G1Affine, EccChip>>,
+        >,
+    >,
 {
     assert!(!snarks.is_empty(), "trying to aggregate 0 snarks");
     let assign_instances = |instances: &[Vec<Value<Fr>>]| {
@@ -91,20 +313,26 @@ where
             let instances = assign_instances(&snark.instances);
             // read the transcript and perform Fiat-Shamir
             // run through verification computation and produce the final pair `succinct`
-            let mut transcript = PoseidonTranscript::<Rc<Halo2Loader>, _>::from_spec(
-                loader,
-                snark.proof(),
-                POSEIDON_SPEC.clone(),
-            );
-            let proof = PlonkSuccinctVerifier::<AS>::read_proof(
-                svk,
-                &protocol,
-                &instances,
-                &mut transcript,
-            )
-            .unwrap();
+            let proof = with_poseidon_width!(snark.poseidon_config, |T, RATE| {
+                let mut transcript = PoseidonTranscript::<
+                    Rc<loader::halo2::Halo2Loader<G1Affine, EccChip>>,
+                    _,
+                    T,
+                    RATE,
+                >::from_spec(
+                    loader, snark.proof(), snark.poseidon_config.spec()
+                );
+                PlonkSuccinctVerifier::<AS, AE>::read_proof(
+                    svk,
+                    &protocol,
+                    &instances,
+                    &mut transcript,
+                )
+                .unwrap()
+            });
             let accumulator =
-                PlonkSuccinctVerifier::<AS>::verify(svk, &protocol, &instances, &proof).unwrap();
+                PlonkSuccinctVerifier::<AS, AE>::verify(svk, &protocol, &instances, &proof)
+                    .unwrap();
 
             previous_instances.push(
                 instances
@@ -119,17 +347,20 @@ where
         .collect_vec();
 
     let accumulator = if accumulators.len() > 1 {
-        let mut transcript = PoseidonTranscript::<Rc<Halo2Loader>, _>::from_spec(
-            loader,
-            as_proof,
-            POSEIDON_SPEC.clone(),
-        );
-        let proof = <AS as AccumulationScheme<_, _>>::read_proof(
-            &Default::default(),
-            &accumulators,
-            &mut transcript,
-        )
-        .unwrap();
+        let proof = with_poseidon_width!(poseidon_config, |T, RATE| {
+            let mut transcript = PoseidonTranscript::<
+                Rc<loader::halo2::Halo2Loader<G1Affine, EccChip>>,
+                _,
+                T,
+                RATE,
+            >::from_spec(loader, as_proof, poseidon_config.spec());
+            <AS as AccumulationScheme<_, _>>::read_proof(
+                &Default::default(),
+                &accumulators,
+                &mut transcript,
+            )
+            .unwrap()
+        });
         <AS as AccumulationScheme<_, _>>::verify(&Default::default(), &accumulators, &proof)
             .unwrap()
     } else {
@@ -143,16 +374,21 @@ where
 /// Many things will fail if `AS` does not match how `snarks` were actually created.
 ///
 /// In practice, `AS` is either `SHPLONK` or `GWC`.
+///
+/// `AE` is the [`AggregationEncoding`] of accumulators, both in instances of
+/// `snarks` and of the circuit itself, which is [`LimbsEncoding`] by default.
 #[derive(Clone)]
-pub struct AggregationCircuit<AS> {
+pub struct AggregationCircuit<AS, AE = LimbsEncoding<LIMBS, BITS>> {
     svk: Svk,
     pub snarks: Vec<SnarkWitness>,
     instances: Vec<Fr>,
+    proof_suffix: Vec<Fr>,
     as_proof: Value<Vec<u8>>,
-    _as: PhantomData<AS>,
+    poseidon_config: PoseidonConfig,
+    _marker: PhantomData<(AS, AE)>,
 }
 
-impl<AS> AggregationCircuit<AS>
+impl<AS, AE> AggregationCircuit<AS, AE>
 // without unstable rust, I don't know how to make this where clause go away...
 where
     for<'a> AS: PolynomialCommitmentScheme<
@@ -176,6 +412,7 @@ where
             Accumulator = KzgAccumulator<G1Affine, NativeLoader>,
             VerifyingKey = KzgAsVerifyingKey,
         > + AccumulationSchemeProver<G1Affine, ProvingKey = KzgAsProvingKey<G1Affine>>,
+    AE: AggregationEncoding,
 {
     /// Given snarks, this creates a circuit and runs the `GateThreadBuilder` to verify all the snarks.
     /// By default, the returned circuit has public instances equal to the limbs of the pair of elliptic curve points, referred to as the `accumulator`, that need to be verified in a final pairing check.
@@ -184,7 +421,20 @@ where
     ///
     /// Warning: will fail silently if `snarks` were created using a different multi-open scheme than `AS`
     /// where `AS` can be either [`crate::SHPLONK`] or [`crate::GWC`] (for original PLONK multi-open scheme)
+    ///
+    /// `snarks` could also contain several proofs batched by [`super::gen_snark_batch`], whose
+    /// accumulators are all accumulated.
     pub fn new(params: &ParamsKZG<Bn256>, snarks: impl IntoIterator<Item = Snark>) -> Self {
+        Self::new_with_poseidon_config(params, snarks, Default::default())
+    }
+
+    /// Same as [`Self::new`], but uses Poseidon with given [`PoseidonConfig`] for the transcript
+    /// of accumulation proof. Each snark is still read with its own [`Snark::poseidon_config`].
+    pub fn new_with_poseidon_config(
+        params: &ParamsKZG<Bn256>,
+        snarks: impl IntoIterator<Item = Snark>,
+        poseidon_config: PoseidonConfig,
+    ) -> Self {
         let svk: Svk = params.get_g()[0].into();
         let snarks = snarks.into_iter().collect_vec();
 
@@ -192,27 +442,36 @@ where
         let accumulators = snarks
             .iter()
             .flat_map(|snark| {
-                let mut transcript_read = PoseidonTranscript::<NativeLoader, &[u8]>::from_spec(
-                    snark.proof(),
-                    POSEIDON_SPEC.clone(),
-                );
-                let proof = PlonkSuccinctVerifier::<AS>::read_proof(
+                let proof = with_poseidon_width!(snark.poseidon_config, |T, RATE| {
+                    let mut transcript_read =
+                        PoseidonTranscript::<NativeLoader, &[u8], T, RATE>::from_spec(
+                            snark.proof(),
+                            snark.poseidon_config.spec(),
+                        );
+                    PlonkSuccinctVerifier::<AS, AE>::read_proof(
+                        &svk,
+                        &snark.protocol,
+                        &snark.instances,
+                        &mut transcript_read,
+                    )
+                    .unwrap()
+                });
+                PlonkSuccinctVerifier::<AS, AE>::verify(
                     &svk,
                     &snark.protocol,
                     &snark.instances,
-                    &mut transcript_read,
+                    &proof,
                 )
-                .unwrap();
-                PlonkSuccinctVerifier::<AS>::verify(&svk, &snark.protocol, &snark.instances, &proof)
-                    .unwrap()
+                .unwrap()
             })
             .collect_vec();
 
-        let (accumulator, as_proof) = {
-            let mut transcript_write = PoseidonTranscript::<NativeLoader, Vec<u8>>::from_spec(
-                vec![],
-                POSEIDON_SPEC.clone(),
-            );
+        let (accumulator, as_proof) = with_poseidon_width!(poseidon_config, |T, RATE| {
+            let mut transcript_write =
+                PoseidonTranscript::<NativeLoader, Vec<u8>, T, RATE>::from_spec(
+                    vec![],
+                    poseidon_config.spec(),
+                );
             let rng = StdRng::from_entropy();
             let accumulator = AS::create_proof(
                 &Default::default(),
@@ -222,19 +481,18 @@ where
             )
             .unwrap();
             (accumulator, transcript_write.finalize())
-        };
+        });
 
-        let KzgAccumulator { lhs, rhs } = accumulator;
-        let instances = [lhs.x, lhs.y, rhs.x, rhs.y]
-            .map(fe_to_limbs::<_, _, LIMBS, BITS>)
-            .concat();
+        let (instances, proof_suffix) = AE::encode(&accumulator);
 
         Self {
             svk,
             snarks: snarks.into_iter().map_into().collect(),
             instances,
+            proof_suffix,
             as_proof: Value::known(as_proof),
-            _as: PhantomData,
+            poseidon_config,
+            _marker: PhantomData,
         }
     }
 
@@ -248,9 +506,10 @@ where
 
     /// In a single Halo2 region, aggregates previous snarks but does not expose public instances.
     ///
-    /// Returns `(accumulator_limbs, prev_instances)` as `AssignedCell`s.
+    /// Returns `(accumulator_instances, prev_instances)` as `AssignedCell`s, where
+    /// `accumulator_instances` is the accumulator encoded by `AE`.
     ///
-    /// The `accumulator_limbs` **must** be exposed as public instances.
+    /// The `accumulator_instances` **must** be exposed as public instances.
     /// One can create a wrapper circuit around `Self` to expose more instances from `prev_instances` as necessary.
     ///
     /// # Assumptions
@@ -268,22 +527,17 @@ where
 
                 let ecc_chip = config.ecc_chip();
                 let loader = Halo2Loader::new(ecc_chip, ctx);
-                let (prev_instances, accumulator) =
-                    aggregate::<AS>(&self.svk, &loader, &self.snarks, self.as_proof());
+                let (prev_instances, accumulator) = aggregate_with_poseidon_config::<AS, AE, _>(
+                    &self.svk,
+                    &loader,
+                    &self.snarks,
+                    self.as_proof(),
+                    self.poseidon_config,
+                );
 
-                let accumulator_limbs = [accumulator.lhs, accumulator.rhs]
-                    .iter()
-                    .map(|ec_point| {
-                        loader
-                            .ecc_chip()
-                            .assign_ec_point_to_limbs(&mut loader.ctx_mut(), ec_point.assigned())
-                    })
-                    .collect::<Result<Vec<_>, plonk::Error>>()?
-                    .into_iter()
-                    .flatten()
-                    .collect_vec();
+                let accumulator_instances = AE::assign(&loader, &accumulator)?;
 
-                Ok((accumulator_limbs, prev_instances))
+                Ok((accumulator_instances, prev_instances))
             },
         )
     }
@@ -326,7 +580,7 @@ impl AggregationConfig {
     }
 }
 
-impl<AS> Circuit<Fr> for AggregationCircuit<AS>
+impl<AS, AE> Circuit<Fr> for AggregationCircuit<AS, AE>
 // without unstable rust, I don't know how to make this where clause go away...
 where
     for<'a> AS: PolynomialCommitmentScheme<
@@ -350,6 +604,7 @@ where
             Accumulator = KzgAccumulator<G1Affine, NativeLoader>,
             VerifyingKey = KzgAsVerifyingKey,
         > + AccumulationSchemeProver<G1Affine, ProvingKey = KzgAsProvingKey<G1Affine>>,
+    AE: AggregationEncoding,
 {
     type Config = AggregationConfig;
     type FloorPlanner = SimpleFloorPlanner;
@@ -365,8 +620,10 @@ where
                 .map(SnarkWitness::without_witnesses)
                 .collect(),
             instances: Vec::new(),
+            proof_suffix: Vec::new(),
             as_proof: Value::unknown(),
-            _as: PhantomData,
+            poseidon_config: self.poseidon_config,
+            _marker: PhantomData,
         }
     }
 
@@ -387,17 +644,17 @@ where
         let range_chip = config.range_chip();
         range_chip.load_table(&mut layouter)?;
 
-        let (accumulator_limbs, _) = self.aggregation_region(config, &mut layouter)?;
+        let (accumulator_instances, _) = self.aggregation_region(config, &mut layouter)?;
 
-        for (row, limb) in accumulator_limbs.into_iter().enumerate() {
-            main_gate.expose_public(layouter.namespace(|| ""), limb, row)?;
+        for (row, instance) in accumulator_instances.into_iter().enumerate() {
+            main_gate.expose_public(layouter.namespace(|| ""), instance, row)?;
         }
         // @dev: one could expose more instances here if necessary
         Ok(())
     }
 }
 
-impl<AS> CircuitExt<Fr> for AggregationCircuit<AS>
+impl<AS, AE> CircuitExt<Fr> for AggregationCircuit<AS, AE>
 // without unstable rust, I don't know how to make this where clause go away...
 where
     for<'a> AS: PolynomialCommitmentScheme<
@@ -421,6 +678,7 @@ where
             Accumulator = KzgAccumulator<G1Affine, NativeLoader>,
             VerifyingKey = KzgAsVerifyingKey,
         > + AccumulationSchemeProver<G1Affine, ProvingKey = KzgAsProvingKey<G1Affine>>,
+    AE: AggregationEncoding,
 {
     fn num_instance(&self) -> Vec<usize> {
         vec![self.instances.len()]
@@ -431,6 +689,130 @@ where
     }
 
     fn accumulator_indices() -> Option<Vec<(usize, usize)>> {
-        Some((0..4 * LIMBS).map(|idx| (0, idx)).collect())
+        Some((0..AE::num_instance()).map(|idx| (0, idx)).collect())
+    }
+
+    fn proof_suffix(&self) -> Vec<Fr> {
+        self.proof_suffix.clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::AggregationCircuit;
+    use crate::{
+        gen_pk,
+        halo2::{gen_snark_batch_shplonk, gen_snark_shplonk, gen_srs, PoseidonTranscript},
+        test::Square,
+        CircuitExt, HashedLimbsEncoding, Limbs3x88Encoding, NativeLoader, PlonkVerifier, Snark,
+        SHPLONK,
+    };
+    use halo2_proofs::{
+        dev::MockProver,
+        halo2curves::{
+            bn256::{Bn256, Fr},
+            ff::PrimeField,
+        },
+        plonk::Circuit,
+        poly::commitment::ParamsProver,
+    };
+    use itertools::Itertools;
+    use snark_verifier::{pcs::kzg::KzgDecidingKey, verifier::SnarkVerifier};
+
+    const K: u32 = 22;
+
+    fn gen_application_snarks(n: u64) -> Vec<Snark> {
+        let params = gen_srs(8);
+        let pk = gen_pk(&params, &Square::default(), None);
+        (1..=n)
+            .map(|x| gen_snark_shplonk(&params, &pk, Square(Fr::from(x)), None::<&str>))
+            .collect()
+    }
+
+    #[test]
+    fn test_aggregation_limbs_3x88() {
+        let snarks = gen_application_snarks(2);
+        let params = gen_srs(K);
+
+        let circuit = AggregationCircuit::<SHPLONK, Limbs3x88Encoding>::new(&params, snarks);
+        let instances = circuit.instances();
+        assert_eq!(instances[0].len(), 4 * 3);
+        MockProver::run(K, &circuit, instances.clone())
+            .unwrap()
+            .assert_satisfied();
+
+        let mut invalid_instances = instances;
+        invalid_instances[0][0] += Fr::one();
+        assert!(MockProver::run(K, &circuit, invalid_instances)
+            .unwrap()
+            .verify()
+            .is_err());
+    }
+
+    #[test]
+    fn test_aggregation_batched_snark() {
+        let params = gen_srs(8);
+        let pk = gen_pk(&params, &Square::default(), None);
+        let batched = gen_snark_batch_shplonk(
+            &params,
+            &pk,
+            vec![Square(Fr::from(2)), Square(Fr::from(3))],
+            None::<&str>,
+        );
+        let single = gen_snark_shplonk(&params, &pk, Square(Fr::from(4)), None::<&str>);
+        let params = gen_srs(K);
+
+        let circuit =
+            AggregationCircuit::<SHPLONK, Limbs3x88Encoding>::new(&params, [batched, single]);
+        MockProver::run(K, &circuit, circuit.instances())
+            .unwrap()
+            .assert_satisfied();
+    }
+
+    #[test]
+    #[ignore = "cause it takes several minutes to run"]
+    fn test_aggregation_hashed_proof_suffix() {
+        let snarks = gen_application_snarks(2);
+        let params = gen_srs(K);
+        let dk: KzgDecidingKey<Bn256> = (params.get_g()[0], params.g2(), params.s_g2()).into();
+
+        let circuit = AggregationCircuit::<SHPLONK, HashedLimbsEncoding>::new(&params, snarks);
+        let proof_suffix = circuit.proof_suffix();
+        let pk = gen_pk(&params, &circuit.without_witnesses(), None);
+        let snark = gen_snark_shplonk(&params, &pk, circuit, None::<&str>);
+
+        // Limbs of accumulator are written right after the proof.
+        let suffix = proof_suffix
+            .iter()
+            .flat_map(|limb| limb.to_repr())
+            .collect_vec();
+        assert!(snark.proof().ends_with(&suffix));
+
+        let verify = |proof: &[u8]| {
+            let mut transcript = PoseidonTranscript::<NativeLoader, _>::from_spec(
+                proof,
+                snark.poseidon_config.spec(),
+            );
+            PlonkVerifier::<SHPLONK, HashedLimbsEncoding>::read_proof(
+                &dk,
+                &snark.protocol,
+                &snark.instances,
+                &mut transcript,
+            )
+            .and_then(|proof| {
+                PlonkVerifier::<SHPLONK, HashedLimbsEncoding>::verify(
+                    &dk,
+                    &snark.protocol,
+                    &snark.instances,
+                    &proof,
+                )
+            })
+            .is_ok()
+        };
+        assert!(verify(snark.proof()));
+
+        let mut tampered = snark.proof.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(!verify(&tampered));
     }
 }
@@ -0,0 +1,609 @@
+//! [`AggregationCircuit`] using the halo2-lib (`halo2-base` + `halo2-ecc`) backend for the
+//! [`Halo2Loader`](loader::halo2::Halo2Loader).
+//!
+//! It has the same interface as [`super::AggregationCircuit`], so the two backends can be
+//! compared by swapping the import.
+use super::{aggregate_with_poseidon_config, Svk};
+use crate::{
+    halo2::PoseidonTranscript, CircuitExt, DefaultLimbsEncoding, PlonkSuccinctVerifier,
+    PoseidonConfig, Snark, SnarkWitness, BITS, LIMBS,
+};
+use halo2_proofs::{
+    circuit::{Cell, Layouter, SimpleFloorPlanner, Value},
+    halo2curves::bn256::{Bn256, Fq, Fr, G1Affine},
+    plonk::{self, Circuit, Column, ConstraintSystem, Instance},
+    poly::{commitment::ParamsProver, kzg::commitment::ParamsKZG},
+};
+use itertools::Itertools;
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use snark_verifier::{
+    loader::{
+        self,
+        halo2::{
+            halo2_base::{
+                gates::{flex_gate::FlexGateConfig, range::RangeConfig, RangeInstructions},
+                utils::modulus,
+                AssignedValue, SKIP_FIRST_PASS,
+            },
+            halo2_ecc::{
+                ecc::EccChip,
+                fields::fp::{FpConfig, FpStrategy},
+            },
+        },
+        native::NativeLoader,
+    },
+    pcs::{
+        kzg::{
+            CompressedEncoding, CompressedEncodingInstructions, HashedEncoding, KzgAccumulator,
+            KzgAsProvingKey, KzgAsVerifyingKey, LimbsEncoding, LimbsEncodingInstructions,
+        },
+        AccumulationScheme, AccumulationSchemeProver, AccumulatorEncoding,
+        PolynomialCommitmentScheme,
+    },
+    verifier::SnarkVerifier,
+};
+use std::{env::var, fs::File, marker::PhantomData, rc::Rc};
+
+pub type BaseFieldEccChip =
+    snark_verifier::loader::halo2::halo2_ecc::ecc::BaseFieldEccChip<G1Affine>;
+pub type Halo2Loader<'a> = loader::halo2::Halo2Loader<'a, G1Affine, BaseFieldEccChip>;
+
+/// Same as [`super::AggregationEncoding`] but for the halo2-lib backend.
+///
+/// Only encodings with [`LIMBS`] limbs of [`BITS`] bits are supported, since
+/// [`AggregationConfig`] requires the base field chip to use them.
+pub trait AggregationEncoding:
+    super::AggregationEncoding
+    + for<'a> AccumulatorEncoding<
+        G1Affine,
+        Rc<Halo2Loader<'a>>,
+        Accumulator = KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>>,
+    >
+{
+    /// Encodes assigned accumulator into cells to be exposed as instances.
+    fn assign<'a>(
+        loader: &Rc<Halo2Loader<'a>>,
+        accumulator: &KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>>,
+    ) -> Result<Vec<AssignedValue<'a, Fr>>, plonk::Error>;
+}
+
+impl AggregationEncoding for LimbsEncoding<LIMBS, BITS> {
+    fn assign<'a>(
+        loader: &Rc<Halo2Loader<'a>>,
+        accumulator: &KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>>,
+    ) -> Result<Vec<AssignedValue<'a, Fr>>, plonk::Error> {
+        Ok([&accumulator.lhs, &accumulator.rhs]
+            .iter()
+            .map(|ec_point| {
+                LimbsEncodingInstructions::<_, LIMBS, BITS>::assign_ec_point_to_limbs(
+                    &*loader.ecc_chip(),
+                    &mut loader.ctx_mut(),
+                    ec_point.assigned(),
+                )
+            })
+            .collect::<Result<Vec<_>, plonk::Error>>()?
+            .into_iter()
+            .flatten()
+            .collect())
+    }
+}
+
+impl AggregationEncoding for CompressedEncoding<LIMBS, BITS> {
+    fn assign<'a>(
+        loader: &Rc<Halo2Loader<'a>>,
+        accumulator: &KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>>,
+    ) -> Result<Vec<AssignedValue<'a, Fr>>, plonk::Error> {
+        Ok([&accumulator.lhs, &accumulator.rhs]
+            .iter()
+            .map(|ec_point| {
+                CompressedEncodingInstructions::<_, LIMBS, BITS>::assign_ec_point_to_compressed(
+                    &*loader.ecc_chip(),
+                    &mut loader.ctx_mut(),
+                    ec_point.assigned(),
+                )
+            })
+            .collect::<Result<Vec<_>, plonk::Error>>()?
+            .into_iter()
+            .flatten()
+            .collect())
+    }
+}
+
+impl<const T: usize, const RATE: usize, const R_F: usize, const R_P: usize> AggregationEncoding
+    for HashedEncoding<LIMBS, BITS, T, RATE, R_F, R_P>
+{
+    fn assign<'a>(
+        loader: &Rc<Halo2Loader<'a>>,
+        accumulator: &KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>>,
+    ) -> Result<Vec<AssignedValue<'a, Fr>>, plonk::Error> {
+        let limbs =
+            <LimbsEncoding<LIMBS, BITS> as AggregationEncoding>::assign(loader, accumulator)?
+                .into_iter()
+                .map(|limb| loader.scalar_from_assigned(limb))
+                .collect_vec();
+        Ok(vec![Self::hash(&limbs).into_assigned()])
+    }
+}
+
+/// Parameters of the halo2-lib [`AggregationConfig`].
+///
+/// `limb_bits` and `num_limbs` must be equal to [`BITS`] and [`LIMBS`], otherwise the
+/// accumulator can't be decoded by [`crate::PlonkVerifier`].
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct AggregationConfigParams {
+    pub strategy: FpStrategy,
+    pub degree: u32,
+    pub num_advice: usize,
+    pub num_lookup_advice: usize,
+    pub num_fixed: usize,
+    pub lookup_bits: usize,
+    pub limb_bits: usize,
+    pub num_limbs: usize,
+}
+
+impl Default for AggregationConfigParams {
+    fn default() -> Self {
+        Self {
+            strategy: FpStrategy::Simple,
+            degree: 21,
+            num_advice: 5,
+            num_lookup_advice: 1,
+            num_fixed: 1,
+            lookup_bits: 20,
+            limb_bits: BITS,
+            num_limbs: LIMBS,
+        }
+    }
+}
+
+impl AggregationConfigParams {
+    /// Reads the params from the json file at `AGGREGATION_CONFIG` env var if specified,
+    /// otherwise returns the default.
+    pub fn from_env() -> Self {
+        var("AGGREGATION_CONFIG")
+            .map(|path| serde_json::from_reader(File::open(path).unwrap()).unwrap())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AggregationConfig {
+    pub base_field_config: FpConfig<Fr, Fq>,
+    pub instance: Column<Instance>,
+}
+
+impl AggregationConfig {
+    pub fn configure(meta: &mut ConstraintSystem<Fr>, params: AggregationConfigParams) -> Self {
+        assert_eq!(params.limb_bits, BITS);
+        assert_eq!(params.num_limbs, LIMBS);
+
+        let base_field_config = FpConfig::configure(
+            meta,
+            params.strategy,
+            &[params.num_advice],
+            &[params.num_lookup_advice],
+            params.num_fixed,
+            params.lookup_bits,
+            params.limb_bits,
+            params.num_limbs,
+            modulus::<Fq>(),
+            0,
+            params.degree as usize,
+        );
+
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        Self {
+            base_field_config,
+            instance,
+        }
+    }
+
+    pub fn range(&self) -> &RangeConfig<Fr> {
+        &self.base_field_config.range
+    }
+
+    pub fn gate(&self) -> &FlexGateConfig<Fr> {
+        &self.base_field_config.range.gate
+    }
+
+    pub fn ecc_chip(&self) -> BaseFieldEccChip {
+        EccChip::construct(self.base_field_config.clone())
+    }
+}
+
+/// Same as [`super::AggregationCircuit`] but using the halo2-lib backend.
+#[derive(Clone)]
+pub struct AggregationCircuit<AS, AE = DefaultLimbsEncoding> {
+    svk: Svk,
+    pub snarks: Vec<SnarkWitness>,
+    instances: Vec<Fr>,
+    proof_suffix: Vec<Fr>,
+    as_proof: Value<Vec<u8>>,
+    poseidon_config: PoseidonConfig,
+    _marker: PhantomData<(AS, AE)>,
+}
+
+impl<AS, AE> AggregationCircuit<AS, AE>
+where
+    for<'a> AS: PolynomialCommitmentScheme<
+            G1Affine,
+            Rc<Halo2Loader<'a>>,
+            VerifyingKey = Svk,
+            Output = KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>>,
+        > + AccumulationScheme<
+            G1Affine,
+            Rc<Halo2Loader<'a>>,
+            Accumulator = KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>>,
+            VerifyingKey = KzgAsVerifyingKey,
+        > + PolynomialCommitmentScheme<
+            G1Affine,
+            NativeLoader,
+            VerifyingKey = Svk,
+            Output = KzgAccumulator<G1Affine, NativeLoader>,
+        > + AccumulationScheme<
+            G1Affine,
+            NativeLoader,
+            Accumulator = KzgAccumulator<G1Affine, NativeLoader>,
+            VerifyingKey = KzgAsVerifyingKey,
+        > + AccumulationSchemeProver<G1Affine, ProvingKey = KzgAsProvingKey<G1Affine>>,
+    AE: AggregationEncoding,
+{
+    /// See [`super::AggregationCircuit::new`].
+    pub fn new(params: &ParamsKZG<Bn256>, snarks: impl IntoIterator<Item = Snark>) -> Self {
+        Self::new_with_poseidon_config(params, snarks, Default::default())
+    }
+
+    /// See [`super::AggregationCircuit::new_with_poseidon_config`].
+    pub fn new_with_poseidon_config(
+        params: &ParamsKZG<Bn256>,
+        snarks: impl IntoIterator<Item = Snark>,
+        poseidon_config: PoseidonConfig,
+    ) -> Self {
+        let svk: Svk = params.get_g()[0].into();
+        let snarks = snarks.into_iter().collect_vec();
+
+        let accumulators = snarks
+            .iter()
+            .flat_map(|snark| {
+                let proof = with_poseidon_width!(snark.poseidon_config, |T, RATE| {
+                    let mut transcript_read =
+                        PoseidonTranscript::<NativeLoader, &[u8], T, RATE>::from_spec(
+                            snark.proof(),
+                            snark.poseidon_config.spec(),
+                        );
+                    PlonkSuccinctVerifier::<AS, AE>::read_proof(
+                        &svk,
+                        &snark.protocol,
+                        &snark.instances,
+                        &mut transcript_read,
+                    )
+                    .unwrap()
+                });
+                PlonkSuccinctVerifier::<AS, AE>::verify(
+                    &svk,
+                    &snark.protocol,
+                    &snark.instances,
+                    &proof,
+                )
+                .unwrap()
+            })
+            .collect_vec();
+
+        let (accumulator, as_proof) = with_poseidon_width!(poseidon_config, |T, RATE| {
+            let mut transcript_write =
+                PoseidonTranscript::<NativeLoader, Vec<u8>, T, RATE>::from_spec(
+                    vec![],
+                    poseidon_config.spec(),
+                );
+            let rng = StdRng::from_entropy();
+            let accumulator = AS::create_proof(
+                &Default::default(),
+                &accumulators,
+                &mut transcript_write,
+                rng,
+            )
+            .unwrap();
+            (accumulator, transcript_write.finalize())
+        });
+
+        let (instances, proof_suffix) = AE::encode(&accumulator);
+
+        Self {
+            svk,
+            snarks: snarks.into_iter().map_into().collect(),
+            instances,
+            proof_suffix,
+            as_proof: Value::known(as_proof),
+            poseidon_config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn as_proof(&self) -> Value<&[u8]> {
+        self.as_proof.as_ref().map(|proof| proof.as_slice())
+    }
+
+    pub fn instance(&self) -> &[Fr] {
+        &self.instances
+    }
+
+    /// Same as [`super::AggregationCircuit::aggregation_region`], but returns [`Cell`]s of
+    /// `(accumulator_instances, prev_instances)` instead, since assigned values of halo2-lib
+    /// can't outlive the region.
+    ///
+    /// The `accumulator_instances` **must** be exposed as public instances.
+    ///
+    /// # Assumptions
+    /// * Lookup table of [`AggregationConfig::range`] has already been loaded
+    #[allow(clippy::type_complexity)]
+    pub fn aggregation_region(
+        &self,
+        config: AggregationConfig,
+        layouter: &mut impl Layouter<Fr>,
+    ) -> Result<(Vec<Cell>, Vec<Vec<Cell>>), plonk::Error> {
+        let mut first_pass = SKIP_FIRST_PASS;
+        let mut accumulator_instances = Vec::new();
+        let mut prev_instances = Vec::new();
+        layouter.assign_region(
+            || "",
+            |region| {
+                if first_pass {
+                    first_pass = false;
+                    return Ok(());
+                }
+                let ctx = config.base_field_config.new_context(region);
+
+                let loader = Halo2Loader::new(config.ecc_chip(), ctx);
+                let (instances, accumulator) = aggregate_with_poseidon_config::<AS, AE, _>(
+                    &self.svk,
+                    &loader,
+                    &self.snarks,
+                    self.as_proof(),
+                    self.poseidon_config,
+                );
+
+                accumulator_instances = <AE as AggregationEncoding>::assign(&loader, &accumulator)?
+                    .iter()
+                    .map(|limb| limb.cell())
+                    .collect_vec();
+                prev_instances = instances
+                    .iter()
+                    .map(|instances| instances.iter().map(|instance| instance.cell()).collect())
+                    .collect();
+
+                config.range().finalize(&mut loader.ctx_mut());
+                #[cfg(feature = "display")]
+                loader.ctx_mut().print_stats(&["Range"]);
+
+                Ok(())
+            },
+        )?;
+        Ok((accumulator_instances, prev_instances))
+    }
+}
+
+impl<AS, AE> Circuit<Fr> for AggregationCircuit<AS, AE>
+where
+    for<'a> AS: PolynomialCommitmentScheme<
+            G1Affine,
+            Rc<Halo2Loader<'a>>,
+            VerifyingKey = Svk,
+            Output = KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>>,
+        > + AccumulationScheme<
+            G1Affine,
+            Rc<Halo2Loader<'a>>,
+            Accumulator = KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>>,
+            VerifyingKey = KzgAsVerifyingKey,
+        > + PolynomialCommitmentScheme<
+            G1Affine,
+            NativeLoader,
+            VerifyingKey = Svk,
+            Output = KzgAccumulator<G1Affine, NativeLoader>,
+        > + AccumulationScheme<
+            G1Affine,
+            NativeLoader,
+            Accumulator = KzgAccumulator<G1Affine, NativeLoader>,
+            VerifyingKey = KzgAsVerifyingKey,
+        > + AccumulationSchemeProver<G1Affine, ProvingKey = KzgAsProvingKey<G1Affine>>,
+    AE: AggregationEncoding,
+{
+    type Config = AggregationConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "halo2_circuit_params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            svk: self.svk,
+            snarks: self
+                .snarks
+                .iter()
+                .map(SnarkWitness::without_witnesses)
+                .collect(),
+            instances: Vec::new(),
+            proof_suffix: Vec::new(),
+            as_proof: Value::unknown(),
+            poseidon_config: self.poseidon_config,
+            _marker: PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        AggregationConfig::configure(meta, AggregationConfigParams::from_env())
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), plonk::Error> {
+        config.range().load_lookup_table(&mut layouter)?;
+        let instance = config.instance;
+
+        let (accumulator_instances, _) = self.aggregation_region(config, &mut layouter)?;
+
+        for (row, limb) in accumulator_instances.into_iter().enumerate() {
+            layouter.constrain_instance(limb, instance, row)?;
+        }
+        // @dev: one could expose more instances here if necessary
+        Ok(())
+    }
+}
+
+impl<AS, AE> CircuitExt<Fr> for AggregationCircuit<AS, AE>
+where
+    for<'a> AS: PolynomialCommitmentScheme<
+            G1Affine,
+            Rc<Halo2Loader<'a>>,
+            VerifyingKey = Svk,
+            Output = KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>>,
+        > + AccumulationScheme<
+            G1Affine,
+            Rc<Halo2Loader<'a>>,
+            Accumulator = KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>>,
+            VerifyingKey = KzgAsVerifyingKey,
+        > + PolynomialCommitmentScheme<
+            G1Affine,
+            NativeLoader,
+            VerifyingKey = Svk,
+            Output = KzgAccumulator<G1Affine, NativeLoader>,
+        > + AccumulationScheme<
+            G1Affine,
+            NativeLoader,
+            Accumulator = KzgAccumulator<G1Affine, NativeLoader>,
+            VerifyingKey = KzgAsVerifyingKey,
+        > + AccumulationSchemeProver<G1Affine, ProvingKey = KzgAsProvingKey<G1Affine>>,
+    AE: AggregationEncoding,
+{
+    fn num_instance(&self) -> Vec<usize> {
+        vec![self.instances.len()]
+    }
+
+    fn instances(&self) -> Vec<Vec<Fr>> {
+        vec![self.instances.clone()]
+    }
+
+    fn accumulator_indices() -> Option<Vec<(usize, usize)>> {
+        Some((0..AE::num_instance()).map(|idx| (0, idx)).collect())
+    }
+
+    fn proof_suffix(&self) -> Vec<Fr> {
+        self.proof_suffix.clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AggregationCircuit, AggregationConfigParams};
+    use crate::{
+        gen_pk,
+        halo2::{aggregation, gen_proof_shplonk, gen_snark_shplonk, gen_srs},
+        test::Square,
+        CircuitExt, CompressedLimbsEncoding, HashedLimbsEncoding, Snark, SHPLONK,
+    };
+    use halo2_proofs::{
+        dev::{CircuitCost, MockProver},
+        halo2curves::bn256::{Fr, G1},
+        plonk::Circuit,
+    };
+    use std::time::Instant;
+
+    const K_HALO2_WRONG: u32 = 22;
+
+    fn gen_application_snarks(n: u64) -> Vec<Snark> {
+        let params = gen_srs(8);
+        let pk = gen_pk(&params, &Square::default(), None);
+        (1..=n)
+            .map(|x| gen_snark_shplonk(&params, &pk, Square(Fr::from(x)), None::<&str>))
+            .collect()
+    }
+
+    #[test]
+    fn test_halo2_lib_aggregation() {
+        let k = AggregationConfigParams::from_env().degree;
+        let snarks = gen_application_snarks(2);
+        let params = gen_srs(k);
+
+        let circuit = AggregationCircuit::<SHPLONK>::new(&params, snarks.clone());
+        let instances = circuit.instances();
+        MockProver::run(k, &circuit, instances.clone())
+            .unwrap()
+            .assert_satisfied();
+
+        // Exposes the same accumulator as the halo2_wrong backend
+        let halo2_wrong = aggregation::AggregationCircuit::<SHPLONK>::new(&params, snarks);
+        assert_eq!(instances, halo2_wrong.instances());
+
+        let mut invalid_instances = instances;
+        invalid_instances[0][0] += Fr::one();
+        assert!(MockProver::run(k, &circuit, invalid_instances)
+            .unwrap()
+            .verify()
+            .is_err());
+    }
+
+    #[test]
+    fn test_halo2_lib_aggregation_encodings() {
+        let k = AggregationConfigParams::from_env().degree;
+        let snarks = gen_application_snarks(2);
+        let params = gen_srs(k);
+
+        let circuit =
+            AggregationCircuit::<SHPLONK, CompressedLimbsEncoding>::new(&params, snarks.clone());
+        MockProver::run(k, &circuit, circuit.instances())
+            .unwrap()
+            .assert_satisfied();
+        let halo2_wrong = aggregation::AggregationCircuit::<SHPLONK, CompressedLimbsEncoding>::new(
+            &params,
+            snarks.clone(),
+        );
+        assert_eq!(circuit.instances(), halo2_wrong.instances());
+
+        let circuit =
+            AggregationCircuit::<SHPLONK, HashedLimbsEncoding>::new(&params, snarks.clone());
+        MockProver::run(k, &circuit, circuit.instances())
+            .unwrap()
+            .assert_satisfied();
+        let halo2_wrong =
+            aggregation::AggregationCircuit::<SHPLONK, HashedLimbsEncoding>::new(&params, snarks);
+        assert_eq!(circuit.instances(), halo2_wrong.instances());
+        assert_eq!(circuit.proof_suffix(), halo2_wrong.proof_suffix());
+    }
+
+    #[test]
+    #[ignore = "cause it takes several minutes to run"]
+    fn test_halo2_lib_aggregation_cost() {
+        let k = AggregationConfigParams::from_env().degree;
+        let snarks = gen_application_snarks(2);
+
+        let params = gen_srs(k);
+        let circuit = AggregationCircuit::<SHPLONK>::new(&params, snarks.clone());
+        println!(
+            "halo2_lib: {:#?}",
+            CircuitCost::<G1, _>::measure(k as usize, &circuit)
+        );
+        let pk = gen_pk(&params, &circuit.without_witnesses(), None);
+        let instances = circuit.instances();
+        let start = Instant::now();
+        gen_proof_shplonk(&params, &pk, circuit, instances, None);
+        let halo2_lib_proving_time = start.elapsed();
+
+        let params = gen_srs(K_HALO2_WRONG);
+        let circuit = aggregation::AggregationCircuit::<SHPLONK>::new(&params, snarks);
+        println!(
+            "halo2_wrong: {:#?}",
+            CircuitCost::<G1, _>::measure(K_HALO2_WRONG as usize, &circuit)
+        );
+        let pk = gen_pk(&params, &circuit.without_witnesses(), None);
+        let instances = circuit.instances();
+        let start = Instant::now();
+        gen_proof_shplonk(&params, &pk, circuit, instances, None);
+        let halo2_wrong_proving_time = start.elapsed();
+
+        println!("halo2_lib proving time: {halo2_lib_proving_time:?}");
+        println!("halo2_wrong proving time: {halo2_wrong_proving_time:?}");
+    }
+}
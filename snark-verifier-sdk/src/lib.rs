@@ -15,7 +15,7 @@ use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 pub use snark_verifier::loader::native::NativeLoader;
 use snark_verifier::{
-    pcs::kzg::{Bdfg21, Gwc19, KzgAs, LimbsEncoding},
+    pcs::kzg::{Bdfg21, CompressedEncoding, Gwc19, HashedEncoding, KzgAs, LimbsEncoding},
     verifier::{self, plonk::PlonkProtocol},
 };
 use std::{
@@ -24,42 +24,188 @@ use std::{
     path::Path,
 };
 
+/// Evaluates `$body` with type `$ae` set to the accumulator encoding given by
+/// [`AccumulatorEncodingKind`].
+#[allow(unused_macros)]
+macro_rules! with_accumulator_encoding {
+    ($kind:expr, |$ae:ident| $body:expr) => {
+        match $kind {
+            $crate::AccumulatorEncodingKind::Limbs => {
+                type $ae = $crate::DefaultLimbsEncoding;
+                $body
+            }
+            $crate::AccumulatorEncodingKind::Limbs3x88 => {
+                type $ae = $crate::Limbs3x88Encoding;
+                $body
+            }
+            $crate::AccumulatorEncodingKind::Compressed => {
+                type $ae = $crate::CompressedLimbsEncoding;
+                $body
+            }
+            $crate::AccumulatorEncodingKind::Hashed => {
+                type $ae = $crate::HashedLimbsEncoding;
+                $body
+            }
+        }
+    };
+}
+
 #[cfg(feature = "loader_evm")]
 pub mod evm;
 #[cfg(feature = "loader_halo2")]
 pub mod halo2;
 
+#[cfg(test)]
+mod test;
+
 pub const LIMBS: usize = 4;
 pub const BITS: usize = 68;
 
+/// Accumulator encoding of instances with [`LIMBS`] limbs of [`BITS`] bits.
+pub type DefaultLimbsEncoding = LimbsEncoding<LIMBS, BITS>;
+/// Accumulator encoding of instances with 3 limbs of 88 bits, which takes 12
+/// instances instead of 16.
+pub type Limbs3x88Encoding = LimbsEncoding<3, 88>;
+/// Accumulator encoding of instances with limbs of x coordinates and signs of
+/// y coordinates, which takes `2 * (LIMBS + 1)` instances.
+pub type CompressedLimbsEncoding = CompressedEncoding<LIMBS, BITS>;
+/// Accumulator encoding with a single instance being Poseidon digest of the
+/// limbs, which are appended to the proof instead (see
+/// [`CircuitExt::proof_suffix`]).
+pub type HashedLimbsEncoding = HashedEncoding<LIMBS, BITS, 5, 4, 8, 60>;
+
 /// AS stands for accumulation scheme.
 /// AS can be either `Kzg<Bn256, Gwc19>` (the original PLONK KZG multi-open) or `Kzg<Bn256, Bdfg21>` (SHPLONK)
-pub type PlonkVerifier<AS> = verifier::plonk::PlonkVerifier<AS, LimbsEncoding<LIMBS, BITS>>;
-pub type PlonkSuccinctVerifier<AS> =
-    verifier::plonk::PlonkSuccinctVerifier<AS, LimbsEncoding<LIMBS, BITS>>;
+///
+/// AE stands for accumulator encoding of instances, which is
+/// [`DefaultLimbsEncoding`] by default.
+pub type PlonkVerifier<AS, AE = DefaultLimbsEncoding> = verifier::plonk::PlonkVerifier<AS, AE>;
+pub type PlonkSuccinctVerifier<AS, AE = DefaultLimbsEncoding> =
+    verifier::plonk::PlonkSuccinctVerifier<AS, AE>;
 pub type SHPLONK = KzgAs<Bn256, Bdfg21>;
 pub type GWC = KzgAs<Bn256, Gwc19>;
 
+/// Selects accumulator encoding of instances at runtime, where each variant
+/// corresponds to a type alias used as `AE` of [`PlonkVerifier`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "derive_serde", derive(Serialize, Deserialize))]
+pub enum AccumulatorEncodingKind {
+    /// [`DefaultLimbsEncoding`].
+    #[default]
+    Limbs,
+    /// [`Limbs3x88Encoding`].
+    Limbs3x88,
+    /// [`CompressedLimbsEncoding`].
+    Compressed,
+    /// [`HashedLimbsEncoding`].
+    Hashed,
+}
+
+impl AccumulatorEncodingKind {
+    /// Returns number of instances taken by an accumulator.
+    pub fn num_instance(&self) -> usize {
+        match self {
+            Self::Limbs => 4 * LIMBS,
+            Self::Limbs3x88 => 4 * 3,
+            Self::Compressed => 2 * (LIMBS + 1),
+            Self::Hashed => 1,
+        }
+    }
+}
+
+/// Parameters of Poseidon used as hasher of transcript for native proofs.
+///
+/// Width `t` (with rate `t - 1`) is a const generic of the transcript, so
+/// only `3` and `5` are supported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "derive_serde", derive(Serialize, Deserialize))]
+pub struct PoseidonConfig {
+    /// Width of the state.
+    pub t: usize,
+    /// Number of full rounds.
+    pub r_f: usize,
+    /// Number of partial rounds.
+    pub r_p: usize,
+}
+
+impl PoseidonConfig {
+    pub fn new(t: usize, r_f: usize, r_p: usize) -> Self {
+        assert!(
+            matches!(t, 3 | 5),
+            "Unsupported Poseidon width {t}, only 3 and 5 are supported"
+        );
+        Self { t, r_f, r_p }
+    }
+}
+
+impl Default for PoseidonConfig {
+    fn default() -> Self {
+        Self::new(5, 8, 60)
+    }
+}
+
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "derive_serde", derive(Serialize, Deserialize))]
 pub struct Snark {
     pub protocol: PlonkProtocol<G1Affine>,
     pub instances: Vec<Vec<Fr>>,
     pub proof: Vec<u8>,
+    /// Poseidon parameters of transcript `proof` is created with.
+    #[cfg_attr(feature = "derive_serde", serde(default))]
+    pub poseidon_config: PoseidonConfig,
+    /// Number of proofs of the same circuit batched in `proof`, where
+    /// `instances` of each proof are concatenated in order.
+    #[cfg_attr(feature = "derive_serde", serde(default = "default_num_proof"))]
+    pub num_proof: usize,
+}
+
+#[cfg(feature = "derive_serde")]
+fn default_num_proof() -> usize {
+    1
 }
 
 impl Snark {
+    /// Creates a [`Snark`] whose proof is created with default
+    /// [`PoseidonConfig`].
     pub fn new(protocol: PlonkProtocol<G1Affine>, instances: Vec<Vec<Fr>>, proof: Vec<u8>) -> Self {
+        Self::new_with_poseidon_config(protocol, instances, proof, Default::default())
+    }
+
+    pub fn new_with_poseidon_config(
+        protocol: PlonkProtocol<G1Affine>,
+        instances: Vec<Vec<Fr>>,
+        proof: Vec<u8>,
+        poseidon_config: PoseidonConfig,
+    ) -> Self {
         Self {
             protocol,
             instances,
             proof,
+            poseidon_config,
+            num_proof: 1,
         }
     }
 
+    /// Returns [`Snark`] with `num_proof` proofs batched, which should match
+    /// the `num_proof` `protocol` is compiled with.
+    pub fn with_num_proof(mut self, num_proof: usize) -> Self {
+        assert!(num_proof > 0);
+        assert_eq!(self.instances.len() % num_proof, 0);
+        self.num_proof = num_proof;
+        self
+    }
+
     pub fn proof(&self) -> &[u8] {
         &self.proof[..]
     }
+
+    /// Returns instances of each batched proof.
+    pub fn instances_per_proof(&self) -> Vec<&[Vec<Fr>]> {
+        let num_instance = self.instances.len() / self.num_proof;
+        (0..self.num_proof)
+            .map(|idx| &self.instances[idx * num_instance..(idx + 1) * num_instance])
+            .collect()
+    }
 }
 
 impl From<Snark> for SnarkWitness {
@@ -72,6 +218,7 @@ impl From<Snark> for SnarkWitness {
                 .map(|instances| instances.into_iter().map(Value::known).collect_vec())
                 .collect(),
             proof: Value::known(snark.proof),
+            poseidon_config: snark.poseidon_config,
         }
     }
 }
@@ -81,6 +228,7 @@ pub struct SnarkWitness {
     protocol: PlonkProtocol<G1Affine>,
     instances: Vec<Vec<Value<Fr>>>,
     proof: Value<Vec<u8>>,
+    poseidon_config: PoseidonConfig,
 }
 
 impl SnarkWitness {
@@ -93,6 +241,7 @@ impl SnarkWitness {
                 .map(|instances| vec![Value::unknown(); instances.len()])
                 .collect(),
             proof: Value::unknown(),
+            poseidon_config: self.poseidon_config,
         }
     }
 
@@ -114,6 +263,13 @@ pub trait CircuitExt<F: Field>: Circuit<F> {
         None
     }
 
+    /// Scalars to be appended to the proof, e.g. limbs of accumulator
+    /// encoded by [`HashedLimbsEncoding`], which are read from transcript
+    /// after the proof.
+    fn proof_suffix(&self) -> Vec<F> {
+        vec![]
+    }
+
     /// Output the simple selector columns (before selector compression) of the circuit
     fn selectors(_: &Self::Config) -> Vec<Selector> {
         vec![]
@@ -0,0 +1,67 @@
+use crate::CircuitExt;
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    halo2curves::bn256::Fr,
+    plonk::{self, Advice, Circuit, Column, ConstraintSystem, Instance, Selector},
+    poly::Rotation,
+};
+
+#[derive(Clone, Copy)]
+pub struct SquareConfig {
+    q: Selector,
+    a: Column<Advice>,
+    instance: Column<Instance>,
+}
+
+/// Proves knowledge of square root of the instance.
+#[derive(Clone, Default)]
+pub struct Square(pub Fr);
+
+impl Circuit<Fr> for Square {
+    type Config = SquareConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "halo2_circuit_params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let q = meta.selector();
+        let a = meta.advice_column();
+        let instance = meta.instance_column();
+        meta.enable_equality(a);
+        meta.enable_equality(instance);
+        meta.create_gate("a·a == a_next", |meta| {
+            let q = meta.query_selector(q);
+            let a_cur = meta.query_advice(a, Rotation::cur());
+            let a_next = meta.query_advice(a, Rotation::next());
+            Some(q * (a_cur.clone() * a_cur - a_next))
+        });
+        SquareConfig { q, a, instance }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), plonk::Error> {
+        let square = layouter.assign_region(
+            || "",
+            |mut region| {
+                config.q.enable(&mut region, 0)?;
+                region.assign_advice(|| "", config.a, 0, || Value::known(self.0))?;
+                region.assign_advice(|| "", config.a, 1, || Value::known(self.0.square()))
+            },
+        )?;
+        layouter.constrain_instance(square.cell(), config.instance, 0)
+    }
+}
+
+impl CircuitExt<Fr> for Square {
+    fn instances(&self) -> Vec<Vec<Fr>> {
+        vec![vec![self.0.square()]]
+    }
+}
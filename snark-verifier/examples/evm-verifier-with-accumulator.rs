@@ -17,7 +17,7 @@ use itertools::Itertools;
 use rand::rngs::OsRng;
 use snark_verifier::{
     loader::{
-        evm::{self, deploy_and_call, encode_calldata, EvmLoader},
+        evm::{self, deploy_and_call, encode_calldata_unchecked, EvmLoader},
         native::NativeLoader,
     },
     pcs::kzg::{Gwc19, KzgAs, LimbsEncoding},
@@ -458,7 +458,8 @@ mod aggregation {
                     let accumulator_limbs = [accumulator.lhs, accumulator.rhs]
                         .iter()
                         .map(|ec_point| {
-                            loader.ecc_chip().assign_ec_point_to_limbs(
+                            LimbsEncodingInstructions::<_, LIMBS, BITS>::assign_ec_point_to_limbs(
+                                &*loader.ecc_chip(),
                                 &mut loader.ctx_mut(),
                                 ec_point.assigned(),
                             )
@@ -586,7 +587,7 @@ fn gen_aggregation_evm_verifier(
 }
 
 fn evm_verify(deployment_code: Vec<u8>, instances: Vec<Vec<Fr>>, proof: Vec<u8>) {
-    let calldata = encode_calldata(&instances, &proof);
+    let calldata = encode_calldata_unchecked(&instances, &proof);
     let gas_cost = deploy_and_call(deployment_code, calldata).unwrap();
     dbg!(gas_cost);
 }
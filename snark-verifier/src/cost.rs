@@ -15,6 +15,35 @@ pub struct Cost {
     pub num_msm: usize,
     /// Number of pairings to perform.
     pub num_pairing: usize,
+    /// Number of scalar field additions (including subtractions and
+    /// negations).
+    pub num_scalar_add: usize,
+    /// Number of scalar field multiplications.
+    pub num_scalar_mul: usize,
+    /// Number of scalar field inversions, after batch inversion.
+    pub num_inversion: usize,
+    /// Number of challenges squeezed from transcript.
+    pub num_squeeze: usize,
+}
+
+impl Cost {
+    /// Returns number of field elements absorbed into transcript, assuming
+    /// each commitment is absorbed as its 2 coordinates.
+    pub fn num_absorption(&self) -> usize {
+        self.num_instance + 2 * self.num_commitment + self.num_evaluation
+    }
+
+    /// Returns size of calldata in bytes, assuming each commitment is encoded
+    /// as uncompressed point and each scalar takes 32 bytes.
+    pub fn calldata_size(&self) -> usize {
+        self.num_commitment * 64 + (self.num_evaluation + self.num_instance) * 32
+    }
+
+    /// Returns size of calldata in bytes, assuming each commitment is encoded
+    /// as compressed point and each scalar takes 32 bytes.
+    pub fn compressed_calldata_size(&self) -> usize {
+        (self.num_commitment + self.num_evaluation + self.num_instance) * 32
+    }
 }
 
 impl Add<Cost> for Cost {
@@ -26,6 +55,10 @@ impl Add<Cost> for Cost {
         self.num_evaluation += rhs.num_evaluation;
         self.num_msm += rhs.num_msm;
         self.num_pairing += rhs.num_pairing;
+        self.num_scalar_add += rhs.num_scalar_add;
+        self.num_scalar_mul += rhs.num_scalar_mul;
+        self.num_inversion += rhs.num_inversion;
+        self.num_squeeze += rhs.num_squeeze;
         self
     }
 }
@@ -2,10 +2,17 @@
 
 mod code;
 pub(crate) mod loader;
+mod optimizer;
 pub(crate) mod util;
 
-pub use loader::{EcPoint, EvmLoader, Scalar};
+pub use loader::{EcPoint, EvmLoader, EvmLoaderConfig, Scalar};
+pub(crate) use optimizer::MemoryPtr;
 pub use util::{
-    compile_yul, deploy_and_call, encode_calldata, estimate_gas, fe_to_u256, modulus, u256_to_fe,
-    Address, B256, U256, U512,
+    compile_yul, decode_error_message, deploy_and_call, deploy_and_call_with_error_messages,
+    deploy_and_call_with_output, encode_abi_calldata, encode_calldata_unchecked,
+    encode_calldata_with_preimage, encode_ec_points, estimate_gas, estimate_gas_cost,
+    estimate_gas_cost_compressed, fe_to_u256, modulus, u256_to_fe, verify_function_selector,
+    Address, GasCost, PublicInputHash, B256, U256, U512, VERIFY_FUNCTION_SIGNATURE,
 };
+#[cfg(feature = "system_halo2")]
+pub use util::{encode_calldata, encode_calldata_with_compression};
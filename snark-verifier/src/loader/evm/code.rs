@@ -1,4 +1,8 @@
+use crate::loader::evm::optimizer::relocate;
+use std::ops::Range;
+
 pub enum Precompiled {
+    Sha256 = 0x02,
     BigModExp = 0x05,
     Bn254Add = 0x6,
     Bn254ScalarMul = 0x7,
@@ -8,17 +12,23 @@ pub enum Precompiled {
 #[derive(Clone, Debug)]
 pub struct YulCode {
     // runtime code area
-    runtime: String,
+    runtime: Vec<String>,
 }
 
 impl YulCode {
     pub fn new() -> Self {
         YulCode {
-            runtime: String::new(),
+            runtime: Vec::new(),
         }
     }
 
-    pub fn code(&self, base_modulus: String, scalar_modulus: String) -> String {
+    pub fn code(
+        &self,
+        base_modulus: String,
+        scalar_modulus: String,
+        allocations: &[Range<usize>],
+        reuse_memory: bool,
+    ) -> String {
         format!(
             "
         object \"plonk_verifier\" {{
@@ -50,19 +60,23 @@ impl YulCode {
                             let x_cube := mulmod(x_square, x, {base_modulus})
                             let x_cube_plus_3 := addmod(x_cube, 3, {base_modulus})
                             let is_affine:bool := eq(x_cube_plus_3, y_square)
-                            valid := and(valid, is_affine)
+                            let is_identity:bool := and(iszero(x), iszero(y))
+                            valid := and(valid, or(is_affine, is_identity))
                         }}
                     }}
                     {}
                 }}
             }}
         }}",
-            self.runtime
+            relocate(&self.runtime, allocations, reuse_memory).join("\n")
         )
     }
 
-    pub fn runtime_append(&mut self, mut code: String) {
-        code.push('\n');
-        self.runtime.push_str(&code);
+    pub fn runtime_prepend(&mut self, code: String) {
+        self.runtime.insert(0, code);
+    }
+
+    pub fn runtime_append(&mut self, code: String) {
+        self.runtime.push(code);
     }
 }
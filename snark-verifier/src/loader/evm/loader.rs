@@ -2,23 +2,25 @@ use crate::{
     loader::{
         evm::{
             code::{Precompiled, YulCode},
-            fe_to_u256, modulus, u256_to_fe, U256, U512,
+            fe_to_u256, modulus, u256_to_fe, verify_function_selector, MemoryPtr, PublicInputHash,
+            U256, U512,
         },
         EcPointLoader, LoadedEcPoint, LoadedScalar, Loader, ScalarLoader,
     },
     util::{
-        arithmetic::{CurveAffine, FieldOps, PrimeField},
+        arithmetic::{ec_point_to_coordinates, CurveAffine, FieldOps, PrimeField},
         Itertools,
     },
     Error,
 };
 use hex;
+use num_integer::Integer;
 use std::{
     cell::RefCell,
     collections::HashMap,
     fmt::{self, Debug},
     iter,
-    ops::{Add, AddAssign, DerefMut, Mul, MulAssign, Neg, Sub, SubAssign},
+    ops::{Add, AddAssign, DerefMut, Mul, MulAssign, Neg, Range, Sub, SubAssign},
     rc::Rc,
 };
 
@@ -48,6 +50,43 @@ impl<T: Debug> Value<T> {
     }
 }
 
+/// Configuration of [`EvmLoader`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EvmLoaderConfig {
+    /// Whether to reject non-canonical scalars in calldata. Otherwise they are
+    /// reduced by scalar modulus, which makes calldata malleable.
+    pub strict_calldata: bool,
+    /// Whether to generate verifier exposing `verify(uint256[],bytes)` that
+    /// returns `true` if proof is valid and `false` otherwise, instead of
+    /// taking raw calldata and reverting on invalid proof.
+    ///
+    /// It still reverts if selector mismatches or calldata is not encoded by
+    /// [`encode_abi_calldata`](crate::loader::evm::encode_abi_calldata) with
+    /// expected number of instances and length of proof.
+    pub abi: bool,
+    /// Whether to generate verifier that reverts at the first failing check
+    /// with its error code as a 32 bytes revert data, which could be decoded
+    /// by [`EvmLoader::error_messages`]. With [`EvmLoaderConfig::abi`] it also
+    /// reverts instead of returning `false`. It costs more gas so is only
+    /// meant for debugging.
+    pub debug: bool,
+    /// Whether to let intermediate values not alive at the same time share
+    /// memory, which is decided by memory accesses of generated code when it's
+    /// finalized. It saves memory expansion gas and code size.
+    pub reuse_memory: bool,
+}
+
+impl Default for EvmLoaderConfig {
+    fn default() -> Self {
+        Self {
+            strict_calldata: true,
+            abi: false,
+            debug: false,
+            reuse_memory: true,
+        }
+    }
+}
+
 /// `Loader` implementation for generating yul code as EVM verifier.
 #[derive(Clone, Debug)]
 pub struct EvmLoader {
@@ -55,7 +94,14 @@ pub struct EvmLoader {
     scalar_modulus: U256,
     code: RefCell<YulCode>,
     ptr: RefCell<usize>,
+    allocations: RefCell<Vec<Range<usize>>>,
     cache: RefCell<HashMap<String, usize>>,
+    config: EvmLoaderConfig,
+    instance_calldata_offset: RefCell<usize>,
+    proof_calldata_offset: RefCell<Option<usize>>,
+    calldata_end: RefCell<usize>,
+    error_messages: RefCell<Vec<String>>,
+    return_data: RefCell<Option<(usize, usize)>>,
 }
 
 fn hex_encode_u256(value: &U256) -> String {
@@ -63,8 +109,47 @@ fn hex_encode_u256(value: &U256) -> String {
 }
 
 impl EvmLoader {
-    /// Initialize a [`EvmLoader`] with base and scalar field.
+    /// Initialize a [`EvmLoader`] with base and scalar field, which rejects
+    /// non-canonical scalars in calldata.
     pub fn new<Base, Scalar>() -> Rc<Self>
+    where
+        Base: PrimeField<Repr = [u8; 0x20]>,
+        Scalar: PrimeField<Repr = [u8; 32]>,
+    {
+        Self::new_with_config::<Base, Scalar>(Default::default())
+    }
+
+    /// Initialize a [`EvmLoader`] with base and scalar field. When
+    /// `strict_calldata` is `false`, scalars in calldata are reduced by scalar
+    /// modulus instead of being rejected when non-canonical, which makes
+    /// calldata malleable.
+    pub fn new_with_strict_calldata<Base, Scalar>(strict_calldata: bool) -> Rc<Self>
+    where
+        Base: PrimeField<Repr = [u8; 0x20]>,
+        Scalar: PrimeField<Repr = [u8; 32]>,
+    {
+        Self::new_with_config::<Base, Scalar>(EvmLoaderConfig {
+            strict_calldata,
+            ..Default::default()
+        })
+    }
+
+    /// Initialize a [`EvmLoader`] with base and scalar field, which generates
+    /// verifier with ABI as described in [`EvmLoaderConfig::abi`].
+    pub fn new_with_abi<Base, Scalar>() -> Rc<Self>
+    where
+        Base: PrimeField<Repr = [u8; 0x20]>,
+        Scalar: PrimeField<Repr = [u8; 32]>,
+    {
+        Self::new_with_config::<Base, Scalar>(EvmLoaderConfig {
+            abi: true,
+            ..Default::default()
+        })
+    }
+
+    /// Initialize a [`EvmLoader`] with base and scalar field and given
+    /// [`EvmLoaderConfig`].
+    pub fn new_with_config<Base, Scalar>(config: EvmLoaderConfig) -> Rc<Self>
     where
         Base: PrimeField<Repr = [u8; 0x20]>,
         Scalar: PrimeField<Repr = [u8; 32]>,
@@ -72,33 +157,95 @@ impl EvmLoader {
         let base_modulus = modulus::<Base>();
         let scalar_modulus = modulus::<Scalar>();
         let code = YulCode::new();
+        let instance_calldata_offset = if config.abi {
+            // Selector, offsets of `instances` and `proof` and length of
+            // `instances`.
+            0x64
+        } else {
+            0
+        };
 
         Rc::new(Self {
             base_modulus,
             scalar_modulus,
             code: RefCell::new(code),
             ptr: Default::default(),
+            allocations: Default::default(),
             cache: Default::default(),
+            config,
+            instance_calldata_offset: RefCell::new(instance_calldata_offset),
+            proof_calldata_offset: Default::default(),
+            calldata_end: Default::default(),
+            error_messages: Default::default(),
+            return_data: Default::default(),
         })
     }
 
     /// Returns generated yul code.
     pub fn yul_code(self: &Rc<Self>) -> String {
-        let code = "
-            if not(success) { revert(0, 0) }
-            return(0, 0)"
-            .to_string();
+        let header_check = if self.config.abi {
+            Some(self.abi_header_check())
+        } else if self.config.debug {
+            Some(self.calldata_size_check())
+        } else {
+            None
+        };
+        if let Some(header_check) = header_check {
+            self.code.borrow_mut().runtime_prepend(header_check);
+        }
+        let code = if self.config.abi {
+            "
+            mstore(0x00, success)
+            return(0x00, 0x20)"
+                .to_string()
+        } else {
+            let (ptr, len) = match *self.return_data.borrow() {
+                Some((ptr, len)) => (MemoryPtr(ptr).to_string(), format!("{len:#x}")),
+                None => ("0".to_string(), "0".to_string()),
+            };
+            format!(
+                "
+            if not(success) {{ revert(0, 0) }}
+            return({ptr}, {len})"
+            )
+        };
         self.code.borrow_mut().runtime_append(code);
         self.code.borrow().code(
             hex_encode_u256(&self.base_modulus),
             hex_encode_u256(&self.scalar_modulus),
+            &self.allocations.borrow(),
+            self.config.reuse_memory,
         )
     }
 
+    /// Returns error messages of checks in verifier generated in debug mode,
+    /// where the error code in revert data is the index of its message.
+    pub fn error_messages(&self) -> Vec<String> {
+        self.error_messages.borrow().clone()
+    }
+
+    /// Makes generated verifier return `ec_points` as concatenated `(x, y)`
+    /// coordinates when all checks pass, e.g. to output accumulators of
+    /// [`PlonkSuccinctVerifier`] to be decided later together with others.
+    ///
+    /// [`PlonkSuccinctVerifier`]: crate::verifier::plonk::PlonkSuccinctVerifier
+    pub fn return_ec_points(self: &Rc<Self>, ec_points: &[EcPoint]) {
+        assert!(!self.config.abi, "Verifier with ABI only returns bool");
+        let ptr = self.allocate(0x40 * ec_points.len());
+        for (idx, ec_point) in ec_points.iter().enumerate() {
+            self.copy_ec_point(ec_point, ptr + idx * 0x40);
+        }
+        *self.return_data.borrow_mut() = Some((ptr, 0x40 * ec_points.len()));
+    }
+
     /// Allocates memory chunk with given `size` and returns pointer.
+    ///
+    /// The pointer might be relocated in generated code if
+    /// [`EvmLoaderConfig::reuse_memory`] is enabled.
     pub fn allocate(self: &Rc<Self>, size: usize) -> usize {
         let ptr = *self.ptr.borrow();
         *self.ptr.borrow_mut() += size;
+        self.allocations.borrow_mut().push(ptr..ptr + size);
         ptr
     }
 
@@ -106,6 +253,138 @@ impl EvmLoader {
         *self.ptr.borrow()
     }
 
+    /// Returns calldata offset of the first instance of proof being read.
+    pub(crate) fn instance_calldata_offset(&self) -> usize {
+        *self.instance_calldata_offset.borrow()
+    }
+
+    /// Returns calldata offset of the first instance of proof to be read,
+    /// which is right after the previous proof if any has been read, so
+    /// proofs of several protocols could be verified by the same verifier.
+    pub(crate) fn next_instance_calldata_offset(&self) -> usize {
+        if self.proof_calldata_offset.borrow().is_some() {
+            assert!(
+                !self.config.abi,
+                "Verifier with ABI only takes single proof"
+            );
+            *self.instance_calldata_offset.borrow_mut() = *self.calldata_end.borrow();
+            *self.proof_calldata_offset.borrow_mut() = None;
+        }
+        self.instance_calldata_offset()
+    }
+
+    /// Returns calldata offset of proof given calldata offset right after the
+    /// last instance.
+    pub(crate) fn proof_calldata_offset(&self, instance_end: usize) -> usize {
+        // Skip length of `proof` if ABI encoded.
+        let offset = if self.config.abi {
+            instance_end + 0x20
+        } else {
+            instance_end
+        };
+        *self.proof_calldata_offset.borrow_mut() = Some(offset);
+        offset
+    }
+
+    fn track_calldata(&self, offset: usize, len: usize) {
+        let mut calldata_end = self.calldata_end.borrow_mut();
+        *calldata_end = (*calldata_end).max(offset + len);
+    }
+
+    /// Returns code to check `condition`, which folds it into `success`, or
+    /// reverts with error code of `message` if in debug mode.
+    fn check(&self, condition: &str, message: impl FnOnce() -> String) -> String {
+        if self.config.debug {
+            let mut error_messages = self.error_messages.borrow_mut();
+            let code = error_messages.len();
+            error_messages.push(message());
+            format!("if iszero({condition}) {{ mstore(0x00, {code:#x}) revert(0x00, 0x20) }}")
+        } else {
+            format!("success := and({condition}, success)")
+        }
+    }
+
+    /// Returns description of element in calldata at `offset` for error
+    /// message.
+    fn calldata_element(&self, kind: &str, offset: usize) -> String {
+        match *self.proof_calldata_offset.borrow() {
+            Some(proof_offset) if offset >= proof_offset => format!(
+                "{kind} at proof offset {:#x} (calldata offset {offset:#x})",
+                offset - proof_offset
+            ),
+            _ => format!(
+                "{kind} of instance {} (calldata offset {offset:#x})",
+                (offset - self.instance_calldata_offset()) / 0x20
+            ),
+        }
+    }
+
+    fn calldata_size_check(&self) -> String {
+        let calldata_size = *self.calldata_end.borrow();
+        self.check(&format!("eq(calldatasize(), {calldata_size:#x})"), || {
+            format!("Unexpected calldata length, expected {calldata_size:#x} bytes")
+        })
+    }
+
+    fn abi_header_check(&self) -> String {
+        let selector = hex_encode_u256(&U256::from_be_slice(&verify_function_selector()));
+        let proof_offset = self
+            .proof_calldata_offset
+            .borrow()
+            .expect("Instances should be loaded before proof");
+        let num_instance = (proof_offset - 0x84) / 0x20;
+        let proof_len = *self.calldata_end.borrow() - proof_offset;
+        let calldata_size = proof_offset + Integer::next_multiple_of(&proof_len, &0x20);
+        let conditions = [
+            (
+                format!("eq(shr(224, calldataload(0x00)), {selector})"),
+                "Function selector mismatch".to_string(),
+            ),
+            (
+                format!("eq(calldatasize(), {calldata_size:#x})"),
+                format!("Unexpected calldata length, expected {calldata_size:#x} bytes"),
+            ),
+            (
+                "eq(calldataload(0x04), 0x40)".to_string(),
+                "Unexpected offset of instances".to_string(),
+            ),
+            (
+                format!("eq(calldataload(0x24), {:#x})", proof_offset - 0x24),
+                "Unexpected offset of proof".to_string(),
+            ),
+            (
+                format!("eq(calldataload(0x44), {num_instance:#x})"),
+                format!("Unexpected number of instances, expected {num_instance}"),
+            ),
+            (
+                format!(
+                    "eq(calldataload({:#x}), {proof_len:#x})",
+                    proof_offset - 0x20
+                ),
+                format!("Unexpected length of proof, expected {proof_len:#x} bytes"),
+            ),
+        ];
+        if self.config.debug {
+            conditions
+                .into_iter()
+                .map(|(condition, message)| self.check(&condition, || message))
+                .join("\n")
+        } else {
+            let valid = conditions
+                .into_iter()
+                .map(|(condition, _)| format!("valid := and({condition}, valid)"))
+                .join("\n            ");
+            format!(
+                "
+        {{
+            let valid := true
+            {valid}
+            if iszero(valid) {{ revert(0, 0) }}
+        }}"
+            )
+        }
+    }
+
     pub(crate) fn code_mut(&self) -> impl DerefMut<Target = YulCode> + '_ {
         self.code.borrow_mut()
     }
@@ -116,7 +395,7 @@ impl EvmLoader {
                 format!("{constant}")
             }
             Value::Memory(ptr) => {
-                format!("mload({ptr:#x})")
+                format!("mload({})", MemoryPtr(ptr))
             }
             Value::Negated(value) => {
                 let v = self.push(&self.scalar(*value));
@@ -135,36 +414,197 @@ impl EvmLoader {
         }
     }
 
-    /// Calldata load a field element.
+    /// Calldata load a field element, and validate it's canonical if
+    /// `strict_calldata` is enabled.
     pub fn calldataload_scalar(self: &Rc<Self>, offset: usize) -> Scalar {
+        self.track_calldata(offset, 0x20);
         let ptr = self.allocate(0x20);
-        let code = format!("mstore({ptr:#x}, mod(calldataload({offset:#x}), f_q))");
+        let code = if self.config.strict_calldata {
+            let check = self.check("lt(value, f_q)", || {
+                format!("Non-canonical {}", self.calldata_element("scalar", offset))
+            });
+            format!(
+                "
+        {{
+            let value := calldataload({offset:#x})
+            {check}
+            mstore({ptr}, value)
+        }}",
+                ptr = MemoryPtr(ptr),
+            )
+        } else {
+            format!(
+                "mstore({}, mod(calldataload({offset:#x}), f_q))",
+                MemoryPtr(ptr)
+            )
+        };
         self.code.borrow_mut().runtime_append(code);
         self.scalar(Value::Memory(ptr))
     }
 
-    /// Calldata load an elliptic curve point and validate it's on affine plane.
-    /// Note that identity will cause the verification to fail.
+    /// Calldata load an elliptic curve point and validate it's on affine plane
+    /// or it's identity encoded as `(0, 0)`.
     pub fn calldataload_ec_point(self: &Rc<Self>, offset: usize) -> EcPoint {
+        self.track_calldata(offset, 0x40);
         let x_ptr = self.allocate(0x40);
         let y_ptr = x_ptr + 0x20;
         let x_cd_ptr = offset;
         let y_cd_ptr = offset + 0x20;
-        let validate_code = self.validate_ec_point();
+        let validate_code = self.validate_ec_point(|| {
+            format!(
+                "Invalid {}",
+                self.calldata_element("elliptic curve point", offset)
+            )
+        });
         let code = format!(
             "
         {{
             let x := calldataload({x_cd_ptr:#x})
-            mstore({x_ptr:#x}, x)
+            mstore({x_ptr}, x)
             let y := calldataload({y_cd_ptr:#x})
-            mstore({y_ptr:#x}, y)
+            mstore({y_ptr}, y)
             {validate_code}
-        }}"
+        }}",
+            x_ptr = MemoryPtr(x_ptr),
+            y_ptr = MemoryPtr(y_ptr),
+        );
+        self.code.borrow_mut().runtime_append(code);
+        self.ec_point(Value::Memory(x_ptr))
+    }
+
+    /// Calldata load a compressed elliptic curve point, which is encoded as
+    /// 32 bytes x coordinate in big-endian with the most significant bit
+    /// indicating `y` is odd and the second most significant bit indicating
+    /// it's identity, then decompress it by computing square root of
+    /// `x^3 + 3` with `modexp` precompile.
+    ///
+    /// It requires base field modulus `p` to satisfy `p = 3 mod 4` and to
+    /// have at most 254 bits, which is the case of bn254.
+    pub fn calldataload_ec_point_compressed(self: &Rc<Self>, offset: usize) -> EcPoint {
+        assert_eq!(self.base_modulus % U256::from(4), U256::from(3));
+        assert!(self.base_modulus.bit_len() <= 254);
+
+        self.track_calldata(offset, 0x20);
+        // Allocate the whole `modexp` input, whose first 2 words are then
+        // overwritten by the decompressed point.
+        let x_ptr = self.allocate(0xc0);
+        let y_ptr = x_ptr + 0x20;
+        let [x_mask, identity, sqrt_exp] = [
+            (U256::from(1) << 254) - U256::from(1),
+            U256::from(1) << 254,
+            (self.base_modulus + U256::from(1)) >> 2,
+        ]
+        .map(|value| hex_encode_u256(&value));
+        let element = self.calldata_element("compressed elliptic curve point", offset);
+        let [x_check, modexp_check, y_check, identity_check] = [
+            ("lt(x, f_p)", "Non-canonical x coordinate of"),
+            (
+                &*format!(
+                    "eq(staticcall(gas(), {:#x}, {}, 0xc0, {}, 0x20), 1)",
+                    Precompiled::BigModExp as usize,
+                    MemoryPtr(x_ptr),
+                    MemoryPtr(y_ptr),
+                ),
+                "Precompile modexp failed to decompress",
+            ),
+            ("eq(mulmod(y, y, f_p), y_square)", "Invalid"),
+            (
+                &*format!("eq(compressed, {identity})"),
+                "Non-canonical identity of",
+            ),
+        ]
+        .map(|(condition, message)| self.check(condition, || format!("{message} {element}")));
+        let code = format!(
+            "
+        {{
+            let compressed := calldataload({offset:#x})
+            let x := and(compressed, {x_mask})
+            switch and(compressed, {identity})
+            case 0 {{
+                {x_check}
+                let y_square := addmod(mulmod(mulmod(x, x, f_p), x, f_p), 3, f_p)
+                mstore({x_ptr}, 0x20)
+                mstore({}, 0x20)
+                mstore({}, 0x20)
+                mstore({}, y_square)
+                mstore({}, {sqrt_exp})
+                mstore({}, f_p)
+                {modexp_check}
+                let y := mload({y_ptr})
+                {y_check}
+                if iszero(eq(and(y, 1), shr(255, compressed))) {{
+                    y := sub(f_p, y)
+                }}
+                mstore({x_ptr}, x)
+                mstore({y_ptr}, y)
+            }}
+            default {{
+                {identity_check}
+                mstore({x_ptr}, 0)
+                mstore({y_ptr}, 0)
+            }}
+        }}",
+            MemoryPtr(x_ptr + 0x20),
+            MemoryPtr(x_ptr + 0x40),
+            MemoryPtr(x_ptr + 0x60),
+            MemoryPtr(x_ptr + 0x80),
+            MemoryPtr(x_ptr + 0xa0),
+            x_ptr = MemoryPtr(x_ptr),
+            y_ptr = MemoryPtr(y_ptr),
         );
         self.code.borrow_mut().runtime_append(code);
         self.ec_point(Value::Memory(x_ptr))
     }
 
+    /// Calldata load `len` bytes of public inputs, hash them with `hash` and
+    /// returns the digest as [`Scalar`] in the same way as
+    /// [`PublicInputHash::digest`].
+    ///
+    /// It's not supported with [`EvmLoaderConfig::abi`], since preimage has no
+    /// place in `verify(uint256[],bytes)`.
+    pub fn calldataload_public_input_digest(
+        self: &Rc<Self>,
+        offset: usize,
+        len: usize,
+        hash: PublicInputHash,
+    ) -> Scalar {
+        assert!(
+            !self.config.abi,
+            "Public input hash is not supported by verifier with ABI"
+        );
+        self.track_calldata(offset, len);
+        let ptr = self.allocate(0x20);
+        // Memory after `ptr` is not allocated yet, so it's safe to be used to
+        // hold the preimage.
+        let preimage_ptr = self.ptr();
+        let mask = hex_encode_u256(
+            &((U256::from(1) << (self.scalar_modulus.bit_len() - 1)) - U256::from(1)),
+        );
+        let (ptr, preimage_ptr) = (MemoryPtr(ptr), MemoryPtr(preimage_ptr));
+        let hash_code = match hash {
+            PublicInputHash::Keccak256 => {
+                format!("mstore({ptr}, keccak256({preimage_ptr}, {len}))")
+            }
+            PublicInputHash::Sha256 => self.check(
+                &format!(
+                    "eq(staticcall(gas(), {:#x}, {preimage_ptr}, {len}, {ptr}, 0x20), 1)",
+                    Precompiled::Sha256 as usize
+                ),
+                || "Precompile sha256 failed to hash public inputs".to_string(),
+            ),
+        };
+        let code = format!(
+            "
+        {{
+            calldatacopy({preimage_ptr}, {offset:#x}, {len})
+            {hash_code}
+            mstore({ptr}, and(mload({ptr}), {mask}))
+        }}"
+        );
+        self.code.borrow_mut().runtime_append(code);
+        self.scalar(Value::Memory(ptr.0))
+    }
+
     /// Decode an elliptic curve point from limbs.
     pub fn ec_point_from_limbs<const LIMBS: usize, const BITS: usize>(
         self: &Rc<Self>,
@@ -183,7 +623,7 @@ impl EvmLoader {
             }
         }
         let x_ptr = ptr;
-        code.push_str(format!("mstore({x_ptr}, x)\n").as_str());
+        code.push_str(format!("mstore({}, x)\n", MemoryPtr(x_ptr)).as_str());
         for (idx, limb) in y_limbs.iter().enumerate() {
             let limb_i = self.push(limb);
             let shift = idx * BITS;
@@ -194,8 +634,9 @@ impl EvmLoader {
             }
         }
         let y_ptr = ptr + 0x20;
-        code.push_str(format!("mstore({y_ptr}, y)\n").as_str());
-        let validate_code = self.validate_ec_point();
+        code.push_str(format!("mstore({}, y)\n", MemoryPtr(y_ptr)).as_str());
+        let validate_code = self
+            .validate_ec_point(|| "Invalid elliptic curve point decoded from limbs".to_string());
         let code = format!(
             "{{
             {code}
@@ -206,8 +647,87 @@ impl EvmLoader {
         self.ec_point(Value::Memory(ptr))
     }
 
-    fn validate_ec_point(self: &Rc<Self>) -> String {
-        "success := and(validate_ec_point(x, y), success)".to_string()
+    /// Decode an elliptic curve point from limbs of x coordinate and sign of
+    /// y coordinate, where sign is `1` if y coordinate is odd, otherwise `0`.
+    pub fn ec_point_from_compressed_limbs<const LIMBS: usize, const BITS: usize>(
+        self: &Rc<Self>,
+        x_limbs: [&Scalar; LIMBS],
+        sign: &Scalar,
+    ) -> EcPoint {
+        assert_eq!(self.base_modulus % U256::from(4), U256::from(3));
+
+        // Allocate the whole `modexp` input, whose first 2 words are then
+        // overwritten by the decompressed point.
+        let x_ptr = self.allocate(0xc0);
+        let y_ptr = x_ptr + 0x20;
+        let sqrt_exp = hex_encode_u256(&((self.base_modulus + U256::from(1)) >> 2));
+        let mut code = String::new();
+        for (idx, limb) in x_limbs.iter().enumerate() {
+            let limb_i = self.push(limb);
+            let shift = idx * BITS;
+            if idx == 0 {
+                code.push_str(format!("let x := {limb_i}\n").as_str());
+            } else {
+                code.push_str(format!("x := add(x, shl({shift}, {limb_i}))\n").as_str());
+            }
+        }
+        let sign = self.push(sign);
+        let [x_check, sign_check, modexp_check, y_check] = [
+            ("lt(x, f_p)", "Non-canonical x coordinate of"),
+            ("lt(sign, 2)", "Invalid sign of"),
+            (
+                &*format!(
+                    "eq(staticcall(gas(), {:#x}, {}, 0xc0, {}, 0x20), 1)",
+                    Precompiled::BigModExp as usize,
+                    MemoryPtr(x_ptr),
+                    MemoryPtr(y_ptr),
+                ),
+                "Precompile modexp failed to decompress",
+            ),
+            ("eq(mulmod(y, y, f_p), y_square)", "Invalid"),
+        ]
+        .map(|(condition, message)| {
+            self.check(condition, || {
+                format!("{message} elliptic curve point decoded from compressed limbs")
+            })
+        });
+        let code = format!(
+            "
+        {{
+            {code}
+            let sign := {sign}
+            {x_check}
+            {sign_check}
+            let y_square := addmod(mulmod(mulmod(x, x, f_p), x, f_p), 3, f_p)
+            mstore({x_ptr}, 0x20)
+            mstore({}, 0x20)
+            mstore({}, 0x20)
+            mstore({}, y_square)
+            mstore({}, {sqrt_exp})
+            mstore({}, f_p)
+            {modexp_check}
+            let y := mload({y_ptr})
+            {y_check}
+            if iszero(eq(and(y, 1), sign)) {{
+                y := sub(f_p, y)
+            }}
+            mstore({x_ptr}, x)
+            mstore({y_ptr}, y)
+        }}",
+            MemoryPtr(x_ptr + 0x20),
+            MemoryPtr(x_ptr + 0x40),
+            MemoryPtr(x_ptr + 0x60),
+            MemoryPtr(x_ptr + 0x80),
+            MemoryPtr(x_ptr + 0xa0),
+            x_ptr = MemoryPtr(x_ptr),
+            y_ptr = MemoryPtr(y_ptr),
+        );
+        self.code.borrow_mut().runtime_append(code);
+        self.ec_point(Value::Memory(x_ptr))
+    }
+
+    fn validate_ec_point(self: &Rc<Self>, message: impl FnOnce() -> String) -> String {
+        self.check("validate_ec_point(x, y)", message)
     }
 
     pub(crate) fn scalar(self: &Rc<Self>, value: Value<U256>) -> Scalar {
@@ -229,7 +749,7 @@ impl EvmLoader {
                 let ptr = self.allocate(0x20);
                 self.code
                     .borrow_mut()
-                    .runtime_append(format!("mstore({ptr:#x}, {v})"));
+                    .runtime_append(format!("mstore({}, {v})", MemoryPtr(ptr)));
                 self.cache.borrow_mut().insert(identifier, ptr);
                 ptr
             };
@@ -252,7 +772,11 @@ impl EvmLoader {
     /// hash.
     pub fn keccak256(self: &Rc<Self>, ptr: usize, len: usize) -> usize {
         let hash_ptr = self.allocate(0x20);
-        let code = format!("mstore({hash_ptr:#x}, keccak256({ptr:#x}, {len}))");
+        let code = format!(
+            "mstore({}, keccak256({}, {len}))",
+            MemoryPtr(hash_ptr),
+            MemoryPtr(ptr)
+        );
         self.code.borrow_mut().runtime_append(code);
         hash_ptr
     }
@@ -262,7 +786,7 @@ impl EvmLoader {
         let scalar = self.push(scalar);
         self.code
             .borrow_mut()
-            .runtime_append(format!("mstore({ptr:#x}, {scalar})"));
+            .runtime_append(format!("mstore({}, {scalar})", MemoryPtr(ptr)));
     }
 
     /// Allocates a new field element and copies the given value into it.
@@ -272,29 +796,26 @@ impl EvmLoader {
         self.scalar(Value::Memory(ptr))
     }
 
-    /// Allocates a new elliptic curve point and copies the given value into it.
-    pub fn dup_ec_point(self: &Rc<Self>, value: &EcPoint) -> EcPoint {
-        let ptr = self.allocate(0x40);
+    /// Copies an elliptic curve point into given `ptr`.
+    pub fn copy_ec_point(self: &Rc<Self>, value: &EcPoint, ptr: usize) {
+        let x_ptr = MemoryPtr(ptr);
+        let y_ptr = MemoryPtr(ptr + 0x20);
         match value.value {
             Value::Constant((x, y)) => {
-                let x_ptr = ptr;
-                let y_ptr = ptr + 0x20;
                 let x = hex_encode_u256(&x);
                 let y = hex_encode_u256(&y);
                 let code = format!(
-                    "mstore({x_ptr:#x}, {x})
-                    mstore({y_ptr:#x}, {y})"
+                    "mstore({x_ptr}, {x})
+                    mstore({y_ptr}, {y})"
                 );
                 self.code.borrow_mut().runtime_append(code);
             }
             Value::Memory(src_ptr) => {
-                let x_ptr = ptr;
-                let y_ptr = ptr + 0x20;
-                let src_x = src_ptr;
-                let src_y = src_ptr + 0x20;
+                let src_x = MemoryPtr(src_ptr);
+                let src_y = MemoryPtr(src_ptr + 0x20);
                 let code = format!(
-                    "mstore({x_ptr:#x}, mload({src_x:#x}))
-                    mstore({y_ptr:#x}, mload({src_y:#x}))"
+                    "mstore({x_ptr}, mload({src_x}))
+                    mstore({y_ptr}, mload({src_y}))"
                 );
                 self.code.borrow_mut().runtime_append(code);
             }
@@ -302,18 +823,32 @@ impl EvmLoader {
                 unreachable!()
             }
         }
+    }
+
+    /// Allocates a new elliptic curve point and copies the given value into it.
+    pub fn dup_ec_point(self: &Rc<Self>, value: &EcPoint) -> EcPoint {
+        let ptr = self.allocate(0x40);
+        self.copy_ec_point(value, ptr);
         self.ec_point(Value::Memory(ptr))
     }
 
     fn staticcall(self: &Rc<Self>, precompile: Precompiled, cd_ptr: usize, rd_ptr: usize) {
-        let (cd_len, rd_len) = match precompile {
-            Precompiled::BigModExp => (0xc0, 0x20),
-            Precompiled::Bn254Add => (0x80, 0x40),
-            Precompiled::Bn254ScalarMul => (0x60, 0x40),
-            Precompiled::Bn254Pairing => (0x180, 0x20),
+        let (name, cd_len, rd_len) = match precompile {
+            Precompiled::Sha256 => unreachable!("Input of sha256 has variable length"),
+            Precompiled::BigModExp => ("modexp", 0xc0, 0x20),
+            Precompiled::Bn254Add => ("ecAdd", 0x80, 0x40),
+            Precompiled::Bn254ScalarMul => ("ecMul", 0x60, 0x40),
+            Precompiled::Bn254Pairing => ("ecPairing", 0x180, 0x20),
         };
         let a = precompile as usize;
-        let code = format!("success := and(eq(staticcall(gas(), {a:#x}, {cd_ptr:#x}, {cd_len:#x}, {rd_ptr:#x}, {rd_len:#x}), 1), success)");
+        let code = self.check(
+            &format!(
+                "eq(staticcall(gas(), {a:#x}, {}, {cd_len:#x}, {}, {rd_len:#x}), 1)",
+                MemoryPtr(cd_ptr),
+                MemoryPtr(rd_ptr)
+            ),
+            || format!("Precompile {name} failed"),
+        );
         self.code.borrow_mut().runtime_append(code);
     }
 
@@ -332,18 +867,34 @@ impl EvmLoader {
         self.scalar(Value::Memory(rd_ptr))
     }
 
-    fn ec_point_add(self: &Rc<Self>, lhs: &EcPoint, rhs: &EcPoint) -> EcPoint {
-        let rd_ptr = self.dup_ec_point(lhs).ptr();
-        self.dup_ec_point(rhs);
-        self.staticcall(Precompiled::Bn254Add, rd_ptr, rd_ptr);
-        self.ec_point(Value::Memory(rd_ptr))
-    }
+    /// Performs multi-scalar multiplication by accumulating terms in place,
+    /// where the memory is laid out as `[acc, term, scalar]` so `ecMul` and
+    /// `ecAdd` take `[term, scalar]` and `[acc, term]` as input respectively
+    /// without copying the accumulator and terms around.
+    fn msm(self: &Rc<Self>, pairs: &[(&Scalar, &EcPoint)]) -> EcPoint {
+        let is_one = |scalar: &Scalar| match scalar.value {
+            Value::Constant(constant) => constant == U256::from(1),
+            _ => false,
+        };
+        if let [(scalar, ec_point)] = pairs {
+            if is_one(scalar) {
+                return (*ec_point).clone();
+            }
+        }
 
-    fn ec_point_scalar_mul(self: &Rc<Self>, ec_point: &EcPoint, scalar: &Scalar) -> EcPoint {
-        let rd_ptr = self.dup_ec_point(ec_point).ptr();
-        self.dup_scalar(scalar);
-        self.staticcall(Precompiled::Bn254ScalarMul, rd_ptr, rd_ptr);
-        self.ec_point(Value::Memory(rd_ptr))
+        let acc_ptr = self.allocate(0xa0);
+        for (idx, (scalar, ec_point)) in pairs.iter().enumerate() {
+            let term_ptr = if idx == 0 { acc_ptr } else { acc_ptr + 0x40 };
+            self.copy_ec_point(ec_point, term_ptr);
+            if !is_one(scalar) {
+                self.copy_scalar(scalar, term_ptr + 0x40);
+                self.staticcall(Precompiled::Bn254ScalarMul, term_ptr, term_ptr);
+            }
+            if idx > 0 {
+                self.staticcall(Precompiled::Bn254Add, acc_ptr, acc_ptr);
+            }
+        }
+        self.ec_point(Value::Memory(acc_ptr))
     }
 
     /// Performs pairing.
@@ -357,46 +908,53 @@ impl EvmLoader {
         let rd_ptr = self.dup_ec_point(lhs).ptr();
         self.allocate(0x80);
         let g2_0 = hex_encode_u256(&g2.0);
-        let g2_0_ptr = rd_ptr + 0x40;
+        let g2_0_ptr = MemoryPtr(rd_ptr + 0x40);
         let g2_1 = hex_encode_u256(&g2.1);
-        let g2_1_ptr = rd_ptr + 0x60;
+        let g2_1_ptr = MemoryPtr(rd_ptr + 0x60);
         let g2_2 = hex_encode_u256(&g2.2);
-        let g2_2_ptr = rd_ptr + 0x80;
+        let g2_2_ptr = MemoryPtr(rd_ptr + 0x80);
         let g2_3 = hex_encode_u256(&g2.3);
-        let g2_3_ptr = rd_ptr + 0xa0;
+        let g2_3_ptr = MemoryPtr(rd_ptr + 0xa0);
         let code = format!(
-            "mstore({g2_0_ptr:#x}, {g2_0})
-            mstore({g2_1_ptr:#x}, {g2_1})
-            mstore({g2_2_ptr:#x}, {g2_2})
-            mstore({g2_3_ptr:#x}, {g2_3})"
+            "mstore({g2_0_ptr}, {g2_0})
+            mstore({g2_1_ptr}, {g2_1})
+            mstore({g2_2_ptr}, {g2_2})
+            mstore({g2_3_ptr}, {g2_3})"
         );
         self.code.borrow_mut().runtime_append(code);
         self.dup_ec_point(rhs);
         self.allocate(0x80);
         let minus_s_g2_0 = hex_encode_u256(&minus_s_g2.0);
-        let minus_s_g2_0_ptr = rd_ptr + 0x100;
+        let minus_s_g2_0_ptr = MemoryPtr(rd_ptr + 0x100);
         let minus_s_g2_1 = hex_encode_u256(&minus_s_g2.1);
-        let minus_s_g2_1_ptr = rd_ptr + 0x120;
+        let minus_s_g2_1_ptr = MemoryPtr(rd_ptr + 0x120);
         let minus_s_g2_2 = hex_encode_u256(&minus_s_g2.2);
-        let minus_s_g2_2_ptr = rd_ptr + 0x140;
+        let minus_s_g2_2_ptr = MemoryPtr(rd_ptr + 0x140);
         let minus_s_g2_3 = hex_encode_u256(&minus_s_g2.3);
-        let minus_s_g2_3_ptr = rd_ptr + 0x160;
+        let minus_s_g2_3_ptr = MemoryPtr(rd_ptr + 0x160);
         let code = format!(
-            "mstore({minus_s_g2_0_ptr:#x}, {minus_s_g2_0})
-            mstore({minus_s_g2_1_ptr:#x}, {minus_s_g2_1})
-            mstore({minus_s_g2_2_ptr:#x}, {minus_s_g2_2})
-            mstore({minus_s_g2_3_ptr:#x}, {minus_s_g2_3})"
+            "mstore({minus_s_g2_0_ptr}, {minus_s_g2_0})
+            mstore({minus_s_g2_1_ptr}, {minus_s_g2_1})
+            mstore({minus_s_g2_2_ptr}, {minus_s_g2_2})
+            mstore({minus_s_g2_3_ptr}, {minus_s_g2_3})"
         );
         self.code.borrow_mut().runtime_append(code);
         self.staticcall(Precompiled::Bn254Pairing, rd_ptr, rd_ptr);
-        let code = format!("success := and(eq(mload({rd_ptr:#x}), 1), success)");
+        let code = self.check(&format!("eq(mload({}), 1)", MemoryPtr(rd_ptr)), || {
+            "Pairing check failed".to_string()
+        });
         self.code.borrow_mut().runtime_append(code);
     }
 
     fn add(self: &Rc<Self>, lhs: &Scalar, rhs: &Scalar) -> Scalar {
-        if let (Value::Constant(lhs), Value::Constant(rhs)) = (&lhs.value, &rhs.value) {
-            let out = (U512::from(*lhs) + U512::from(*rhs)) % U512::from(self.scalar_modulus);
-            return self.scalar(Value::Constant(U256::from(out)));
+        match (&lhs.value, &rhs.value) {
+            (Value::Constant(lhs), Value::Constant(rhs)) => {
+                let out = (U512::from(*lhs) + U512::from(*rhs)) % U512::from(self.scalar_modulus);
+                return self.scalar(Value::Constant(U256::from(out)));
+            }
+            (Value::Constant(constant), _) if *constant == U256::ZERO => return rhs.clone(),
+            (_, Value::Constant(constant)) if *constant == U256::ZERO => return lhs.clone(),
+            _ => {}
         }
 
         self.scalar(Value::Sum(
@@ -409,6 +967,9 @@ impl EvmLoader {
         if rhs.is_const() {
             return self.add(lhs, &self.neg(rhs));
         }
+        if matches!(lhs.value, Value::Constant(constant) if constant == U256::ZERO) {
+            return self.neg(rhs);
+        }
 
         self.scalar(Value::Sum(
             Box::new(lhs.value.clone()),
@@ -417,9 +978,19 @@ impl EvmLoader {
     }
 
     fn mul(self: &Rc<Self>, lhs: &Scalar, rhs: &Scalar) -> Scalar {
-        if let (Value::Constant(lhs), Value::Constant(rhs)) = (&lhs.value, &rhs.value) {
-            let out = (U512::from(*lhs) * U512::from(*rhs)) % U512::from(self.scalar_modulus);
-            return self.scalar(Value::Constant(U256::from(out)));
+        match (&lhs.value, &rhs.value) {
+            (Value::Constant(lhs), Value::Constant(rhs)) => {
+                let out = (U512::from(*lhs) * U512::from(*rhs)) % U512::from(self.scalar_modulus);
+                return self.scalar(Value::Constant(U256::from(out)));
+            }
+            (Value::Constant(constant), _) | (_, Value::Constant(constant))
+                if *constant == U256::ZERO =>
+            {
+                return self.scalar(Value::Constant(U256::ZERO));
+            }
+            (Value::Constant(constant), _) if *constant == U256::from(1) => return rhs.clone(),
+            (_, Value::Constant(constant)) if *constant == U256::from(1) => return lhs.clone(),
+            _ => {}
         }
 
         self.scalar(Value::Product(
@@ -429,8 +1000,13 @@ impl EvmLoader {
     }
 
     fn neg(self: &Rc<Self>, scalar: &Scalar) -> Scalar {
-        if let Value::Constant(constant) = scalar.value {
-            return self.scalar(Value::Constant(self.scalar_modulus - constant));
+        match &scalar.value {
+            Value::Constant(constant) => {
+                let out = (self.scalar_modulus - *constant) % self.scalar_modulus;
+                return self.scalar(Value::Constant(out));
+            }
+            Value::Negated(value) => return self.scalar(*value.clone()),
+            _ => {}
         }
 
         self.scalar(Value::Negated(Box::new(scalar.value.clone())))
@@ -663,28 +1239,37 @@ where
     type LoadedEcPoint = EcPoint;
 
     fn ec_point_load_const(&self, value: &C) -> EcPoint {
-        let coordinates = value.coordinates().unwrap();
-        let [x, y] = [coordinates.x(), coordinates.y()]
+        let [x, y] = ec_point_to_coordinates(value)
             .map(|coordinate| U256::try_from_le_slice(coordinate.to_repr().as_ref()).unwrap());
         self.ec_point(Value::Constant((x, y)))
     }
 
-    fn ec_point_assert_eq(&self, _: &str, _: &EcPoint, _: &EcPoint) -> Result<(), Error> {
-        unimplemented!()
+    fn ec_point_assert_eq(
+        &self,
+        annotation: &str,
+        lhs: &EcPoint,
+        rhs: &EcPoint,
+    ) -> Result<(), Error> {
+        let [lhs, rhs] = [lhs, rhs].map(|ec_point| match ec_point.value() {
+            Value::Constant((x, y)) => [format!("{x}"), format!("{y}")],
+            Value::Memory(ptr) => [ptr, ptr + 0x20].map(|ptr| format!("mload({})", MemoryPtr(ptr))),
+            _ => unreachable!(),
+        });
+        let code = self.check(
+            &format!(
+                "and(eq({}, {}), eq({}, {}))",
+                lhs[0], rhs[0], lhs[1], rhs[1]
+            ),
+            || annotation.to_string(),
+        );
+        self.code.borrow_mut().runtime_append(code);
+        Ok(())
     }
 
     fn multi_scalar_multiplication(
         pairs: &[(&<Self as ScalarLoader<C::Scalar>>::LoadedScalar, &EcPoint)],
     ) -> EcPoint {
-        pairs
-            .iter()
-            .cloned()
-            .map(|(scalar, ec_point)| match scalar.value {
-                Value::Constant(constant) if U256::from(1) == constant => ec_point.clone(),
-                _ => ec_point.loader.ec_point_scalar_mul(ec_point, scalar),
-            })
-            .reduce(|acc, ec_point| acc.loader.ec_point_add(&acc, &ec_point))
-            .unwrap()
+        pairs[0].1.loader.msm(pairs)
     }
 }
 
@@ -695,8 +1280,13 @@ impl<F: PrimeField<Repr = [u8; 0x20]>> ScalarLoader<F> for Rc<EvmLoader> {
         self.scalar(Value::Constant(fe_to_u256(*value)))
     }
 
-    fn assert_eq(&self, _: &str, _: &Scalar, _: &Scalar) -> Result<(), Error> {
-        unimplemented!()
+    fn assert_eq(&self, annotation: &str, lhs: &Scalar, rhs: &Scalar) -> Result<(), Error> {
+        let code = self.check(
+            &format!("eq({}, {})", self.push(lhs), self.push(rhs)),
+            || annotation.to_string(),
+        );
+        self.code.borrow_mut().runtime_append(code);
+        Ok(())
     }
 
     fn sum_with_coeff_and_const(&self, values: &[(F, &Scalar)], constant: F) -> Scalar {
@@ -734,7 +1324,7 @@ impl<F: PrimeField<Repr = [u8; 0x20]>> ScalarLoader<F> for Rc<EvmLoader> {
         }
 
         let ptr = self.allocate(0x20);
-        code.push_str(format!("mstore({ptr}, result)").as_str());
+        code.push_str(format!("mstore({}, result)", MemoryPtr(ptr)).as_str());
         self.code.borrow_mut().runtime_append(format!(
             "{{
             {code}
@@ -798,7 +1388,7 @@ impl<F: PrimeField<Repr = [u8; 0x20]>> ScalarLoader<F> for Rc<EvmLoader> {
         }
 
         let ptr = self.allocate(0x20);
-        code.push_str(format!("mstore({ptr}, result)").as_str());
+        code.push_str(format!("mstore({}, result)", MemoryPtr(ptr)).as_str());
         self.code.borrow_mut().runtime_append(format!(
             "{{
             {code}
@@ -819,7 +1409,7 @@ impl<F: PrimeField<Repr = [u8; 0x20]>> ScalarLoader<F> for Rc<EvmLoader> {
     // 5. inv <- v_n * inv
     fn batch_invert<'a>(values: impl IntoIterator<Item = &'a mut Scalar>) {
         let values = values.into_iter().collect_vec();
-        let loader = &values.first().unwrap().loader;
+        let loader = values.first().unwrap().loader.clone();
         let products = iter::once(values[0].clone())
             .chain(
                 iter::repeat_with(|| loader.allocate(0x20))
@@ -837,8 +1427,9 @@ impl<F: PrimeField<Repr = [u8; 0x20]>> ScalarLoader<F> for Rc<EvmLoader> {
                 format!(
                     "
                 prod := mulmod({v}, prod, f_q)
-                mstore({ptr:#x}, prod)
-            "
+                mstore({}, prod)
+            ",
+                    MemoryPtr(ptr)
                 )
                 .as_str(),
             );
@@ -857,6 +1448,9 @@ impl<F: PrimeField<Repr = [u8; 0x20]>> ScalarLoader<F> for Rc<EvmLoader> {
             let v
         "
         );
+        // Inverses are stored in newly allocated memory instead of the values'
+        // own, which might be shared with other scalars.
+        let mut inv_ptrs = Vec::with_capacity(values.len());
         for (value, product) in values.iter().rev().zip(
             products
                 .iter()
@@ -865,30 +1459,36 @@ impl<F: PrimeField<Repr = [u8; 0x20]>> ScalarLoader<F> for Rc<EvmLoader> {
                 .map(Some)
                 .chain(iter::once(None)),
         ) {
+            let inv_ptr = loader.allocate(0x20);
             if let Some(product) = product {
-                let val_ptr = value.ptr();
                 let prod_ptr = product.ptr();
                 let v = loader.push(value);
                 code.push_str(
                     format!(
                         "
                     v := {v}
-                    mstore({val_ptr}, mulmod(mload({prod_ptr:#x}), inv, f_q))
+                    mstore({}, mulmod(mload({}), inv, f_q))
                     inv := mulmod(v, inv, f_q)
-                "
+                ",
+                        MemoryPtr(inv_ptr),
+                        MemoryPtr(prod_ptr)
                     )
                     .as_str(),
                 );
             } else {
-                let ptr = value.ptr();
-                code.push_str(format!("mstore({ptr:#x}, inv)\n").as_str());
+                code.push_str(format!("mstore({}, inv)\n", MemoryPtr(inv_ptr)).as_str());
             }
+            inv_ptrs.push(inv_ptr);
         }
         loader.code.borrow_mut().runtime_append(format!(
             "{{
             {code}
         }}"
         ));
+
+        for (value, inv_ptr) in values.into_iter().rev().zip(inv_ptrs) {
+            *value = loader.scalar(Value::Memory(inv_ptr));
+        }
     }
 }
 
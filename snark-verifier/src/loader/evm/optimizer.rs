@@ -0,0 +1,394 @@
+//! Memory reuse of generated yul code.
+//!
+//! [`EvmLoader`] allocates fresh memory for every intermediate value and never
+//! frees it, which inflates memory expansion gas. Since the generated code is
+//! straight-line, the live range of each allocation could be derived from the
+//! memory operations referencing it, then allocations not alive at the same
+//! time could share the same memory.
+//!
+//! [`EvmLoader`]: crate::loader::evm::EvmLoader
+
+use std::{
+    fmt::{self, Display},
+    ops::Range,
+};
+
+/// Prefix of memory pointer formatted by [`MemoryPtr`], which is not a valid
+/// character of yul so it can't be confused with anything else.
+const MARKER: char = '@';
+
+/// Memory pointer to be formatted into generated code, which is relocated by
+/// [`relocate`] when the code is finalized.
+///
+/// All pointers to allocated memory should be formatted with it and be used as
+/// arguments of memory operations directly, otherwise [`relocate`] can't track
+/// the accesses and falls back to not reusing memory.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct MemoryPtr(pub(crate) usize);
+
+impl Display for MemoryPtr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{MARKER}{:#x}", self.0)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum AccessKind {
+    Read,
+    Write,
+}
+
+#[derive(Clone, Debug)]
+struct Access {
+    statement: usize,
+    kind: AccessKind,
+    range: Range<usize>,
+}
+
+/// Returns memory accesses of `statement` in the order they are executed,
+/// where arguments are evaluated before the call, or `None` if any memory
+/// operation doesn't take a pointer formatted by [`MemoryPtr`] or a literal `0`
+/// as its pointer argument, or any pointer is used elsewhere.
+fn parse_accesses(idx: usize, statement: &str) -> Option<Vec<Access>> {
+    struct Call<'a> {
+        name: &'a str,
+        args: Vec<&'a str>,
+        arg_start: usize,
+    }
+
+    let bytes = statement.as_bytes();
+    let mut calls = Vec::<Call>::new();
+    let mut accesses = Vec::new();
+    let mut num_consumed = 0;
+    for (offset, byte) in bytes.iter().enumerate() {
+        match byte {
+            b'(' => {
+                let name_start = bytes[..offset]
+                    .iter()
+                    .rposition(|byte| !(byte.is_ascii_alphanumeric() || *byte == b'_'))
+                    .map_or(0, |position| position + 1);
+                calls.push(Call {
+                    name: &statement[name_start..offset],
+                    args: Vec::new(),
+                    arg_start: offset + 1,
+                });
+            }
+            b',' => {
+                if let Some(call) = calls.last_mut() {
+                    call.args.push(statement[call.arg_start..offset].trim());
+                    call.arg_start = offset + 1;
+                }
+            }
+            b')' => {
+                let mut call = calls.pop()?;
+                let last_arg = statement[call.arg_start..offset].trim();
+                if !(call.args.is_empty() && last_arg.is_empty()) {
+                    call.args.push(last_arg);
+                }
+
+                // Returns `Some(None)` for null pointer which is not tracked.
+                let ptr = |idx: usize| {
+                    let arg = *call.args.get(idx)?;
+                    match arg.strip_prefix(MARKER) {
+                        Some(ptr) => try_parse_literal(ptr).map(Some),
+                        None => (try_parse_literal(arg)? == 0).then_some(None),
+                    }
+                };
+                let len = |idx: usize| try_parse_literal(call.args.get(idx)?);
+                let mut access = |kind, ptr: Option<Option<usize>>, len: Option<usize>| {
+                    if let Some(ptr) = ptr? {
+                        num_consumed += 1;
+                        accesses.push(Access {
+                            statement: idx,
+                            kind,
+                            range: ptr..ptr + len?,
+                        });
+                    }
+                    Some(())
+                };
+                match call.name {
+                    "mload" => access(AccessKind::Read, ptr(0), Some(0x20))?,
+                    "mstore" => access(AccessKind::Write, ptr(0), Some(0x20))?,
+                    "mstore8" => access(AccessKind::Write, ptr(0), Some(1))?,
+                    "keccak256" => access(AccessKind::Read, ptr(0), len(1))?,
+                    "return" => access(AccessKind::Read, ptr(0), len(1))?,
+                    "calldatacopy" => access(AccessKind::Write, ptr(0), len(2))?,
+                    "staticcall" => {
+                        access(AccessKind::Read, ptr(2), len(3))?;
+                        access(AccessKind::Write, ptr(4), len(5))?;
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    (calls.is_empty() && num_consumed == statement.matches(MARKER).count()).then_some(accesses)
+}
+
+fn try_parse_literal(literal: &str) -> Option<usize> {
+    match literal.strip_prefix("0x") {
+        Some(hex) => usize::from_str_radix(hex, 16),
+        None => literal.parse(),
+    }
+    .ok()
+}
+
+fn parse_literal(literal: &str) -> usize {
+    try_parse_literal(literal)
+        .unwrap_or_else(|| panic!("Expected number literal but got {literal}"))
+}
+
+/// Replaces pointers formatted by [`MemoryPtr`] in `statement` with the
+/// relocated ones.
+fn rewrite(statement: &str, relocate: impl Fn(usize) -> usize) -> String {
+    let mut rewritten = String::with_capacity(statement.len());
+    let mut rest = statement;
+    while let Some(start) = rest.find(MARKER) {
+        rewritten.push_str(&rest[..start]);
+        let literal = &rest[start + 1..];
+        let end = literal
+            .char_indices()
+            .skip(2)
+            .find(|(_, c)| !c.is_ascii_hexdigit())
+            .map_or(literal.len(), |(end, _)| end);
+        rewritten.push_str(&format!("{:#x}", relocate(parse_literal(&literal[..end]))));
+        rest = &literal[end..];
+    }
+    rewritten.push_str(rest);
+    rewritten
+}
+
+/// Group of consecutive allocations which are accessed together and so must
+/// be relocated together.
+#[derive(Clone, Debug)]
+struct Group {
+    range: Range<usize>,
+    live: Option<Range<usize>>,
+    zero_dependent: bool,
+}
+
+/// Returns `statements` with pointers formatted by [`MemoryPtr`] replaced.
+///
+/// If `reuse_memory` is `true` and all memory accesses could be tracked (see
+/// [`MemoryPtr`]), allocations are relocated so that ones not alive at the
+/// same time share memory. An allocation is alive from the first
+/// statement accessing it to the last one, and allocations accessed by the
+/// same memory operation (e.g. input of precompiles or transcript to hash)
+/// are relocated together to preserve their layout. Memory beyond
+/// allocations accessed as scratch is treated as allocated as well.
+///
+/// Allocations are expected to be contiguous from `0` in order as returned by
+/// [`EvmLoader::allocate`](crate::loader::evm::EvmLoader::allocate).
+pub(crate) fn relocate(
+    statements: &[String],
+    allocations: &[Range<usize>],
+    reuse_memory: bool,
+) -> Vec<String> {
+    let accesses = statements
+        .iter()
+        .enumerate()
+        .map(|(idx, statement)| parse_accesses(idx, statement))
+        .collect::<Option<Vec<_>>>()
+        .map(|accesses| accesses.into_iter().flatten().collect::<Vec<_>>());
+    let accesses = match accesses {
+        Some(accesses) if reuse_memory => accesses,
+        _ => {
+            return statements
+                .iter()
+                .map(|statement| rewrite(statement, |ptr| ptr))
+                .collect()
+        }
+    };
+
+    let mut blocks = allocations.to_vec();
+    let allocated_end = blocks.last().map_or(0, |block| block.end);
+    assert!(blocks
+        .iter()
+        .scan(0, |end, block| {
+            let contiguous = *end == block.start && block.start <= block.end;
+            *end = block.end;
+            Some(contiguous)
+        })
+        .all(|contiguous| contiguous));
+    let accessed_end = accesses
+        .iter()
+        .map(|access| access.range.end.max(access.range.start + 1))
+        .max()
+        .unwrap_or_default();
+    if accessed_end > allocated_end {
+        blocks.push(allocated_end..round_up(accessed_end));
+    }
+    blocks.retain(|block| !block.is_empty());
+    let block_of = |ptr: usize| blocks.partition_point(|block| block.end <= ptr);
+
+    // Merge blocks accessed by the same operation into groups.
+    let mut merge_next = vec![false; blocks.len()];
+    for access in accesses.iter() {
+        let end = access.range.end.max(access.range.start + 1);
+        for merge_next in &mut merge_next[block_of(access.range.start)..block_of(end - 1)] {
+            *merge_next = true;
+        }
+    }
+    let mut groups = Vec::<Group>::new();
+    let mut group_of_block = Vec::with_capacity(blocks.len());
+    for (idx, block) in blocks.iter().enumerate() {
+        match groups.last_mut() {
+            Some(group) if idx > 0 && merge_next[idx - 1] => group.range.end = block.end,
+            _ => groups.push(Group {
+                range: block.clone(),
+                live: None,
+                zero_dependent: false,
+            }),
+        }
+        group_of_block.push(groups.len() - 1);
+    }
+    let group_of = |ptr: usize| group_of_block[block_of(ptr)];
+
+    // Compute live range of each group, and whether it reads memory before
+    // written, which relies on fresh memory being zero.
+    let mut written = vec![0u32; round_up(accessed_end) / 0x20];
+    for access in accesses.iter() {
+        let group = &mut groups[group_of(access.range.start)];
+        group.live = Some(match &group.live {
+            Some(live) => live.start..access.statement + 1,
+            None => access.statement..access.statement + 1,
+        });
+        for ptr in access.range.clone() {
+            let mask = 1 << (ptr % 0x20);
+            let written = &mut written[ptr / 0x20];
+            match access.kind {
+                AccessKind::Read if *written & mask == 0 => {
+                    groups[group_of(ptr)].zero_dependent = true;
+                }
+                AccessKind::Read => {}
+                AccessKind::Write => *written |= mask,
+            }
+        }
+    }
+
+    // Assign memory to groups in order of their live ranges with first-fit,
+    // where zero dependent ones are always assigned with fresh memory.
+    let mut order = (0..groups.len())
+        .filter(|idx| groups[*idx].live.is_some())
+        .collect::<Vec<_>>();
+    order.sort_by_key(|idx| groups[*idx].live.as_ref().unwrap().start);
+    let mut relocated = vec![0; groups.len()];
+    let mut active = Vec::<(Range<usize>, usize)>::new();
+    let mut high_water_mark = 0;
+    for idx in order {
+        let group = &groups[idx];
+        let live = group.live.clone().unwrap();
+        let size = round_up(group.range.len());
+        active.retain(|(_, end)| *end > live.start);
+        active.sort_by_key(|(range, _)| range.start);
+
+        let start = if group.zero_dependent {
+            high_water_mark
+        } else {
+            let mut start = 0;
+            for (range, _) in active.iter() {
+                if start + size <= range.start {
+                    break;
+                }
+                start = start.max(range.end);
+            }
+            start
+        };
+        relocated[idx] = start;
+        active.push((start..start + size, live.end));
+        high_water_mark = high_water_mark.max(start + size);
+    }
+
+    statements
+        .iter()
+        .map(|statement| {
+            rewrite(statement, |ptr| {
+                let group = group_of(ptr);
+                relocated[group] + ptr - groups[group].range.start
+            })
+        })
+        .collect()
+}
+
+fn round_up(value: usize) -> usize {
+    (value + 0x1f) & !0x1f
+}
+
+#[cfg(test)]
+mod test {
+    use super::{relocate, MemoryPtr};
+
+    #[test]
+    fn test_relocate() {
+        let statements = [
+            format!("mstore({}, 1)", MemoryPtr(0x00)),
+            format!("mstore({}, mload({}))", MemoryPtr(0x20), MemoryPtr(0x00)),
+            format!("mstore({}, mload({}))", MemoryPtr(0x40), MemoryPtr(0x20)),
+            format!("mstore(0x00, keccak256({}, 0x40))", MemoryPtr(0x40)),
+        ]
+        .map(String::from);
+        let allocations = [0x00..0x20, 0x20..0x40, 0x40..0x60];
+
+        assert_eq!(
+            relocate(&statements, &allocations, false),
+            [
+                "mstore(0x0, 1)",
+                "mstore(0x20, mload(0x0))",
+                "mstore(0x40, mload(0x20))",
+                "mstore(0x00, keccak256(0x40, 0x40))",
+            ]
+        );
+        // Last allocation is read before written at 0x60, so it can't reuse
+        // memory of dead ones.
+        assert_eq!(
+            relocate(&statements, &allocations, true),
+            [
+                "mstore(0x0, 1)",
+                "mstore(0x20, mload(0x0))",
+                "mstore(0x40, mload(0x20))",
+                "mstore(0x00, keccak256(0x40, 0x40))",
+            ]
+        );
+
+        let statements = statements.map(|statement| statement.replace("0x40))", "0x20))"));
+        // Now the first allocation is dead when the last one is alive.
+        assert_eq!(
+            relocate(&statements, &allocations, true),
+            [
+                "mstore(0x0, 1)",
+                "mstore(0x20, mload(0x0))",
+                "mstore(0x0, mload(0x20))",
+                "mstore(0x00, keccak256(0x0, 0x20))",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_relocate_untracked_access() {
+        let statements = [
+            format!("mstore({}, 1)", MemoryPtr(0x00)),
+            format!("mstore({}, mload({}))", MemoryPtr(0x20), MemoryPtr(0x00)),
+            format!("mstore({}, mload({}))", MemoryPtr(0x40), MemoryPtr(0x20)),
+            format!("mstore(0x00, keccak256({}, 0x20))", MemoryPtr(0x40)),
+        ]
+        .map(String::from);
+        let allocations = [0x00..0x20, 0x20..0x40, 0x40..0x60];
+        let without_reuse = relocate(&statements, &allocations, false);
+
+        // Raw pointer, pointer in variable, or pointer used in arithmetic can't
+        // be tracked, then memory is not reused.
+        for untracked in [
+            "mstore(0x80, mload(0x40))".to_string(),
+            "let ptr := 0x20 mstore(0x00, mload(ptr))".to_string(),
+            format!("mstore(0x00, mload(add({}, 0x20)))", MemoryPtr(0x00)),
+        ] {
+            let mut statements = statements.to_vec();
+            statements.push(untracked.clone());
+            let mut expected = without_reuse.to_vec();
+            expected.push(untracked.replace(&MemoryPtr(0x00).to_string(), "0x0"));
+            assert_eq!(relocate(&statements, &allocations, true), expected);
+        }
+    }
+}
@@ -1,14 +1,27 @@
 use crate::{
     cost::Cost,
-    util::{arithmetic::PrimeField, Itertools},
+    util::{
+        arithmetic::{ec_point_to_coordinates, CurveAffine, PrimeField},
+        hash::{Digest, Keccak256, Sha256},
+        Itertools,
+    },
 };
+use num_integer::Integer;
 use std::{
     io::Write,
     iter,
     process::{Command, Stdio},
 };
 
-pub use executor::deploy_and_call;
+#[cfg(feature = "system_halo2")]
+use crate::{
+    loader::native::NativeLoader, system::halo2::transcript::evm::EvmTranscript,
+    verifier::SnarkVerifier, Error,
+};
+
+pub use executor::{
+    deploy_and_call, deploy_and_call_with_error_messages, deploy_and_call_with_output,
+};
 pub use revm::primitives::ruint::aliases::{B160 as Address, B256, U256, U512};
 
 pub(crate) mod executor;
@@ -74,8 +87,64 @@ where
     U256::from_le_bytes((-F::ONE).to_repr()) + U256::from(1)
 }
 
-/// Encode instances and proof into calldata.
-pub fn encode_calldata<F>(instances: &[Vec<F>], proof: &[u8]) -> Vec<u8>
+/// Encode instances and proof into calldata, after validating `proof` by
+/// reading it with [`EvmTranscript`] as `PV` does.
+///
+/// Returns error if `proof` contains any non-canonical scalar, invalid
+/// elliptic curve point or trailing bytes, which would otherwise make the
+/// calldata malleable or be rejected by verifier.
+#[cfg(feature = "system_halo2")]
+pub fn encode_calldata<C, PV>(
+    vk: &PV::VerifyingKey,
+    protocol: &PV::Protocol,
+    instances: &[Vec<C::Scalar>],
+    proof: &[u8],
+) -> Result<Vec<u8>, Error>
+where
+    C: CurveAffine,
+    C::Scalar: PrimeField<Repr = [u8; 32]>,
+    PV: SnarkVerifier<C, NativeLoader>,
+{
+    encode_calldata_with_compression::<C, PV>(vk, protocol, instances, proof, false)
+}
+
+/// Encode instances and proof into calldata like [`encode_calldata`], but
+/// expects elliptic curve points in `proof` to be compressed if `compressed`
+/// is `true`.
+#[cfg(feature = "system_halo2")]
+pub fn encode_calldata_with_compression<C, PV>(
+    vk: &PV::VerifyingKey,
+    protocol: &PV::Protocol,
+    instances: &[Vec<C::Scalar>],
+    proof: &[u8],
+    compressed: bool,
+) -> Result<Vec<u8>, Error>
+where
+    C: CurveAffine,
+    C::Scalar: PrimeField<Repr = [u8; 32]>,
+    PV: SnarkVerifier<C, NativeLoader>,
+{
+    let mut stream = proof;
+    let mut transcript =
+        EvmTranscript::<C, NativeLoader, _, _>::new_with_compression(&mut stream, compressed);
+    PV::read_proof(vk, protocol, instances, &mut transcript)?;
+    if !stream.is_empty() {
+        return Err(Error::Transcript(
+            std::io::ErrorKind::InvalidData,
+            format!("Unexpected {} trailing bytes in proof", stream.len()),
+        ));
+    }
+
+    Ok(encode_calldata_unchecked(instances, proof))
+}
+
+/// Encode instances and proof into calldata without validating `proof`.
+///
+/// Instances are always encoded canonically since they are given as field
+/// elements, but `proof` is copied as is, so it's caller's responsibility to
+/// make sure it's the one written by `EvmTranscript`, otherwise prefer
+/// [`encode_calldata`].
+pub fn encode_calldata_unchecked<F>(instances: &[Vec<F>], proof: &[u8]) -> Vec<u8>
 where
     F: PrimeField<Repr = [u8; 32]>,
 {
@@ -90,15 +159,243 @@ where
         .collect()
 }
 
-/// Estimate gas cost with given [`Cost`].
-pub fn estimate_gas(cost: Cost) -> usize {
-    let proof_size = cost.num_commitment * 64 + (cost.num_evaluation + cost.num_instance) * 32;
+/// Signature of the function exposed by verifier generated by [`EvmLoader`]
+/// initialized with [`EvmLoader::new_with_abi`].
+///
+/// [`EvmLoader`]: crate::loader::evm::EvmLoader
+/// [`EvmLoader::new_with_abi`]: crate::loader::evm::EvmLoader::new_with_abi
+pub const VERIFY_FUNCTION_SIGNATURE: &str = "verify(uint256[],bytes)";
+
+/// Returns selector of [`VERIFY_FUNCTION_SIGNATURE`].
+pub fn verify_function_selector() -> [u8; 4] {
+    Keccak256::digest(VERIFY_FUNCTION_SIGNATURE.as_bytes())[..4]
+        .try_into()
+        .unwrap()
+}
 
-    let intrinsic_cost = 21000;
-    let calldata_cost = (proof_size as f64 * 15.25).ceil() as usize;
-    let ec_operation_cost = (45100 + cost.num_pairing * 34000) + (cost.num_msm - 2) * 6350;
+/// Encode instances and proof into calldata of [`VERIFY_FUNCTION_SIGNATURE`]
+/// with ABI encoding, which is the same as `abi.encodeCall` in Solidity.
+pub fn encode_abi_calldata<F>(instances: &[Vec<F>], proof: &[u8]) -> Vec<u8>
+where
+    F: PrimeField<Repr = [u8; 32]>,
+{
+    let num_instance = instances.iter().map(Vec::len).sum::<usize>();
+    let word = |value: usize| U256::from(value).to_be_bytes::<32>();
+    let padding = Integer::next_multiple_of(&proof.len(), &0x20) - proof.len();
 
-    intrinsic_cost + calldata_cost + ec_operation_cost
+    iter::empty()
+        .chain(verify_function_selector())
+        .chain(word(0x40))
+        .chain(word(0x60 + 0x20 * num_instance))
+        .chain(word(num_instance))
+        .chain(encode_calldata_unchecked(instances, &[]))
+        .chain(word(proof.len()))
+        .chain(proof.iter().cloned())
+        .chain(iter::repeat(0).take(padding))
+        .collect()
+}
+
+/// Hash function to compress public inputs into a single instance as digest,
+/// so verifier could take public inputs in any encoding as calldata without
+/// paying for each of them as an instance.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PublicInputHash {
+    /// Keccak256 computed by `KECCAK256` opcode.
+    Keccak256,
+    /// Sha256 computed by `sha256` precompile.
+    Sha256,
+}
+
+impl PublicInputHash {
+    /// Returns digest of `preimage` as a field element, which takes the least
+    /// significant `F::NUM_BITS - 1` bits of big-endian hash output, so it's
+    /// always canonical without reduction.
+    ///
+    /// Circuit is expected to constrain its last instance to be the digest
+    /// computed in the same way.
+    pub fn digest<F>(&self, preimage: &[u8]) -> F
+    where
+        F: PrimeField<Repr = [u8; 32]>,
+    {
+        let hash: [u8; 32] = match self {
+            PublicInputHash::Keccak256 => Keccak256::digest(preimage).into(),
+            PublicInputHash::Sha256 => Sha256::digest(preimage).into(),
+        };
+        let mask = (U256::from(1) << (modulus::<F>().bit_len() - 1)) - U256::from(1);
+        u256_to_fe(U256::from_be_bytes(hash) & mask)
+    }
+}
+
+/// Encode instances, preimage of digest and proof into calldata for verifier
+/// that hashes public inputs with [`PublicInputHash`].
+///
+/// The `instances` are the same as the ones given to prover, where the last
+/// one is digest of `preimage`, which is replaced by `preimage` in calldata.
+pub fn encode_calldata_with_preimage<F>(
+    hash: PublicInputHash,
+    instances: &[Vec<F>],
+    preimage: &[u8],
+    proof: &[u8],
+) -> Vec<u8>
+where
+    F: PrimeField<Repr = [u8; 32]>,
+{
+    let (digest, instances) = instances
+        .iter()
+        .flatten()
+        .collect_vec()
+        .split_last()
+        .map(|(digest, instances)| (**digest, instances.to_vec()))
+        .expect("Digest should be the last instance");
+    assert_eq!(digest, hash.digest(preimage));
+
+    iter::empty()
+        .chain(
+            instances
+                .into_iter()
+                .flat_map(|value| value.to_repr().as_ref().iter().rev().cloned().collect_vec()),
+        )
+        .chain(preimage.iter().cloned())
+        .chain(proof.iter().cloned())
+        .collect()
+}
+
+/// Encode elliptic curve points as concatenated `(x, y)` coordinates, which
+/// is the same as return data of verifier generated with
+/// [`EvmLoader::return_ec_points`](crate::loader::evm::EvmLoader::return_ec_points).
+pub fn encode_ec_points<C>(ec_points: &[C]) -> Vec<u8>
+where
+    C: CurveAffine,
+    C::Base: PrimeField<Repr = [u8; 32]>,
+{
+    ec_points
+        .iter()
+        .flat_map(ec_point_to_coordinates)
+        .flat_map(|value| value.to_repr().as_ref().iter().rev().cloned().collect_vec())
+        .collect()
+}
+
+/// Decode revert data of verifier generated in debug mode into error message
+/// by `error_messages` returned by
+/// [`EvmLoader::error_messages`](crate::loader::evm::EvmLoader::error_messages).
+/// Returns `None` if revert data is not an error code.
+pub fn decode_error_message<'a>(output: &[u8], error_messages: &'a [String]) -> Option<&'a str> {
+    if output.len() != 0x20 {
+        return None;
+    }
+    let code = U256::from_be_slice(output);
+    error_messages
+        .get(usize::try_from(code).ok()?)
+        .map(String::as_str)
+}
+
+/// Gas cost of transaction intrinsic.
+pub const GAS_INTRINSIC: usize = 21000;
+/// Average gas cost per byte of calldata, which is 16 for non-zero byte and 4
+/// for zero byte.
+pub const GAS_PER_CALLDATA_BYTE: f64 = 15.25;
+/// Gas cost of `STATICCALL` to a precompile, which is always warm.
+pub const GAS_PRECOMPILE_CALL: usize = 100;
+/// Gas cost of `ecAdd` precompile (EIP-1108).
+pub const GAS_EC_ADD: usize = 150;
+/// Gas cost of `ecMul` precompile (EIP-1108).
+pub const GAS_EC_MUL: usize = 6000;
+/// Base gas cost of `ecPairing` precompile (EIP-1108).
+pub const GAS_EC_PAIRING_BASE: usize = 45000;
+/// Gas cost per pair of `ecPairing` precompile (EIP-1108).
+pub const GAS_EC_PAIRING_PER_PAIR: usize = 34000;
+/// Gas cost of `modexp` precompile (EIP-2565) for inverting a bn254 scalar,
+/// where base, exponent and modulus are all 32 bytes and exponent `r - 2`
+/// has 254 bits, which is `max(200, 16 * 253 / 3)`.
+pub const GAS_MODEXP_INVERSION: usize = 1349;
+/// Gas cost of `modexp` precompile (EIP-2565) for square root of a bn254 base
+/// field element, where base, exponent and modulus are all 32 bytes and
+/// exponent `(p + 1) / 4` has 252 bits, which is `max(200, 16 * 251 / 3)`.
+pub const GAS_MODEXP_SQRT: usize = 1338;
+/// Base gas cost of `KECCAK256`.
+pub const GAS_KECCAK256_BASE: usize = 30;
+/// Gas cost per word of `KECCAK256`.
+pub const GAS_KECCAK256_PER_WORD: usize = 6;
+/// Approximate gas cost of a scalar addition or multiplication generated by
+/// [`EvmLoader`](crate::loader::evm::EvmLoader), which includes loading
+/// operands from memory, `addmod` or `mulmod` and storing the result back.
+pub const GAS_SCALAR_OPERATION: usize = 30;
+
+/// Breakdown of estimated gas cost of a verifier.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GasCost {
+    /// Transaction intrinsic gas cost.
+    pub intrinsic: usize,
+    /// Gas cost of calldata.
+    pub calldata: usize,
+    /// Gas cost of `ecAdd` precompile calls.
+    pub ec_add: usize,
+    /// Gas cost of `ecMul` precompile calls.
+    pub ec_mul: usize,
+    /// Gas cost of `ecPairing` precompile calls.
+    pub ec_pairing: usize,
+    /// Gas cost of `modexp` precompile calls.
+    pub modexp: usize,
+    /// Gas cost of decompressing elliptic curve points in calldata.
+    pub ec_point_decompression: usize,
+    /// Gas cost of `KECCAK256` for squeezing challenges.
+    pub keccak256: usize,
+    /// Gas cost of scalar field arithmetic.
+    pub scalar_arithmetic: usize,
+}
+
+impl GasCost {
+    /// Returns total gas cost.
+    pub fn total(&self) -> usize {
+        self.intrinsic
+            + self.calldata
+            + self.ec_add
+            + self.ec_mul
+            + self.ec_pairing
+            + self.modexp
+            + self.ec_point_decompression
+            + self.keccak256
+            + self.scalar_arithmetic
+    }
+}
+
+/// Estimate gas cost breakdown with given [`Cost`].
+pub fn estimate_gas_cost(cost: &Cost) -> GasCost {
+    let num_keccak256_word = cost.num_absorption() + cost.num_squeeze;
+    GasCost {
+        intrinsic: GAS_INTRINSIC,
+        calldata: (cost.calldata_size() as f64 * GAS_PER_CALLDATA_BYTE).ceil() as usize,
+        ec_add: cost.num_msm * (GAS_EC_ADD + GAS_PRECOMPILE_CALL),
+        ec_mul: cost.num_msm * (GAS_EC_MUL + GAS_PRECOMPILE_CALL),
+        ec_pairing: if cost.num_pairing == 0 {
+            0
+        } else {
+            GAS_EC_PAIRING_BASE + cost.num_pairing * GAS_EC_PAIRING_PER_PAIR + GAS_PRECOMPILE_CALL
+        },
+        modexp: cost.num_inversion * (GAS_MODEXP_INVERSION + GAS_PRECOMPILE_CALL),
+        keccak256: cost.num_squeeze * GAS_KECCAK256_BASE
+            + num_keccak256_word * GAS_KECCAK256_PER_WORD,
+        scalar_arithmetic: (cost.num_scalar_add + cost.num_scalar_mul) * GAS_SCALAR_OPERATION,
+        ..Default::default()
+    }
+}
+
+/// Estimate gas cost breakdown with given [`Cost`], assuming commitments in
+/// proof are compressed, which trades calldata for decompression.
+pub fn estimate_gas_cost_compressed(cost: &Cost) -> GasCost {
+    GasCost {
+        calldata: (cost.compressed_calldata_size() as f64 * GAS_PER_CALLDATA_BYTE).ceil() as usize,
+        // Besides `modexp`, each decompression takes about 5 scalar operations
+        // to compute `x^3 + 3`, check square and negate `y` if necessary.
+        ec_point_decompression: cost.num_commitment
+            * (GAS_MODEXP_SQRT + GAS_PRECOMPILE_CALL + 5 * GAS_SCALAR_OPERATION),
+        ..estimate_gas_cost(cost)
+    }
+}
+
+/// Estimate gas cost with given [`Cost`].
+pub fn estimate_gas(cost: Cost) -> usize {
+    estimate_gas_cost(&cost).total()
 }
 
 /// Compile given yul `code` into deployment bytecode.
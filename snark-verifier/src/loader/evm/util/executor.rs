@@ -1,3 +1,4 @@
+use super::decode_error_message;
 use revm::{
     primitives::{CreateScheme, ExecutionResult, Output, TransactTo, TxEnv},
     InMemoryDB, EVM,
@@ -6,6 +7,65 @@ use revm::{
 /// Deploy contract and then call with calldata.
 /// Returns gas_used of call to deployed contract if both transactions are successful.
 pub fn deploy_and_call(deployment_code: Vec<u8>, calldata: Vec<u8>) -> Result<u64, String> {
+    deploy_and_call_with_output(deployment_code, calldata).map(|(gas_used, _)| gas_used)
+}
+
+/// Deploy contract and then call with calldata.
+/// Returns gas_used and output of call to deployed contract if both
+/// transactions are successful.
+pub fn deploy_and_call_with_output(
+    deployment_code: Vec<u8>,
+    calldata: Vec<u8>,
+) -> Result<(u64, Vec<u8>), String> {
+    call_result(deploy_and_transact(deployment_code, calldata)?)
+}
+
+fn call_result(result: ExecutionResult) -> Result<(u64, Vec<u8>), String> {
+    match result {
+        ExecutionResult::Success {
+            gas_used, output, ..
+        } => Ok((gas_used, output.into_data().to_vec())),
+        ExecutionResult::Revert { gas_used, output } => Err(format!(
+            "Contract call transaction reverts with gas_used {gas_used} and output {:#x}",
+            output
+        )),
+        ExecutionResult::Halt { reason, gas_used } => Err(format!(
+            "Contract call transaction halts unexpectedly with gas_used {gas_used} and reason {:?}",
+            reason
+        )),
+    }
+}
+
+/// Deploy contract generated in debug mode and then call with calldata.
+/// Returns gas_used of call to deployed contract if both transactions are
+/// successful, otherwise decodes error code in revert data of call into
+/// message by `error_messages` returned by
+/// [`EvmLoader::error_messages`](crate::loader::evm::EvmLoader::error_messages).
+pub fn deploy_and_call_with_error_messages(
+    deployment_code: Vec<u8>,
+    calldata: Vec<u8>,
+    error_messages: &[String],
+) -> Result<u64, String> {
+    match deploy_and_transact(deployment_code, calldata)? {
+        ExecutionResult::Revert { gas_used, output } => {
+            match decode_error_message(&output, error_messages) {
+                Some(message) => Err(format!(
+                    "Contract call transaction reverts with gas_used {gas_used} and error: {message}"
+                )),
+                None => Err(format!(
+                    "Contract call transaction reverts with gas_used {gas_used} and output {:#x}",
+                    output
+                )),
+            }
+        }
+        result => call_result(result).map(|(gas_used, _)| gas_used),
+    }
+}
+
+fn deploy_and_transact(
+    deployment_code: Vec<u8>,
+    calldata: Vec<u8>,
+) -> Result<ExecutionResult, String> {
     let mut evm = EVM {
         env: Default::default(),
         db: Some(InMemoryDB::default()),
@@ -44,16 +104,5 @@ pub fn deploy_and_call(deployment_code: Vec<u8>, calldata: Vec<u8>) -> Result<u6
         ..Default::default()
     };
 
-    let result = evm.transact_commit().unwrap();
-    match result {
-        ExecutionResult::Success { gas_used, .. } => Ok(gas_used),
-        ExecutionResult::Revert { gas_used, output } => Err(format!(
-            "Contract call transaction reverts with gas_used {gas_used} and output {:#x}",
-            output
-        )),
-        ExecutionResult::Halt { reason, gas_used } => Err(format!(
-            "Contract call transaction halts unexpectedly with gas_used {gas_used} and reason {:?}",
-            reason
-        )),
-    }
+    Ok(evm.transact_commit().unwrap())
 }
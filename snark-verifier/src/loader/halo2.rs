@@ -1,17 +1,27 @@
 //! `Loader` implementation for generating verifier in [`halo2_proofs`] circuit.
 
+mod cost;
 pub(crate) mod loader;
 mod shim;
 
+#[cfg(feature = "loader_evm")]
+mod public_input_hash;
+
 #[cfg(test)]
 pub(crate) mod test;
 
+pub use cost::RowCost;
 pub use loader::{EcPoint, Halo2Loader, Scalar};
-pub use shim::{Context, EccInstructions, IntegerInstructions};
+pub use shim::{Context, EccInstructions, IntegerInstructions, PairingInstructions};
 pub use util::Valuetools;
 
 pub use halo2_wrong_ecc;
 
+#[cfg(feature = "halo2_lib")]
+pub use halo2_base;
+#[cfg(feature = "halo2_lib")]
+pub use halo2_ecc;
+
 mod util {
     use halo2_proofs::circuit::Value;
 
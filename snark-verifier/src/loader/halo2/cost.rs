@@ -0,0 +1,190 @@
+use crate::{
+    cost::Cost,
+    loader::{
+        halo2::{
+            shim::{Context, EccInstructions, PairingInstructions},
+            EcPoint, Halo2Loader, Scalar,
+        },
+        EcPointLoader,
+    },
+    util::{
+        arithmetic::{CurveAffine, Field, FieldOps, FromUniformBytes, MultiMillerLoop},
+        hash::Poseidon,
+    },
+};
+use halo2_proofs::circuit::Value;
+use num_integer::Integer;
+use std::rc::Rc;
+
+/// Number of rows (in terms of [`Context::offset`]) each operation takes in
+/// [`Halo2Loader`], for predicting rows of a verifier given its [`Cost`].
+///
+/// For backends that lay out cells vertically in a single column at a time
+/// (e.g. `halo2_lib`), the rows are the number of advice cells, which should be
+/// divided by number of advice columns to get the actual rows.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RowCost {
+    /// Rows to assign a scalar witness.
+    pub scalar_assignment: usize,
+    /// Rows to assign an elliptic curve point witness.
+    pub ec_point_assignment: usize,
+    /// Rows of a scalar field addition.
+    pub scalar_add: usize,
+    /// Rows of a scalar field multiplication.
+    pub scalar_mul: usize,
+    /// Rows of a scalar field inversion.
+    pub inversion: usize,
+    /// Rows of each term in multi-scalar multiplication.
+    pub msm_term: usize,
+    /// Rows of a sponge permutation of transcript.
+    pub permutation: usize,
+    /// Rate of the sponge of transcript.
+    pub rate: usize,
+    /// Rows of each pairing in pairing check, which is only measured when
+    /// the backend supports [`PairingInstructions`].
+    pub pairing: usize,
+}
+
+impl RowCost {
+    /// Measure rows of each operation by performing it on given `loader`.
+    ///
+    /// Since it assigns witnesses and constraints, the given `loader` should
+    /// be a throwaway one, which is not used to synthesize the verifier.
+    pub fn calibrate<'a, C, EccChip>(loader: &Rc<Halo2Loader<'a, C, EccChip>>) -> Self
+    where
+        C: CurveAffine,
+        EccChip: EccInstructions<'a, C>,
+    {
+        let measure = |f: &dyn Fn()| {
+            let start = loader.ctx().offset();
+            f();
+            loader.ctx().offset() - start
+        };
+
+        let scalar = || loader.assign_scalar(Value::known(C::Scalar::ONE));
+        let ec_point = || loader.assign_ec_point(Value::known(C::generator()));
+        let [lhs, rhs] = [(); 2].map(|_| scalar());
+        let [base_0, base_1] = [(); 2].map(|_| ec_point());
+
+        let scalar_assignment = measure(&|| {
+            scalar();
+        });
+        let ec_point_assignment = measure(&|| {
+            ec_point();
+        });
+        let scalar_add = measure(&|| {
+            let _ = lhs.clone() + &rhs;
+        });
+        let scalar_mul = measure(&|| {
+            let _ = lhs.clone() * &rhs;
+        });
+        let inversion = measure(&|| {
+            let _ = FieldOps::invert(&lhs);
+        });
+        let msm_term = {
+            let msm = |pairs: &[(&Scalar<'a, C, EccChip>, &EcPoint<'a, C, EccChip>)]| {
+                Rc::<Halo2Loader<'a, C, EccChip>>::multi_scalar_multiplication(pairs);
+            };
+            let one_term = measure(&|| msm(&[(&lhs, &base_0)]));
+            let two_terms = measure(&|| msm(&[(&lhs, &base_0), (&rhs, &base_1)]));
+            two_terms.saturating_sub(one_term)
+        };
+
+        Self {
+            scalar_assignment,
+            ec_point_assignment,
+            scalar_add,
+            scalar_mul,
+            inversion,
+            msm_term,
+            ..Default::default()
+        }
+    }
+
+    /// Measure rows of a Poseidon permutation with given parameters by
+    /// performing it on given `loader`, and use it as cost of transcript.
+    ///
+    /// Since it assigns witnesses and constraints, the given `loader` should
+    /// be a throwaway one, which is not used to synthesize the verifier.
+    pub fn with_poseidon<'a, C, EccChip, const T: usize, const RATE: usize>(
+        mut self,
+        loader: &Rc<Halo2Loader<'a, C, EccChip>>,
+        r_f: usize,
+        r_p: usize,
+    ) -> Self
+    where
+        C: CurveAffine,
+        C::Scalar: FromUniformBytes<64>,
+        EccChip: EccInstructions<'a, C>,
+    {
+        let mut poseidon = Poseidon::<C::Scalar, _, T, RATE>::new(loader, r_f, r_p);
+        let elements = (0..RATE - 1)
+            .map(|_| loader.assign_scalar(Value::known(C::Scalar::ONE)))
+            .collect::<Vec<_>>();
+        poseidon.update(&elements);
+
+        let start = loader.ctx().offset();
+        poseidon.squeeze();
+        self.permutation = loader.ctx().offset() - start;
+        self.rate = RATE;
+        self
+    }
+
+    /// Measure rows of a pairing check of 2 pairings by performing it on given
+    /// `loader`, and use half of it as cost of each pairing.
+    ///
+    /// Since it assigns witnesses and constraints, the given `loader` should
+    /// be a throwaway one, which is not used to synthesize the verifier.
+    pub fn with_pairing<'a, M, EccChip>(
+        mut self,
+        loader: &Rc<Halo2Loader<'a, M::G1Affine, EccChip>>,
+    ) -> Self
+    where
+        M: MultiMillerLoop,
+        EccChip: PairingInstructions<'a, M>,
+    {
+        let g1 = M::G1Affine::generator();
+        let g2 = M::G2Affine::generator();
+        let [lhs, rhs] = [g1, -g1].map(|ec_point| loader.assign_ec_point(Value::known(ec_point)));
+
+        let start = loader.ctx().offset();
+        loader
+            .ecc_chip()
+            .assert_pairing_check(
+                &mut loader.ctx_mut(),
+                &[(lhs.assigned(), g2), (rhs.assigned(), g2)],
+            )
+            .unwrap();
+        self.pairing = (loader.ctx().offset() - start) / 2;
+        self
+    }
+
+    /// Estimate rows of verifier with given [`Cost`].
+    ///
+    /// Note that it doesn't include rows to assign instances, which is
+    /// usually done by caller.
+    pub fn estimate_rows(&self, cost: &Cost) -> usize {
+        let num_permutation = if self.rate == 0 {
+            0
+        } else {
+            cost.num_squeeze + cost.num_absorption() / self.rate
+        };
+        cost.num_evaluation * self.scalar_assignment
+            + cost.num_commitment * self.ec_point_assignment
+            + cost.num_scalar_add * self.scalar_add
+            + cost.num_scalar_mul * self.scalar_mul
+            + cost.num_inversion * self.inversion
+            + cost.num_msm * self.msm_term
+            + num_permutation * self.permutation
+            + cost.num_pairing * self.pairing
+    }
+
+    /// Estimate minimal `k` of circuit to fit verifier with given [`Cost`],
+    /// where `num_column` is the number of columns rows could be spread over
+    /// and `num_unusable_row` is the number of rows reserved for blinding.
+    pub fn estimate_k(&self, cost: &Cost, num_column: usize, num_unusable_row: usize) -> u32 {
+        let num_row =
+            Integer::div_ceil(&self.estimate_rows(cost), &num_column.max(1)) + num_unusable_row;
+        num_row.next_power_of_two().trailing_zeros()
+    }
+}
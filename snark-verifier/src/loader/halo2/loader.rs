@@ -4,7 +4,7 @@ use crate::{
         EcPointLoader, LoadedEcPoint, LoadedScalar, Loader, ScalarLoader,
     },
     util::{
-        arithmetic::{CurveAffine, Field, FieldOps},
+        arithmetic::{CurveAffine, Field, FieldOps, PrimeCurveAffine},
         Itertools,
     },
 };
@@ -115,6 +115,12 @@ impl<'a, C: CurveAffine, EccChip: EccInstructions<'a, C>> Halo2Loader<'a, C, Ecc
     }
 
     /// Assign an elliptic curve point witness.
+    ///
+    /// Note that whether identity is accepted depends on [`EccInstructions`],
+    /// e.g. `halo2_wrong_ecc` requires it to be on affine plane, while constant
+    /// identity is always supported in multi-scalar multiplication. Use
+    /// [`Halo2Loader::assign_ec_point_maybe_identity`] for witness that could
+    /// be identity.
     pub fn assign_ec_point(
         self: &Rc<Self>,
         ec_point: circuit::Value<C>,
@@ -126,6 +132,45 @@ impl<'a, C: CurveAffine, EccChip: EccInstructions<'a, C>> Halo2Loader<'a, C, Ecc
         self.ec_point_from_assigned(assigned)
     }
 
+    /// Assign an elliptic curve point witness which could be identity, e.g.
+    /// commitment read from transcript.
+    ///
+    /// Since [`EccInstructions`] only represents points on affine plane, a
+    /// boolean flag of being not identity is assigned along with the point,
+    /// and generator is assigned instead when it's identity. The flag is taken
+    /// into account in transcript and multi-scalar multiplication, and it's
+    /// constrained to be `1` when the point is used elsewhere via
+    /// [`EcPoint::assigned`]. It costs an extra scalar multiplication for each
+    /// term of multi-scalar multiplication the point is in, so
+    /// [`Halo2Loader::assign_ec_point`] should be used when the point is known
+    /// to be not identity.
+    pub fn assign_ec_point_maybe_identity(
+        self: &Rc<Self>,
+        ec_point: circuit::Value<C>,
+    ) -> EcPoint<'a, C, EccChip> {
+        let is_identity = ec_point.map(|ec_point| bool::from(ec_point.is_identity()));
+        let non_identity = self.assign_scalar(is_identity.map(|is_identity| {
+            if is_identity {
+                C::Scalar::ZERO
+            } else {
+                C::Scalar::ONE
+            }
+        }));
+        self.assert_eq("", &(non_identity.clone() * &non_identity), &non_identity)
+            .unwrap();
+
+        let ec_point =
+            self.assign_ec_point(ec_point.zip(is_identity).map(|(ec_point, is_identity)| {
+                if is_identity {
+                    C::generator()
+                } else {
+                    ec_point
+                }
+            }));
+        *ec_point.non_identity.borrow_mut() = Some(non_identity);
+        ec_point
+    }
+
     /// Returns [`EcPoint`] with assigned elliptic curve point.
     pub fn ec_point_from_assigned(
         self: &Rc<Self>,
@@ -144,6 +189,7 @@ impl<'a, C: CurveAffine, EccChip: EccInstructions<'a, C>> Halo2Loader<'a, C, Ecc
             loader: self.clone(),
             index,
             value: value.into(),
+            non_identity: RefCell::default(),
         }
     }
 
@@ -292,6 +338,10 @@ impl<'a, C: CurveAffine, EccChip: EccInstructions<'a, C>> Halo2Loader<'a, C, Ecc
             println!("{}: {}", identifier, cost);
         }
     }
+
+    pub fn row_meterings(self: &Rc<Self>) -> Vec<(String, usize)> {
+        self.row_meterings.borrow().clone()
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -354,7 +404,7 @@ impl<'a, C: CurveAffine, EccChip: EccInstructions<'a, C>> Scalar<'a, C, EccChip>
         self.value.borrow()
     }
 
-    fn maybe_const(&self) -> Option<C::Scalar> {
+    pub(crate) fn maybe_const(&self) -> Option<C::Scalar> {
         self.value().deref().maybe_const()
     }
 }
@@ -499,30 +549,59 @@ pub struct EcPoint<'a, C: CurveAffine, EccChip: EccInstructions<'a, C>> {
     loader: Rc<Halo2Loader<'a, C, EccChip>>,
     index: usize,
     value: RefCell<Value<C, EccChip::AssignedEcPoint>>,
+    non_identity: RefCell<Option<Scalar<'a, C, EccChip>>>,
 }
 
 impl<'a, C: CurveAffine, EccChip: EccInstructions<'a, C>> EcPoint<'a, C, EccChip> {
-    /// Into [`EccInstructions::AssignedEcPoint`].
+    /// Into [`EccInstructions::AssignedEcPoint`], which is constrained to be
+    /// not identity if it could be.
     pub fn into_assigned(self) -> EccChip::AssignedEcPoint {
+        self.assert_non_identity();
         match self.value.into_inner() {
             Value::Constant(constant) => self.loader.assign_const_ec_point(constant),
             Value::Assigned(assigned) => assigned,
         }
     }
 
-    /// Returns reference of [`EccInstructions::AssignedEcPoint`].
+    /// Returns reference of [`EccInstructions::AssignedEcPoint`], which is
+    /// constrained to be not identity if it could be.
     pub fn assigned(&self) -> Ref<EccChip::AssignedEcPoint> {
+        self.assert_non_identity();
+        self.assigned_with_flag().0
+    }
+
+    /// Returns reference of [`EccInstructions::AssignedEcPoint`] and flag of
+    /// it being not identity if it could be identity, in which case the
+    /// assigned point is meaningless when the flag is `0`.
+    pub(crate) fn assigned_with_flag(
+        &self,
+    ) -> (
+        Ref<EccChip::AssignedEcPoint>,
+        Option<Scalar<'a, C, EccChip>>,
+    ) {
         if let Some(constant) = self.maybe_const() {
             *self.value.borrow_mut() = Value::Assigned(self.loader.assign_const_ec_point(constant))
         }
-        Ref::map(self.value.borrow(), Value::assigned)
+        (
+            Ref::map(self.value.borrow(), Value::assigned),
+            self.non_identity.borrow().clone(),
+        )
+    }
+
+    fn assert_non_identity(&self) {
+        let non_identity = self.non_identity.borrow_mut().take();
+        if let Some(non_identity) = non_identity {
+            self.loader
+                .assert_eq("", &non_identity, &self.loader.load_one())
+                .expect("Elliptic curve point should not be identity");
+        }
     }
 
     fn value(&self) -> Ref<Value<C, EccChip::AssignedEcPoint>> {
         self.value.borrow()
     }
 
-    fn maybe_const(&self) -> Option<C> {
+    pub(crate) fn maybe_const(&self) -> Option<C> {
         self.value().deref().maybe_const()
     }
 }
@@ -548,6 +627,7 @@ impl<'a, C: CurveAffine, EccChip: EccInstructions<'a, C>> Debug for EcPoint<'a,
         f.debug_struct("EcPoint")
             .field("index", &self.index)
             .field("value", &self.value)
+            .field("non_identity", &self.non_identity)
             .finish()
     }
 }
@@ -567,8 +647,11 @@ impl<'a, C: CurveAffine, EccChip: EccInstructions<'a, C>> ScalarLoader<C::Scalar
         lhs: &Scalar<'a, C, EccChip>,
         rhs: &Scalar<'a, C, EccChip>,
     ) -> Result<(), crate::Error> {
+        // Assign constants before borrowing `ctx` mutably, since assigning
+        // borrows it too.
+        let (lhs, rhs) = (lhs.assigned(), rhs.assigned());
         self.scalar_chip()
-            .assert_equal(&mut self.ctx_mut(), &lhs.assigned(), &rhs.assigned())
+            .assert_equal(&mut self.ctx_mut(), &lhs, &rhs)
             .map_err(|_| crate::Error::AssertionFailure(annotation.to_string()))
     }
 
@@ -603,6 +686,57 @@ impl<'a, C: CurveAffine, EccChip: EccInstructions<'a, C>> ScalarLoader<C::Scalar
                 .unwrap(),
         ))
     }
+
+    /// Montgomery batch inversion with a single [`IntegerInstructions::invert`].
+    ///
+    /// Constants are inverted natively, where zero is left as zero. If any assigned
+    /// value is zero, the accumulated product is zero and the invert constraint is
+    /// unsatisfied, same as inverting it separately.
+    fn batch_invert<'b>(values: impl IntoIterator<Item = &'b mut Scalar<'a, C, EccChip>>)
+    where
+        Scalar<'a, C, EccChip>: 'b,
+    {
+        let mut assigned = Vec::new();
+        for value in values {
+            match value.maybe_const() {
+                Some(constant) => {
+                    let inv = Field::invert(&constant).unwrap_or(constant);
+                    *value = value.loader.scalar(Value::Constant(inv));
+                }
+                None => assigned.push(value),
+            }
+        }
+        if assigned.is_empty() {
+            return;
+        }
+
+        let loader = assigned[0].loader.clone();
+        loader.start_cost_metering(&format!("batch_invert of {} scalars", assigned.len()));
+
+        let mut products = Vec::with_capacity(assigned.len());
+        products.push(assigned[0].deref().clone());
+        for value in assigned.iter().skip(1) {
+            products.push(loader.mul(products.last().unwrap(), value));
+        }
+
+        let mut inv = loader.invert(products.last().unwrap());
+        for (value, product) in assigned
+            .into_iter()
+            .rev()
+            .zip(products.iter().rev().skip(1).map(Some).chain([None]))
+        {
+            *value = match product {
+                Some(product) => {
+                    let value_inv = loader.mul(&inv, product);
+                    inv = loader.mul(&inv, value);
+                    value_inv
+                }
+                None => inv.clone(),
+            };
+        }
+
+        loader.end_cost_metering();
+    }
 }
 
 impl<'a, C: CurveAffine, EccChip: EccInstructions<'a, C>> EcPointLoader<C>
@@ -642,6 +776,23 @@ impl<'a, C: CurveAffine, EccChip: EccInstructions<'a, C>> EcPointLoader<C>
     ) -> EcPoint<'a, C, EccChip> {
         let loader = &pairs[0].0.loader;
 
+        // Scale bases which could be identity by their flags, so identity
+        // contributes nothing to the sum.
+        let flagged_scalars = pairs
+            .iter()
+            .map(|(scalar, base)| {
+                let non_identity = base.non_identity.borrow().clone();
+                non_identity.map(|non_identity| Halo2Loader::mul(loader, scalar, &non_identity))
+            })
+            .collect_vec();
+        let pairs = pairs
+            .iter()
+            .zip(flagged_scalars.iter())
+            .map(|((scalar, base), flagged_scalar)| {
+                (flagged_scalar.as_ref().unwrap_or(*scalar), *base)
+            })
+            .collect_vec();
+
         let (constant, fixed_base, variable_base_non_scaled, variable_base_scaled) =
             pairs.iter().cloned().fold(
                 (C::identity(), Vec::new(), Vec::new(), Vec::new()),
@@ -653,6 +804,7 @@ impl<'a, C: CurveAffine, EccChip: EccInstructions<'a, C>> EcPointLoader<C>
                 ),
                  (scalar, base)| {
                     match (scalar.value().deref(), base.value().deref()) {
+                        (_, Value::Constant(base)) if bool::from(base.is_identity()) => {}
                         (Value::Constant(scalar), Value::Constant(base)) => {
                             constant = (*base * scalar + constant).into()
                         }
@@ -692,7 +844,7 @@ impl<'a, C: CurveAffine, EccChip: EccInstructions<'a, C>> EcPointLoader<C>
             .then(|| {
                 let variable_base_scaled = variable_base_scaled
                     .into_iter()
-                    .map(|(scalar, base)| (scalar.assigned(), base.assigned()))
+                    .map(|(scalar, base)| (scalar.assigned(), base.assigned_with_flag().0))
                     .collect_vec();
                 loader
                     .ecc_chip
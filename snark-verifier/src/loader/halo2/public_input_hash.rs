@@ -0,0 +1,555 @@
+//! In-circuit digest of public inputs, which is the counterpart of
+//! [`EvmTranscript::load_instances_with_digest`].
+//!
+//! Hash functions are implemented bitwise with [`ScalarLoader`] only, so it
+//! works with any [`EccInstructions`] but is not cheap, which takes roughly
+//! 75k rows per 64 bytes block of SHA-256 and 300k rows per 136 bytes block of
+//! Keccak-256 with `halo2_wrong`'s `MainGate`.
+//!
+//! [`EvmTranscript::load_instances_with_digest`]: crate::system::halo2::transcript::evm::EvmTranscript::load_instances_with_digest
+
+use crate::{
+    loader::{
+        evm::PublicInputHash,
+        halo2::{EccInstructions, Halo2Loader, Scalar, Valuetools},
+        ScalarLoader,
+    },
+    util::{
+        arithmetic::{CurveAffine, PrimeField},
+        Itertools,
+    },
+    Error,
+};
+use halo2_proofs::circuit::Value;
+use std::{iter, rc::Rc};
+
+const SHA256_IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+const SHA256_K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+const KECCAK_RATE: usize = 136;
+
+const KECCAK_RC: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// Rotation offsets of lane `x + 5 * y`.
+const KECCAK_ROTATION: [u32; 25] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+impl<'a, C: CurveAffine, EccChip: EccInstructions<'a, C>> Halo2Loader<'a, C, EccChip>
+where
+    C::Scalar: PrimeField<Repr = [u8; 32]>,
+{
+    /// Assign public inputs and returns them with their digest, which equals to
+    /// [`PublicInputHash::digest`] of inputs encoded as concatenated 32 bytes
+    /// big-endian words.
+    ///
+    /// The digest is supposed to be exposed as the last instance, then verifier
+    /// using [`EvmTranscript::load_instances_with_digest`] takes the encoded
+    /// inputs as preimage in calldata instead.
+    ///
+    /// [`EvmTranscript::load_instances_with_digest`]: crate::system::halo2::transcript::evm::EvmTranscript::load_instances_with_digest
+    pub fn assign_public_inputs_with_digest(
+        self: &Rc<Self>,
+        hash: PublicInputHash,
+        inputs: impl IntoIterator<Item = Value<C::Scalar>>,
+    ) -> Result<(Vec<Scalar<'a, C, EccChip>>, Scalar<'a, C, EccChip>), Error> {
+        let chip = BitChip { loader: self };
+
+        let mut preimage = Vec::new();
+        let inputs = inputs
+            .into_iter()
+            .map(|value| {
+                let input = self.assign_scalar(value);
+                let bits = chip.decompose_canonical(&input, value)?;
+                // Big-endian bytes with little-endian bits
+                preimage.extend((0..32).rev().flat_map(|idx| {
+                    (8 * idx..8 * idx + 8)
+                        .map(|idx| bits.get(idx).cloned().unwrap_or(Bit::Constant(false)))
+                }));
+                Ok(input)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let hash = match hash {
+            PublicInputHash::Keccak256 => chip.keccak256(preimage),
+            PublicInputHash::Sha256 => chip.sha256(preimage)?,
+        };
+        // Little-endian bits of hash as big-endian integer
+        let bits = (0..32)
+            .rev()
+            .flat_map(|idx| hash[8 * idx..8 * idx + 8].iter())
+            .take(C::Scalar::NUM_BITS as usize - 1)
+            .cloned()
+            .collect_vec();
+        let digest = chip.compose(&bits);
+
+        Ok((inputs, digest))
+    }
+}
+
+#[derive(Clone)]
+enum Bit<'a, C: CurveAffine, EccChip: EccInstructions<'a, C>> {
+    Constant(bool),
+    Assigned(Scalar<'a, C, EccChip>, Value<bool>),
+}
+
+impl<'a, C: CurveAffine, EccChip: EccInstructions<'a, C>> Bit<'a, C, EccChip> {
+    fn value(&self) -> Value<bool> {
+        match self {
+            Bit::Constant(constant) => Value::known(*constant),
+            Bit::Assigned(_, value) => *value,
+        }
+    }
+}
+
+struct BitChip<'b, 'a, C: CurveAffine, EccChip: EccInstructions<'a, C>> {
+    loader: &'b Rc<Halo2Loader<'a, C, EccChip>>,
+}
+
+impl<'b, 'a, C: CurveAffine, EccChip: EccInstructions<'a, C>> BitChip<'b, 'a, C, EccChip>
+where
+    C::Scalar: PrimeField<Repr = [u8; 32]>,
+{
+    fn assign(&self, value: Value<bool>) -> Result<Bit<'a, C, EccChip>, Error> {
+        let bit = self
+            .loader
+            .assign_scalar(value.map(|value| C::Scalar::from(value as u64)));
+        let square = self
+            .loader
+            .sum_products_with_coeff_and_const(&[(C::Scalar::ONE, &bit, &bit)], C::Scalar::ZERO);
+        self.loader.assert_eq("bit is boolean", &square, &bit)?;
+        Ok(Bit::Assigned(bit, value))
+    }
+
+    fn compose(&self, bits: &[Bit<'a, C, EccChip>]) -> Scalar<'a, C, EccChip> {
+        self.compose_words(&[bits])
+    }
+
+    /// Decompose `scalar` into `num_bits` little-endian bits.
+    fn decompose(
+        &self,
+        scalar: &Scalar<'a, C, EccChip>,
+        value: Value<C::Scalar>,
+        num_bits: usize,
+    ) -> Result<Vec<Bit<'a, C, EccChip>>, Error> {
+        let repr = value.map(|value| value.to_repr());
+        let bits = (0..num_bits)
+            .map(|idx| self.assign(repr.map(|repr| (repr[idx / 8] >> (idx % 8)) & 1 == 1)))
+            .collect::<Result<Vec<_>, Error>>()?;
+        let composed = self.compose(&bits);
+        self.loader
+            .assert_eq("bits composition", &composed, scalar)?;
+        Ok(bits)
+    }
+
+    /// Decompose `scalar` into little-endian bits of its canonical
+    /// representation, so the encoding of `scalar` is unique.
+    fn decompose_canonical(
+        &self,
+        scalar: &Scalar<'a, C, EccChip>,
+        value: Value<C::Scalar>,
+    ) -> Result<Vec<Bit<'a, C, EccChip>>, Error> {
+        let bits = self.decompose(scalar, value, C::Scalar::NUM_BITS as usize)?;
+
+        // Compare with `modulus - 1` from the most significant bit, where
+        // `is_prefix_eq` is whether higher bits are all equal to its bits.
+        let max = (-C::Scalar::ONE).to_repr();
+        let zero = self.loader.load_const(&C::Scalar::ZERO);
+        let mut is_prefix_eq = Bit::Constant(true);
+        for (idx, bit) in bits.iter().enumerate().rev() {
+            let is_eq = self.and(&is_prefix_eq, bit);
+            if (max[idx / 8] >> (idx % 8)) & 1 == 1 {
+                is_prefix_eq = is_eq;
+            } else if let Bit::Assigned(is_eq, _) = is_eq {
+                self.loader
+                    .assert_eq("canonical representation", &is_eq, &zero)?;
+            }
+        }
+
+        Ok(bits)
+    }
+
+    fn not(&self, bit: &Bit<'a, C, EccChip>) -> Bit<'a, C, EccChip> {
+        match bit {
+            Bit::Constant(constant) => Bit::Constant(!constant),
+            Bit::Assigned(bit, value) => Bit::Assigned(
+                self.loader
+                    .sum_with_coeff_and_const(&[(-C::Scalar::ONE, bit)], C::Scalar::ONE),
+                value.map(|value| !value),
+            ),
+        }
+    }
+
+    fn and(&self, lhs: &Bit<'a, C, EccChip>, rhs: &Bit<'a, C, EccChip>) -> Bit<'a, C, EccChip> {
+        match (lhs, rhs) {
+            (Bit::Constant(constant), bit) | (bit, Bit::Constant(constant)) => {
+                if *constant {
+                    bit.clone()
+                } else {
+                    Bit::Constant(false)
+                }
+            }
+            (Bit::Assigned(lhs, lhs_value), Bit::Assigned(rhs, rhs_value)) => Bit::Assigned(
+                self.loader.sum_products_with_coeff_and_const(
+                    &[(C::Scalar::ONE, lhs, rhs)],
+                    C::Scalar::ZERO,
+                ),
+                lhs_value.zip(*rhs_value).map(|(lhs, rhs)| lhs & rhs),
+            ),
+        }
+    }
+
+    fn xor(&self, lhs: &Bit<'a, C, EccChip>, rhs: &Bit<'a, C, EccChip>) -> Bit<'a, C, EccChip> {
+        match (lhs, rhs) {
+            (Bit::Constant(constant), bit) | (bit, Bit::Constant(constant)) => {
+                if *constant {
+                    self.not(bit)
+                } else {
+                    bit.clone()
+                }
+            }
+            (Bit::Assigned(lhs, lhs_value), Bit::Assigned(rhs, rhs_value)) => {
+                let product = self.loader.sum_products_with_coeff_and_const(
+                    &[(C::Scalar::ONE, lhs, rhs)],
+                    C::Scalar::ZERO,
+                );
+                Bit::Assigned(
+                    self.loader.sum_with_coeff_and_const(
+                        &[
+                            (C::Scalar::ONE, lhs),
+                            (C::Scalar::ONE, rhs),
+                            (-C::Scalar::ONE.double(), &product),
+                        ],
+                        C::Scalar::ZERO,
+                    ),
+                    lhs_value.zip(*rhs_value).map(|(lhs, rhs)| lhs ^ rhs),
+                )
+            }
+        }
+    }
+
+    /// Returns `lhs | rhs` given they are never both `1`.
+    fn disjoint_or(
+        &self,
+        lhs: &Bit<'a, C, EccChip>,
+        rhs: &Bit<'a, C, EccChip>,
+    ) -> Bit<'a, C, EccChip> {
+        match (lhs, rhs) {
+            (Bit::Assigned(lhs, lhs_value), Bit::Assigned(rhs, rhs_value)) => Bit::Assigned(
+                self.loader.sum_with_coeff_and_const(
+                    &[(C::Scalar::ONE, lhs), (C::Scalar::ONE, rhs)],
+                    C::Scalar::ZERO,
+                ),
+                lhs_value.zip(*rhs_value).map(|(lhs, rhs)| lhs | rhs),
+            ),
+            _ => self.xor(lhs, rhs),
+        }
+    }
+
+    fn xor_words(&self, words: &[&[Bit<'a, C, EccChip>]]) -> Vec<Bit<'a, C, EccChip>> {
+        (0..words[0].len())
+            .map(|idx| {
+                words
+                    .iter()
+                    .skip(1)
+                    .fold(words[0][idx].clone(), |acc, word| {
+                        self.xor(&acc, &word[idx])
+                    })
+            })
+            .collect()
+    }
+
+    /// Returns sum of 32 bits words modulo `2^32`.
+    fn add_words(
+        &self,
+        words: &[&[Bit<'a, C, EccChip>]],
+    ) -> Result<Vec<Bit<'a, C, EccChip>>, Error> {
+        if let Some(sum) = words
+            .iter()
+            .map(|word| constant_value(word))
+            .sum::<Option<u64>>()
+        {
+            return Ok(constant_bits(sum, 32));
+        }
+
+        let sum = self.compose_words(words);
+        let value = words
+            .iter()
+            .map(|word| word_value(word))
+            .fold_zipped(0, |acc, word| acc + word as u64)
+            .map(C::Scalar::from);
+        let num_carry_bits = (usize::BITS - (words.len() - 1).leading_zeros()) as usize;
+        let mut bits = self.decompose(&sum, value, 32 + num_carry_bits)?;
+        bits.truncate(32);
+        Ok(bits)
+    }
+
+    /// Returns sum of little-endian `words`.
+    fn compose_words(&self, words: &[&[Bit<'a, C, EccChip>]]) -> Scalar<'a, C, EccChip> {
+        let mut constant = C::Scalar::ZERO;
+        let mut terms = Vec::new();
+        for word in words {
+            let coeffs = iter::successors(Some(C::Scalar::ONE), |coeff| Some(coeff.double()));
+            for (bit, coeff) in word.iter().zip(coeffs) {
+                match bit {
+                    Bit::Constant(true) => constant += coeff,
+                    Bit::Constant(false) => {}
+                    Bit::Assigned(bit, _) => terms.push((coeff, bit)),
+                }
+            }
+        }
+        if terms.is_empty() {
+            self.loader.load_const(&constant)
+        } else {
+            self.loader.sum_with_coeff_and_const(&terms, constant)
+        }
+    }
+
+    fn sha256(
+        &self,
+        mut message: Vec<Bit<'a, C, EccChip>>,
+    ) -> Result<Vec<Bit<'a, C, EccChip>>, Error> {
+        let num_bits = message.len() as u64;
+        message.extend(byte_constant(0x80));
+        while message.len() % 512 != 448 {
+            message.extend(byte_constant(0));
+        }
+        message.extend(num_bits.to_be_bytes().into_iter().flat_map(byte_constant));
+
+        let mut state = SHA256_IV
+            .map(|word| constant_bits(word as u64, 32))
+            .to_vec();
+        for block in message.chunks(512) {
+            // Big-endian bytes to little-endian bits of 32 bits words
+            let mut w = block
+                .chunks(32)
+                .map(|bytes| {
+                    (0..32)
+                        .map(|idx| bytes[8 * (3 - idx / 8) + idx % 8].clone())
+                        .collect_vec()
+                })
+                .collect_vec();
+            for t in 16..64 {
+                let s0 = self.xor_words(&[
+                    &rotate_right(&w[t - 15], 7),
+                    &rotate_right(&w[t - 15], 18),
+                    &shift_right(&w[t - 15], 3),
+                ]);
+                let s1 = self.xor_words(&[
+                    &rotate_right(&w[t - 2], 17),
+                    &rotate_right(&w[t - 2], 19),
+                    &shift_right(&w[t - 2], 10),
+                ]);
+                w.push(self.add_words(&[&s1, &w[t - 7], &s0, &w[t - 16]])?);
+            }
+
+            let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] =
+                <[_; 8]>::try_from(state.clone()).ok().unwrap();
+            for (k, w) in SHA256_K.into_iter().zip(w.iter()) {
+                let s1 = self.xor_words(&[
+                    &rotate_right(&e, 6),
+                    &rotate_right(&e, 11),
+                    &rotate_right(&e, 25),
+                ]);
+                let ch = e
+                    .iter()
+                    .zip(f.iter().zip(g.iter()))
+                    .map(|(e, (f, g))| {
+                        self.disjoint_or(&self.and(e, f), &self.and(&self.not(e), g))
+                    })
+                    .collect_vec();
+                let s0 = self.xor_words(&[
+                    &rotate_right(&a, 2),
+                    &rotate_right(&a, 13),
+                    &rotate_right(&a, 22),
+                ]);
+                let maj = a
+                    .iter()
+                    .zip(b.iter().zip(c.iter()))
+                    .map(|(a, (b, c))| {
+                        self.disjoint_or(&self.and(a, b), &self.and(c, &self.xor(a, b)))
+                    })
+                    .collect_vec();
+                let k = constant_bits(k as u64, 32);
+
+                let next_e = self.add_words(&[&d, &h, &s1, &ch, &k, w])?;
+                let next_a = self.add_words(&[&h, &s1, &ch, &k, w, &s0, &maj])?;
+                h = g;
+                g = f;
+                f = e;
+                e = next_e;
+                d = c;
+                c = b;
+                b = a;
+                a = next_a;
+            }
+
+            state = state
+                .iter()
+                .zip([a, b, c, d, e, f, g, h].iter())
+                .map(|(lhs, rhs)| self.add_words(&[lhs, rhs]))
+                .collect::<Result<_, Error>>()?;
+        }
+
+        // Little-endian bits of 32 bits words to big-endian bytes
+        Ok(state
+            .iter()
+            .flat_map(|word| (0..32).map(|idx| word[8 * (3 - idx / 8) + idx % 8].clone()))
+            .collect())
+    }
+
+    fn keccak256(&self, mut message: Vec<Bit<'a, C, EccChip>>) -> Vec<Bit<'a, C, EccChip>> {
+        message.extend(byte_constant(0x01));
+        while message.len() % (8 * KECCAK_RATE) != 0 {
+            message.extend(byte_constant(0));
+        }
+        let last = message.last_mut().unwrap();
+        *last = self.not(last);
+
+        let mut state = vec![constant_bits(0, 64); 25];
+        for block in message.chunks(8 * KECCAK_RATE) {
+            // Little-endian bytes to little-endian bits of 64 bits lanes
+            for (lane, bits) in state.iter_mut().zip(block.chunks(64)) {
+                *lane = self.xor_words(&[lane.as_slice(), bits]);
+            }
+            self.keccak_f(&mut state);
+        }
+
+        state[..4].concat()
+    }
+
+    fn keccak_f(&self, state: &mut [Vec<Bit<'a, C, EccChip>>]) {
+        for rc in KECCAK_RC {
+            // θ
+            let c = (0..5)
+                .map(|x| self.xor_words(&(0..5).map(|y| state[x + 5 * y].as_slice()).collect_vec()))
+                .collect_vec();
+            let d = (0..5)
+                .map(|x| self.xor_words(&[&c[(x + 4) % 5], &rotate_left(&c[(x + 1) % 5], 1)]))
+                .collect_vec();
+            for (idx, lane) in state.iter_mut().enumerate() {
+                *lane = self.xor_words(&[lane.as_slice(), &d[idx % 5]]);
+            }
+
+            // ρ and π
+            let mut b = vec![Vec::new(); 25];
+            for (idx, lane) in state.iter().enumerate() {
+                let (x, y) = (idx % 5, idx / 5);
+                b[y + 5 * ((2 * x + 3 * y) % 5)] = rotate_left(lane, KECCAK_ROTATION[idx]);
+            }
+
+            // χ
+            for (idx, lane) in state.iter_mut().enumerate() {
+                let (x, y) = (idx % 5, idx / 5);
+                *lane = b[idx]
+                    .iter()
+                    .zip(b[(x + 1) % 5 + 5 * y].iter())
+                    .zip(b[(x + 2) % 5 + 5 * y].iter())
+                    .map(|((b0, b1), b2)| self.xor(b0, &self.and(&self.not(b1), b2)))
+                    .collect();
+            }
+
+            // ι
+            state[0] = self.xor_words(&[&state[0], &constant_bits(rc, 64)]);
+        }
+    }
+}
+
+fn constant_value<'a, C: CurveAffine, EccChip: EccInstructions<'a, C>>(
+    word: &[Bit<'a, C, EccChip>],
+) -> Option<u64> {
+    word.iter()
+        .enumerate()
+        .try_fold(0, |acc, (idx, bit)| match bit {
+            Bit::Constant(bit) => Some(acc | ((*bit as u64) << idx)),
+            Bit::Assigned(..) => None,
+        })
+}
+
+fn word_value<'a, C: CurveAffine, EccChip: EccInstructions<'a, C>>(
+    word: &[Bit<'a, C, EccChip>],
+) -> Value<u32> {
+    word.iter()
+        .map(Bit::value)
+        .enumerate()
+        .fold(Value::known(0), |acc, (idx, bit)| {
+            acc.zip(bit).map(|(acc, bit)| acc | ((bit as u32) << idx))
+        })
+}
+
+fn constant_bits<'a, C: CurveAffine, EccChip: EccInstructions<'a, C>>(
+    value: u64,
+    num_bits: usize,
+) -> Vec<Bit<'a, C, EccChip>> {
+    (0..num_bits)
+        .map(|idx| Bit::Constant((value >> idx) & 1 == 1))
+        .collect()
+}
+
+fn byte_constant<'a, C: CurveAffine, EccChip: EccInstructions<'a, C>>(
+    byte: u8,
+) -> Vec<Bit<'a, C, EccChip>> {
+    constant_bits(byte as u64, 8)
+}
+
+fn rotate_right<'a, C: CurveAffine, EccChip: EccInstructions<'a, C>>(
+    word: &[Bit<'a, C, EccChip>],
+    n: usize,
+) -> Vec<Bit<'a, C, EccChip>> {
+    (0..word.len())
+        .map(|idx| word[(idx + n) % word.len()].clone())
+        .collect()
+}
+
+fn rotate_left<'a, C: CurveAffine, EccChip: EccInstructions<'a, C>>(
+    word: &[Bit<'a, C, EccChip>],
+    n: u32,
+) -> Vec<Bit<'a, C, EccChip>> {
+    rotate_right(word, word.len() - n as usize % word.len())
+}
+
+fn shift_right<'a, C: CurveAffine, EccChip: EccInstructions<'a, C>>(
+    word: &[Bit<'a, C, EccChip>],
+    n: usize,
+) -> Vec<Bit<'a, C, EccChip>> {
+    (0..word.len())
+        .map(|idx| word.get(idx + n).cloned().unwrap_or(Bit::Constant(false)))
+        .collect()
+}
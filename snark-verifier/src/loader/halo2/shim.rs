@@ -1,4 +1,4 @@
-use crate::util::arithmetic::{CurveAffine, PrimeField};
+use crate::util::arithmetic::{CurveAffine, MultiMillerLoop, PrimeField};
 use halo2_proofs::{
     circuit::{Cell, Value},
     plonk::Error,
@@ -86,6 +86,12 @@ pub trait IntegerInstructions<'a, F: PrimeField>: Clone + Debug {
         lhs: &Self::AssignedInteger,
         rhs: &Self::AssignedInteger,
     ) -> Result<(), Error>;
+
+    /// Returns witness value of `integer`, which is unknown by default for
+    /// chips not exposing it.
+    fn integer_value(&self, _: &Self::AssignedInteger) -> Value<F> {
+        Value::unknown()
+    }
 }
 
 /// Instructions to handle elliptic curve point operations.
@@ -158,20 +164,33 @@ pub trait EccInstructions<'a, C: CurveAffine>: Clone + Debug {
     ) -> Result<(), Error>;
 }
 
+/// Instructions to perform pairing check, which is usually only supported by
+/// [`EccInstructions`] of specific curve.
+pub trait PairingInstructions<'a, M: MultiMillerLoop>: EccInstructions<'a, M::G1Affine> {
+    /// Enforce `∏ e(g1_i, g2_i) == 1` for given `(g1_i, g2_i)` where each
+    /// `g2_i` is a constant.
+    fn assert_pairing_check(
+        &self,
+        ctx: &mut Self::Context,
+        terms: &[(impl Deref<Target = Self::AssignedEcPoint>, M::G2Affine)],
+    ) -> Result<(), Error>;
+}
+
 mod halo2_wrong {
     use crate::{
-        loader::halo2::{Context, EccInstructions, IntegerInstructions},
+        loader::halo2::{Context, EccInstructions, IntegerInstructions, PairingInstructions},
         util::{
-            arithmetic::{CurveAffine, Group, PrimeField},
+            arithmetic::{modulus, Curve, CurveAffine, Field, Group, PrimeField},
             Itertools,
         },
     };
     use halo2_proofs::{
         circuit::{AssignedCell, Cell, Value},
+        halo2curves::bn256::{Bn256, Fq, Fq2, Fr, G1Affine, G2Affine, G2},
         plonk::Error,
     };
     use halo2_wrong_ecc::{
-        integer::rns::Common,
+        integer::{rns::Common, AssignedInteger, IntegerChip, IntegerInstructions as _},
         maingate::{
             CombinationOption, CombinationOptionCommon, MainGate, MainGateInstructions, RegionCtx,
             Term,
@@ -351,6 +370,10 @@ mod halo2_wrong {
             MainGateInstructions::assert_equal(self, ctx, lhs, rhs)
                 .and(eq.then_some(()).ok_or(Error::Synthesis))
         }
+
+        fn integer_value(&self, integer: &Self::AssignedInteger) -> Value<F> {
+            integer.value().copied()
+        }
     }
 
     impl<'a, C: CurveAffine, const LIMBS: usize, const BITS: usize> EccInstructions<'a, C>
@@ -471,4 +494,921 @@ mod halo2_wrong {
                 .and(eq.then_some(()).ok_or(Error::Synthesis))
         }
     }
+
+    type AssignedFq<const LIMBS: usize, const BITS: usize> = AssignedInteger<Fq, Fr, LIMBS, BITS>;
+    type AssignedFq2<const LIMBS: usize, const BITS: usize> = [AssignedFq<LIMBS, BITS>; 2];
+    type AssignedFq6<const LIMBS: usize, const BITS: usize> = [AssignedFq2<LIMBS, BITS>; 3];
+    type AssignedFq12<const LIMBS: usize, const BITS: usize> = [AssignedFq6<LIMBS, BITS>; 2];
+
+    /// Parameter `x` of BN254.
+    const BN_X: u64 = 4965661367192848881;
+
+    /// Returns bits of `value` after the most significant one, from high to low.
+    fn bits_after_msb(value: u128) -> impl Iterator<Item = bool> {
+        (0..127 - value.leading_zeros())
+            .rev()
+            .map(move |idx| (value >> idx) & 1 == 1)
+    }
+
+    /// Returns whether to square the accumulated value before each step of
+    /// Miller loop of optimal ate pairing, where each step multiplies a line.
+    fn miller_loop_steps() -> impl Iterator<Item = bool> {
+        bits_after_msb(6 * BN_X as u128 + 2)
+            .enumerate()
+            .flat_map(|(idx, bit)| iter::once(idx != 0).chain(bit.then_some(false)))
+            .chain([false, false])
+    }
+
+    /// Returns `(λ, λ·x_T - y_T)` of each line in Miller loop with fixed `q`,
+    /// where the line evaluated at `P` is `y_P - λ·x_P·w + (λ·x_T - y_T)·w^3`
+    /// with `(x, y) -> (x·w^2, y·w^3)` as the untwisting isomorphism.
+    fn miller_loop_lines(q: G2Affine) -> Vec<(Fq2, Fq2)> {
+        let coordinates = |ec_point: G2Affine| {
+            let coordinates = ec_point.coordinates().unwrap();
+            (*coordinates.x(), *coordinates.y())
+        };
+        // Frobenius endomorphism acts as multiplication by `p` on G2, and
+        // `p ≡ 6x^2 (mod r)` for BN curves.
+        let p = Fr::from(6) * Fr::from(BN_X).square();
+        let q_1 = (G2::from(q) * p).to_affine();
+        let minus_q_2 = -(G2::from(q_1) * p).to_affine();
+        let [q, q_1, minus_q_2] = [q, q_1, minus_q_2].map(coordinates);
+
+        let mut lines = Vec::new();
+        let mut t = q;
+        let mut add = |(x_0, y_0): (Fq2, Fq2), x_1: Fq2, lambda: Fq2| {
+            lines.push((lambda, lambda * x_0 - y_0));
+            let x_2 = lambda.square() - x_0 - x_1;
+            (x_2, lambda * (x_0 - x_2) - y_0)
+        };
+        let addends = bits_after_msb(6 * BN_X as u128 + 2)
+            .flat_map(|bit| iter::once(None).chain(bit.then_some(Some(q))))
+            .chain([Some(q_1), Some(minus_q_2)]);
+        for addend in addends {
+            t = match addend {
+                None => {
+                    let x_square = t.0.square();
+                    let lambda = (x_square.double() + x_square) * t.1.double().invert().unwrap();
+                    add(t, t.0, lambda)
+                }
+                Some((x, y)) => {
+                    let lambda = (y - t.1) * (x - t.0).invert().unwrap();
+                    add(t, x, lambda)
+                }
+            };
+        }
+
+        lines
+    }
+
+    /// Arithmetic of BN254 extension fields `Fq2 = Fq[u]/(u^2 + 1)`, `Fq6 =
+    /// Fq2[v]/(v^3 - ξ)` where `ξ = u + 9` and `Fq12 = Fq6[w]/(w^2 - v)`, built
+    /// on [`IntegerChip`] of `Fq`.
+    struct Bn254TowerChip<'b, const LIMBS: usize, const BITS: usize> {
+        integer_chip: &'b IntegerChip<Fq, Fr, LIMBS, BITS>,
+    }
+
+    impl<'b, const LIMBS: usize, const BITS: usize> Bn254TowerChip<'b, LIMBS, BITS> {
+        fn fq2_constant(
+            &self,
+            ctx: &mut RegionCtx<'_, Fr>,
+            constant: Fq2,
+        ) -> Result<AssignedFq2<LIMBS, BITS>, Error> {
+            Ok([
+                self.integer_chip.assign_constant(ctx, constant.c0)?,
+                self.integer_chip.assign_constant(ctx, constant.c1)?,
+            ])
+        }
+
+        fn fq2_add(
+            &self,
+            ctx: &mut RegionCtx<'_, Fr>,
+            lhs: &AssignedFq2<LIMBS, BITS>,
+            rhs: &AssignedFq2<LIMBS, BITS>,
+        ) -> Result<AssignedFq2<LIMBS, BITS>, Error> {
+            Ok([
+                self.integer_chip.add(ctx, &lhs[0], &rhs[0])?,
+                self.integer_chip.add(ctx, &lhs[1], &rhs[1])?,
+            ])
+        }
+
+        fn fq2_sub(
+            &self,
+            ctx: &mut RegionCtx<'_, Fr>,
+            lhs: &AssignedFq2<LIMBS, BITS>,
+            rhs: &AssignedFq2<LIMBS, BITS>,
+        ) -> Result<AssignedFq2<LIMBS, BITS>, Error> {
+            Ok([
+                self.integer_chip.sub(ctx, &lhs[0], &rhs[0])?,
+                self.integer_chip.sub(ctx, &lhs[1], &rhs[1])?,
+            ])
+        }
+
+        fn fq2_neg(
+            &self,
+            ctx: &mut RegionCtx<'_, Fr>,
+            value: &AssignedFq2<LIMBS, BITS>,
+        ) -> Result<AssignedFq2<LIMBS, BITS>, Error> {
+            Ok([
+                self.integer_chip.neg(ctx, &value[0])?,
+                self.integer_chip.neg(ctx, &value[1])?,
+            ])
+        }
+
+        fn fq2_conjugate(
+            &self,
+            ctx: &mut RegionCtx<'_, Fr>,
+            value: &AssignedFq2<LIMBS, BITS>,
+        ) -> Result<AssignedFq2<LIMBS, BITS>, Error> {
+            Ok([value[0].clone(), self.integer_chip.neg(ctx, &value[1])?])
+        }
+
+        fn fq2_mul(
+            &self,
+            ctx: &mut RegionCtx<'_, Fr>,
+            lhs: &AssignedFq2<LIMBS, BITS>,
+            rhs: &AssignedFq2<LIMBS, BITS>,
+        ) -> Result<AssignedFq2<LIMBS, BITS>, Error> {
+            let integer_chip = self.integer_chip;
+            let t_0 = integer_chip.mul(ctx, &lhs[0], &rhs[0])?;
+            let t_1 = integer_chip.mul(ctx, &lhs[1], &rhs[1])?;
+            let lhs_sum = integer_chip.add(ctx, &lhs[0], &lhs[1])?;
+            let rhs_sum = integer_chip.add(ctx, &rhs[0], &rhs[1])?;
+            let t_2 = integer_chip.mul(ctx, &lhs_sum, &rhs_sum)?;
+            let t_2 = integer_chip.sub(ctx, &t_2, &t_0)?;
+            Ok([
+                integer_chip.sub(ctx, &t_0, &t_1)?,
+                integer_chip.sub(ctx, &t_2, &t_1)?,
+            ])
+        }
+
+        fn fq2_mul_by_fq(
+            &self,
+            ctx: &mut RegionCtx<'_, Fr>,
+            lhs: &AssignedFq2<LIMBS, BITS>,
+            rhs: &AssignedFq<LIMBS, BITS>,
+        ) -> Result<AssignedFq2<LIMBS, BITS>, Error> {
+            Ok([
+                self.integer_chip.mul(ctx, &lhs[0], rhs)?,
+                self.integer_chip.mul(ctx, &lhs[1], rhs)?,
+            ])
+        }
+
+        /// Returns `value·ξ`.
+        fn fq2_mul_by_nonresidue(
+            &self,
+            ctx: &mut RegionCtx<'_, Fr>,
+            value: &AssignedFq2<LIMBS, BITS>,
+        ) -> Result<AssignedFq2<LIMBS, BITS>, Error> {
+            let value_2 = self.fq2_add(ctx, value, value)?;
+            let value_4 = self.fq2_add(ctx, &value_2, &value_2)?;
+            let value_8 = self.fq2_add(ctx, &value_4, &value_4)?;
+            let value_9 = self.fq2_add(ctx, &value_8, value)?;
+            Ok([
+                self.integer_chip.sub(ctx, &value_9[0], &value[1])?,
+                self.integer_chip.add(ctx, &value_9[1], &value[0])?,
+            ])
+        }
+
+        fn fq2_invert(
+            &self,
+            ctx: &mut RegionCtx<'_, Fr>,
+            value: &AssignedFq2<LIMBS, BITS>,
+        ) -> Result<AssignedFq2<LIMBS, BITS>, Error> {
+            let integer_chip = self.integer_chip;
+            let c_0_square = integer_chip.square(ctx, &value[0])?;
+            let c_1_square = integer_chip.square(ctx, &value[1])?;
+            let norm = integer_chip.add(ctx, &c_0_square, &c_1_square)?;
+            let [c_0, c_1] = self.fq2_conjugate(ctx, value)?;
+            Ok([
+                integer_chip.div_incomplete(ctx, &c_0, &norm)?,
+                integer_chip.div_incomplete(ctx, &c_1, &norm)?,
+            ])
+        }
+
+        fn fq6_add(
+            &self,
+            ctx: &mut RegionCtx<'_, Fr>,
+            lhs: &AssignedFq6<LIMBS, BITS>,
+            rhs: &AssignedFq6<LIMBS, BITS>,
+        ) -> Result<AssignedFq6<LIMBS, BITS>, Error> {
+            Ok([
+                self.fq2_add(ctx, &lhs[0], &rhs[0])?,
+                self.fq2_add(ctx, &lhs[1], &rhs[1])?,
+                self.fq2_add(ctx, &lhs[2], &rhs[2])?,
+            ])
+        }
+
+        fn fq6_sub(
+            &self,
+            ctx: &mut RegionCtx<'_, Fr>,
+            lhs: &AssignedFq6<LIMBS, BITS>,
+            rhs: &AssignedFq6<LIMBS, BITS>,
+        ) -> Result<AssignedFq6<LIMBS, BITS>, Error> {
+            Ok([
+                self.fq2_sub(ctx, &lhs[0], &rhs[0])?,
+                self.fq2_sub(ctx, &lhs[1], &rhs[1])?,
+                self.fq2_sub(ctx, &lhs[2], &rhs[2])?,
+            ])
+        }
+
+        fn fq6_neg(
+            &self,
+            ctx: &mut RegionCtx<'_, Fr>,
+            value: &AssignedFq6<LIMBS, BITS>,
+        ) -> Result<AssignedFq6<LIMBS, BITS>, Error> {
+            Ok([
+                self.fq2_neg(ctx, &value[0])?,
+                self.fq2_neg(ctx, &value[1])?,
+                self.fq2_neg(ctx, &value[2])?,
+            ])
+        }
+
+        /// Returns `lhs_0·rhs_1 + lhs_1·rhs_0` given `t_0 = lhs_0·rhs_0` and
+        /// `t_1 = lhs_1·rhs_1`.
+        fn fq2_cross_mul(
+            &self,
+            ctx: &mut RegionCtx<'_, Fr>,
+            lhs: [&AssignedFq2<LIMBS, BITS>; 2],
+            rhs: [&AssignedFq2<LIMBS, BITS>; 2],
+            t_0: &AssignedFq2<LIMBS, BITS>,
+            t_1: &AssignedFq2<LIMBS, BITS>,
+        ) -> Result<AssignedFq2<LIMBS, BITS>, Error> {
+            let lhs_sum = self.fq2_add(ctx, lhs[0], lhs[1])?;
+            let rhs_sum = self.fq2_add(ctx, rhs[0], rhs[1])?;
+            let product = self.fq2_mul(ctx, &lhs_sum, &rhs_sum)?;
+            let product = self.fq2_sub(ctx, &product, t_0)?;
+            self.fq2_sub(ctx, &product, t_1)
+        }
+
+        fn fq6_mul(
+            &self,
+            ctx: &mut RegionCtx<'_, Fr>,
+            lhs: &AssignedFq6<LIMBS, BITS>,
+            rhs: &AssignedFq6<LIMBS, BITS>,
+        ) -> Result<AssignedFq6<LIMBS, BITS>, Error> {
+            let t_0 = self.fq2_mul(ctx, &lhs[0], &rhs[0])?;
+            let t_1 = self.fq2_mul(ctx, &lhs[1], &rhs[1])?;
+            let t_2 = self.fq2_mul(ctx, &lhs[2], &rhs[2])?;
+            let t_12 =
+                self.fq2_cross_mul(ctx, [&lhs[1], &lhs[2]], [&rhs[1], &rhs[2]], &t_1, &t_2)?;
+            let t_01 =
+                self.fq2_cross_mul(ctx, [&lhs[0], &lhs[1]], [&rhs[0], &rhs[1]], &t_0, &t_1)?;
+            let t_02 =
+                self.fq2_cross_mul(ctx, [&lhs[0], &lhs[2]], [&rhs[0], &rhs[2]], &t_0, &t_2)?;
+            let t_12 = self.fq2_mul_by_nonresidue(ctx, &t_12)?;
+            let t_2 = self.fq2_mul_by_nonresidue(ctx, &t_2)?;
+            Ok([
+                self.fq2_add(ctx, &t_0, &t_12)?,
+                self.fq2_add(ctx, &t_01, &t_2)?,
+                self.fq2_add(ctx, &t_02, &t_1)?,
+            ])
+        }
+
+        /// Returns `value·v`.
+        fn fq6_mul_by_nonresidue(
+            &self,
+            ctx: &mut RegionCtx<'_, Fr>,
+            value: &AssignedFq6<LIMBS, BITS>,
+        ) -> Result<AssignedFq6<LIMBS, BITS>, Error> {
+            Ok([
+                self.fq2_mul_by_nonresidue(ctx, &value[2])?,
+                value[0].clone(),
+                value[1].clone(),
+            ])
+        }
+
+        /// Returns `value·(c_0 + c_1·v)`.
+        fn fq6_mul_by_01(
+            &self,
+            ctx: &mut RegionCtx<'_, Fr>,
+            value: &AssignedFq6<LIMBS, BITS>,
+            c_0: &AssignedFq2<LIMBS, BITS>,
+            c_1: &AssignedFq2<LIMBS, BITS>,
+        ) -> Result<AssignedFq6<LIMBS, BITS>, Error> {
+            let [a_0, a_1, a_2] = value;
+            let a_0_c_0 = self.fq2_mul(ctx, a_0, c_0)?;
+            let a_0_c_1 = self.fq2_mul(ctx, a_0, c_1)?;
+            let a_1_c_0 = self.fq2_mul(ctx, a_1, c_0)?;
+            let a_1_c_1 = self.fq2_mul(ctx, a_1, c_1)?;
+            let a_2_c_0 = self.fq2_mul(ctx, a_2, c_0)?;
+            let a_2_c_1 = self.fq2_mul(ctx, a_2, c_1)?;
+            let a_2_c_1 = self.fq2_mul_by_nonresidue(ctx, &a_2_c_1)?;
+            Ok([
+                self.fq2_add(ctx, &a_0_c_0, &a_2_c_1)?,
+                self.fq2_add(ctx, &a_0_c_1, &a_1_c_0)?,
+                self.fq2_add(ctx, &a_1_c_1, &a_2_c_0)?,
+            ])
+        }
+
+        fn fq6_invert(
+            &self,
+            ctx: &mut RegionCtx<'_, Fr>,
+            value: &AssignedFq6<LIMBS, BITS>,
+        ) -> Result<AssignedFq6<LIMBS, BITS>, Error> {
+            let [a_0, a_1, a_2] = value;
+            let c_0 = {
+                let a_0_square = self.fq2_mul(ctx, a_0, a_0)?;
+                let a_1_a_2 = self.fq2_mul(ctx, a_1, a_2)?;
+                let a_1_a_2 = self.fq2_mul_by_nonresidue(ctx, &a_1_a_2)?;
+                self.fq2_sub(ctx, &a_0_square, &a_1_a_2)?
+            };
+            let c_1 = {
+                let a_2_square = self.fq2_mul(ctx, a_2, a_2)?;
+                let a_2_square = self.fq2_mul_by_nonresidue(ctx, &a_2_square)?;
+                let a_0_a_1 = self.fq2_mul(ctx, a_0, a_1)?;
+                self.fq2_sub(ctx, &a_2_square, &a_0_a_1)?
+            };
+            let c_2 = {
+                let a_1_square = self.fq2_mul(ctx, a_1, a_1)?;
+                let a_0_a_2 = self.fq2_mul(ctx, a_0, a_2)?;
+                self.fq2_sub(ctx, &a_1_square, &a_0_a_2)?
+            };
+            let norm = {
+                let a_0_c_0 = self.fq2_mul(ctx, a_0, &c_0)?;
+                let a_2_c_1 = self.fq2_mul(ctx, a_2, &c_1)?;
+                let a_1_c_2 = self.fq2_mul(ctx, a_1, &c_2)?;
+                let sum = self.fq2_add(ctx, &a_2_c_1, &a_1_c_2)?;
+                let sum = self.fq2_mul_by_nonresidue(ctx, &sum)?;
+                self.fq2_add(ctx, &a_0_c_0, &sum)?
+            };
+            let norm_inv = self.fq2_invert(ctx, &norm)?;
+            Ok([
+                self.fq2_mul(ctx, &c_0, &norm_inv)?,
+                self.fq2_mul(ctx, &c_1, &norm_inv)?,
+                self.fq2_mul(ctx, &c_2, &norm_inv)?,
+            ])
+        }
+
+        fn fq12_constant(
+            &self,
+            ctx: &mut RegionCtx<'_, Fr>,
+            constant: [[Fq2; 3]; 2],
+        ) -> Result<AssignedFq12<LIMBS, BITS>, Error> {
+            let [[c_0, c_1, c_2], [c_3, c_4, c_5]] = constant;
+            Ok([
+                [
+                    self.fq2_constant(ctx, c_0)?,
+                    self.fq2_constant(ctx, c_1)?,
+                    self.fq2_constant(ctx, c_2)?,
+                ],
+                [
+                    self.fq2_constant(ctx, c_3)?,
+                    self.fq2_constant(ctx, c_4)?,
+                    self.fq2_constant(ctx, c_5)?,
+                ],
+            ])
+        }
+
+        fn fq12_mul(
+            &self,
+            ctx: &mut RegionCtx<'_, Fr>,
+            lhs: &AssignedFq12<LIMBS, BITS>,
+            rhs: &AssignedFq12<LIMBS, BITS>,
+        ) -> Result<AssignedFq12<LIMBS, BITS>, Error> {
+            let t_0 = self.fq6_mul(ctx, &lhs[0], &rhs[0])?;
+            let t_1 = self.fq6_mul(ctx, &lhs[1], &rhs[1])?;
+            let lhs_sum = self.fq6_add(ctx, &lhs[0], &lhs[1])?;
+            let rhs_sum = self.fq6_add(ctx, &rhs[0], &rhs[1])?;
+            let c_1 = self.fq6_mul(ctx, &lhs_sum, &rhs_sum)?;
+            let c_1 = self.fq6_sub(ctx, &c_1, &t_0)?;
+            let c_1 = self.fq6_sub(ctx, &c_1, &t_1)?;
+            let t_1 = self.fq6_mul_by_nonresidue(ctx, &t_1)?;
+            Ok([self.fq6_add(ctx, &t_0, &t_1)?, c_1])
+        }
+
+        fn fq12_square(
+            &self,
+            ctx: &mut RegionCtx<'_, Fr>,
+            value: &AssignedFq12<LIMBS, BITS>,
+        ) -> Result<AssignedFq12<LIMBS, BITS>, Error> {
+            let [a_0, a_1] = value;
+            let t = self.fq6_mul(ctx, a_0, a_1)?;
+            let sum = self.fq6_add(ctx, a_0, a_1)?;
+            let a_1_v = self.fq6_mul_by_nonresidue(ctx, a_1)?;
+            let a_1_v = self.fq6_add(ctx, a_0, &a_1_v)?;
+            let c_0 = self.fq6_mul(ctx, &sum, &a_1_v)?;
+            let c_0 = self.fq6_sub(ctx, &c_0, &t)?;
+            let t_v = self.fq6_mul_by_nonresidue(ctx, &t)?;
+            Ok([self.fq6_sub(ctx, &c_0, &t_v)?, self.fq6_add(ctx, &t, &t)?])
+        }
+
+        fn fq12_conjugate(
+            &self,
+            ctx: &mut RegionCtx<'_, Fr>,
+            value: &AssignedFq12<LIMBS, BITS>,
+        ) -> Result<AssignedFq12<LIMBS, BITS>, Error> {
+            Ok([value[0].clone(), self.fq6_neg(ctx, &value[1])?])
+        }
+
+        fn fq12_invert(
+            &self,
+            ctx: &mut RegionCtx<'_, Fr>,
+            value: &AssignedFq12<LIMBS, BITS>,
+        ) -> Result<AssignedFq12<LIMBS, BITS>, Error> {
+            let [a_0, a_1] = value;
+            let a_0_square = self.fq6_mul(ctx, a_0, a_0)?;
+            let a_1_square = self.fq6_mul(ctx, a_1, a_1)?;
+            let a_1_square = self.fq6_mul_by_nonresidue(ctx, &a_1_square)?;
+            let norm = self.fq6_sub(ctx, &a_0_square, &a_1_square)?;
+            let norm_inv = self.fq6_invert(ctx, &norm)?;
+            let c_1 = self.fq6_mul(ctx, a_1, &norm_inv)?;
+            Ok([self.fq6_mul(ctx, a_0, &norm_inv)?, self.fq6_neg(ctx, &c_1)?])
+        }
+
+        /// Returns `value^(p^power)`.
+        fn fq12_frobenius_map(
+            &self,
+            ctx: &mut RegionCtx<'_, Fr>,
+            value: &AssignedFq12<LIMBS, BITS>,
+            power: u32,
+        ) -> Result<AssignedFq12<LIMBS, BITS>, Error> {
+            // Coefficient `c` of `w^e` is mapped to `c^(p^power)·γ^e` where
+            // `γ = w^(p^power - 1) = ξ^((p^power - 1)/6)`.
+            let xi = Fq2 {
+                c0: Fq::from(9),
+                c1: Fq::ONE,
+            };
+            let gamma =
+                xi.pow_vartime(((modulus::<Fq>().pow(power) - 1u32) / 6u32).to_u64_digits());
+            let map = |ctx: &mut RegionCtx<'_, Fr>,
+                       coeff: &AssignedFq2<LIMBS, BITS>,
+                       e: u64|
+             -> Result<AssignedFq2<LIMBS, BITS>, Error> {
+                let coeff = if power % 2 == 1 {
+                    self.fq2_conjugate(ctx, coeff)?
+                } else {
+                    coeff.clone()
+                };
+                if e == 0 {
+                    return Ok(coeff);
+                }
+                let gamma = self.fq2_constant(ctx, gamma.pow_vartime([e]))?;
+                self.fq2_mul(ctx, &coeff, &gamma)
+            };
+            let [[c_0, c_2, c_4], [c_1, c_3, c_5]] = value;
+            Ok([
+                [map(ctx, c_0, 0)?, map(ctx, c_2, 2)?, map(ctx, c_4, 4)?],
+                [map(ctx, c_1, 1)?, map(ctx, c_3, 3)?, map(ctx, c_5, 5)?],
+            ])
+        }
+
+        fn fq6_mul_by_fq(
+            &self,
+            ctx: &mut RegionCtx<'_, Fr>,
+            lhs: &AssignedFq6<LIMBS, BITS>,
+            rhs: &AssignedFq<LIMBS, BITS>,
+        ) -> Result<AssignedFq6<LIMBS, BITS>, Error> {
+            Ok([
+                self.fq2_mul_by_fq(ctx, &lhs[0], rhs)?,
+                self.fq2_mul_by_fq(ctx, &lhs[1], rhs)?,
+                self.fq2_mul_by_fq(ctx, &lhs[2], rhs)?,
+            ])
+        }
+
+        /// Returns `value·(a + (b + c·v)·w)`, which is the sparse form of line
+        /// evaluated in Miller loop.
+        fn fq12_mul_by_line(
+            &self,
+            ctx: &mut RegionCtx<'_, Fr>,
+            value: &AssignedFq12<LIMBS, BITS>,
+            a: &AssignedFq<LIMBS, BITS>,
+            b: &AssignedFq2<LIMBS, BITS>,
+            c: &AssignedFq2<LIMBS, BITS>,
+        ) -> Result<AssignedFq12<LIMBS, BITS>, Error> {
+            let [f_0, f_1] = value;
+            let f_0_a = self.fq6_mul_by_fq(ctx, f_0, a)?;
+            let f_1_a = self.fq6_mul_by_fq(ctx, f_1, a)?;
+            let f_0_bc = self.fq6_mul_by_01(ctx, f_0, b, c)?;
+            let f_1_bc = self.fq6_mul_by_01(ctx, f_1, b, c)?;
+            let f_1_bc = self.fq6_mul_by_nonresidue(ctx, &f_1_bc)?;
+            Ok([
+                self.fq6_add(ctx, &f_0_a, &f_1_bc)?,
+                self.fq6_add(ctx, &f_1_a, &f_0_bc)?,
+            ])
+        }
+
+        /// Returns `value^x`.
+        fn fq12_pow_by_x(
+            &self,
+            ctx: &mut RegionCtx<'_, Fr>,
+            value: &AssignedFq12<LIMBS, BITS>,
+        ) -> Result<AssignedFq12<LIMBS, BITS>, Error> {
+            let mut acc = value.clone();
+            for bit in bits_after_msb(BN_X as u128) {
+                acc = self.fq12_square(ctx, &acc)?;
+                if bit {
+                    acc = self.fq12_mul(ctx, &acc, value)?;
+                }
+            }
+            Ok(acc)
+        }
+
+        /// Returns `value^((p^12 - 1)/r·m)` for some `m` coprime to `r`, so it's
+        /// `1` if and only if the reduced pairing is. The hard part follows the
+        /// addition chain from "Faster Hashing to G2" by Fuentes-Castañeda et al.
+        fn final_exponentiation(
+            &self,
+            ctx: &mut RegionCtx<'_, Fr>,
+            value: &AssignedFq12<LIMBS, BITS>,
+        ) -> Result<AssignedFq12<LIMBS, BITS>, Error> {
+            // Easy part `value^((p^6 - 1)·(p^2 + 1))`
+            let conjugate = self.fq12_conjugate(ctx, value)?;
+            let inverse = self.fq12_invert(ctx, value)?;
+            let f = self.fq12_mul(ctx, &conjugate, &inverse)?;
+            let f_p2 = self.fq12_frobenius_map(ctx, &f, 2)?;
+            let f = self.fq12_mul(ctx, &f_p2, &f)?;
+
+            // Hard part, where conjugate is inverse since `f` is unitary now
+            let y_0 = self.fq12_pow_by_x(ctx, &f)?;
+            let y_0 = self.fq12_conjugate(ctx, &y_0)?;
+            let y_1 = self.fq12_square(ctx, &y_0)?;
+            let y_2 = self.fq12_square(ctx, &y_1)?;
+            let y_3 = self.fq12_mul(ctx, &y_2, &y_1)?;
+            let y_4 = self.fq12_pow_by_x(ctx, &y_3)?;
+            let y_4 = self.fq12_conjugate(ctx, &y_4)?;
+            let y_5 = self.fq12_square(ctx, &y_4)?;
+            let y_6 = self.fq12_pow_by_x(ctx, &y_5)?;
+            let y_3 = self.fq12_conjugate(ctx, &y_3)?;
+            let y_7 = self.fq12_mul(ctx, &y_6, &y_4)?;
+            let y_8 = self.fq12_mul(ctx, &y_7, &y_3)?;
+            let y_9 = self.fq12_mul(ctx, &y_8, &y_1)?;
+            let y_10 = self.fq12_mul(ctx, &y_8, &y_4)?;
+            let y_11 = self.fq12_mul(ctx, &y_10, &f)?;
+            let y_12 = self.fq12_frobenius_map(ctx, &y_9, 1)?;
+            let y_13 = self.fq12_mul(ctx, &y_12, &y_11)?;
+            let y_8 = self.fq12_frobenius_map(ctx, &y_8, 2)?;
+            let y_14 = self.fq12_mul(ctx, &y_8, &y_13)?;
+            let f = self.fq12_conjugate(ctx, &f)?;
+            let y_15 = self.fq12_mul(ctx, &f, &y_9)?;
+            let y_15 = self.fq12_frobenius_map(ctx, &y_15, 3)?;
+            self.fq12_mul(ctx, &y_15, &y_14)
+        }
+    }
+
+    impl<'a, const LIMBS: usize, const BITS: usize> PairingInstructions<'a, Bn256>
+        for BaseFieldEccChip<G1Affine, LIMBS, BITS>
+    {
+        fn assert_pairing_check(
+            &self,
+            ctx: &mut Self::Context,
+            terms: &[(impl Deref<Target = Self::AssignedEcPoint>, G2Affine)],
+        ) -> Result<(), Error> {
+            assert!(!terms.is_empty());
+
+            // Lines are computed out of circuit since each `g2_i` is a constant
+            let terms = terms
+                .iter()
+                .filter(|(_, g2)| !bool::from(g2.is_identity()))
+                .map(|(g1, g2)| (g1.deref(), miller_loop_lines(*g2)))
+                .collect_vec();
+            if terms.is_empty() {
+                return Ok(());
+            }
+
+            let tower_chip = Bn254TowerChip {
+                integer_chip: self.integer_chip(),
+            };
+            let one = {
+                let mut one = [[Fq2::ZERO; 3]; 2];
+                one[0][0] = Fq2::ONE;
+                one
+            };
+
+            let mut f = tower_chip.fq12_constant(ctx, one)?;
+            for (idx, square) in miller_loop_steps().enumerate() {
+                if square {
+                    f = tower_chip.fq12_square(ctx, &f)?;
+                }
+                for (g1, lines) in terms.iter() {
+                    let (lambda, c) = lines[idx];
+                    let minus_lambda = tower_chip.fq2_constant(ctx, -lambda)?;
+                    let b = tower_chip.fq2_mul_by_fq(ctx, &minus_lambda, g1.x())?;
+                    let c = tower_chip.fq2_constant(ctx, c)?;
+                    f = tower_chip.fq12_mul_by_line(ctx, &f, g1.y(), &b, &c)?;
+                }
+            }
+            let f = tower_chip.final_exponentiation(ctx, &f)?;
+
+            let one = tower_chip.fq12_constant(ctx, one)?;
+            for (lhs, rhs) in f
+                .iter()
+                .flatten()
+                .flatten()
+                .zip(one.iter().flatten().flatten())
+            {
+                self.integer_chip().assert_equal(ctx, lhs, rhs)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "halo2_lib")]
+mod halo2_lib {
+    use crate::{
+        loader::halo2::{Context, EccInstructions, IntegerInstructions, PairingInstructions},
+        util::arithmetic::{CurveAffine, Field, PrimeField},
+    };
+    use halo2_base::{
+        gates::{flex_gate::FlexGateConfig, GateInstructions, RangeInstructions},
+        utils::PrimeField as Halo2BaseField,
+        AssignedValue,
+        QuantumCell::{Constant, Existing, Witness},
+    };
+    use halo2_ecc::{
+        bigint::CRTInteger,
+        bn254::{pairing::PairingChip, Fp12Chip, Fp2Chip},
+        ecc::{fixed_base::FixedEcPoint, BaseFieldEccChip, EcPoint},
+        fields::{FieldChip, PrimeFieldChip},
+    };
+    use halo2_proofs::{
+        circuit::{Cell, Value},
+        halo2curves::bn256::{Bn256, Fq12, G1Affine, G2Affine},
+        plonk::Error,
+    };
+    use std::ops::Deref;
+
+    type AssignedInteger<'v, C> = CRTInteger<'v, <C as CurveAffine>::ScalarExt>;
+    type AssignedEcPoint<'v, C> = EcPoint<<C as CurveAffine>::ScalarExt, AssignedInteger<'v, C>>;
+
+    impl<'a, F: Halo2BaseField> Context for halo2_base::Context<'a, F> {
+        fn constrain_equal(&mut self, lhs: Cell, rhs: Cell) -> Result<(), Error> {
+            self.region.constrain_equal(lhs, rhs)
+        }
+
+        fn offset(&self) -> usize {
+            self.advice_alloc[self.current_phase].1
+        }
+    }
+
+    impl<'a, F: PrimeField + Halo2BaseField> IntegerInstructions<'a, F> for FlexGateConfig<F> {
+        type Context = halo2_base::Context<'a, F>;
+        type AssignedCell = AssignedValue<'a, F>;
+        type AssignedInteger = AssignedValue<'a, F>;
+
+        fn assign_integer(
+            &self,
+            ctx: &mut Self::Context,
+            integer: Value<F>,
+        ) -> Result<Self::AssignedInteger, Error> {
+            Ok(self.assign_region_last(ctx, vec![Witness(integer)], vec![]))
+        }
+
+        fn assign_constant(
+            &self,
+            ctx: &mut Self::Context,
+            integer: F,
+        ) -> Result<Self::AssignedInteger, Error> {
+            Ok(self.assign_region_last(ctx, vec![Constant(integer)], vec![]))
+        }
+
+        fn sum_with_coeff_and_const(
+            &self,
+            ctx: &mut Self::Context,
+            values: &[(F, impl Deref<Target = Self::AssignedInteger>)],
+            constant: F,
+        ) -> Result<Self::AssignedInteger, Error> {
+            let mut a = Vec::with_capacity(values.len() + 1);
+            let mut b = Vec::with_capacity(values.len() + 1);
+            if constant != F::ZERO {
+                a.push(Constant(F::ONE));
+                b.push(Constant(constant));
+            }
+            a.extend(values.iter().map(|(_, a)| Existing(a)));
+            b.extend(values.iter().map(|(c, _)| Constant(*c)));
+            Ok(self.inner_product(ctx, a, b))
+        }
+
+        fn sum_products_with_coeff_and_const(
+            &self,
+            ctx: &mut Self::Context,
+            values: &[(
+                F,
+                impl Deref<Target = Self::AssignedInteger>,
+                impl Deref<Target = Self::AssignedInteger>,
+            )],
+            constant: F,
+        ) -> Result<Self::AssignedInteger, Error> {
+            match values.len() {
+                0 => IntegerInstructions::assign_constant(self, ctx, constant),
+                _ => Ok(self.sum_products_with_coeff_and_var(
+                    ctx,
+                    values
+                        .iter()
+                        .map(|(c, a, b)| (*c, Existing(a), Existing(b))),
+                    Constant(constant),
+                )),
+            }
+        }
+
+        fn sub(
+            &self,
+            ctx: &mut Self::Context,
+            lhs: &Self::AssignedInteger,
+            rhs: &Self::AssignedInteger,
+        ) -> Result<Self::AssignedInteger, Error> {
+            Ok(GateInstructions::sub(
+                self,
+                ctx,
+                Existing(lhs),
+                Existing(rhs),
+            ))
+        }
+
+        fn neg(
+            &self,
+            ctx: &mut Self::Context,
+            value: &Self::AssignedInteger,
+        ) -> Result<Self::AssignedInteger, Error> {
+            Ok(GateInstructions::neg(self, ctx, Existing(value)))
+        }
+
+        fn invert(
+            &self,
+            ctx: &mut Self::Context,
+            value: &Self::AssignedInteger,
+        ) -> Result<Self::AssignedInteger, Error> {
+            let is_zero = self.is_zero(ctx, value);
+            self.assert_is_const(ctx, &is_zero, F::ZERO);
+            Ok(GateInstructions::div_unsafe(
+                self,
+                ctx,
+                Constant(F::ONE),
+                Existing(value),
+            ))
+        }
+
+        fn assert_equal(
+            &self,
+            ctx: &mut Self::Context,
+            lhs: &Self::AssignedInteger,
+            rhs: &Self::AssignedInteger,
+        ) -> Result<(), Error> {
+            let mut eq = true;
+            lhs.value().zip(rhs.value()).map(|(lhs, rhs)| {
+                eq &= lhs == rhs;
+            });
+            ctx.region
+                .constrain_equal(lhs.cell(), rhs.cell())
+                .and(eq.then_some(()).ok_or(Error::Synthesis))
+        }
+
+        fn integer_value(&self, integer: &Self::AssignedInteger) -> Value<F> {
+            integer.value().copied()
+        }
+    }
+
+    impl<'a, C: CurveAffine> EccInstructions<'a, C> for BaseFieldEccChip<C>
+    where
+        C::Scalar: Halo2BaseField,
+        C::Base: Halo2BaseField,
+    {
+        type Context = halo2_base::Context<'a, C::Scalar>;
+        type ScalarChip = FlexGateConfig<C::Scalar>;
+        type AssignedCell = AssignedValue<'a, C::Scalar>;
+        type AssignedScalar = AssignedValue<'a, C::Scalar>;
+        type AssignedEcPoint = AssignedEcPoint<'a, C>;
+
+        fn scalar_chip(&self) -> &Self::ScalarChip {
+            self.field_chip.range().gate()
+        }
+
+        fn assign_constant(
+            &self,
+            ctx: &mut Self::Context,
+            ec_point: C,
+        ) -> Result<Self::AssignedEcPoint, Error> {
+            let fixed = FixedEcPoint::<C::Scalar, C>::from_curve(
+                ec_point,
+                self.field_chip.num_limbs,
+                self.field_chip.limb_bits,
+            );
+            Ok(FixedEcPoint::assign(
+                fixed,
+                self.field_chip(),
+                ctx,
+                self.field_chip().native_modulus(),
+            ))
+        }
+
+        fn assign_point(
+            &self,
+            ctx: &mut Self::Context,
+            ec_point: Value<C>,
+        ) -> Result<Self::AssignedEcPoint, Error> {
+            let assigned = self.assign_point(ctx, ec_point);
+            let is_valid = self.is_on_curve_or_infinity::<C>(ctx, &assigned);
+            self.field_chip()
+                .range()
+                .gate()
+                .assert_is_const(ctx, &is_valid, C::Scalar::ONE);
+            Ok(assigned)
+        }
+
+        fn sum_with_const(
+            &self,
+            ctx: &mut Self::Context,
+            values: &[impl Deref<Target = Self::AssignedEcPoint>],
+            constant: C,
+        ) -> Result<Self::AssignedEcPoint, Error> {
+            let constant = (!bool::from(constant.is_identity()))
+                .then(|| EccInstructions::assign_constant(self, ctx, constant))
+                .transpose()?;
+            Ok(self.sum::<C>(ctx, constant.iter().chain(values.iter().map(Deref::deref))))
+        }
+
+        fn fixed_base_msm(
+            &mut self,
+            ctx: &mut Self::Context,
+            pairs: &[(impl Deref<Target = Self::AssignedScalar>, C)],
+        ) -> Result<Self::AssignedEcPoint, Error> {
+            assert!(!pairs.is_empty());
+
+            let (scalars, bases): (Vec<_>, Vec<_>) = pairs
+                .iter()
+                .filter(|(_, base)| !bool::from(base.is_identity()))
+                .map(|(scalar, base)| (vec![scalar.deref().clone()], *base))
+                .unzip();
+            Ok(BaseFieldEccChip::<C>::fixed_base_msm::<C>(
+                self,
+                ctx,
+                &bases,
+                &scalars,
+                C::Scalar::NUM_BITS as usize,
+                0,
+                4,
+            ))
+        }
+
+        fn variable_base_msm(
+            &mut self,
+            ctx: &mut Self::Context,
+            pairs: &[(
+                impl Deref<Target = Self::AssignedScalar>,
+                impl Deref<Target = Self::AssignedEcPoint>,
+            )],
+        ) -> Result<Self::AssignedEcPoint, Error> {
+            assert!(!pairs.is_empty());
+
+            let (scalars, bases): (Vec<_>, Vec<_>) = pairs
+                .iter()
+                .map(|(scalar, base)| (vec![scalar.deref().clone()], base.deref().clone()))
+                .unzip();
+            Ok(BaseFieldEccChip::<C>::variable_base_msm::<C>(
+                self,
+                ctx,
+                &bases,
+                &scalars,
+                C::Scalar::NUM_BITS as usize,
+                4,
+            ))
+        }
+
+        fn assert_equal(
+            &self,
+            ctx: &mut Self::Context,
+            lhs: &Self::AssignedEcPoint,
+            rhs: &Self::AssignedEcPoint,
+        ) -> Result<(), Error> {
+            self.assert_equal(ctx, lhs, rhs);
+            Ok(())
+        }
+    }
+    impl<'a> PairingInstructions<'a, Bn256> for BaseFieldEccChip<G1Affine> {
+        fn assert_pairing_check(
+            &self,
+            ctx: &mut Self::Context,
+            terms: &[(impl Deref<Target = Self::AssignedEcPoint>, G2Affine)],
+        ) -> Result<(), Error> {
+            assert!(!terms.is_empty());
+
+            let fp2_chip = Fp2Chip::construct(&self.field_chip);
+            let g2s = terms
+                .iter()
+                .map(|(_, g2)| {
+                    let coordinates = g2.coordinates().unwrap();
+                    let [x, y] = [coordinates.x(), coordinates.y()]
+                        .map(|coordinate| fp2_chip.load_constant(ctx, *coordinate));
+                    EcPoint::construct(x, y)
+                })
+                .collect::<Vec<_>>();
+
+            let pairing_chip = PairingChip::construct(&self.field_chip);
+            let gt = pairing_chip.multi_miller_loop(
+                ctx,
+                terms
+                    .iter()
+                    .zip(g2s.iter())
+                    .map(|((g1, _), g2)| (g1.deref(), g2))
+                    .collect(),
+            );
+            let gt = pairing_chip.final_exp(ctx, &gt);
+
+            let fp12_chip = Fp12Chip::construct(&self.field_chip);
+            let one = fp12_chip.load_constant(ctx, Fq12::ONE);
+            fp12_chip.assert_equal(ctx, &gt, &one);
+            Ok(())
+        }
+    }
 }
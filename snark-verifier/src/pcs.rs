@@ -12,13 +12,17 @@ use crate::{
 use rand::Rng;
 use std::{fmt::Debug, marker::PhantomData};
 
+mod bdfg21;
+#[cfg(feature = "experimental")]
+pub mod folding;
+pub mod fri;
 pub mod ipa;
 pub mod kzg;
 
 /// Query to an oracle.
 /// It assumes all queries are based on the same point, but with some `shift`.
 #[derive(Clone, Debug)]
-pub struct Query<F: PrimeField, T = ()> {
+pub struct Query<F, T = ()> {
     /// Index of polynomial to query
     pub poly: usize,
     /// Shift of the query point.
@@ -27,7 +31,7 @@ pub struct Query<F: PrimeField, T = ()> {
     pub eval: T,
 }
 
-impl<F: PrimeField> Query<F> {
+impl<F> Query<F> {
     /// Initialize [`Query`] without evaluation.
     pub fn new(poly: usize, shift: F) -> Self {
         Self {
@@ -79,6 +83,65 @@ where
     ) -> Result<Self::Output, Error>;
 }
 
+/// Query to a multilinear oracle.
+/// It assumes all queries are based on the same point.
+#[derive(Clone, Debug)]
+pub struct MultilinearQuery<T = ()> {
+    /// Index of polynomial to query
+    pub poly: usize,
+    /// Evaluation read from transcript.
+    pub eval: T,
+}
+
+impl MultilinearQuery {
+    /// Initialize [`MultilinearQuery`] without evaluation.
+    pub fn new(poly: usize) -> Self {
+        Self { poly, eval: () }
+    }
+
+    /// Returns [`MultilinearQuery`] with evaluation.
+    pub fn with_evaluation<T>(self, eval: T) -> MultilinearQuery<T> {
+        MultilinearQuery {
+            poly: self.poly,
+            eval,
+        }
+    }
+}
+
+/// Multilinear polynomial commitment scheme verifier.
+pub trait MultilinearPolynomialCommitmentScheme<C, L>: Clone + Debug
+where
+    C: CurveAffine,
+    L: Loader<C>,
+{
+    /// Verifying key.
+    type VerifyingKey: Clone + Debug;
+    /// Structured proof read from transcript.
+    type Proof: Clone + Debug;
+    /// Output of verification.
+    type Output: Clone + Debug;
+
+    /// Read [`MultilinearPolynomialCommitmentScheme::Proof`] from transcript.
+    fn read_proof<T>(
+        vk: &Self::VerifyingKey,
+        num_var: usize,
+        queries: &[MultilinearQuery],
+        transcript: &mut T,
+    ) -> Result<Self::Proof, Error>
+    where
+        T: TranscriptRead<C, L>;
+
+    /// Verify [`MultilinearPolynomialCommitmentScheme::Proof`] and output
+    /// [`MultilinearPolynomialCommitmentScheme::Output`].
+    fn verify(
+        vk: &Self::VerifyingKey,
+        commitments: &[Msm<C, L>],
+        point: &[L::LoadedScalar],
+        queries: &[MultilinearQuery<L::LoadedScalar>],
+        proof: &Self::Proof,
+    ) -> Result<Self::Output, Error>;
+}
+
 /// Accumulation scheme verifier.
 pub trait AccumulationScheme<C, L>
 where
@@ -168,6 +231,21 @@ where
     /// Decode an [`AccumulatorEncoding::Accumulator`] from serveral
     /// [`crate::loader::ScalarLoader::LoadedScalar`]s.
     fn from_repr(repr: &[&L::LoadedScalar]) -> Result<Self::Accumulator, Error>;
+
+    /// Decode an [`AccumulatorEncoding::Accumulator`] from serveral
+    /// [`crate::loader::ScalarLoader::LoadedScalar`]s, with access to the
+    /// transcript for encodings that carry extra data in the proof. It's
+    /// called after the proof of the
+    /// [`PolynomialCommitmentScheme`] has been read.
+    fn read_repr<T>(
+        repr: &[&L::LoadedScalar],
+        _transcript: &mut T,
+    ) -> Result<Self::Accumulator, Error>
+    where
+        T: TranscriptRead<C, L>,
+    {
+        Self::from_repr(repr)
+    }
 }
 
 impl<C, L, PCS> AccumulatorEncoding<C, L> for PhantomData<PCS>
@@ -0,0 +1,317 @@
+//! Query set helpers shared by the SHPLONK style multi-open schemes, which
+//! are [`kzg::Bdfg21`] and [`ipa::Bdfg21`].
+//!
+//! [`kzg::Bdfg21`]: crate::pcs::kzg::Bdfg21
+//! [`ipa::Bdfg21`]: crate::pcs::ipa::Bdfg21
+
+use crate::{
+    cost::Cost,
+    loader::{LoadedScalar, Loader, ScalarLoader},
+    pcs::Query,
+    util::{
+        arithmetic::{CurveAffine, Fraction, PrimeField},
+        msm::Msm,
+        Itertools,
+    },
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    marker::PhantomData,
+};
+
+pub(crate) fn query_sets<F: PrimeField + Ord, T: Clone>(
+    queries: &[Query<F, T>],
+) -> Vec<QuerySet<F, T>> {
+    let poly_shifts = queries.iter().fold(
+        Vec::<(usize, Vec<F>, Vec<&T>)>::new(),
+        |mut poly_shifts, query| {
+            if let Some(pos) = poly_shifts
+                .iter()
+                .position(|(poly, _, _)| *poly == query.poly)
+            {
+                let (_, shifts, evals) = &mut poly_shifts[pos];
+                if !shifts.contains(&query.shift) {
+                    shifts.push(query.shift);
+                    evals.push(&query.eval);
+                }
+            } else {
+                poly_shifts.push((query.poly, vec![query.shift], vec![&query.eval]));
+            }
+            poly_shifts
+        },
+    );
+
+    poly_shifts.into_iter().fold(
+        Vec::<QuerySet<F, T>>::new(),
+        |mut sets, (poly, shifts, evals)| {
+            if let Some(pos) = sets.iter().position(|set| {
+                BTreeSet::from_iter(set.shifts.iter()) == BTreeSet::from_iter(shifts.iter())
+            }) {
+                let set = &mut sets[pos];
+                if !set.polys.contains(&poly) {
+                    set.polys.push(poly);
+                    set.evals.push(
+                        set.shifts
+                            .iter()
+                            .map(|lhs| {
+                                let idx = shifts.iter().position(|rhs| lhs == rhs).unwrap();
+                                evals[idx]
+                            })
+                            .collect(),
+                    );
+                }
+            } else {
+                let set = QuerySet {
+                    shifts,
+                    polys: vec![poly],
+                    evals: vec![evals],
+                };
+                sets.push(set);
+            }
+            sets
+        },
+    )
+}
+
+pub(crate) fn query_set_coeffs<F: PrimeField + Ord, T: LoadedScalar<F>>(
+    sets: &[QuerySet<F, T>],
+    z: &T,
+    z_prime: &T,
+) -> Vec<QuerySetCoeff<F, T>> {
+    let loader = z.loader();
+
+    let superset = sets
+        .iter()
+        .flat_map(|set| set.shifts.clone())
+        .sorted()
+        .dedup();
+
+    let size = sets
+        .iter()
+        .map(|set| set.shifts.len())
+        .chain(Some(2))
+        .max()
+        .unwrap();
+    let powers_of_z = z.powers(size);
+    let z_prime_minus_z_shift_i = BTreeMap::from_iter(superset.map(|shift| {
+        (
+            shift,
+            z_prime.clone() - z.clone() * loader.load_const(&shift),
+        )
+    }));
+
+    let mut z_s_1 = None;
+    let mut coeffs = sets
+        .iter()
+        .map(|set| {
+            let coeff = QuerySetCoeff::new(
+                &set.shifts,
+                &powers_of_z,
+                z_prime,
+                &z_prime_minus_z_shift_i,
+                &z_s_1,
+            );
+            if z_s_1.is_none() {
+                z_s_1 = Some(coeff.z_s.clone());
+            };
+            coeff
+        })
+        .collect_vec();
+
+    T::Loader::batch_invert(coeffs.iter_mut().flat_map(QuerySetCoeff::denoms));
+    T::Loader::batch_invert(coeffs.iter_mut().flat_map(QuerySetCoeff::denoms));
+    coeffs.iter_mut().for_each(QuerySetCoeff::evaluate);
+
+    coeffs
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct QuerySet<'a, F, T> {
+    pub(crate) shifts: Vec<F>,
+    pub(crate) polys: Vec<usize>,
+    pub(crate) evals: Vec<Vec<&'a T>>,
+}
+
+impl<'a, F: PrimeField, T: LoadedScalar<F>> QuerySet<'a, F, T> {
+    pub(crate) fn msm<C: CurveAffine, L: Loader<C, LoadedScalar = T>>(
+        &self,
+        coeff: &QuerySetCoeff<F, T>,
+        commitments: &[Msm<'a, C, L>],
+        powers_of_mu: &[T],
+    ) -> Msm<C, L> {
+        self.polys
+            .iter()
+            .zip(self.evals.iter())
+            .zip(powers_of_mu.iter())
+            .map(|((poly, evals), power_of_mu)| {
+                let loader = power_of_mu.loader();
+                let commitment = coeff
+                    .commitment_coeff
+                    .as_ref()
+                    .map(|commitment_coeff| {
+                        commitments[*poly].clone() * commitment_coeff.evaluated()
+                    })
+                    .unwrap_or_else(|| commitments[*poly].clone());
+                let r_eval = loader.sum_products(
+                    &coeff
+                        .eval_coeffs
+                        .iter()
+                        .zip(evals.iter().cloned())
+                        .map(|(coeff, eval)| (coeff.evaluated(), eval))
+                        .collect_vec(),
+                ) * coeff.r_eval_coeff.as_ref().unwrap().evaluated();
+                (commitment - Msm::constant(r_eval)) * power_of_mu
+            })
+            .sum()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct QuerySetCoeff<F, T> {
+    pub(crate) z_s: T,
+    eval_coeffs: Vec<Fraction<T>>,
+    commitment_coeff: Option<Fraction<T>>,
+    r_eval_coeff: Option<Fraction<T>>,
+    _marker: PhantomData<F>,
+}
+
+impl<F, T> QuerySetCoeff<F, T>
+where
+    F: PrimeField + Ord,
+    T: LoadedScalar<F>,
+{
+    fn new(
+        shifts: &[F],
+        powers_of_z: &[T],
+        z_prime: &T,
+        z_prime_minus_z_shift_i: &BTreeMap<F, T>,
+        z_s_1: &Option<T>,
+    ) -> Self {
+        let loader = z_prime.loader();
+
+        let normalized_ell_primes = shifts
+            .iter()
+            .enumerate()
+            .map(|(j, shift_j)| {
+                shifts
+                    .iter()
+                    .enumerate()
+                    .filter(|&(i, _)| i != j)
+                    .map(|(_, shift_i)| (*shift_j - shift_i))
+                    .reduce(|acc, value| acc * value)
+                    .unwrap_or(F::ONE)
+            })
+            .collect_vec();
+
+        let z = &powers_of_z[1];
+        let z_pow_k_minus_one = &powers_of_z[shifts.len() - 1];
+
+        let barycentric_weights = shifts
+            .iter()
+            .zip(normalized_ell_primes.iter())
+            .map(|(shift, normalized_ell_prime)| {
+                loader.sum_products_with_coeff(&[
+                    (*normalized_ell_prime, z_pow_k_minus_one, z_prime),
+                    (-(*normalized_ell_prime * shift), z_pow_k_minus_one, z),
+                ])
+            })
+            .map(Fraction::one_over)
+            .collect_vec();
+
+        let z_s = loader.product(
+            &shifts
+                .iter()
+                .map(|shift| z_prime_minus_z_shift_i.get(shift).unwrap())
+                .collect_vec(),
+        );
+        let z_s_1_over_z_s = z_s_1.clone().map(|z_s_1| Fraction::new(z_s_1, z_s.clone()));
+
+        Self {
+            z_s,
+            eval_coeffs: barycentric_weights,
+            commitment_coeff: z_s_1_over_z_s,
+            r_eval_coeff: None,
+            _marker: PhantomData,
+        }
+    }
+
+    fn denoms(&mut self) -> impl IntoIterator<Item = &'_ mut T> {
+        if self.eval_coeffs.first().unwrap().denom().is_some() {
+            return self
+                .eval_coeffs
+                .iter_mut()
+                .chain(self.commitment_coeff.as_mut())
+                .filter_map(Fraction::denom_mut)
+                .collect_vec();
+        }
+
+        if self.r_eval_coeff.is_none() {
+            let loader = self.z_s.loader();
+            self.eval_coeffs
+                .iter_mut()
+                .chain(self.commitment_coeff.as_mut())
+                .for_each(Fraction::evaluate);
+            let barycentric_weights_sum = loader.sum(
+                &self
+                    .eval_coeffs
+                    .iter()
+                    .map(Fraction::evaluated)
+                    .collect_vec(),
+            );
+            self.r_eval_coeff = Some(match self.commitment_coeff.as_ref() {
+                Some(coeff) => Fraction::new(coeff.evaluated().clone(), barycentric_weights_sum),
+                None => Fraction::one_over(barycentric_weights_sum),
+            });
+            return vec![self.r_eval_coeff.as_mut().unwrap().denom_mut().unwrap()];
+        }
+
+        unreachable!()
+    }
+
+    fn evaluate(&mut self) {
+        self.r_eval_coeff.as_mut().unwrap().evaluate();
+    }
+}
+
+/// Estimate the cost of computing the query set coefficients and the batched
+/// [`Msm`] of `queries`, which is common to both [`kzg::Bdfg21`] and
+/// [`ipa::Bdfg21`].
+///
+/// [`kzg::Bdfg21`]: crate::pcs::kzg::Bdfg21
+/// [`ipa::Bdfg21`]: crate::pcs::ipa::Bdfg21
+pub(crate) fn query_sets_cost<F: PrimeField + Ord>(queries: &[Query<F>]) -> Cost {
+    let sets = query_sets(queries);
+    let num_set = sets.len();
+    let num_superset = sets
+        .iter()
+        .flat_map(|set| set.shifts.iter())
+        .sorted()
+        .dedup()
+        .count();
+    let max_num_shift = sets.iter().map(|set| set.shifts.len()).max().unwrap_or(2);
+    let max_num_poly = sets.iter().map(|set| set.polys.len()).max().unwrap_or(1);
+    let num_shift = sets.iter().map(|set| set.shifts.len()).sum::<usize>();
+    let num_poly = sets.iter().map(|set| set.polys.len()).sum::<usize>();
+    let num_eval = sets
+        .iter()
+        .map(|set| set.polys.len() * set.shifts.len())
+        .sum::<usize>();
+    // Denominators of barycentric weights and `z_s_1 / z_s` in first batch
+    // inversion, and denominators of `r_eval_coeff` in second one, each
+    // takes 3 multiplications in batch inversion and 1 to evaluate.
+    let num_denom = (num_shift + num_set - 1) + num_set;
+
+    let coeffs_cost = Cost {
+        num_scalar_add: num_superset + 2 * num_shift,
+        num_scalar_mul: max_num_shift.max(2) + num_superset + 5 * num_shift + 4 * num_denom,
+        num_inversion: 2,
+        ..Default::default()
+    };
+    let msm_cost = Cost {
+        num_scalar_add: num_eval + num_poly,
+        num_scalar_mul: num_eval + 4 * num_poly + (max_num_poly - 1) + 2 * num_set + 1,
+        ..Default::default()
+    };
+
+    coeffs_cost + msm_cost
+}
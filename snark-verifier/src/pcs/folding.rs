@@ -0,0 +1,264 @@
+//! Experimental folding scheme of relaxed PLONK instances in the style of
+//! [Nova](https://eprint.iacr.org/2021/370) and
+//! [Protostar](https://eprint.iacr.org/2023/620), implemented as
+//! [`AccumulationScheme`](crate::pcs::AccumulationScheme).
+//!
+//! The quotient numerator of a [`PlonkProtocol`] is compiled into
+//! [`RelaxedPlonk`], whose constraint is homogenized with a slack scalar `u`,
+//! so instances, challenges and commitments of witnesses can be folded by
+//! random linear combination, with cross terms committed by prover into the
+//! error vector. Witness and error vectors are committed in Lagrange basis, so
+//! commitments of witness polynomials of PLONK can be used directly.
+//!
+//! The first accumulator given to [`FoldingAs`] is the running one, and the
+//! rest are fresh instances, whose slack is checked to be `1`, error is taken
+//! as zero, and challenges are checked to be squeezed from transcript by
+//! [`FoldingAs::squeeze_challenges`]. Commitments are not hiding, and the
+//! decider requires the whole witness, so it's only available with feature
+//! `experimental`.
+
+use crate::{
+    loader::native::NativeLoader,
+    util::{
+        arithmetic::{powers, Curve, CurveAffine, Domain, PrimeField},
+        msm::multi_scalar_multiplication,
+        Itertools,
+    },
+    verifier::plonk::{
+        protocol::{CommonPolynomial, Expression},
+        PlonkProtocol,
+    },
+};
+use std::cmp::max;
+
+mod accumulation;
+mod accumulator;
+mod decider;
+
+pub use accumulation::{FoldingAs, FoldingAsProof};
+pub use accumulator::{FoldingAccumulator, FoldingWitness};
+
+/// Relaxed PLONK relation compiled from [`PlonkProtocol`], which requires
+/// the homogenized constraint evaluated on each row of the domain to be equal
+/// to the error vector.
+///
+/// Polynomials are indexed in the order of preprocessed, instance and then
+/// witness, which is the same as [`PlonkProtocol`].
+#[derive(Clone, Debug)]
+pub struct RelaxedPlonk<F: PrimeField> {
+    /// Working domain.
+    pub domain: Domain<F>,
+    /// Number of preprocessed polynomials.
+    pub num_preprocessed: usize,
+    /// Number of instances in each instance polynomial.
+    pub num_instance: Vec<usize>,
+    /// Number of witness polynomials in each phase.
+    pub num_witness: Vec<usize>,
+    /// Number of challenges to squeeze after each phase.
+    pub num_challenge: Vec<usize>,
+    /// Degree of constraint in instances, witnesses and challenges, which is
+    /// the degree of slack `u` after homogenization.
+    pub degree: usize,
+    constraint: Expression<F>,
+}
+
+impl<F: PrimeField> RelaxedPlonk<F> {
+    /// Compile quotient numerator of [`PlonkProtocol`] into [`RelaxedPlonk`].
+    pub fn new<C: CurveAffine<Scalar = F>>(protocol: &PlonkProtocol<C>) -> Self {
+        let num_preprocessed = protocol.preprocessed.len();
+        let constraint = protocol.quotient.numerator.clone();
+        let degree = constraint.evaluate(
+            &|_| 0,
+            &|_| 0,
+            &|query| usize::from(query.poly >= num_preprocessed),
+            &|_| 1,
+            &|a| a,
+            &|a, b| max(a, b),
+            &|a, b| a + b,
+            &|a, _| a,
+        );
+        Self {
+            domain: protocol.domain.clone(),
+            num_preprocessed,
+            num_instance: protocol.num_instance.clone(),
+            num_witness: protocol.num_witness.clone(),
+            num_challenge: protocol.num_challenge.clone(),
+            degree: max(degree, 1),
+            constraint,
+        }
+    }
+
+    /// Returns evaluations of the homogenized constraint with slack `u` on
+    /// each row of the domain, which should be equal to the error vector.
+    pub fn evaluate(
+        &self,
+        preprocessed: &[Vec<F>],
+        u: &F,
+        instances: &[Vec<F>],
+        witnesses: &[Vec<F>],
+        challenges: &[F],
+    ) -> Vec<F> {
+        let n = self.domain.n;
+        let instance_offset = self.num_preprocessed;
+        let witness_offset = instance_offset + self.num_instance.len();
+        let powers_of_u = powers(*u).take(self.degree + 1).collect_vec();
+        let identity = powers(self.domain.gen).take(n).collect_vec();
+
+        (0..n)
+            .map(|row| {
+                let rotated = |rotation: i32| (row as i64 + rotation as i64).rem_euclid(n as i64);
+                let lagrange = |i: i32| {
+                    if rotated(0) == (i as i64).rem_euclid(n as i64) {
+                        F::ONE
+                    } else {
+                        F::ZERO
+                    }
+                };
+                // Each value is represented by its homogeneous parts, where
+                // the `i`-th one has degree `i` in folded values.
+                let parts = self.constraint.evaluate(
+                    &|scalar| vec![scalar],
+                    &|poly| {
+                        vec![match poly {
+                            CommonPolynomial::Identity => identity[row],
+                            CommonPolynomial::Lagrange(i) => lagrange(i),
+                        }]
+                    },
+                    &|query| {
+                        let row = rotated(query.rotation.0) as usize;
+                        if query.poly < instance_offset {
+                            vec![preprocessed[query.poly][row]]
+                        } else if query.poly < witness_offset {
+                            let instances = &instances[query.poly - instance_offset];
+                            vec![F::ZERO, instances.get(row).copied().unwrap_or(F::ZERO)]
+                        } else {
+                            vec![F::ZERO, witnesses[query.poly - witness_offset][row]]
+                        }
+                    },
+                    &|index| vec![F::ZERO, challenges[index]],
+                    &|a| a.into_iter().map(|a| -a).collect(),
+                    &|a, b| {
+                        let (mut lhs, rhs) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+                        for (lhs, rhs) in lhs.iter_mut().zip(rhs) {
+                            *lhs += rhs;
+                        }
+                        lhs
+                    },
+                    &|a, b| {
+                        let mut product = vec![F::ZERO; a.len() + b.len() - 1];
+                        for (i, a) in a.iter().enumerate() {
+                            for (j, b) in b.iter().enumerate() {
+                                product[i + j] += *a * b;
+                            }
+                        }
+                        product
+                    },
+                    &|a, scalar| a.into_iter().map(|a| a * scalar).collect(),
+                );
+                parts
+                    .iter()
+                    .zip(powers_of_u.iter().rev())
+                    .map(|(part, power_of_u)| *part * power_of_u)
+                    .sum()
+            })
+            .collect()
+    }
+}
+
+/// Folding accumulation scheme proving key, which is also used as deciding
+/// key because decider needs to check the whole witness.
+#[derive(Clone, Debug)]
+pub struct FoldingProvingKey<C: CurveAffine> {
+    /// Relation of folded instances.
+    pub relation: RelaxedPlonk<C::Scalar>,
+    /// Evaluations of preprocessed polynomials on the domain.
+    pub preprocessed: Vec<Vec<C::Scalar>>,
+    /// Committing key in Lagrange basis.
+    pub g_lagrange: Vec<C>,
+}
+
+impl<C: CurveAffine> FoldingProvingKey<C> {
+    /// Initialize a [`FoldingProvingKey`].
+    ///
+    /// # Panic
+    ///
+    /// If `preprocessed` or `g_lagrange` doesn't match the `relation`.
+    pub fn new(
+        relation: RelaxedPlonk<C::Scalar>,
+        preprocessed: Vec<Vec<C::Scalar>>,
+        g_lagrange: Vec<C>,
+    ) -> Self {
+        assert_eq!(preprocessed.len(), relation.num_preprocessed);
+        assert!(preprocessed
+            .iter()
+            .all(|preprocessed| preprocessed.len() == relation.domain.n));
+        assert_eq!(g_lagrange.len(), relation.domain.n);
+        Self {
+            relation,
+            preprocessed,
+            g_lagrange,
+        }
+    }
+
+    /// Returns [`FoldingVerifyingKey`].
+    pub fn vk(&self) -> FoldingVerifyingKey {
+        FoldingVerifyingKey::new(
+            self.relation.degree,
+            self.relation.num_witness.clone(),
+            self.relation.num_challenge.clone(),
+        )
+    }
+
+    /// Commit `values` in Lagrange basis.
+    pub fn commit(&self, values: &[C::Scalar]) -> C {
+        multi_scalar_multiplication(values, &self.g_lagrange[..values.len()]).to_affine()
+    }
+
+    /// Returns a fresh [`FoldingAccumulator`] with slack `1` and zero error,
+    /// where `witnesses` are assumed to satisfy the constraint on each row,
+    /// and `challenges` should be squeezed by [`FoldingAs::squeeze_challenges`]
+    /// to be folded into others.
+    pub fn accumulator(
+        &self,
+        instances: Vec<Vec<C::Scalar>>,
+        witnesses: Vec<Vec<C::Scalar>>,
+        challenges: Vec<C::Scalar>,
+    ) -> FoldingAccumulator<C, NativeLoader> {
+        let error = vec![C::Scalar::ZERO; self.relation.domain.n];
+        FoldingAccumulator::new(
+            C::Scalar::ONE,
+            instances,
+            witnesses
+                .iter()
+                .map(|witness| self.commit(witness))
+                .collect(),
+            challenges,
+            C::identity(),
+        )
+        .with_witness(FoldingWitness::new(witnesses, error))
+    }
+}
+
+/// Folding accumulation scheme verifying key.
+#[derive(Clone, Debug)]
+pub struct FoldingVerifyingKey {
+    /// Degree of [`RelaxedPlonk`], where `degree - 1` cross terms are needed
+    /// for each folding.
+    pub degree: usize,
+    /// Number of witness polynomials in each phase of fresh instances.
+    pub num_witness: Vec<usize>,
+    /// Number of challenges to squeeze after each phase of fresh instances.
+    pub num_challenge: Vec<usize>,
+}
+
+impl FoldingVerifyingKey {
+    /// Initialize a [`FoldingVerifyingKey`].
+    pub fn new(degree: usize, num_witness: Vec<usize>, num_challenge: Vec<usize>) -> Self {
+        assert_eq!(num_witness.len(), num_challenge.len());
+        Self {
+            degree,
+            num_witness,
+            num_challenge,
+        }
+    }
+}
@@ -0,0 +1,775 @@
+use crate::{
+    loader::{native::NativeLoader, LoadedScalar, Loader, ScalarLoader},
+    pcs::{
+        folding::{FoldingAccumulator, FoldingProvingKey, FoldingVerifyingKey, FoldingWitness},
+        AccumulationScheme, AccumulationSchemeProver,
+    },
+    util::{
+        arithmetic::{powers, CurveAffine, Field, PrimeField},
+        msm::Msm,
+        transcript::{Transcript, TranscriptRead, TranscriptWrite},
+        Itertools,
+    },
+    Error,
+};
+use rand::Rng;
+use std::{iter, marker::PhantomData};
+
+/// Folding accumulation scheme, which folds fresh instances one by one into
+/// the first accumulator with a random linear combination.
+#[derive(Clone, Debug)]
+pub struct FoldingAs<C>(PhantomData<C>);
+
+impl<C> FoldingAs<C>
+where
+    C: CurveAffine,
+{
+    /// Squeezes challenges of a fresh instance from `transcript` like its
+    /// PLONK transcript does, which absorbs `instances` and then commitments
+    /// of witness polynomials of each phase before squeezing challenges of
+    /// the phase.
+    ///
+    /// Both [`FoldingAs::read_proof`](AccumulationScheme::read_proof) and
+    /// [`FoldingAs::create_proof`](AccumulationSchemeProver::create_proof)
+    /// start with calling it with a fresh transcript on each fresh instance in
+    /// order, so prover should create fresh instances in the same way.
+    pub fn squeeze_challenges<L, T>(
+        vk: &FoldingVerifyingKey,
+        instances: &[Vec<L::LoadedScalar>],
+        witnesses: &[L::LoadedEcPoint],
+        transcript: &mut T,
+    ) -> Result<Vec<L::LoadedScalar>, Error>
+    where
+        L: Loader<C>,
+        T: Transcript<C, L>,
+    {
+        if witnesses.len() != vk.num_witness.iter().sum::<usize>() {
+            return Err(Error::InvalidInstances);
+        }
+
+        for instance in instances.iter().flatten() {
+            transcript.common_scalar(instance)?;
+        }
+        let mut witnesses = witnesses.iter();
+        let mut challenges = Vec::new();
+        for (num_witness, num_challenge) in vk.num_witness.iter().zip(vk.num_challenge.iter()) {
+            for witness in witnesses.by_ref().take(*num_witness) {
+                transcript.common_ec_point(witness)?;
+            }
+            challenges
+                .extend(iter::repeat_with(|| transcript.squeeze_challenge()).take(*num_challenge));
+        }
+        Ok(challenges)
+    }
+}
+
+impl<C, L> AccumulationScheme<C, L> for FoldingAs<C>
+where
+    C: CurveAffine,
+    L: Loader<C>,
+{
+    type Accumulator = FoldingAccumulator<C, L>;
+    type VerifyingKey = FoldingVerifyingKey;
+    type Proof = FoldingAsProof<C, L>;
+
+    fn read_proof<T>(
+        vk: &Self::VerifyingKey,
+        instances: &[Self::Accumulator],
+        transcript: &mut T,
+    ) -> Result<Self::Proof, Error>
+    where
+        T: TranscriptRead<C, L>,
+    {
+        FoldingAsProof::read(vk, instances, transcript)
+    }
+
+    fn verify(
+        vk: &Self::VerifyingKey,
+        instances: &[Self::Accumulator],
+        proof: &Self::Proof,
+    ) -> Result<Self::Accumulator, Error> {
+        if instances.is_empty() || instances.len() != proof.rs.len() + 1 {
+            return Err(Error::InvalidInstances);
+        }
+
+        let loader = instances[0].u.loader();
+        let one = loader.load_one();
+        let fresh = instances[1..]
+            .iter()
+            .zip(proof.challenges.iter())
+            .map(|(instance, challenges)| {
+                if instance.challenges.len() != challenges.len() {
+                    return Err(Error::InvalidInstances);
+                }
+                loader.assert_eq("Slack of fresh instance", &instance.u, &one)?;
+                for (claimed, squeezed) in instance.challenges.iter().zip(challenges.iter()) {
+                    loader.assert_eq("Challenge of fresh instance", claimed, squeezed)?;
+                }
+                Ok(FoldingAccumulator::new(
+                    one.clone(),
+                    instance.instances.clone(),
+                    instance.witnesses.clone(),
+                    challenges.clone(),
+                    instance.error.clone(),
+                ))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let accumulator = proof
+            .cross_terms
+            .iter()
+            .zip(proof.rs.iter())
+            .zip(fresh.iter())
+            .fold(
+                instances[0].clone(),
+                |accumulator, ((cross_terms, r), instance)| {
+                    fold(vk.degree, &accumulator, instance, cross_terms, r)
+                },
+            );
+
+        Ok(accumulator)
+    }
+}
+
+/// Folding accumulation scheme proof.
+#[derive(Clone, Debug)]
+pub struct FoldingAsProof<C, L>
+where
+    C: CurveAffine,
+    L: Loader<C>,
+{
+    challenges: Vec<Vec<L::LoadedScalar>>,
+    cross_terms: Vec<Vec<L::LoadedEcPoint>>,
+    rs: Vec<L::LoadedScalar>,
+}
+
+impl<C, L> FoldingAsProof<C, L>
+where
+    C: CurveAffine,
+    L: Loader<C>,
+{
+    fn read<T>(
+        vk: &FoldingVerifyingKey,
+        instances: &[FoldingAccumulator<C, L>],
+        transcript: &mut T,
+    ) -> Result<Self, Error>
+    where
+        T: TranscriptRead<C, L>,
+    {
+        assert!(!instances.is_empty());
+
+        let challenges = instances[1..]
+            .iter()
+            .map(|instance| {
+                FoldingAs::<C>::squeeze_challenges(
+                    vk,
+                    &instance.instances,
+                    &instance.witnesses,
+                    transcript,
+                )
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        instances[0].absorb_into(transcript)?;
+
+        let (cross_terms, rs) = instances[1..]
+            .iter()
+            .map(|_| {
+                Ok((
+                    transcript.read_n_ec_points(vk.degree - 1)?,
+                    transcript.squeeze_challenge(),
+                ))
+            })
+            .collect::<Result<Vec<_>, Error>>()?
+            .into_iter()
+            .unzip();
+
+        Ok(Self {
+            challenges,
+            cross_terms,
+            rs,
+        })
+    }
+}
+
+impl<C> AccumulationSchemeProver<C> for FoldingAs<C>
+where
+    C: CurveAffine,
+{
+    type ProvingKey = FoldingProvingKey<C>;
+
+    fn create_proof<T, R>(
+        pk: &Self::ProvingKey,
+        instances: &[FoldingAccumulator<C, NativeLoader>],
+        transcript: &mut T,
+        _: R,
+    ) -> Result<FoldingAccumulator<C, NativeLoader>, Error>
+    where
+        T: TranscriptWrite<C>,
+        R: Rng,
+    {
+        assert!(!instances.is_empty());
+
+        let vk = pk.vk();
+        for instance in instances[1..].iter() {
+            let challenges = Self::squeeze_challenges::<NativeLoader, _>(
+                &vk,
+                &instance.instances,
+                &instance.witnesses,
+                transcript,
+            )?;
+            if instance.u != C::Scalar::ONE
+                || instance.error != C::identity()
+                || instance.challenges != challenges
+            {
+                return Err(Error::InvalidInstances);
+            }
+        }
+        instances[0].absorb_into(transcript)?;
+
+        let degree = pk.relation.degree;
+        let witness = |accumulator: &FoldingAccumulator<C, NativeLoader>| {
+            accumulator.witness().cloned().ok_or_else(|| {
+                Error::InvalidProtocol("Witness of accumulator is required".to_string())
+            })
+        };
+
+        let mut accumulator = instances[0].clone();
+        witness(&accumulator)?;
+        for instance in instances[1..].iter() {
+            let (lhs, rhs) = (witness(&accumulator)?, witness(instance)?);
+            let cross_terms = cross_terms(pk, (&accumulator, &lhs), (instance, &rhs));
+            let commitments = cross_terms
+                .iter()
+                .map(|cross_term| pk.commit(cross_term))
+                .collect_vec();
+            for commitment in commitments.iter() {
+                transcript.write_ec_point(*commitment)?;
+            }
+
+            let r = transcript.squeeze_challenge();
+
+            let witnesses = lhs
+                .witnesses
+                .iter()
+                .zip(rhs.witnesses.iter())
+                .map(|(lhs, rhs)| fold_values(lhs, rhs, &r))
+                .collect();
+            let error = iter::once(&lhs.error)
+                .chain(cross_terms.iter())
+                .zip(powers(r))
+                .fold(
+                    vec![C::Scalar::ZERO; pk.relation.domain.n],
+                    |mut error, (values, power_of_r)| {
+                        for (error, value) in error.iter_mut().zip(values) {
+                            *error += power_of_r * value;
+                        }
+                        error
+                    },
+                );
+            accumulator = fold(degree, &accumulator, instance, &commitments, &r)
+                .with_witness(FoldingWitness::new(witnesses, error));
+        }
+
+        Ok(accumulator)
+    }
+}
+
+/// Fold fresh `rhs` into `lhs` with challenge `r`, where the error is folded
+/// as `E_lhs + Σ_{i=1}^{degree-1} r^i T_i` since error of `rhs` is zero.
+fn fold<C, L>(
+    degree: usize,
+    lhs: &FoldingAccumulator<C, L>,
+    rhs: &FoldingAccumulator<C, L>,
+    cross_terms: &[L::LoadedEcPoint],
+    r: &L::LoadedScalar,
+) -> FoldingAccumulator<C, L>
+where
+    C: CurveAffine,
+    L: Loader<C>,
+{
+    let fold_scalar = |lhs: &L::LoadedScalar, rhs: &L::LoadedScalar| lhs.clone() + rhs.clone() * r;
+    let fold_ec_point = |lhs: &L::LoadedEcPoint, rhs: &L::LoadedEcPoint| {
+        (Msm::<C, L>::base(lhs) + Msm::base(rhs) * r).evaluate(None)
+    };
+
+    let u = fold_scalar(&lhs.u, &rhs.u);
+    let instances = lhs
+        .instances
+        .iter()
+        .zip(rhs.instances.iter())
+        .map(|(lhs, rhs)| {
+            lhs.iter()
+                .zip(rhs.iter())
+                .map(|(lhs, rhs)| fold_scalar(lhs, rhs))
+                .collect_vec()
+        })
+        .collect_vec();
+    let witnesses = lhs
+        .witnesses
+        .iter()
+        .zip(rhs.witnesses.iter())
+        .map(|(lhs, rhs)| fold_ec_point(lhs, rhs))
+        .collect_vec();
+    let challenges = lhs
+        .challenges
+        .iter()
+        .zip(rhs.challenges.iter())
+        .map(|(lhs, rhs)| fold_scalar(lhs, rhs))
+        .collect_vec();
+    let error = iter::once(&lhs.error)
+        .chain(cross_terms.iter())
+        .zip(r.powers(degree).iter())
+        .map(|(base, power_of_r)| Msm::<C, L>::base(base) * power_of_r)
+        .sum::<Msm<_, _>>()
+        .evaluate(None);
+
+    FoldingAccumulator::new(u, instances, witnesses, challenges, error)
+}
+
+fn fold_values<F: PrimeField>(lhs: &[F], rhs: &[F], r: &F) -> Vec<F> {
+    lhs.iter()
+        .zip(rhs.iter())
+        .map(|(lhs, rhs)| *lhs + *r * rhs)
+        .collect()
+}
+
+/// Returns cross terms `T_1, ..., T_{degree-1}` by evaluating the relation at
+/// `lhs + t·rhs` for `t` in `0..=degree`, and interpolating coefficients of
+/// each row.
+fn cross_terms<C: CurveAffine>(
+    pk: &FoldingProvingKey<C>,
+    (lhs, lhs_witness): (
+        &FoldingAccumulator<C, NativeLoader>,
+        &FoldingWitness<C::Scalar>,
+    ),
+    (rhs, rhs_witness): (
+        &FoldingAccumulator<C, NativeLoader>,
+        &FoldingWitness<C::Scalar>,
+    ),
+) -> Vec<Vec<C::Scalar>> {
+    let degree = pk.relation.degree;
+    let evals = (0..=degree as u64)
+        .map(|t| {
+            let t = C::Scalar::from(t);
+            let instances = lhs
+                .instances
+                .iter()
+                .zip(rhs.instances.iter())
+                .map(|(lhs, rhs)| fold_values(lhs, rhs, &t))
+                .collect_vec();
+            let witnesses = lhs_witness
+                .witnesses
+                .iter()
+                .zip(rhs_witness.witnesses.iter())
+                .map(|(lhs, rhs)| fold_values(lhs, rhs, &t))
+                .collect_vec();
+            pk.relation.evaluate(
+                &pk.preprocessed,
+                &(lhs.u + t * rhs.u),
+                &instances,
+                &witnesses,
+                &fold_values(&lhs.challenges, &rhs.challenges, &t),
+            )
+        })
+        .collect_vec();
+
+    let lagranges = lagrange_coeffs::<C::Scalar>(degree);
+    (1..degree)
+        .map(|i| {
+            (0..pk.relation.domain.n)
+                .map(|row| {
+                    lagranges
+                        .iter()
+                        .zip(evals.iter())
+                        .map(|(lagrange, evals)| lagrange[i] * evals[row])
+                        .sum()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Returns coefficients of Lagrange basis polynomials on `0..=degree`.
+fn lagrange_coeffs<F: PrimeField>(degree: usize) -> Vec<Vec<F>> {
+    let points = (0..=degree as u64).map(F::from).collect_vec();
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, point_i)| {
+            let (numer, denom) = points.iter().enumerate().filter(|(j, _)| *j != i).fold(
+                (vec![F::ONE], F::ONE),
+                |(numer, denom), (_, point_j)| {
+                    let mut next = vec![F::ZERO; numer.len() + 1];
+                    for (k, coeff) in numer.iter().enumerate() {
+                        next[k] -= *coeff * point_j;
+                        next[k + 1] += coeff;
+                    }
+                    (next, denom * (*point_i - point_j))
+                },
+            );
+            let denom_inv = Field::invert(&denom).unwrap();
+            numer.into_iter().map(|coeff| coeff * denom_inv).collect()
+        })
+        .collect()
+}
+
+#[cfg(all(test, feature = "loader_halo2", feature = "system_halo2"))]
+mod test {
+    use crate::{
+        loader::{self, native::NativeLoader, EcPointLoader, Loader, ScalarLoader},
+        pcs::{
+            folding::{
+                FoldingAccumulator, FoldingAs, FoldingProvingKey, FoldingVerifyingKey, RelaxedPlonk,
+            },
+            AccumulationDecider, AccumulationScheme, AccumulationSchemeProver,
+        },
+        system::{self, halo2::test::MainGateWithRangeConfig},
+        util::{
+            arithmetic::{root_of_unity, CurveAffine, Domain, Field},
+            transcript::TranscriptRead,
+            Itertools,
+        },
+        verifier::plonk::{
+            protocol::{CommonPolynomial, Expression, Query, QuotientPolynomial},
+            PlonkProtocol,
+        },
+        Error,
+    };
+    use halo2_curves::{
+        bn256::{Fq, Fr, G1Affine, G1},
+        group::{Curve, Group},
+    };
+    use halo2_proofs::{
+        circuit::{floor_planner::V1, Layouter, Value},
+        dev::MockProver,
+        plonk::{self, Circuit, ConstraintSystem},
+    };
+    use halo2_wrong_ecc::{
+        integer::rns::Rns,
+        maingate::{RangeInstructions, RegionCtx},
+    };
+    use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+    use std::{iter, rc::Rc};
+
+    const K: u32 = 20;
+    const LIMBS: usize = 4;
+    const BITS: usize = 68;
+    const T: usize = 5;
+    const RATE: usize = 4;
+    const R_F: usize = 8;
+    const R_P: usize = 60;
+
+    const DOMAIN_K: usize = 3;
+
+    type BaseFieldEccChip = halo2_wrong_ecc::BaseFieldEccChip<G1Affine, LIMBS, BITS>;
+    type Halo2Loader<'a> = loader::halo2::Halo2Loader<'a, G1Affine, BaseFieldEccChip>;
+    type PoseidonTranscript<L, S> =
+        system::halo2::transcript::halo2::PoseidonTranscript<G1Affine, L, S, T, RATE, R_F, R_P>;
+    type As = FoldingAs<G1Affine>;
+
+    /// Polynomials are `q`, `instance`, `a`, `b` and `c`, where `q` selects
+    /// `a·b = c` and the first row of `a` is the instance.
+    fn protocol() -> PlonkProtocol<G1Affine> {
+        let [q, instance, a, b, c] =
+            [0, 1, 2, 3, 4].map(|poly| Expression::<Fr>::from(Query::new(poly, 0)));
+        let l_0 = Expression::<Fr>::from(CommonPolynomial::Lagrange(0));
+        PlonkProtocol {
+            domain: Domain::new(DOMAIN_K, root_of_unity(DOMAIN_K)),
+            preprocessed: vec![G1Affine::generator()],
+            num_instance: vec![1],
+            num_witness: vec![3],
+            num_challenge: vec![1],
+            evaluations: Vec::new(),
+            queries: Vec::new(),
+            quotient: QuotientPolynomial::new(
+                1,
+                3,
+                Expression::DistributePowers(
+                    vec![&q * &(&a * &b - &c), l_0 * (&a - &instance)],
+                    Box::new(Expression::Challenge(0)),
+                ),
+            ),
+            transcript_initial_state: None,
+            instance_committing_key: None,
+            linearization: None,
+            accumulator_indices: Vec::new(),
+        }
+    }
+
+    fn pk(mut rng: impl rand::RngCore) -> FoldingProvingKey<G1Affine> {
+        let relation = RelaxedPlonk::new(&protocol());
+        let n = relation.domain.n;
+        let q = (0..n)
+            .map(|row| if row % 2 == 0 { Fr::ONE } else { Fr::ZERO })
+            .collect_vec();
+        let g_lagrange = iter::repeat_with(|| G1::random(&mut rng).to_affine())
+            .take(n)
+            .collect();
+        FoldingProvingKey::new(relation, vec![q], g_lagrange)
+    }
+
+    fn rand_vec(n: usize, mut rng: impl rand::RngCore) -> Vec<Fr> {
+        iter::repeat_with(|| Fr::random(&mut rng)).take(n).collect()
+    }
+
+    /// Returns instances and witnesses, which satisfy the constraint if
+    /// `valid`.
+    fn witness(
+        pk: &FoldingProvingKey<G1Affine>,
+        valid: bool,
+        mut rng: impl rand::RngCore,
+    ) -> (Vec<Vec<Fr>>, Vec<Vec<Fr>>) {
+        let n = pk.relation.domain.n;
+        let a = rand_vec(n, &mut rng);
+        let b = rand_vec(n, &mut rng);
+        let mut c = a
+            .iter()
+            .zip(b.iter())
+            .zip(pk.preprocessed[0].iter())
+            .map(|((a, b), q)| if *q == Fr::ONE { *a * b } else { *a + b })
+            .collect_vec();
+        if !valid {
+            c[0] += Fr::ONE;
+        }
+        (vec![vec![a[0]]], vec![a, b, c])
+    }
+
+    fn running(
+        pk: &FoldingProvingKey<G1Affine>,
+        mut rng: impl rand::RngCore,
+    ) -> FoldingAccumulator<G1Affine, NativeLoader> {
+        let (instances, witnesses) = witness(pk, true, &mut rng);
+        pk.accumulator(instances, witnesses, vec![Fr::random(&mut rng)])
+    }
+
+    /// Returns fresh instances with challenges squeezed from a fresh
+    /// transcript in order, the same as prover and verifier do.
+    fn fresh(
+        pk: &FoldingProvingKey<G1Affine>,
+        valid: &[bool],
+        mut rng: impl rand::RngCore,
+    ) -> Vec<FoldingAccumulator<G1Affine, NativeLoader>> {
+        let vk = pk.vk();
+        let mut transcript = PoseidonTranscript::<NativeLoader, _>::new(Vec::new());
+        valid
+            .iter()
+            .map(|valid| {
+                let (instances, witnesses) = witness(pk, *valid, &mut rng);
+                let commitments = witnesses
+                    .iter()
+                    .map(|witness| pk.commit(witness))
+                    .collect_vec();
+                let challenges = As::squeeze_challenges::<NativeLoader, _>(
+                    &vk,
+                    &instances,
+                    &commitments,
+                    &mut transcript,
+                )
+                .unwrap();
+                pk.accumulator(instances, witnesses, challenges)
+            })
+            .collect()
+    }
+
+    fn create_proof(
+        pk: &FoldingProvingKey<G1Affine>,
+        instances: &[FoldingAccumulator<G1Affine, NativeLoader>],
+        rng: impl rand::RngCore,
+    ) -> (FoldingAccumulator<G1Affine, NativeLoader>, Vec<u8>) {
+        let mut transcript = PoseidonTranscript::<NativeLoader, _>::new(Vec::new());
+        let accumulator = As::create_proof(pk, instances, &mut transcript, rng).unwrap();
+        (accumulator, transcript.finalize())
+    }
+
+    fn verify<L: Loader<G1Affine>>(
+        vk: &FoldingVerifyingKey,
+        instances: &[FoldingAccumulator<G1Affine, L>],
+        transcript: &mut impl TranscriptRead<G1Affine, L>,
+    ) -> Result<FoldingAccumulator<G1Affine, L>, Error> {
+        let proof = <As as AccumulationScheme<_, L>>::read_proof(vk, instances, transcript)?;
+        <As as AccumulationScheme<_, L>>::verify(vk, instances, &proof)
+    }
+
+    #[derive(Clone)]
+    struct FoldingCircuit {
+        vk: FoldingVerifyingKey,
+        instances: Vec<FoldingAccumulator<G1Affine, NativeLoader>>,
+        expected: FoldingAccumulator<G1Affine, NativeLoader>,
+        proof: Value<Vec<u8>>,
+    }
+
+    impl Circuit<Fr> for FoldingCircuit {
+        type Config = MainGateWithRangeConfig;
+        type FloorPlanner = V1;
+        #[cfg(feature = "halo2_circuit_params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                proof: Value::unknown(),
+                ..self.clone()
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            MainGateWithRangeConfig::configure(
+                meta,
+                vec![BITS / LIMBS],
+                Rns::<Fq, Fr, LIMBS, BITS>::construct().overflow_lengths(),
+            )
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), plonk::Error> {
+            config.range_chip().load_table(&mut layouter)?;
+
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let loader = Halo2Loader::new(config.ecc_chip(), RegionCtx::new(region, 0));
+                    let scalar = |scalar: &Fr| loader.assign_scalar(Value::known(*scalar));
+                    let ec_point =
+                        |ec_point: &G1Affine| loader.assign_ec_point(Value::known(*ec_point));
+                    let instances = self
+                        .instances
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, accumulator)| {
+                            // Error of fresh instance is taken as zero by
+                            // verifier, so any point could replace identity,
+                            // which can't be assigned.
+                            let error = if idx == 0 {
+                                ec_point(&accumulator.error)
+                            } else {
+                                loader.ec_point_load_const(&G1Affine::generator())
+                            };
+                            FoldingAccumulator::new(
+                                scalar(&accumulator.u),
+                                accumulator
+                                    .instances
+                                    .iter()
+                                    .map(|instances| instances.iter().map(scalar).collect())
+                                    .collect(),
+                                accumulator.witnesses.iter().map(ec_point).collect(),
+                                accumulator.challenges.iter().map(scalar).collect(),
+                                error,
+                            )
+                        })
+                        .collect_vec();
+                    let mut transcript = PoseidonTranscript::<Rc<Halo2Loader>, _>::new(
+                        &loader,
+                        self.proof.as_ref().map(Vec::as_slice),
+                    );
+                    let accumulator = verify(&self.vk, &instances, &mut transcript)
+                        .map_err(|_| plonk::Error::Synthesis)?;
+
+                    loader
+                        .assert_eq("u", &accumulator.u, &loader.load_const(&self.expected.u))
+                        .map_err(|_| plonk::Error::Synthesis)?;
+                    for (lhs, rhs) in accumulator
+                        .witnesses
+                        .iter()
+                        .chain(iter::once(&accumulator.error))
+                        .zip(
+                            self.expected
+                                .witnesses
+                                .iter()
+                                .chain(iter::once(&self.expected.error)),
+                        )
+                    {
+                        loader
+                            .ec_point_assert_eq("Folded", lhs, &loader.ec_point_load_const(rhs))
+                            .map_err(|_| plonk::Error::Synthesis)?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn rng() -> ChaCha20Rng {
+        ChaCha20Rng::from_seed(Default::default())
+    }
+
+    #[test]
+    fn test_folding() {
+        let mut rng = rng();
+        let pk = pk(&mut rng);
+        for valid in [true, false] {
+            let instances = iter::once(running(&pk, &mut rng))
+                .chain(fresh(&pk, &[true, valid], &mut rng))
+                .collect_vec();
+            let (accumulator, proof) = create_proof(&pk, &instances, &mut rng);
+
+            let mut transcript = PoseidonTranscript::<NativeLoader, _>::new(proof.as_slice());
+            let folded = verify(&pk.vk(), &instances, &mut transcript).unwrap();
+            assert_eq!(folded.u, accumulator.u);
+            assert_eq!(folded.instances, accumulator.instances);
+            assert_eq!(folded.witnesses, accumulator.witnesses);
+            assert_eq!(folded.challenges, accumulator.challenges);
+            assert_eq!(folded.error, accumulator.error);
+
+            assert_eq!(As::decide(&pk, accumulator).is_ok(), valid);
+            assert!(As::decide(&pk, folded).is_err());
+        }
+    }
+
+    #[test]
+    fn test_folding_forged_challenges() {
+        let mut rng = rng();
+        let pk = pk(&mut rng);
+        let instances = iter::once(running(&pk, &mut rng))
+            .chain(fresh(&pk, &[true, true], &mut rng))
+            .collect_vec();
+        let (_, proof) = create_proof(&pk, &instances, &mut rng);
+
+        let mut forged = instances;
+        forged[2].challenges[0] += Fr::ONE;
+
+        let mut transcript = PoseidonTranscript::<NativeLoader, _>::new(Vec::new());
+        assert!(matches!(
+            As::create_proof(&pk, &forged, &mut transcript, &mut rng),
+            Err(Error::InvalidInstances)
+        ));
+
+        let mut transcript = PoseidonTranscript::<NativeLoader, _>::new(proof.as_slice());
+        assert!(matches!(
+            verify(&pk.vk(), &forged, &mut transcript),
+            Err(Error::AssertionFailure(annotation)) if annotation == "Challenge of fresh instance"
+        ));
+    }
+
+    #[test]
+    #[ignore = "cause it takes several minutes to run"]
+    fn test_folding_halo2_loader() {
+        let mut rng = rng();
+        let pk = pk(&mut rng);
+        let accumulator = {
+            let instances = iter::once(running(&pk, &mut rng))
+                .chain(fresh(&pk, &[true], &mut rng))
+                .collect_vec();
+            create_proof(&pk, &instances, &mut rng).0
+        };
+        let instances = iter::once(accumulator)
+            .chain(fresh(&pk, &[true, true], &mut rng))
+            .collect_vec();
+        let (expected, proof) = create_proof(&pk, &instances, &mut rng);
+
+        let mut circuit = FoldingCircuit {
+            vk: pk.vk(),
+            instances,
+            expected,
+            proof: Value::known(proof),
+        };
+        MockProver::run(K, &circuit, vec![Vec::new()])
+            .unwrap()
+            .assert_satisfied();
+
+        circuit.instances[2].challenges[0] += Fr::ONE;
+        let prover = MockProver::run(K, &circuit, vec![Vec::new()]);
+        assert!(!matches!(prover.map(|prover| prover.verify()), Ok(Ok(()))));
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{
+    loader::Loader,
+    util::{arithmetic::CurveAffine, transcript::Transcript},
+    Error,
+};
+
+/// Relaxed PLONK instance as accumulator of
+/// [`FoldingAs`](super::FoldingAs), where the witness is only kept by native
+/// prover for further folding and deciding.
+#[derive(Clone, Debug)]
+pub struct FoldingAccumulator<C, L>
+where
+    C: CurveAffine,
+    L: Loader<C>,
+{
+    /// Slack scalar of homogenized constraint.
+    pub u: L::LoadedScalar,
+    /// Instances of each instance polynomial.
+    pub instances: Vec<Vec<L::LoadedScalar>>,
+    /// Commitments of witness polynomials.
+    pub witnesses: Vec<L::LoadedEcPoint>,
+    /// Challenges.
+    pub challenges: Vec<L::LoadedScalar>,
+    /// Commitment of error vector.
+    pub error: L::LoadedEcPoint,
+    witness: Option<FoldingWitness<C::Scalar>>,
+}
+
+impl<C, L> FoldingAccumulator<C, L>
+where
+    C: CurveAffine,
+    L: Loader<C>,
+{
+    /// Initialize a [`FoldingAccumulator`] without witness.
+    pub fn new(
+        u: L::LoadedScalar,
+        instances: Vec<Vec<L::LoadedScalar>>,
+        witnesses: Vec<L::LoadedEcPoint>,
+        challenges: Vec<L::LoadedScalar>,
+        error: L::LoadedEcPoint,
+    ) -> Self {
+        Self {
+            u,
+            instances,
+            witnesses,
+            challenges,
+            error,
+            witness: None,
+        }
+    }
+
+    /// Returns [`FoldingAccumulator`] with `witness`.
+    pub fn with_witness(mut self, witness: FoldingWitness<C::Scalar>) -> Self {
+        self.witness = Some(witness);
+        self
+    }
+
+    /// Returns witness if any.
+    pub fn witness(&self) -> Option<&FoldingWitness<C::Scalar>> {
+        self.witness.as_ref()
+    }
+
+    pub(super) fn absorb_into<T>(&self, transcript: &mut T) -> Result<(), Error>
+    where
+        T: Transcript<C, L>,
+    {
+        transcript.common_scalar(&self.u)?;
+        for instance in self.instances.iter().flatten() {
+            transcript.common_scalar(instance)?;
+        }
+        for witness in self.witnesses.iter() {
+            transcript.common_ec_point(witness)?;
+        }
+        for challenge in self.challenges.iter() {
+            transcript.common_scalar(challenge)?;
+        }
+        transcript.common_ec_point(&self.error)
+    }
+}
+
+/// Witness of [`FoldingAccumulator`].
+#[derive(Clone, Debug)]
+pub struct FoldingWitness<F> {
+    /// Evaluations of witness polynomials on the domain.
+    pub witnesses: Vec<Vec<F>>,
+    /// Error vector.
+    pub error: Vec<F>,
+}
+
+impl<F> FoldingWitness<F> {
+    /// Initialize a [`FoldingWitness`].
+    pub fn new(witnesses: Vec<Vec<F>>, error: Vec<F>) -> Self {
+        Self { witnesses, error }
+    }
+}
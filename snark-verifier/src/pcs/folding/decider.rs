@@ -0,0 +1,59 @@
+mod native {
+    use crate::{
+        loader::native::NativeLoader,
+        pcs::{
+            folding::{FoldingAccumulator, FoldingAs, FoldingProvingKey},
+            AccumulationDecider,
+        },
+        util::{arithmetic::CurveAffine, Itertools},
+        Error,
+    };
+
+    impl<C> AccumulationDecider<C, NativeLoader> for FoldingAs<C>
+    where
+        C: CurveAffine,
+    {
+        type DecidingKey = FoldingProvingKey<C>;
+
+        fn decide(
+            dk: &Self::DecidingKey,
+            accumulator: FoldingAccumulator<C, NativeLoader>,
+        ) -> Result<(), Error> {
+            let witness = accumulator.witness().ok_or_else(|| {
+                Error::AssertionFailure("Witness of accumulator is available".to_string())
+            })?;
+            if witness.witnesses.len() != accumulator.witnesses.len()
+                || !witness
+                    .witnesses
+                    .iter()
+                    .zip(accumulator.witnesses.iter())
+                    .all(|(values, commitment)| dk.commit(values) == *commitment)
+            {
+                return Err(Error::AssertionFailure("W_i == commit(G, w_i)".to_string()));
+            }
+            if dk.commit(&witness.error) != accumulator.error {
+                return Err(Error::AssertionFailure("E == commit(G, e)".to_string()));
+            }
+            (dk.relation.evaluate(
+                &dk.preprocessed,
+                &accumulator.u,
+                &accumulator.instances,
+                &witness.witnesses,
+                &accumulator.challenges,
+            ) == witness.error)
+                .then_some(())
+                .ok_or_else(|| Error::AssertionFailure("Relaxed constraint == e".to_string()))
+        }
+
+        fn decide_all(
+            dk: &Self::DecidingKey,
+            accumulators: Vec<FoldingAccumulator<C, NativeLoader>>,
+        ) -> Result<(), Error> {
+            accumulators
+                .into_iter()
+                .map(|accumulator| Self::decide(dk, accumulator))
+                .try_collect::<_, Vec<_>, _>()?;
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,979 @@
+//! FRI polynomial commitment scheme, where polynomials are committed as Merkle
+//! tree of evaluations on a low-degree-extension coset, and opened by running
+//! FRI low-degree test on the DEEP quotient.
+//! The notations are following <https://eprint.iacr.org/2019/336.pdf>.
+//!
+//! [`Fri`] works over the scalar field of the [`Loader`], so it verifies proofs
+//! natively in circuit. [`GoldilocksFri`] works over Goldilocks field with
+//! challenges in its quadratic extension, which is emulated in the scalar
+//! field, so STARK proofs over Goldilocks, with Merkle tree and transcript
+//! over the scalar field, can be verified in circuit as well.
+//!
+//! Commitments are Merkle roots given as [`Msm::constant`], which are not
+//! additively homomorphic, so [`Fri`] can't be the PCS of
+//! [`crate::verifier::plonk::PlonkVerifier`], which reads commitments as
+//! elliptic curve points and combines them linearly. Instead it's meant to be
+//! called directly by verifiers that read Merkle roots from transcript.
+
+use crate::{
+    loader::{native::NativeLoader, LoadedScalar, Loader, ScalarLoader},
+    pcs::{PolynomialCommitmentScheme, Query},
+    util::{
+        arithmetic::{batch_invert, fe_to_big, root_of_unity, CurveAffine, Field, PrimeField},
+        hash::Poseidon2Spec,
+        msm::Msm,
+        poly::Polynomial,
+        transcript::{TranscriptRead, TranscriptWrite},
+        Itertools,
+    },
+    Error,
+};
+use num_bigint::BigUint;
+use std::{collections::BTreeSet, fmt::Debug, iter, marker::PhantomData};
+
+mod goldilocks;
+
+pub use goldilocks::{
+    Goldilocks, GoldilocksExt2, GoldilocksFri, GoldilocksFriCommitment, GoldilocksFriProof,
+    LoadedGoldilocksExt2,
+};
+
+/// Two-to-one hash to compress nodes of Merkle tree, which is generic over
+/// [`LoadedScalar`] to be used by both prover and verifier.
+pub trait MerkleHasher<F: PrimeField>: Clone + Debug {
+    /// Compress `lhs` and `rhs` into their parent node.
+    fn hash<L: LoadedScalar<F>>(&self, lhs: &L, rhs: &L) -> L;
+}
+
+impl<F: PrimeField> MerkleHasher<F> for Poseidon2Spec<F, 3> {
+    fn hash<L: LoadedScalar<F>>(&self, lhs: &L, rhs: &L) -> L {
+        self.compress(&[lhs.clone(), rhs.clone()])
+    }
+}
+
+/// [`Loader`] that can witness values computed from scalars, which is
+/// required by [`Fri`] to derive query indices from challenges, and by
+/// [`GoldilocksFri`] to emulate arithmetic of Goldilocks field.
+pub trait FriLoader<C: CurveAffine>: Loader<C> {
+    /// Returns `num_output` scalars computed by `f` from values of `scalars`
+    /// without any constraint, which should be constrained by caller.
+    fn witness(
+        &self,
+        scalars: &[&Self::LoadedScalar],
+        num_output: usize,
+        f: impl Fn(&[C::Scalar]) -> Vec<C::Scalar>,
+    ) -> Vec<Self::LoadedScalar>;
+
+    /// Returns little-endian `num_bits` bits of `scalar` without any
+    /// constraint, which should be constrained by caller.
+    fn witness_bits(
+        &self,
+        scalar: &Self::LoadedScalar,
+        num_bits: usize,
+    ) -> Vec<Self::LoadedScalar> {
+        self.witness(&[scalar], num_bits, |values| {
+            let big = fe_to_big(values[0]);
+            (0..num_bits as u64)
+                .map(|idx| C::Scalar::from(big.bit(idx) as u64))
+                .collect()
+        })
+    }
+}
+
+impl<C: CurveAffine> FriLoader<C> for NativeLoader {
+    fn witness(
+        &self,
+        scalars: &[&C::Scalar],
+        _: usize,
+        f: impl Fn(&[C::Scalar]) -> Vec<C::Scalar>,
+    ) -> Vec<C::Scalar> {
+        f(&scalars.iter().map(|scalar| **scalar).collect_vec())
+    }
+}
+
+/// FRI polynomial commitment scheme with Merkle tree hashed by `H`.
+///
+/// [`PolynomialCommitmentScheme::verify`] expects each commitment to be a
+/// Merkle root as [`Msm::constant`], and returns
+/// [`Error::InvalidProtocol`] otherwise.
+#[derive(Clone, Debug)]
+pub struct Fri<H>(PhantomData<H>);
+
+/// Verifying key of [`Fri`], which is also used by prover.
+#[derive(Clone, Debug)]
+pub struct FriVerifyingKey<H> {
+    /// Log2 of degree bound of committed polynomials.
+    pub k: usize,
+    /// Log2 of blowup factor of low-degree-extension.
+    pub log_blowup: usize,
+    /// Number of queries.
+    pub num_query: usize,
+    /// Hasher of Merkle tree.
+    pub hasher: H,
+}
+
+impl<H> FriVerifyingKey<H> {
+    /// Initialize a [`FriVerifyingKey`].
+    ///
+    /// # Panic
+    ///
+    /// If `k` or `log_blowup` is zero.
+    pub fn new(k: usize, log_blowup: usize, num_query: usize, hasher: H) -> Self {
+        assert!(k > 0 && log_blowup > 0);
+        Self {
+            k,
+            log_blowup,
+            num_query,
+            hasher,
+        }
+    }
+
+    /// Returns log2 of size of low-degree-extension domain.
+    pub fn log_lde_size(&self) -> usize {
+        self.k + self.log_blowup
+    }
+
+    /// Returns depth of Merkle tree of committed polynomials, where each leaf
+    /// contains a pair of evaluations at `x` and `-x`.
+    fn depth(&self) -> usize {
+        self.log_lde_size() - 1
+    }
+
+    /// Returns generator of low-degree-extension domain.
+    fn omega<F: PrimeField>(&self) -> F {
+        root_of_unity(self.log_lde_size())
+    }
+}
+
+impl<C, L, H> PolynomialCommitmentScheme<C, L> for Fri<H>
+where
+    C: CurveAffine,
+    L: FriLoader<C>,
+    H: MerkleHasher<C::Scalar>,
+{
+    type VerifyingKey = FriVerifyingKey<H>;
+    type Proof = FriProof<C, L>;
+    type Output = ();
+
+    fn read_proof<T>(
+        vk: &FriVerifyingKey<H>,
+        queries: &[Query<C::Scalar>],
+        transcript: &mut T,
+    ) -> Result<FriProof<C, L>, Error>
+    where
+        T: TranscriptRead<C, L>,
+    {
+        FriProof::read(vk, polys(queries).len(), transcript)
+    }
+
+    fn verify(
+        vk: &FriVerifyingKey<H>,
+        commitments: &[Msm<C, L>],
+        z: &L::LoadedScalar,
+        queries: &[Query<C::Scalar, L::LoadedScalar>],
+        proof: &FriProof<C, L>,
+    ) -> Result<(), Error> {
+        let loader = z.loader();
+
+        let polys = polys(queries);
+        let roots = polys
+            .iter()
+            .map(|&poly| {
+                commitments
+                    .get(poly)
+                    .cloned()
+                    .and_then(Msm::try_into_constant)
+                    .ok_or_else(|| {
+                        Error::InvalidProtocol(format!(
+                            "Commitment of polynomial {poly} should be Merkle root"
+                        ))
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let poly_idx = |poly| polys.iter().position(|p| *p == poly).unwrap();
+
+        let shifts = queries.iter().fold(Vec::new(), |mut shifts, query| {
+            if !shifts.contains(&query.shift) {
+                shifts.push(query.shift);
+            }
+            shifts
+        });
+        let shift_idx = |shift| shifts.iter().position(|s| *s == shift).unwrap();
+        let z_shifts = shifts
+            .iter()
+            .map(|shift| loader.sum_with_coeff(&[(*shift, z)]))
+            .collect_vec();
+        let powers_of_beta = proof.beta.powers(queries.len());
+
+        for (challenge, query_proof) in proof.query_challenges.iter().zip(proof.queries.iter()) {
+            let bits = to_canonical_bits::<C, _>(loader, challenge)?;
+            let index = &bits[..vk.depth()];
+
+            let xs = (0..vk.k)
+                .map(|round| domain_point::<C, _, _>(vk, loader, index, round))
+                .collect_vec();
+            let (x_invs, deep_invs) = {
+                let mut invs =
+                    iter::empty()
+                        .chain(xs.iter().cloned())
+                        .chain(z_shifts.iter().flat_map(|z_shift| {
+                            [xs[0].clone() - z_shift, -xs[0].clone() - z_shift]
+                        }))
+                        .collect_vec();
+                L::batch_invert(invs.iter_mut());
+                let deep_invs = invs.split_off(vk.k);
+                (invs, deep_invs)
+            };
+
+            for (opening, root) in query_proof.openings.iter().zip(roots.iter()) {
+                opening.verify(vk, index, root)?;
+            }
+            let deep_quotient = |side: usize| {
+                let terms = queries
+                    .iter()
+                    .zip(powers_of_beta.iter())
+                    .map(|(query, power_of_beta)| {
+                        let eval = &query_proof.openings[poly_idx(query.poly)].evals[side];
+                        let inv = &deep_invs[2 * shift_idx(query.shift) + side];
+                        (power_of_beta.clone() * inv, eval.clone() - &query.eval)
+                    })
+                    .collect_vec();
+                loader.sum_products(&terms.iter().map(|(lhs, rhs)| (lhs, rhs)).collect_vec())
+            };
+            let mut eval = fold::<C::Scalar, _>(
+                &[deep_quotient(0), deep_quotient(1)],
+                &proof.alphas[0],
+                &x_invs[0],
+            );
+
+            for (round, (opening, root)) in query_proof
+                .layers
+                .iter()
+                .zip(proof.roots.iter())
+                .enumerate()
+                .map(|(idx, value)| (idx + 1, value))
+            {
+                let depth = vk.depth() - round;
+                opening.verify(vk, &index[..depth], root)?;
+                let [lhs, rhs] = &opening.evals;
+                let selected = loader.sum_with_coeff(&[
+                    (C::Scalar::ONE, lhs),
+                    (
+                        C::Scalar::ONE,
+                        &(index[depth].clone() * &(rhs.clone() - lhs)),
+                    ),
+                ]);
+                loader.assert_eq("FRI folding", &selected, &eval)?;
+                eval = fold::<C::Scalar, _>(&opening.evals, &proof.alphas[round], &x_invs[round]);
+            }
+
+            loader.assert_eq("FRI remainder", &eval, &proof.remainder)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<H> Fri<H> {
+    /// Commit polynomial `poly` in coefficient form.
+    pub fn commit<F>(vk: &FriVerifyingKey<H>, poly: &[F]) -> FriCommitment<F>
+    where
+        F: PrimeField,
+        H: MerkleHasher<F>,
+    {
+        assert!(poly.len() <= 1 << vk.k);
+
+        let poly = Polynomial::new(poly.to_vec());
+        let omega = vk.omega::<F>();
+        let evals = iter::successors(Some(F::MULTIPLICATIVE_GENERATOR), |x| Some(*x * omega))
+            .take(1 << vk.log_lde_size())
+            .map(|x| poly.evaluate(x))
+            .collect_vec();
+        FriCommitment::new(&vk.hasher, evals)
+    }
+
+    /// Create a proof to open polynomials of `commitments` at `z` with
+    /// `queries`, where evaluations are assumed to be written into transcript
+    /// already.
+    pub fn create_proof<C, T>(
+        vk: &FriVerifyingKey<H>,
+        commitments: &[&FriCommitment<C::Scalar>],
+        z: &C::Scalar,
+        queries: &[Query<C::Scalar, C::Scalar>],
+        transcript: &mut T,
+    ) -> Result<(), Error>
+    where
+        C: CurveAffine,
+        T: TranscriptWrite<C>,
+        H: MerkleHasher<C::Scalar>,
+    {
+        let lde_size = 1 << vk.log_lde_size();
+        let omega = vk.omega::<C::Scalar>();
+        let beta = transcript.squeeze_challenge();
+
+        let deep_quotient = {
+            let xs = iter::successors(Some(C::Scalar::MULTIPLICATIVE_GENERATOR), |x| {
+                Some(*x * omega)
+            })
+            .take(lde_size)
+            .collect_vec();
+            let mut deep_quotient = vec![C::Scalar::ZERO; lde_size];
+            for (query, power_of_beta) in queries
+                .iter()
+                .zip(iter::successors(Some(C::Scalar::ONE), |power| {
+                    Some(*power * beta)
+                }))
+            {
+                let z_shift = query.shift * z;
+                let mut denoms = xs.iter().map(|x| *x - z_shift).collect_vec();
+                batch_invert(&mut denoms);
+                let evals = &commitments[query.poly].evals;
+                for ((quotient, eval), denom) in
+                    deep_quotient.iter_mut().zip(evals.iter()).zip(denoms)
+                {
+                    *quotient += power_of_beta * (*eval - query.eval) * denom;
+                }
+            }
+            deep_quotient
+        };
+
+        let mut alpha = transcript.squeeze_challenge();
+        let mut layers = Vec::<FriCommitment<_>>::with_capacity(vk.k - 1);
+        let mut shift = C::Scalar::MULTIPLICATIVE_GENERATOR;
+        let mut omega = omega;
+        for _ in 1..vk.k {
+            let evals = layers.last().map_or(&deep_quotient, |layer| &layer.evals);
+            let layer = FriCommitment::new(&vk.hasher, fold_evals(evals, &alpha, shift, omega));
+            transcript.write_scalar(layer.root())?;
+            alpha = transcript.squeeze_challenge();
+            layers.push(layer);
+            shift = shift.square();
+            omega = omega.square();
+        }
+        let evals = layers.last().map_or(&deep_quotient, |layer| &layer.evals);
+        let remainder = fold_evals(evals, &alpha, shift, omega);
+        transcript.write_scalar(remainder[0])?;
+
+        let query_challenges = transcript.squeeze_n_challenges(vk.num_query);
+        for challenge in query_challenges {
+            let big = fe_to_big(challenge);
+            let index = (0..vk.depth()).fold(0, |index, idx| {
+                index | ((big.bit(idx as u64) as usize) << idx)
+            });
+            for poly in polys(queries) {
+                commitments[poly].open(index, transcript)?;
+            }
+            for (round, layer) in layers.iter().enumerate() {
+                layer.open(index & ((1 << (vk.depth() - round - 1)) - 1), transcript)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Evaluations of a polynomial on low-degree-extension domain with their
+/// Merkle tree, which are kept by prover to open the committed polynomial.
+#[derive(Clone, Debug)]
+pub struct FriCommitment<F: PrimeField> {
+    evals: Vec<F>,
+    tree: MerkleTree<F>,
+}
+
+impl<F: PrimeField> FriCommitment<F> {
+    fn new(hasher: &impl MerkleHasher<F>, evals: Vec<F>) -> Self {
+        let tree = MerkleTree::new(hasher, &evals);
+        Self { evals, tree }
+    }
+
+    /// Returns Merkle root as the commitment, which should be given to
+    /// verifier as [`Msm::constant`].
+    pub fn root(&self) -> F {
+        self.tree.root()
+    }
+
+    fn open<C, T>(&self, leaf: usize, transcript: &mut T) -> Result<(), Error>
+    where
+        C: CurveAffine<Scalar = F>,
+        T: TranscriptWrite<C>,
+    {
+        transcript.write_scalar(self.evals[leaf])?;
+        transcript.write_scalar(self.evals[leaf + self.evals.len() / 2])?;
+        for sibling in self.tree.siblings(leaf) {
+            transcript.write_scalar(sibling)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+struct MerkleTree<F: PrimeField> {
+    layers: Vec<Vec<F>>,
+}
+
+impl<F: PrimeField> MerkleTree<F> {
+    fn new(hasher: &impl MerkleHasher<F>, evals: &[F]) -> Self {
+        let (lhs, rhs) = evals.split_at(evals.len() / 2);
+        let leaves = lhs
+            .iter()
+            .zip(rhs.iter())
+            .map(|(lhs, rhs)| hasher.hash(lhs, rhs))
+            .collect_vec();
+        let layers = iter::successors(Some(leaves), |layer| {
+            (layer.len() > 1).then(|| {
+                layer
+                    .chunks(2)
+                    .map(|pair| hasher.hash(&pair[0], &pair[1]))
+                    .collect()
+            })
+        })
+        .collect();
+        Self { layers }
+    }
+
+    fn root(&self) -> F {
+        self.layers.last().unwrap()[0]
+    }
+
+    fn siblings(&self, leaf: usize) -> Vec<F> {
+        self.layers
+            .iter()
+            .take(self.layers.len() - 1)
+            .enumerate()
+            .map(|(depth, layer)| layer[(leaf >> depth) ^ 1])
+            .collect()
+    }
+}
+
+/// Structured proof of [`Fri`].
+#[derive(Clone, Debug)]
+pub struct FriProof<C, L>
+where
+    C: CurveAffine,
+    L: Loader<C>,
+{
+    beta: L::LoadedScalar,
+    alphas: Vec<L::LoadedScalar>,
+    roots: Vec<L::LoadedScalar>,
+    remainder: L::LoadedScalar,
+    query_challenges: Vec<L::LoadedScalar>,
+    queries: Vec<FriQueryProof<C, L>>,
+}
+
+impl<C, L> FriProof<C, L>
+where
+    C: CurveAffine,
+    L: Loader<C>,
+{
+    /// Read [`FriProof`] of `num_poly` polynomials from transcript.
+    pub fn read<T, H>(
+        vk: &FriVerifyingKey<H>,
+        num_poly: usize,
+        transcript: &mut T,
+    ) -> Result<Self, Error>
+    where
+        T: TranscriptRead<C, L>,
+    {
+        let beta = transcript.squeeze_challenge();
+        let mut alphas = vec![transcript.squeeze_challenge()];
+        let mut roots = Vec::with_capacity(vk.k - 1);
+        for _ in 1..vk.k {
+            roots.push(transcript.read_scalar()?);
+            alphas.push(transcript.squeeze_challenge());
+        }
+        let remainder = transcript.read_scalar()?;
+        let query_challenges = transcript.squeeze_n_challenges(vk.num_query);
+        let queries = (0..vk.num_query)
+            .map(|_| {
+                let openings = (0..num_poly)
+                    .map(|_| MerkleOpening::read(vk.depth(), transcript))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let layers = (1..vk.k)
+                    .map(|round| MerkleOpening::read(vk.depth() - round, transcript))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(FriQueryProof { openings, layers })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(Self {
+            beta,
+            alphas,
+            roots,
+            remainder,
+            query_challenges,
+            queries,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+struct FriQueryProof<C, L>
+where
+    C: CurveAffine,
+    L: Loader<C>,
+{
+    openings: Vec<MerkleOpening<C, L>>,
+    layers: Vec<MerkleOpening<C, L>>,
+}
+
+#[derive(Clone, Debug)]
+struct MerkleOpening<C, L>
+where
+    C: CurveAffine,
+    L: Loader<C>,
+{
+    evals: [L::LoadedScalar; 2],
+    siblings: Vec<L::LoadedScalar>,
+}
+
+impl<C, L> MerkleOpening<C, L>
+where
+    C: CurveAffine,
+    L: Loader<C>,
+{
+    fn read<T>(depth: usize, transcript: &mut T) -> Result<Self, Error>
+    where
+        T: TranscriptRead<C, L>,
+    {
+        let evals = [transcript.read_scalar()?, transcript.read_scalar()?];
+        let siblings = transcript.read_n_scalars(depth)?;
+        Ok(Self { evals, siblings })
+    }
+
+    /// Verify Merkle path from leaf at `index` in little-endian bits to
+    /// `root`.
+    fn verify<H>(
+        &self,
+        vk: &FriVerifyingKey<H>,
+        index: &[L::LoadedScalar],
+        root: &L::LoadedScalar,
+    ) -> Result<(), Error>
+    where
+        H: MerkleHasher<C::Scalar>,
+    {
+        let leaf = vk.hasher.hash(&self.evals[0], &self.evals[1]);
+        verify_merkle_path::<C::Scalar, _, _>(&vk.hasher, leaf, index, &self.siblings, root)
+    }
+}
+
+/// Verify Merkle path from `leaf` at `index` in little-endian bits with
+/// `siblings` to `root`.
+fn verify_merkle_path<F, L, H>(
+    hasher: &H,
+    leaf: L,
+    index: &[L],
+    siblings: &[L],
+    root: &L,
+) -> Result<(), Error>
+where
+    F: PrimeField,
+    L: LoadedScalar<F>,
+    H: MerkleHasher<F>,
+{
+    let node = index
+        .iter()
+        .zip(siblings.iter())
+        .fold(leaf, |node, (bit, sibling)| {
+            let diff = bit.clone() * &(sibling.clone() - &node);
+            hasher.hash(&(node.clone() + &diff), &(sibling.clone() - &diff))
+        });
+    root.loader().assert_eq("Merkle root", &node, root)
+}
+
+/// Returns indices of distinct polynomials in `queries` in ascending order.
+fn polys<F, T>(queries: &[Query<F, T>]) -> Vec<usize> {
+    queries
+        .iter()
+        .map(|query| query.poly)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Returns point of leaf at `index` in the domain of `round`, which is
+/// $g^{2^r} \omega^{2^r \cdot i}$ where $g$ is the shift of coset.
+fn domain_point<C, L, H>(
+    vk: &FriVerifyingKey<H>,
+    loader: &L,
+    index: &[L::LoadedScalar],
+    round: usize,
+) -> L::LoadedScalar
+where
+    C: CurveAffine,
+    L: Loader<C>,
+{
+    let shift = iter::successors(Some(C::Scalar::MULTIPLICATIVE_GENERATOR), |shift| {
+        Some(shift.square())
+    })
+    .nth(round)
+    .unwrap();
+    let factors = iter::successors(Some(vk.omega::<C::Scalar>()), |omega| Some(omega.square()))
+        .skip(round)
+        .zip(index[..vk.depth() - round].iter())
+        .map(|(omega, bit)| {
+            loader.sum_with_coeff_and_const(&[(omega - C::Scalar::ONE, bit)], C::Scalar::ONE)
+        })
+        .collect_vec();
+    loader.sum_with_coeff(&[(shift, &loader.product(&factors.iter().collect_vec()))])
+}
+
+/// Returns evaluation of folded polynomial at $x^2$ given `evals` at $x$ and
+/// $-x$, which is $(f(x) + f(-x)) / 2 + \alpha (f(x) - f(-x)) / 2x$.
+fn fold<F, L>(evals: &[L; 2], alpha: &L, x_inv: &L) -> L
+where
+    F: PrimeField,
+    L: LoadedScalar<F>,
+{
+    let [lhs, rhs] = evals;
+    let odd = alpha.clone() * &(lhs.clone() - rhs) * x_inv;
+    lhs.loader()
+        .sum_with_coeff(&[(F::TWO_INV, &(lhs.clone() + rhs)), (F::TWO_INV, &odd)])
+}
+
+/// Fold evaluations on coset of `shift` with generator `omega` into
+/// evaluations on its square.
+fn fold_evals<F: PrimeField>(evals: &[F], alpha: &F, shift: F, omega: F) -> Vec<F> {
+    let (lhs, rhs) = evals.split_at(evals.len() / 2);
+    let mut x_invs = iter::successors(Some(shift), |x| Some(*x * omega))
+        .take(lhs.len())
+        .collect_vec();
+    batch_invert(&mut x_invs);
+    lhs.iter()
+        .zip(rhs.iter())
+        .zip(x_invs.iter())
+        .map(|((lhs, rhs), x_inv)| fold::<F, _>(&[*lhs, *rhs], alpha, x_inv))
+        .collect()
+}
+
+/// Decompose `scalar` into little-endian bits, with constraints that bits are
+/// boolean, recompose to `scalar` and are less than modulus, so the
+/// decomposition is unique.
+fn to_canonical_bits<C, L>(
+    loader: &L,
+    scalar: &L::LoadedScalar,
+) -> Result<Vec<L::LoadedScalar>, Error>
+where
+    C: CurveAffine,
+    L: FriLoader<C>,
+{
+    let bits = decompose::<C, _>(loader, scalar, C::Scalar::NUM_BITS as usize)?;
+    assert_bits_le::<C, _>(loader, &bits, &fe_to_big(-C::Scalar::ONE))?;
+    Ok(bits)
+}
+
+/// Decompose `scalar` into `num_bits` little-endian bits, with constraints
+/// that bits are boolean and recompose to `scalar`, so `scalar` is less than
+/// $2^{num\_bits}$.
+fn decompose<C, L>(
+    loader: &L,
+    scalar: &L::LoadedScalar,
+    num_bits: usize,
+) -> Result<Vec<L::LoadedScalar>, Error>
+where
+    C: CurveAffine,
+    L: FriLoader<C>,
+{
+    let bits = loader.witness_bits(scalar, num_bits);
+    for bit in bits.iter() {
+        loader.assert_eq("Boolean", &bit.square(), bit)?;
+    }
+
+    let powers_of_two = iter::successors(Some(C::Scalar::ONE), |power| Some(power.double()));
+    let recomposed = loader.sum_with_coeff(&powers_of_two.zip(bits.iter()).collect_vec());
+    loader.assert_eq("Bits recomposition", &recomposed, scalar)?;
+
+    Ok(bits)
+}
+
+/// Constrain integer of little-endian boolean `bits` to be less than or equal
+/// to `bound`.
+fn assert_bits_le<C, L>(loader: &L, bits: &[L::LoadedScalar], bound: &BigUint) -> Result<(), Error>
+where
+    C: CurveAffine,
+    L: FriLoader<C>,
+{
+    // Compare with `bound` from the most significant bit, where `eq`
+    // indicates all higher bits are equal and `lt` indicates some higher bit
+    // is less, so `lt + eq` is `1` if and only if bits are not greater.
+    let (lt, eq) = bits.iter().enumerate().rev().fold(
+        (loader.load_zero(), loader.load_one()),
+        |(lt, eq), (idx, bit)| {
+            let eq_and_bit = eq.clone() * bit;
+            if bound.bit(idx as u64) {
+                (lt + eq - &eq_and_bit, eq_and_bit)
+            } else {
+                (lt, eq - eq_and_bit)
+            }
+        },
+    );
+    loader.assert_eq("Bits upper bound", &(lt + eq), &loader.load_one())
+}
+
+#[cfg(feature = "loader_halo2")]
+mod halo2 {
+    use crate::{
+        loader::{
+            halo2::{EccInstructions, Halo2Loader, IntegerInstructions, Scalar},
+            ScalarLoader,
+        },
+        pcs::fri::FriLoader,
+        util::arithmetic::CurveAffine,
+    };
+    use halo2_proofs::circuit::Value;
+    use std::rc::Rc;
+
+    impl<'a, C: CurveAffine, EccChip: EccInstructions<'a, C>> FriLoader<C>
+        for Rc<Halo2Loader<'a, C, EccChip>>
+    {
+        /// Outputs of constants are loaded as constants, otherwise they are
+        /// assigned from [`IntegerInstructions::integer_value`], so the chip
+        /// has to expose witness values to generate proof.
+        fn witness(
+            &self,
+            scalars: &[&Scalar<'a, C, EccChip>],
+            num_output: usize,
+            f: impl Fn(&[C::Scalar]) -> Vec<C::Scalar>,
+        ) -> Vec<Scalar<'a, C, EccChip>> {
+            if let Some(constants) = scalars
+                .iter()
+                .map(|scalar| scalar.maybe_const())
+                .collect::<Option<Vec<_>>>()
+            {
+                return f(&constants)
+                    .iter()
+                    .map(|output| self.load_const(output))
+                    .collect();
+            }
+
+            let values = scalars
+                .iter()
+                .fold(Value::known(Vec::new()), |values, scalar| {
+                    let value = match scalar.maybe_const() {
+                        Some(constant) => Value::known(constant),
+                        None => self.scalar_chip().integer_value(&scalar.assigned()),
+                    };
+                    values.zip(value).map(|(mut values, value)| {
+                        values.push(value);
+                        values
+                    })
+                });
+            let outputs = values.map(|values| f(&values));
+            (0..num_output)
+                .map(|idx| self.assign_scalar(outputs.as_ref().map(|outputs| outputs[idx])))
+                .collect()
+        }
+    }
+}
+
+#[cfg(all(test, feature = "loader_halo2", feature = "system_halo2"))]
+mod test {
+    use crate::{
+        loader::{self, native::NativeLoader, EcPointLoader},
+        pcs::{
+            fri::{Fri, FriLoader, FriVerifyingKey},
+            PolynomialCommitmentScheme, Query,
+        },
+        system::{self, halo2::test::MainGateWithRangeConfig},
+        util::{
+            arithmetic::{root_of_unity, Field, PrimeCurveAffine},
+            hash::Poseidon2Spec,
+            msm::Msm,
+            poly::Polynomial,
+            transcript::{Transcript, TranscriptRead, TranscriptWrite},
+            Itertools,
+        },
+        Error,
+    };
+    use halo2_curves::bn256::{Fq, Fr, G1Affine};
+    use halo2_proofs::{
+        circuit::{floor_planner::V1, Layouter, Value},
+        dev::MockProver,
+        plonk::{self, Circuit, ConstraintSystem},
+    };
+    use halo2_wrong_ecc::{
+        integer::rns::Rns,
+        maingate::{RangeInstructions, RegionCtx},
+    };
+    use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+    use std::{iter, rc::Rc};
+
+    const K: u32 = 18;
+    const LIMBS: usize = 4;
+    const BITS: usize = 68;
+    const T: usize = 5;
+    const RATE: usize = 4;
+    const R_F: usize = 8;
+    const R_P: usize = 60;
+
+    type BaseFieldEccChip = halo2_wrong_ecc::BaseFieldEccChip<G1Affine, LIMBS, BITS>;
+    type Halo2Loader<'a> = loader::halo2::Halo2Loader<'a, G1Affine, BaseFieldEccChip>;
+    type PoseidonTranscript<L, S> =
+        system::halo2::transcript::halo2::PoseidonTranscript<G1Affine, L, S, T, RATE, R_F, R_P>;
+    type Pcs = Fri<Poseidon2Spec<Fr, 3>>;
+
+    fn vk() -> FriVerifyingKey<Poseidon2Spec<Fr, 3>> {
+        FriVerifyingKey::new(3, 2, 4, Poseidon2Spec::new(8, 56))
+    }
+
+    fn queries() -> Vec<Query<Fr>> {
+        let omega = root_of_unity(vk().k);
+        vec![
+            Query::new(0, Fr::ONE),
+            Query::new(0, omega),
+            Query::new(1, Fr::ONE),
+        ]
+    }
+
+    fn create_proof(valid: bool) -> Vec<u8> {
+        let vk = vk();
+        let mut rng = ChaCha20Rng::from_seed(Default::default());
+        let polys = iter::repeat_with(|| Polynomial::<Fr>::rand(1 << vk.k, &mut rng))
+            .take(2)
+            .collect_vec();
+        let commitments = polys
+            .iter()
+            .map(|poly| Pcs::commit(&vk, &poly.clone().to_vec()))
+            .collect_vec();
+
+        let mut transcript = PoseidonTranscript::<NativeLoader, _>::new(Vec::new());
+        for commitment in commitments.iter() {
+            transcript.write_scalar(commitment.root()).unwrap();
+        }
+        let z = transcript.squeeze_challenge();
+        let mut queries = queries()
+            .into_iter()
+            .map(|query| {
+                let eval = polys[query.poly].evaluate(query.shift * z);
+                query.with_evaluation(eval)
+            })
+            .collect_vec();
+        if !valid {
+            queries[0].eval += Fr::ONE;
+        }
+        for query in queries.iter() {
+            transcript.write_scalar(query.eval).unwrap();
+        }
+        Pcs::create_proof(
+            &vk,
+            &commitments.iter().collect_vec(),
+            &z,
+            &queries,
+            &mut transcript,
+        )
+        .unwrap();
+        transcript.finalize()
+    }
+
+    /// Verify proof with Merkle roots as commitments, or combined with
+    /// generator if `merkle_root` is `false`.
+    fn verify<L>(
+        transcript: &mut impl TranscriptRead<G1Affine, L>,
+        merkle_root: bool,
+    ) -> Result<(), Error>
+    where
+        L: FriLoader<G1Affine>,
+    {
+        let vk = vk();
+        let roots = transcript.read_n_scalars(2)?;
+        let z = transcript.squeeze_challenge();
+        let evals = transcript.read_n_scalars(queries().len())?;
+        let proof =
+            <Pcs as PolynomialCommitmentScheme<_, L>>::read_proof(&vk, &queries(), transcript)?;
+
+        let generator = transcript
+            .loader()
+            .ec_point_load_const(&G1Affine::generator());
+        let commitments = roots
+            .into_iter()
+            .map(|root| {
+                let commitment = Msm::constant(root);
+                if merkle_root {
+                    commitment
+                } else {
+                    commitment + Msm::base(&generator)
+                }
+            })
+            .collect_vec();
+        let queries = queries()
+            .into_iter()
+            .zip(evals)
+            .map(|(query, eval)| query.with_evaluation(eval))
+            .collect_vec();
+        <Pcs as PolynomialCommitmentScheme<_, L>>::verify(&vk, &commitments, &z, &queries, &proof)
+    }
+
+    #[derive(Clone)]
+    struct FriCircuit {
+        proof: Value<Vec<u8>>,
+    }
+
+    impl Circuit<Fr> for FriCircuit {
+        type Config = MainGateWithRangeConfig;
+        type FloorPlanner = V1;
+        #[cfg(feature = "halo2_circuit_params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                proof: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            MainGateWithRangeConfig::configure(
+                meta,
+                vec![BITS / LIMBS],
+                Rns::<Fq, Fr, LIMBS, BITS>::construct().overflow_lengths(),
+            )
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), plonk::Error> {
+            config.range_chip().load_table(&mut layouter)?;
+
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let loader = Halo2Loader::new(config.ecc_chip(), RegionCtx::new(region, 0));
+                    let mut transcript = PoseidonTranscript::<Rc<Halo2Loader>, _>::new(
+                        &loader,
+                        self.proof.as_ref().map(Vec::as_slice),
+                    );
+                    verify(&mut transcript, true).map_err(|_| plonk::Error::Synthesis)
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_fri() {
+        for (valid, proof) in [true, false].map(|valid| (valid, create_proof(valid))) {
+            let mut transcript = PoseidonTranscript::<NativeLoader, _>::new(proof.as_slice());
+            assert_eq!(verify(&mut transcript, true).is_ok(), valid);
+        }
+
+        // Commitments have to be Merkle roots
+        let proof = create_proof(true);
+        let mut transcript = PoseidonTranscript::<NativeLoader, _>::new(proof.as_slice());
+        assert!(matches!(
+            verify(&mut transcript, false),
+            Err(Error::InvalidProtocol(_))
+        ));
+    }
+
+    #[test]
+    fn test_fri_halo2_loader() {
+        for valid in [true, false] {
+            let circuit = FriCircuit {
+                proof: Value::known(create_proof(valid)),
+            };
+            let prover = MockProver::run(K, &circuit, vec![Vec::new()]);
+            assert_eq!(
+                matches!(prover.map(|prover| prover.verify()), Ok(Ok(()))),
+                valid
+            );
+        }
+    }
+}
@@ -0,0 +1,1204 @@
+//! FRI over Goldilocks field $p = 2^{64} - 2^{32} + 1$, with challenges in
+//! its quadratic extension $\mathbb{F}_p[X] / (X^2 - 7)$.
+//!
+//! Merkle tree and transcript are over the scalar field of the [`FriLoader`],
+//! where each Goldilocks element is written as a scalar, and each extension
+//! element as two. Arithmetic is emulated in the scalar field, where every
+//! loaded element is constrained to be canonical, and every operation is
+//! reduced by witnessing quotient and remainder, so the scalar field has to be
+//! larger than $2^{133}$ to not wrap around.
+
+use crate::{
+    loader::{LoadedScalar, Loader, ScalarLoader},
+    pcs::{
+        fri::{
+            assert_bits_le, decompose, polys, to_canonical_bits, verify_merkle_path, FriLoader,
+            FriVerifyingKey, MerkleHasher, MerkleOpening, MerkleTree,
+        },
+        Query,
+    },
+    util::{
+        arithmetic::{fe_from_big, fe_to_big, CurveAffine, Field, PrimeField},
+        transcript::{TranscriptRead, TranscriptWrite},
+        Itertools,
+    },
+    Error,
+};
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
+use std::{
+    iter,
+    marker::PhantomData,
+    ops::{Add, Mul, Neg, Sub},
+};
+
+/// Element of Goldilocks field in canonical form.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Goldilocks(u64);
+
+impl Goldilocks {
+    /// Modulus $2^{64} - 2^{32} + 1$.
+    pub const MODULUS: u64 = 0xffff_ffff_0000_0001;
+    /// Two-adicity of multiplicative group.
+    pub const TWO_ADICITY: usize = 32;
+    /// Generator of multiplicative group, which is also the shift of
+    /// low-degree-extension coset.
+    pub const MULTIPLICATIVE_GENERATOR: Self = Self(7);
+    /// Additive identity.
+    pub const ZERO: Self = Self(0);
+    /// Multiplicative identity.
+    pub const ONE: Self = Self(1);
+
+    const TWO_INV: Self = Self((Self::MODULUS + 1) / 2);
+
+    /// Returns `value` reduced by modulus.
+    pub fn new(value: u64) -> Self {
+        Self(value % Self::MODULUS)
+    }
+
+    /// Returns canonical value.
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+
+    /// Returns generator of multiplicative subgroup of order $2^k$.
+    ///
+    /// # Panic
+    ///
+    /// If `k` is greater than [`Goldilocks::TWO_ADICITY`].
+    pub fn root_of_unity(k: usize) -> Self {
+        assert!(k <= Self::TWO_ADICITY);
+        Self::MULTIPLICATIVE_GENERATOR.pow((Self::MODULUS - 1) >> k)
+    }
+
+    /// Returns `self` to the power of `exp`.
+    pub fn pow(&self, mut exp: u64) -> Self {
+        let mut base = *self;
+        let mut acc = Self::ONE;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                acc = acc * base;
+            }
+            base = base.square();
+            exp >>= 1;
+        }
+        acc
+    }
+
+    /// Returns `self` squared.
+    pub fn square(&self) -> Self {
+        *self * *self
+    }
+
+    /// Returns inverse of `self`, or `None` if `self` is zero.
+    pub fn invert(&self) -> Option<Self> {
+        (*self != Self::ZERO).then(|| self.pow(Self::MODULUS - 2))
+    }
+
+    fn to_scalar<F: PrimeField>(self) -> F {
+        F::from(self.0)
+    }
+
+    fn from_scalar<F: PrimeField>(scalar: F) -> Self {
+        Self::new(fe_to_big(scalar).to_u64().unwrap_or_default())
+    }
+}
+
+impl Add for Goldilocks {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(((self.0 as u128 + rhs.0 as u128) % Self::MODULUS as u128) as u64)
+    }
+}
+
+impl Sub for Goldilocks {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        let modulus = Self::MODULUS as u128;
+        Self(((self.0 as u128 + modulus - rhs.0 as u128) % modulus) as u64)
+    }
+}
+
+impl Mul for Goldilocks {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self(((self.0 as u128 * rhs.0 as u128) % Self::MODULUS as u128) as u64)
+    }
+}
+
+impl Neg for Goldilocks {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self((Self::MODULUS - self.0) % Self::MODULUS)
+    }
+}
+
+/// Element of quadratic extension of Goldilocks field
+/// $\mathbb{F}_p[X] / (X^2 - 7)$ in coefficients of $1$ and $X$.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GoldilocksExt2(pub [Goldilocks; 2]);
+
+impl GoldilocksExt2 {
+    /// Quadratic non-residue $W$ defining the extension $X^2 = W$.
+    pub const W: Goldilocks = Goldilocks(7);
+    /// Additive identity.
+    pub const ZERO: Self = Self([Goldilocks::ZERO; 2]);
+    /// Multiplicative identity.
+    pub const ONE: Self = Self([Goldilocks::ONE, Goldilocks::ZERO]);
+
+    /// Returns `base` embedded into the extension.
+    pub fn from_base(base: Goldilocks) -> Self {
+        Self([base, Goldilocks::ZERO])
+    }
+
+    /// Returns element derived from `challenge` with two chunks of 63 bits
+    /// from the least significant bit, which is the same as
+    /// [`LoadedGoldilocksExt2::squeeze`].
+    pub fn from_challenge<F: PrimeField>(challenge: F) -> Self {
+        let big = fe_to_big(challenge);
+        let mask = BigUint::from((1u64 << 63) - 1);
+        Self([0, 1].map(|idx| Goldilocks::new(((&big >> (63 * idx)) & &mask).to_u64().unwrap())))
+    }
+
+    /// Returns `self` multiplied by `base`.
+    pub fn mul_base(&self, base: Goldilocks) -> Self {
+        Self(self.0.map(|limb| limb * base))
+    }
+
+    /// Returns inverse of `self`, or `None` if `self` is zero.
+    pub fn invert(&self) -> Option<Self> {
+        let [c0, c1] = self.0;
+        (c0.square() - Self::W * c1.square())
+            .invert()
+            .map(|norm_inv| Self([c0 * norm_inv, -c1 * norm_inv]))
+    }
+}
+
+impl Add for GoldilocksExt2 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self([self.0[0] + rhs.0[0], self.0[1] + rhs.0[1]])
+    }
+}
+
+impl Sub for GoldilocksExt2 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self([self.0[0] - rhs.0[0], self.0[1] - rhs.0[1]])
+    }
+}
+
+impl Mul for GoldilocksExt2 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        let [a0, a1] = self.0;
+        let [b0, b1] = rhs.0;
+        Self([a0 * b0 + Self::W * a1 * b1, a0 * b1 + a1 * b0])
+    }
+}
+
+impl Neg for GoldilocksExt2 {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(self.0.map(Neg::neg))
+    }
+}
+
+/// Element written into transcript as limbs of [`Goldilocks`], and packed into
+/// a scalar as leaf of Merkle tree.
+pub trait Limbs: Copy {
+    /// Returns limbs of element.
+    fn limbs(&self) -> Vec<Goldilocks>;
+
+    /// Returns limbs packed into a scalar in little-endian of 64 bits.
+    fn pack<F: PrimeField>(&self) -> F {
+        let two_to_64 = F::from(u64::MAX) + F::ONE;
+        self.limbs()
+            .iter()
+            .rev()
+            .fold(F::ZERO, |acc, limb| acc * two_to_64 + limb.to_scalar::<F>())
+    }
+}
+
+impl Limbs for Goldilocks {
+    fn limbs(&self) -> Vec<Goldilocks> {
+        vec![*self]
+    }
+}
+
+impl Limbs for GoldilocksExt2 {
+    fn limbs(&self) -> Vec<Goldilocks> {
+        self.0.to_vec()
+    }
+}
+
+/// [`GoldilocksExt2`] loaded by [`FriLoader`] as two scalars, which are
+/// constrained to be canonical.
+#[derive(Clone, Debug)]
+pub struct LoadedGoldilocksExt2<C, L>
+where
+    C: CurveAffine,
+    L: Loader<C>,
+{
+    limbs: [L::LoadedScalar; 2],
+}
+
+impl<C, L> LoadedGoldilocksExt2<C, L>
+where
+    C: CurveAffine,
+    L: FriLoader<C>,
+{
+    /// Read [`LoadedGoldilocksExt2`] from transcript, with constraints that
+    /// limbs are canonical.
+    pub fn read<T>(transcript: &mut T) -> Result<Self, Error>
+    where
+        T: TranscriptRead<C, L>,
+    {
+        let limbs = [transcript.read_scalar()?, transcript.read_scalar()?];
+        let chip = GoldilocksChip::new(limbs[0].loader());
+        for limb in limbs.iter() {
+            chip.assert_canonical(limb)?;
+        }
+        Ok(Self { limbs })
+    }
+
+    /// Squeeze a challenge from transcript, and derive [`LoadedGoldilocksExt2`]
+    /// from it in the same way as [`GoldilocksExt2::from_challenge`].
+    pub fn squeeze<T>(transcript: &mut T) -> Result<Self, Error>
+    where
+        T: TranscriptRead<C, L>,
+    {
+        let challenge = transcript.squeeze_challenge();
+        let loader = challenge.loader();
+        let bits = to_canonical_bits::<C, _>(loader, &challenge)?;
+        let limbs = [0, 1].map(|idx| {
+            let powers_of_two =
+                iter::successors(Some(C::Scalar::ONE), |power| Some(power.double()));
+            loader.sum_with_coeff(
+                &powers_of_two
+                    .zip(bits[63 * idx..63 * (idx + 1)].iter())
+                    .collect_vec(),
+            )
+        });
+        Ok(Self { limbs })
+    }
+
+    fn loader(&self) -> &L {
+        self.limbs[0].loader()
+    }
+}
+
+/// Emulated arithmetic of [`Goldilocks`] and [`GoldilocksExt2`] in scalar
+/// field of [`FriLoader`], where inputs are assumed to be canonical and
+/// outputs are constrained to be canonical.
+#[derive(Debug)]
+struct GoldilocksChip<'a, C, L> {
+    loader: &'a L,
+    _marker: PhantomData<C>,
+}
+
+impl<'a, C, L> GoldilocksChip<'a, C, L>
+where
+    C: CurveAffine,
+    L: FriLoader<C>,
+{
+    fn new(loader: &'a L) -> Self {
+        Self {
+            loader,
+            _marker: PhantomData,
+        }
+    }
+
+    fn load_const(&self, value: Goldilocks) -> L::LoadedScalar {
+        self.loader.load_const(&value.to_scalar())
+    }
+
+    fn load_ext_const(&self, value: GoldilocksExt2) -> LoadedGoldilocksExt2<C, L> {
+        LoadedGoldilocksExt2 {
+            limbs: value.0.map(|limb| self.load_const(limb)),
+        }
+    }
+
+    fn assert_canonical(&self, value: &L::LoadedScalar) -> Result<(), Error> {
+        let bits = decompose::<C, _>(self.loader, value, 64)?;
+        assert_bits_le::<C, _>(self.loader, &bits, &BigUint::from(Goldilocks::MODULUS - 1))
+    }
+
+    /// Returns `value` reduced by modulus, where `value` is assumed to be less
+    /// than $2^{num\_bits}$, by witnessing quotient less than
+    /// $2^{num\_bits - 63}$ and canonical remainder.
+    fn reduce(&self, value: &L::LoadedScalar, num_bits: usize) -> Result<L::LoadedScalar, Error> {
+        let (quotient, remainder) = self
+            .loader
+            .witness(&[value], 2, |values| {
+                let big = fe_to_big(values[0]);
+                let modulus = BigUint::from(Goldilocks::MODULUS);
+                vec![fe_from_big(&big / &modulus), fe_from_big(big % modulus)]
+            })
+            .into_iter()
+            .collect_tuple()
+            .unwrap();
+        decompose::<C, _>(self.loader, &quotient, num_bits.saturating_sub(63))?;
+        self.assert_canonical(&remainder)?;
+
+        let recomposed = self.loader.sum_with_coeff(&[
+            (C::Scalar::from(Goldilocks::MODULUS), &quotient),
+            (C::Scalar::ONE, &remainder),
+        ]);
+        self.loader
+            .assert_eq("Goldilocks reduction", &recomposed, value)?;
+        Ok(remainder)
+    }
+
+    fn add(&self, lhs: &L::LoadedScalar, rhs: &L::LoadedScalar) -> Result<L::LoadedScalar, Error> {
+        self.reduce(&(lhs.clone() + rhs), 65)
+    }
+
+    fn sub(&self, lhs: &L::LoadedScalar, rhs: &L::LoadedScalar) -> Result<L::LoadedScalar, Error> {
+        let value = self.loader.sum_with_coeff_and_const(
+            &[(C::Scalar::ONE, lhs), (-C::Scalar::ONE, rhs)],
+            C::Scalar::from(Goldilocks::MODULUS),
+        );
+        self.reduce(&value, 65)
+    }
+
+    fn mul(&self, lhs: &L::LoadedScalar, rhs: &L::LoadedScalar) -> Result<L::LoadedScalar, Error> {
+        self.reduce(&(lhs.clone() * rhs), 128)
+    }
+
+    fn invert(&self, value: &L::LoadedScalar) -> Result<L::LoadedScalar, Error> {
+        let inv = self
+            .loader
+            .witness(&[value], 1, |values| {
+                let inv = Goldilocks::from_scalar(values[0]).invert();
+                vec![inv.unwrap_or_default().to_scalar()]
+            })
+            .pop()
+            .unwrap();
+        self.assert_canonical(&inv)?;
+        let product = self.mul(value, &inv)?;
+        self.loader
+            .assert_eq("Goldilocks inversion", &product, &self.loader.load_one())?;
+        Ok(inv)
+    }
+
+    fn ext_from_base(&self, value: &L::LoadedScalar) -> LoadedGoldilocksExt2<C, L> {
+        LoadedGoldilocksExt2 {
+            limbs: [value.clone(), self.loader.load_zero()],
+        }
+    }
+
+    fn ext_add(
+        &self,
+        lhs: &LoadedGoldilocksExt2<C, L>,
+        rhs: &LoadedGoldilocksExt2<C, L>,
+    ) -> Result<LoadedGoldilocksExt2<C, L>, Error> {
+        let [c0, c1] = [0, 1].map(|idx| self.add(&lhs.limbs[idx], &rhs.limbs[idx]));
+        Ok(LoadedGoldilocksExt2 { limbs: [c0?, c1?] })
+    }
+
+    fn ext_sub(
+        &self,
+        lhs: &LoadedGoldilocksExt2<C, L>,
+        rhs: &LoadedGoldilocksExt2<C, L>,
+    ) -> Result<LoadedGoldilocksExt2<C, L>, Error> {
+        let [c0, c1] = [0, 1].map(|idx| self.sub(&lhs.limbs[idx], &rhs.limbs[idx]));
+        Ok(LoadedGoldilocksExt2 { limbs: [c0?, c1?] })
+    }
+
+    fn ext_mul(
+        &self,
+        lhs: &LoadedGoldilocksExt2<C, L>,
+        rhs: &LoadedGoldilocksExt2<C, L>,
+    ) -> Result<LoadedGoldilocksExt2<C, L>, Error> {
+        let [a0, a1] = &lhs.limbs;
+        let [b0, b1] = &rhs.limbs;
+        // Both are less than $8p^2 < 2^{131}$.
+        let c0 = self.loader.sum_products_with_coeff(&[
+            (C::Scalar::ONE, a0, b0),
+            (GoldilocksExt2::W.to_scalar(), a1, b1),
+        ]);
+        let c1 = self.loader.sum_products(&[(a0, b1), (a1, b0)]);
+        Ok(LoadedGoldilocksExt2 {
+            limbs: [self.reduce(&c0, 131)?, self.reduce(&c1, 131)?],
+        })
+    }
+
+    fn ext_mul_base(
+        &self,
+        lhs: &LoadedGoldilocksExt2<C, L>,
+        rhs: &L::LoadedScalar,
+    ) -> Result<LoadedGoldilocksExt2<C, L>, Error> {
+        let [c0, c1] = [0, 1].map(|idx| self.mul(&lhs.limbs[idx], rhs));
+        Ok(LoadedGoldilocksExt2 { limbs: [c0?, c1?] })
+    }
+
+    fn ext_mul_const(
+        &self,
+        lhs: &LoadedGoldilocksExt2<C, L>,
+        rhs: Goldilocks,
+    ) -> Result<LoadedGoldilocksExt2<C, L>, Error> {
+        let [c0, c1] = [0, 1].map(|idx| {
+            let value = self
+                .loader
+                .sum_with_coeff(&[(rhs.to_scalar(), &lhs.limbs[idx])]);
+            self.reduce(&value, 128)
+        });
+        Ok(LoadedGoldilocksExt2 { limbs: [c0?, c1?] })
+    }
+
+    fn ext_invert(
+        &self,
+        value: &LoadedGoldilocksExt2<C, L>,
+    ) -> Result<LoadedGoldilocksExt2<C, L>, Error> {
+        let (c0, c1) = self
+            .loader
+            .witness(&[&value.limbs[0], &value.limbs[1]], 2, |values| {
+                let value = GoldilocksExt2([
+                    Goldilocks::from_scalar(values[0]),
+                    Goldilocks::from_scalar(values[1]),
+                ]);
+                let inv = value.invert().unwrap_or_default();
+                inv.0.iter().map(|limb| limb.to_scalar()).collect()
+            })
+            .into_iter()
+            .collect_tuple()
+            .unwrap();
+        self.assert_canonical(&c0)?;
+        self.assert_canonical(&c1)?;
+        let inv = LoadedGoldilocksExt2 { limbs: [c0, c1] };
+        let product = self.ext_mul(value, &inv)?;
+        self.ext_assert_eq(
+            "GoldilocksExt2 inversion",
+            &product,
+            &self.load_ext_const(GoldilocksExt2::ONE),
+        )?;
+        Ok(inv)
+    }
+
+    /// Returns `lhs` if `bit` is `0`, otherwise `rhs`, where `bit` is assumed
+    /// to be boolean, so the output is canonical without reduction.
+    fn ext_select(
+        &self,
+        bit: &L::LoadedScalar,
+        lhs: &LoadedGoldilocksExt2<C, L>,
+        rhs: &LoadedGoldilocksExt2<C, L>,
+    ) -> LoadedGoldilocksExt2<C, L> {
+        LoadedGoldilocksExt2 {
+            limbs: [0, 1].map(|idx| {
+                let [lhs, rhs] = [&lhs.limbs[idx], &rhs.limbs[idx]];
+                lhs.clone() + bit.clone() * &(rhs.clone() - lhs)
+            }),
+        }
+    }
+
+    fn ext_assert_eq(
+        &self,
+        annotation: &str,
+        lhs: &LoadedGoldilocksExt2<C, L>,
+        rhs: &LoadedGoldilocksExt2<C, L>,
+    ) -> Result<(), Error> {
+        for (lhs, rhs) in lhs.limbs.iter().zip(rhs.limbs.iter()) {
+            self.loader.assert_eq(annotation, lhs, rhs)?;
+        }
+        Ok(())
+    }
+
+    /// Returns limbs packed into a scalar in the same way as [`Limbs::pack`].
+    fn ext_pack(&self, value: &LoadedGoldilocksExt2<C, L>) -> L::LoadedScalar {
+        let two_to_64 = C::Scalar::from(u64::MAX) + C::Scalar::ONE;
+        self.loader.sum_with_coeff(&[
+            (C::Scalar::ONE, &value.limbs[0]),
+            (two_to_64, &value.limbs[1]),
+        ])
+    }
+
+    /// Returns point of leaf at `index` in the domain of `round`, which is
+    /// $g^{2^r} \omega^{2^r \cdot i}$ where $g$ is the shift of coset.
+    fn domain_point<H>(
+        &self,
+        vk: &FriVerifyingKey<H>,
+        index: &[L::LoadedScalar],
+        round: usize,
+    ) -> Result<L::LoadedScalar, Error> {
+        let shift = Goldilocks::MULTIPLICATIVE_GENERATOR.pow(1 << round);
+        let omega = Goldilocks::root_of_unity(vk.log_lde_size());
+        // Each factor is either `1` or a power of `omega`, so it's canonical.
+        let factors = iter::successors(Some(omega), |omega| Some(omega.square()))
+            .skip(round)
+            .zip(index[..vk.depth() - round].iter())
+            .map(|(omega, bit)| {
+                self.loader.sum_with_coeff_and_const(
+                    &[((omega - Goldilocks::ONE).to_scalar(), bit)],
+                    C::Scalar::ONE,
+                )
+            })
+            .collect_vec();
+        factors
+            .iter()
+            .try_fold(self.load_const(shift), |acc, factor| self.mul(&acc, factor))
+    }
+
+    /// Returns evaluation of folded polynomial at $x^2$ given `evals` at $x$
+    /// and $-x$, which is $(f(x) + f(-x)) / 2 + \alpha (f(x) - f(-x)) / 2x$.
+    fn fold(
+        &self,
+        evals: &[LoadedGoldilocksExt2<C, L>; 2],
+        alpha: &LoadedGoldilocksExt2<C, L>,
+        x_inv: &L::LoadedScalar,
+    ) -> Result<LoadedGoldilocksExt2<C, L>, Error> {
+        let [lhs, rhs] = evals;
+        let odd = self.ext_mul_base(&self.ext_mul(alpha, &self.ext_sub(lhs, rhs)?)?, x_inv)?;
+        let sum = self.ext_add(&self.ext_add(lhs, rhs)?, &odd)?;
+        self.ext_mul_const(&sum, Goldilocks::TWO_INV)
+    }
+}
+
+/// FRI polynomial commitment scheme over Goldilocks field with Merkle tree
+/// hashed by `H` over the scalar field.
+///
+/// It doesn't implement [`crate::pcs::PolynomialCommitmentScheme`] since
+/// shifts and evaluations of queries are not in the scalar field, but it
+/// takes the same [`FriVerifyingKey`] as [`crate::pcs::fri::Fri`].
+#[derive(Clone, Debug)]
+pub struct GoldilocksFri<H>(PhantomData<H>);
+
+impl<H> GoldilocksFri<H> {
+    /// Verify `proof` of opening polynomials committed as Merkle `roots` at
+    /// `z` with `queries`, where `roots` are indexed by polynomial.
+    pub fn verify<C, L>(
+        vk: &FriVerifyingKey<H>,
+        roots: &[L::LoadedScalar],
+        z: &LoadedGoldilocksExt2<C, L>,
+        queries: &[Query<Goldilocks, LoadedGoldilocksExt2<C, L>>],
+        proof: &GoldilocksFriProof<C, L>,
+    ) -> Result<(), Error>
+    where
+        C: CurveAffine,
+        L: FriLoader<C>,
+        H: MerkleHasher<C::Scalar>,
+    {
+        let chip = GoldilocksChip::new(z.loader());
+
+        let polys = polys(queries);
+        let roots = polys
+            .iter()
+            .map(|&poly| {
+                roots.get(poly).ok_or_else(|| {
+                    Error::InvalidProtocol(format!("Missing Merkle root of polynomial {poly}"))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let poly_idx = |poly| polys.iter().position(|p| *p == poly).unwrap();
+
+        let shifts = queries.iter().fold(Vec::new(), |mut shifts, query| {
+            if !shifts.contains(&query.shift) {
+                shifts.push(query.shift);
+            }
+            shifts
+        });
+        let shift_idx = |shift| shifts.iter().position(|s| *s == shift).unwrap();
+        let z_shifts = shifts
+            .iter()
+            .map(|shift| chip.ext_mul_const(z, *shift))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut powers_of_beta = vec![chip.load_ext_const(GoldilocksExt2::ONE)];
+        for _ in 1..queries.len() {
+            let power = chip.ext_mul(powers_of_beta.last().unwrap(), &proof.beta)?;
+            powers_of_beta.push(power);
+        }
+
+        for (index, query_proof) in proof.indices.iter().zip(proof.queries.iter()) {
+            let xs = (0..vk.k)
+                .map(|round| chip.domain_point(vk, index, round))
+                .collect::<Result<Vec<_>, _>>()?;
+            let x_invs = xs
+                .iter()
+                .map(|x| chip.invert(x))
+                .collect::<Result<Vec<_>, _>>()?;
+            let neg_x = chip.sub(&chip.load_const(Goldilocks::ZERO), &xs[0])?;
+
+            for (opening, root) in query_proof.openings.iter().zip(roots.iter()) {
+                opening.verify(vk, index, root)?;
+            }
+            let deep_quotient = |x: &L::LoadedScalar, side: usize| {
+                let x = chip.ext_from_base(x);
+                let denom_invs = z_shifts
+                    .iter()
+                    .map(|z_shift| chip.ext_invert(&chip.ext_sub(&x, z_shift)?))
+                    .collect::<Result<Vec<_>, _>>()?;
+                queries.iter().zip(powers_of_beta.iter()).try_fold(
+                    chip.load_ext_const(GoldilocksExt2::ZERO),
+                    |acc, (query, power_of_beta)| {
+                        let eval = chip
+                            .ext_from_base(&query_proof.openings[poly_idx(query.poly)].evals[side]);
+                        let diff = chip.ext_sub(&eval, &query.eval)?;
+                        let term = chip.ext_mul(
+                            &chip.ext_mul(power_of_beta, &diff)?,
+                            &denom_invs[shift_idx(query.shift)],
+                        )?;
+                        chip.ext_add(&acc, &term)
+                    },
+                )
+            };
+            let mut eval = chip.fold(
+                &[deep_quotient(&xs[0], 0)?, deep_quotient(&neg_x, 1)?],
+                &proof.alphas[0],
+                &x_invs[0],
+            )?;
+
+            for (round, (opening, root)) in query_proof
+                .layers
+                .iter()
+                .zip(proof.roots.iter())
+                .enumerate()
+                .map(|(idx, value)| (idx + 1, value))
+            {
+                let depth = vk.depth() - round;
+                opening.verify(vk, &index[..depth], root)?;
+                let [lhs, rhs] = &opening.evals;
+                let selected = chip.ext_select(&index[depth], lhs, rhs);
+                chip.ext_assert_eq("FRI folding", &selected, &eval)?;
+                eval = chip.fold(&opening.evals, &proof.alphas[round], &x_invs[round])?;
+            }
+
+            chip.ext_assert_eq("FRI remainder", &eval, &proof.remainder)?;
+        }
+
+        Ok(())
+    }
+
+    /// Commit polynomial `poly` in coefficient form.
+    pub fn commit<F>(vk: &FriVerifyingKey<H>, poly: &[Goldilocks]) -> GoldilocksFriCommitment<F>
+    where
+        F: PrimeField,
+        H: MerkleHasher<F>,
+    {
+        assert!(poly.len() <= 1 << vk.k);
+
+        let evals = coset(vk.log_lde_size())
+            .map(|x| {
+                poly.iter()
+                    .rev()
+                    .fold(Goldilocks::ZERO, |acc, coeff| acc * x + *coeff)
+            })
+            .collect_vec();
+        GoldilocksFriCommitment::new(&vk.hasher, evals)
+    }
+
+    /// Create a proof to open polynomials of `commitments` at `z` with
+    /// `queries`, where evaluations are assumed to be written into transcript
+    /// already.
+    pub fn create_proof<C, T>(
+        vk: &FriVerifyingKey<H>,
+        commitments: &[&GoldilocksFriCommitment<C::Scalar>],
+        z: &GoldilocksExt2,
+        queries: &[Query<Goldilocks, GoldilocksExt2>],
+        transcript: &mut T,
+    ) -> Result<(), Error>
+    where
+        C: CurveAffine,
+        T: TranscriptWrite<C>,
+        H: MerkleHasher<C::Scalar>,
+    {
+        let lde_size = 1 << vk.log_lde_size();
+        let beta = GoldilocksExt2::from_challenge(transcript.squeeze_challenge());
+
+        let deep_quotient = {
+            let xs = coset(vk.log_lde_size()).collect_vec();
+            let mut deep_quotient = vec![GoldilocksExt2::ZERO; lde_size];
+            let mut power_of_beta = GoldilocksExt2::ONE;
+            for query in queries {
+                let z_shift = z.mul_base(query.shift);
+                let evals = &commitments[query.poly].evals;
+                for ((quotient, eval), x) in
+                    deep_quotient.iter_mut().zip(evals.iter()).zip(xs.iter())
+                {
+                    let denom = GoldilocksExt2::from_base(*x) - z_shift;
+                    *quotient = *quotient
+                        + power_of_beta
+                            * (GoldilocksExt2::from_base(*eval) - query.eval)
+                            * denom.invert().unwrap_or_default();
+                }
+                power_of_beta = power_of_beta * beta;
+            }
+            deep_quotient
+        };
+
+        let mut alpha = GoldilocksExt2::from_challenge(transcript.squeeze_challenge());
+        let mut layers = Vec::<GoldilocksFriCommitment<_, _>>::with_capacity(vk.k - 1);
+        let mut shift = Goldilocks::MULTIPLICATIVE_GENERATOR;
+        let mut omega = Goldilocks::root_of_unity(vk.log_lde_size());
+        for _ in 1..vk.k {
+            let evals = layers.last().map_or(&deep_quotient, |layer| &layer.evals);
+            let layer =
+                GoldilocksFriCommitment::new(&vk.hasher, fold_evals(evals, &alpha, shift, omega));
+            transcript.write_scalar(layer.root())?;
+            alpha = GoldilocksExt2::from_challenge(transcript.squeeze_challenge());
+            layers.push(layer);
+            shift = shift.square();
+            omega = omega.square();
+        }
+        let evals = layers.last().map_or(&deep_quotient, |layer| &layer.evals);
+        let remainder = fold_evals(evals, &alpha, shift, omega);
+        for limb in remainder[0].limbs() {
+            transcript.write_scalar(limb.to_scalar())?;
+        }
+
+        let query_challenges = transcript.squeeze_n_challenges(vk.num_query);
+        for challenge in query_challenges {
+            let big = fe_to_big(challenge);
+            let index = (0..vk.depth()).fold(0, |index, idx| {
+                index | ((big.bit(idx as u64) as usize) << idx)
+            });
+            for poly in polys(queries) {
+                commitments[poly].open(index, transcript)?;
+            }
+            for (round, layer) in layers.iter().enumerate() {
+                layer.open(index & ((1 << (vk.depth() - round - 1)) - 1), transcript)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Evaluations in [`Goldilocks`] or [`GoldilocksExt2`] on low-degree-extension
+/// domain with their Merkle tree, which are kept by prover to open the
+/// committed polynomial.
+#[derive(Clone, Debug)]
+pub struct GoldilocksFriCommitment<F: PrimeField, T = Goldilocks> {
+    evals: Vec<T>,
+    tree: MerkleTree<F>,
+}
+
+impl<F: PrimeField, T> GoldilocksFriCommitment<F, T> {
+    /// Returns Merkle root as the commitment.
+    pub fn root(&self) -> F {
+        self.tree.root()
+    }
+}
+
+impl<F: PrimeField, T: Limbs> GoldilocksFriCommitment<F, T> {
+    fn new(hasher: &impl MerkleHasher<F>, evals: Vec<T>) -> Self {
+        let tree = MerkleTree::new(hasher, &evals.iter().map(Limbs::pack).collect_vec());
+        Self { evals, tree }
+    }
+
+    fn open<C, W>(&self, leaf: usize, transcript: &mut W) -> Result<(), Error>
+    where
+        C: CurveAffine<Scalar = F>,
+        W: TranscriptWrite<C>,
+    {
+        for eval in [self.evals[leaf], self.evals[leaf + self.evals.len() / 2]] {
+            for limb in eval.limbs() {
+                transcript.write_scalar(limb.to_scalar())?;
+            }
+        }
+        for sibling in self.tree.siblings(leaf) {
+            transcript.write_scalar(sibling)?;
+        }
+        Ok(())
+    }
+}
+
+/// Structured proof of [`GoldilocksFri`].
+#[derive(Clone, Debug)]
+pub struct GoldilocksFriProof<C, L>
+where
+    C: CurveAffine,
+    L: Loader<C>,
+{
+    beta: LoadedGoldilocksExt2<C, L>,
+    alphas: Vec<LoadedGoldilocksExt2<C, L>>,
+    roots: Vec<L::LoadedScalar>,
+    remainder: LoadedGoldilocksExt2<C, L>,
+    indices: Vec<Vec<L::LoadedScalar>>,
+    queries: Vec<GoldilocksFriQueryProof<C, L>>,
+}
+
+impl<C, L> GoldilocksFriProof<C, L>
+where
+    C: CurveAffine,
+    L: FriLoader<C>,
+{
+    /// Read [`GoldilocksFriProof`] of `num_poly` polynomials from transcript.
+    ///
+    /// # Panic
+    ///
+    /// If the scalar field is not larger than $2^{133}$.
+    pub fn read<T, H>(
+        vk: &FriVerifyingKey<H>,
+        num_poly: usize,
+        transcript: &mut T,
+    ) -> Result<Self, Error>
+    where
+        T: TranscriptRead<C, L>,
+    {
+        assert!(C::Scalar::NUM_BITS > 133);
+
+        let beta = LoadedGoldilocksExt2::squeeze(transcript)?;
+        let mut alphas = vec![LoadedGoldilocksExt2::squeeze(transcript)?];
+        let mut roots = Vec::with_capacity(vk.k - 1);
+        for _ in 1..vk.k {
+            roots.push(transcript.read_scalar()?);
+            alphas.push(LoadedGoldilocksExt2::squeeze(transcript)?);
+        }
+        let remainder = LoadedGoldilocksExt2::read(transcript)?;
+        let indices = transcript
+            .squeeze_n_challenges(vk.num_query)
+            .iter()
+            .map(|challenge| {
+                let mut bits = to_canonical_bits::<C, _>(challenge.loader(), challenge)?;
+                bits.truncate(vk.depth());
+                Ok(bits)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let queries = (0..vk.num_query)
+            .map(|_| {
+                let openings = (0..num_poly)
+                    .map(|_| {
+                        let opening = MerkleOpening::read(vk.depth(), transcript)?;
+                        let chip = GoldilocksChip::new(opening.evals[0].loader());
+                        for eval in opening.evals.iter() {
+                            chip.assert_canonical(eval)?;
+                        }
+                        Ok(opening)
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+                let layers = (1..vk.k)
+                    .map(|round| GoldilocksMerkleOpening::read(vk.depth() - round, transcript))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(GoldilocksFriQueryProof { openings, layers })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(Self {
+            beta,
+            alphas,
+            roots,
+            remainder,
+            indices,
+            queries,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+struct GoldilocksFriQueryProof<C, L>
+where
+    C: CurveAffine,
+    L: Loader<C>,
+{
+    openings: Vec<MerkleOpening<C, L>>,
+    layers: Vec<GoldilocksMerkleOpening<C, L>>,
+}
+
+#[derive(Clone, Debug)]
+struct GoldilocksMerkleOpening<C, L>
+where
+    C: CurveAffine,
+    L: Loader<C>,
+{
+    evals: [LoadedGoldilocksExt2<C, L>; 2],
+    siblings: Vec<L::LoadedScalar>,
+}
+
+impl<C, L> GoldilocksMerkleOpening<C, L>
+where
+    C: CurveAffine,
+    L: FriLoader<C>,
+{
+    fn read<T>(depth: usize, transcript: &mut T) -> Result<Self, Error>
+    where
+        T: TranscriptRead<C, L>,
+    {
+        let evals = [
+            LoadedGoldilocksExt2::read(transcript)?,
+            LoadedGoldilocksExt2::read(transcript)?,
+        ];
+        let siblings = transcript.read_n_scalars(depth)?;
+        Ok(Self { evals, siblings })
+    }
+
+    /// Verify Merkle path from leaf at `index` in little-endian bits to
+    /// `root`, where the leaf is hash of packed evaluations.
+    fn verify<H>(
+        &self,
+        vk: &FriVerifyingKey<H>,
+        index: &[L::LoadedScalar],
+        root: &L::LoadedScalar,
+    ) -> Result<(), Error>
+    where
+        H: MerkleHasher<C::Scalar>,
+    {
+        let chip = GoldilocksChip::new(root.loader());
+        let leaf = vk.hasher.hash(
+            &chip.ext_pack(&self.evals[0]),
+            &chip.ext_pack(&self.evals[1]),
+        );
+        verify_merkle_path::<C::Scalar, _, _>(&vk.hasher, leaf, index, &self.siblings, root)
+    }
+}
+
+/// Returns low-degree-extension coset of size $2^{log\_size}$ shifted by
+/// [`Goldilocks::MULTIPLICATIVE_GENERATOR`].
+fn coset(log_size: usize) -> impl Iterator<Item = Goldilocks> {
+    let omega = Goldilocks::root_of_unity(log_size);
+    iter::successors(Some(Goldilocks::MULTIPLICATIVE_GENERATOR), move |x| {
+        Some(*x * omega)
+    })
+    .take(1 << log_size)
+}
+
+/// Fold evaluations on coset of `shift` with generator `omega` into
+/// evaluations on its square.
+fn fold_evals(
+    evals: &[GoldilocksExt2],
+    alpha: &GoldilocksExt2,
+    shift: Goldilocks,
+    omega: Goldilocks,
+) -> Vec<GoldilocksExt2> {
+    let (lhs, rhs) = evals.split_at(evals.len() / 2);
+    lhs.iter()
+        .zip(rhs.iter())
+        .zip(iter::successors(Some(shift), |x| Some(*x * omega)))
+        .map(|((lhs, rhs), x)| {
+            let odd = (*alpha * (*lhs - *rhs)).mul_base(x.invert().unwrap());
+            (*lhs + *rhs + odd).mul_base(Goldilocks::TWO_INV)
+        })
+        .collect()
+}
+
+#[cfg(all(test, feature = "loader_halo2", feature = "system_halo2"))]
+mod test {
+    use crate::{
+        loader::{self, native::NativeLoader},
+        pcs::{
+            fri::{
+                FriLoader, FriVerifyingKey, Goldilocks, GoldilocksExt2, GoldilocksFri,
+                GoldilocksFriProof, LoadedGoldilocksExt2,
+            },
+            Query,
+        },
+        system::{self, halo2::test::MainGateWithRangeConfig},
+        util::{
+            arithmetic::PrimeField,
+            hash::Poseidon2Spec,
+            transcript::{Transcript, TranscriptRead, TranscriptWrite},
+            Itertools,
+        },
+        Error,
+    };
+    use halo2_curves::bn256::{Fq, Fr, G1Affine};
+    use halo2_proofs::{
+        circuit::{floor_planner::V1, Layouter, Value},
+        dev::MockProver,
+        plonk::{self, Circuit, ConstraintSystem},
+    };
+    use halo2_wrong_ecc::{
+        integer::rns::Rns,
+        maingate::{RangeInstructions, RegionCtx},
+    };
+    use rand_chacha::{
+        rand_core::{RngCore, SeedableRng},
+        ChaCha20Rng,
+    };
+    use std::{iter, rc::Rc};
+
+    const K: u32 = 20;
+    const LIMBS: usize = 4;
+    const BITS: usize = 68;
+    const T: usize = 5;
+    const RATE: usize = 4;
+    const R_F: usize = 8;
+    const R_P: usize = 60;
+
+    type BaseFieldEccChip = halo2_wrong_ecc::BaseFieldEccChip<G1Affine, LIMBS, BITS>;
+    type Halo2Loader<'a> = loader::halo2::Halo2Loader<'a, G1Affine, BaseFieldEccChip>;
+    type PoseidonTranscript<L, S> =
+        system::halo2::transcript::halo2::PoseidonTranscript<G1Affine, L, S, T, RATE, R_F, R_P>;
+    type Pcs = GoldilocksFri<Poseidon2Spec<Fr, 3>>;
+
+    fn vk() -> FriVerifyingKey<Poseidon2Spec<Fr, 3>> {
+        FriVerifyingKey::new(3, 2, 4, Poseidon2Spec::new(8, 56))
+    }
+
+    fn queries() -> Vec<Query<Goldilocks>> {
+        let omega = Goldilocks::root_of_unity(vk().k);
+        vec![
+            Query::new(0, Goldilocks::ONE),
+            Query::new(0, omega),
+            Query::new(1, Goldilocks::ONE),
+        ]
+    }
+
+    fn evaluate(poly: &[Goldilocks], x: GoldilocksExt2) -> GoldilocksExt2 {
+        poly.iter().rev().fold(GoldilocksExt2::ZERO, |acc, coeff| {
+            acc * x + GoldilocksExt2::from_base(*coeff)
+        })
+    }
+
+    fn create_proof(valid: bool) -> Vec<u8> {
+        let vk = vk();
+        let mut rng = ChaCha20Rng::from_seed(Default::default());
+        let polys = iter::repeat_with(|| {
+            iter::repeat_with(|| Goldilocks::new(rng.next_u64()))
+                .take(1 << vk.k)
+                .collect_vec()
+        })
+        .take(2)
+        .collect_vec();
+        let commitments = polys
+            .iter()
+            .map(|poly| Pcs::commit(&vk, poly))
+            .collect_vec();
+
+        let mut transcript = PoseidonTranscript::<NativeLoader, _>::new(Vec::new());
+        for commitment in commitments.iter() {
+            transcript.write_scalar(commitment.root()).unwrap();
+        }
+        let z = GoldilocksExt2::from_challenge(transcript.squeeze_challenge());
+        let mut queries = queries()
+            .into_iter()
+            .map(|query| {
+                let eval = evaluate(&polys[query.poly], z.mul_base(query.shift));
+                query.with_evaluation(eval)
+            })
+            .collect_vec();
+        if !valid {
+            queries[0].eval = queries[0].eval + GoldilocksExt2::ONE;
+        }
+        for query in queries.iter() {
+            for limb in query.eval.0 {
+                transcript.write_scalar(Fr::from(limb.value())).unwrap();
+            }
+        }
+        Pcs::create_proof(
+            &vk,
+            &commitments.iter().collect_vec(),
+            &z,
+            &queries,
+            &mut transcript,
+        )
+        .unwrap();
+        transcript.finalize()
+    }
+
+    fn verify<L>(transcript: &mut impl TranscriptRead<G1Affine, L>) -> Result<(), Error>
+    where
+        L: FriLoader<G1Affine>,
+    {
+        let vk = vk();
+        let roots = transcript.read_n_scalars(2)?;
+        let z = LoadedGoldilocksExt2::squeeze(transcript)?;
+        let queries = queries()
+            .into_iter()
+            .map(|query| Ok(query.with_evaluation(LoadedGoldilocksExt2::read(transcript)?)))
+            .collect::<Result<Vec<_>, Error>>()?;
+        let proof = GoldilocksFriProof::read(&vk, 2, transcript)?;
+        Pcs::verify(&vk, &roots, &z, &queries, &proof)
+    }
+
+    #[derive(Clone)]
+    struct GoldilocksFriCircuit {
+        proof: Value<Vec<u8>>,
+    }
+
+    impl Circuit<Fr> for GoldilocksFriCircuit {
+        type Config = MainGateWithRangeConfig;
+        type FloorPlanner = V1;
+        #[cfg(feature = "halo2_circuit_params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                proof: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            MainGateWithRangeConfig::configure(
+                meta,
+                vec![BITS / LIMBS],
+                Rns::<Fq, Fr, LIMBS, BITS>::construct().overflow_lengths(),
+            )
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), plonk::Error> {
+            config.range_chip().load_table(&mut layouter)?;
+
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let loader = Halo2Loader::new(config.ecc_chip(), RegionCtx::new(region, 0));
+                    let mut transcript = PoseidonTranscript::<Rc<Halo2Loader>, _>::new(
+                        &loader,
+                        self.proof.as_ref().map(Vec::as_slice),
+                    );
+                    verify(&mut transcript).map_err(|_| plonk::Error::Synthesis)
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_goldilocks() {
+        let omega = Goldilocks::root_of_unity(Goldilocks::TWO_ADICITY);
+        assert_eq!(
+            omega.pow(1 << (Goldilocks::TWO_ADICITY - 1)),
+            -Goldilocks::ONE
+        );
+
+        let mut rng = ChaCha20Rng::from_seed(Default::default());
+        let value = GoldilocksExt2([
+            Goldilocks::new(rng.next_u64()),
+            Goldilocks::new(rng.next_u64()),
+        ]);
+        assert_eq!(value * value.invert().unwrap(), GoldilocksExt2::ONE);
+        assert_eq!(GoldilocksExt2::ZERO.invert(), None);
+    }
+
+    #[test]
+    fn test_goldilocks_fri() {
+        for (valid, proof) in [true, false].map(|valid| (valid, create_proof(valid))) {
+            let mut transcript = PoseidonTranscript::<NativeLoader, _>::new(proof.as_slice());
+            assert_eq!(verify(&mut transcript).is_ok(), valid);
+        }
+
+        // Evaluations have to be canonical
+        let mut proof = create_proof(true);
+        let offset = 32 * 2;
+        let eval = Fr::from_repr(proof[offset..offset + 32].try_into().unwrap()).unwrap()
+            + Fr::from(Goldilocks::MODULUS);
+        proof[offset..offset + 32].copy_from_slice(eval.to_repr().as_ref());
+        let mut transcript = PoseidonTranscript::<NativeLoader, _>::new(proof.as_slice());
+        assert!(verify(&mut transcript).is_err());
+    }
+
+    #[test]
+    #[ignore = "cause it takes several minutes to run"]
+    fn test_goldilocks_fri_halo2_loader() {
+        for valid in [true, false] {
+            let circuit = GoldilocksFriCircuit {
+                proof: Value::known(create_proof(valid)),
+            };
+            let prover = MockProver::run(K, &circuit, vec![Vec::new()]);
+            assert_eq!(
+                matches!(prover.map(|prover| prover.verify()), Ok(Ok(()))),
+                valid
+            );
+        }
+    }
+}
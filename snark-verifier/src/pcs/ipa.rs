@@ -26,7 +26,7 @@ mod multiopen;
 pub use accumulation::{IpaAs, IpaAsProof};
 pub use accumulator::IpaAccumulator;
 pub use decider::IpaDecidingKey;
-pub use multiopen::{Bgh19, Bgh19Proof};
+pub use multiopen::{Bdfg21, Bdfg21Proof, Bgh19, Bgh19Proof};
 
 /// Inner product argument polynomial commitment scheme.
 #[derive(Clone, Debug)]
@@ -1,3 +1,5 @@
+mod bdfg21;
 mod bgh19;
 
+pub use bdfg21::{Bdfg21, Bdfg21Proof};
 pub use bgh19::{Bgh19, Bgh19Proof};
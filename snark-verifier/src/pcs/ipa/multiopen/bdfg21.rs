@@ -0,0 +1,597 @@
+use crate::{
+    cost::{Cost, CostEstimation},
+    loader::{native::NativeLoader, LoadedScalar, Loader, ScalarLoader},
+    pcs::{
+        bdfg21::{query_set_coeffs, query_sets, query_sets_cost},
+        ipa::{Ipa, IpaAccumulator, IpaAs, IpaProof, IpaProvingKey, IpaSuccinctVerifyingKey},
+        PolynomialCommitmentScheme, Query,
+    },
+    util::{
+        arithmetic::{powers, CurveAffine, Field, PrimeField},
+        msm::Msm,
+        poly::Polynomial,
+        transcript::{TranscriptRead, TranscriptWrite},
+        Itertools,
+    },
+    Error,
+};
+use rand::Rng;
+
+/// Verifier of multi-open inner product argument in the style of SHPLONK.
+/// Notations are following <https://eprint.iacr.org/2020/081>.
+#[derive(Clone, Debug)]
+pub struct Bdfg21;
+
+impl<C> IpaAs<C, Bdfg21>
+where
+    C: CurveAffine,
+{
+    /// Create a proof of `queries` on `polys` at `z`, where `omegas` are the
+    /// randomizers of the commitments of `polys` if zero-knowledge is enabled.
+    pub fn create_proof<T, R>(
+        pk: &IpaProvingKey<C>,
+        polys: &[Polynomial<C::Scalar>],
+        omegas: Option<&[C::Scalar]>,
+        z: &C::Scalar,
+        queries: &[Query<C::Scalar>],
+        transcript: &mut T,
+        mut rng: R,
+    ) -> Result<IpaAccumulator<C, NativeLoader>, Error>
+    where
+        T: TranscriptWrite<C>,
+        R: Rng,
+    {
+        assert_eq!(pk.zk(), omegas.is_some());
+
+        let sets = query_sets(queries);
+        let roots = sets
+            .iter()
+            .map(|set| set.shifts.iter().map(|shift| *z * shift).collect_vec())
+            .collect_vec();
+
+        let mu = transcript.squeeze_challenge();
+        let gamma = transcript.squeeze_challenge();
+        let powers_of_mu = powers(mu)
+            .take(sets.iter().map(|set| set.polys.len()).max().unwrap())
+            .collect_vec();
+        let powers_of_gamma = powers(gamma).take(sets.len()).collect_vec();
+
+        let (set_polys, set_omegas): (Vec<_>, Vec<_>) = sets
+            .iter()
+            .map(|set| {
+                let poly = set
+                    .polys
+                    .iter()
+                    .zip(powers_of_mu.iter())
+                    .map(|(poly, power_of_mu)| polys[*poly].clone() * *power_of_mu)
+                    .sum::<Polynomial<_>>();
+                let omega = omegas.map(|omegas| {
+                    set.polys
+                        .iter()
+                        .zip(powers_of_mu.iter())
+                        .map(|(poly, power_of_mu)| omegas[*poly] * power_of_mu)
+                        .sum::<C::Scalar>()
+                });
+                (poly, omega)
+            })
+            .unzip();
+        let quotients = set_polys
+            .iter()
+            .zip(roots.iter())
+            .map(|(poly, roots)| vanishing_quotient(poly, roots))
+            .collect_vec();
+
+        let h = quotients
+            .iter()
+            .zip(powers_of_gamma.iter())
+            .map(|(quotient, power_of_gamma)| quotient.clone() * *power_of_gamma)
+            .sum::<Polynomial<_>>();
+        let omega_w = pk.zk().then(|| C::Scalar::random(&mut rng));
+        transcript.write_ec_point(pk.commit(&h, omega_w))?;
+
+        let z_prime = transcript.squeeze_challenge();
+
+        let z_s = roots
+            .iter()
+            .map(|roots| {
+                roots
+                    .iter()
+                    .map(|root| z_prime - root)
+                    .product::<C::Scalar>()
+            })
+            .collect_vec();
+        let coeffs = z_s
+            .iter()
+            .zip(powers_of_gamma.iter())
+            .map(|(z_s_j, power_of_gamma)| z_s[0] * Field::invert(z_s_j).unwrap() * power_of_gamma)
+            .collect_vec();
+
+        let l = set_polys
+            .iter()
+            .zip(quotients.iter())
+            .zip(z_s.iter())
+            .zip(coeffs.iter())
+            .map(|(((poly, quotient), z_s_j), coeff)| {
+                let r_eval = poly.evaluate(z_prime) - quotient.evaluate(z_prime) * z_s_j;
+                (poly.clone() - r_eval) * *coeff
+            })
+            .sum::<Polynomial<_>>()
+            - &(h * z_s[0]);
+        let omega_l = omega_w.map(|omega_w| {
+            set_omegas
+                .iter()
+                .zip(coeffs.iter())
+                .map(|(omega, coeff)| omega.unwrap() * coeff)
+                .sum::<C::Scalar>()
+                - omega_w * z_s[0]
+        });
+
+        Ipa::create_proof(pk, &l.to_vec(), &z_prime, omega_l.as_ref(), transcript, rng)
+    }
+}
+
+impl<C, L> PolynomialCommitmentScheme<C, L> for IpaAs<C, Bdfg21>
+where
+    C: CurveAffine,
+    L: Loader<C>,
+{
+    type VerifyingKey = IpaSuccinctVerifyingKey<C>;
+    type Proof = Bdfg21Proof<C, L>;
+    type Output = IpaAccumulator<C, L>;
+
+    fn read_proof<T>(
+        svk: &Self::VerifyingKey,
+        _: &[Query<C::Scalar>],
+        transcript: &mut T,
+    ) -> Result<Self::Proof, Error>
+    where
+        T: TranscriptRead<C, L>,
+    {
+        Bdfg21Proof::read(svk, transcript)
+    }
+
+    fn verify(
+        svk: &Self::VerifyingKey,
+        commitments: &[Msm<C, L>],
+        z: &L::LoadedScalar,
+        queries: &[Query<C::Scalar, L::LoadedScalar>],
+        proof: &Self::Proof,
+    ) -> Result<Self::Output, Error> {
+        let loader = z.loader();
+        let g = loader.ec_point_load_const(&svk.g);
+
+        // Multiopen
+        let sets = query_sets(queries);
+        let f = {
+            let coeffs = query_set_coeffs(&sets, z, &proof.z_prime);
+
+            let powers_of_mu = proof
+                .mu
+                .powers(sets.iter().map(|set| set.polys.len()).max().unwrap());
+            let msms = sets
+                .iter()
+                .zip(coeffs.iter())
+                .map(|(set, coeff)| set.msm(coeff, commitments, &powers_of_mu));
+
+            let (mut msm, constant) = (msms
+                .zip(proof.gamma.powers(sets.len()).into_iter())
+                .map(|(msm, power_of_gamma)| msm * &power_of_gamma)
+                .sum::<Msm<_, _>>()
+                - Msm::base(&proof.w) * &coeffs[0].z_s)
+                .split();
+            if let Some(constant) = constant {
+                msm += Msm::base(&g) * &constant;
+            }
+            msm
+        };
+
+        // IPA
+        Ipa::succinct_verify(svk, &f, &proof.z_prime, &loader.load_zero(), &proof.ipa)
+    }
+}
+
+/// Structured proof of [`Bdfg21`].
+#[derive(Clone, Debug)]
+pub struct Bdfg21Proof<C, L>
+where
+    C: CurveAffine,
+    L: Loader<C>,
+{
+    // Multiopen
+    mu: L::LoadedScalar,
+    gamma: L::LoadedScalar,
+    w: L::LoadedEcPoint,
+    z_prime: L::LoadedScalar,
+    // IPA
+    ipa: IpaProof<C, L>,
+}
+
+impl<C, L> Bdfg21Proof<C, L>
+where
+    C: CurveAffine,
+    L: Loader<C>,
+{
+    fn read<T: TranscriptRead<C, L>>(
+        svk: &IpaSuccinctVerifyingKey<C>,
+        transcript: &mut T,
+    ) -> Result<Self, Error> {
+        // Multiopen
+        let mu = transcript.squeeze_challenge();
+        let gamma = transcript.squeeze_challenge();
+        let w = transcript.read_ec_point()?;
+        let z_prime = transcript.squeeze_challenge();
+        // IPA
+        let ipa = IpaProof::read(svk, transcript)?;
+        Ok(Bdfg21Proof {
+            mu,
+            gamma,
+            w,
+            z_prime,
+            ipa,
+        })
+    }
+}
+
+/// Returns the quotient of `poly` divided by $\prod_i (X - \text{roots}_i)$
+/// with the remainder discarded, padded to the same length.
+fn vanishing_quotient<F: Field>(poly: &Polynomial<F>, roots: &[F]) -> Polynomial<F> {
+    let mut coeffs = poly.iter().cloned().collect_vec();
+    for root in roots {
+        let mut quotient = vec![F::ZERO; coeffs.len()];
+        let mut acc = F::ZERO;
+        for (idx, coeff) in coeffs.iter().enumerate().skip(1).rev() {
+            acc = *coeff + acc * root;
+            quotient[idx - 1] = acc;
+        }
+        coeffs = quotient;
+    }
+    Polynomial::new(coeffs)
+}
+
+impl<C> CostEstimation<C> for IpaAs<C, Bdfg21>
+where
+    C: CurveAffine,
+    C::Scalar: PrimeField + Ord,
+{
+    type Input = Vec<Query<C::Scalar>>;
+
+    /// Estimate the cost of multi-opening `queries`, excluding the succinct
+    /// check of the inner product argument, which costs the same as the one
+    /// of [`Bgh19`](crate::pcs::ipa::Bgh19) on the same domain.
+    fn estimate_cost(queries: &Vec<Query<C::Scalar>>) -> Cost {
+        query_sets_cost(queries)
+            + Cost {
+                num_commitment: 1,
+                num_squeeze: 3,
+                ..Default::default()
+            }
+    }
+}
+
+#[cfg(all(test, feature = "system_halo2"))]
+mod test {
+    use crate::{
+        cost::CostEstimation,
+        loader::native::NativeLoader,
+        pcs::{
+            ipa::{Bdfg21, Bgh19, IpaAccumulator, IpaAs, IpaProvingKey},
+            AccumulationDecider, PolynomialCommitmentScheme, Query,
+        },
+        util::{
+            arithmetic::{Field, Rotation},
+            msm::Msm,
+            poly::Polynomial,
+            transcript::TranscriptWrite,
+            Itertools,
+        },
+    };
+    use halo2_curves::pasta::pallas;
+    use halo2_proofs::transcript::{
+        Blake2bRead, Blake2bWrite, TranscriptReadBuffer, TranscriptWriterBuffer,
+    };
+    use rand::{rngs::OsRng, Rng};
+
+    type As = IpaAs<pallas::Affine, Bdfg21>;
+
+    struct Instance {
+        commitments: Vec<pallas::Affine>,
+        z: pallas::Scalar,
+        queries: Vec<Query<pallas::Scalar>>,
+        evals: Vec<pallas::Scalar>,
+    }
+
+    impl Instance {
+        fn evaluated_queries(&self) -> Vec<Query<pallas::Scalar, pallas::Scalar>> {
+            self.queries
+                .iter()
+                .zip(self.evals.iter())
+                .map(|(query, eval)| query.clone().with_evaluation(*eval))
+                .collect()
+        }
+    }
+
+    fn queries(pk: &IpaProvingKey<pallas::Affine>) -> Vec<Query<pallas::Scalar>> {
+        [
+            (0, 0),
+            (0, 1),
+            (1, 0),
+            (1, 1),
+            (2, 0),
+            (3, -1),
+            (3, 0),
+            (3, 1),
+            (4, 0),
+        ]
+        .into_iter()
+        .map(|(poly, rotation)| {
+            Query::new(
+                poly,
+                pk.domain
+                    .rotate_scalar(pallas::Scalar::ONE, Rotation(rotation)),
+            )
+        })
+        .collect_vec()
+    }
+
+    fn create_instance<T, R>(
+        pk: &IpaProvingKey<pallas::Affine>,
+        transcript: &mut T,
+        mut rng: R,
+    ) -> Instance
+    where
+        T: TranscriptWrite<pallas::Affine>,
+        R: Rng,
+    {
+        let num_poly = 5;
+        let polys = (0..num_poly)
+            .map(|_| Polynomial::<pallas::Scalar>::rand(pk.domain.n, &mut rng))
+            .collect_vec();
+        let omegas = pk.zk().then(|| {
+            (0..num_poly)
+                .map(|_| pallas::Scalar::random(&mut rng))
+                .collect_vec()
+        });
+        let commitments = polys
+            .iter()
+            .enumerate()
+            .map(|(idx, poly)| pk.commit(poly, omegas.as_ref().map(|omegas| omegas[idx])))
+            .collect_vec();
+        let z = pallas::Scalar::random(&mut rng);
+        let queries = queries(pk);
+        let evals = queries
+            .iter()
+            .map(|query| polys[query.poly].evaluate(z * query.shift))
+            .collect_vec();
+
+        As::create_proof(
+            pk,
+            &polys,
+            omegas.as_deref(),
+            &z,
+            &queries,
+            transcript,
+            &mut rng,
+        )
+        .unwrap();
+
+        Instance {
+            commitments,
+            z,
+            queries,
+            evals,
+        }
+    }
+
+    fn verify_native(
+        pk: &IpaProvingKey<pallas::Affine>,
+        instance: &Instance,
+        proof: &[u8],
+    ) -> Result<IpaAccumulator<pallas::Affine, NativeLoader>, crate::Error> {
+        let svk = pk.svk();
+        let mut transcript = Blake2bRead::init(proof);
+        let proof = As::read_proof(&svk, &instance.queries, &mut transcript)?;
+        As::verify(
+            &svk,
+            &instance.commitments.iter().map(Msm::base).collect_vec(),
+            &instance.z,
+            &instance.evaluated_queries(),
+            &proof,
+        )
+    }
+
+    #[test]
+    fn test_ipa_bdfg21() {
+        let k = 8;
+        let mut rng = OsRng;
+
+        for zk in [false, true] {
+            let pk = IpaProvingKey::<pallas::Affine>::rand(k, zk, &mut rng);
+            let mut transcript = Blake2bWrite::init(Vec::new());
+            let mut instance = create_instance(&pk, &mut transcript, &mut rng);
+            let proof = transcript.finalize();
+
+            let accumulator = verify_native(&pk, &instance, &proof).unwrap();
+            assert!(As::decide(&pk.dk(), accumulator).is_ok());
+
+            instance.evals[3] += pallas::Scalar::ONE;
+            assert!(verify_native(&pk, &instance, &proof).is_err());
+        }
+    }
+
+    #[test]
+    fn test_ipa_bdfg21_cost() {
+        let pk = IpaProvingKey::<pallas::Affine>::rand(8, true, OsRng);
+        let queries = queries(&pk);
+        let bdfg21 = As::estimate_cost(&queries);
+        let bgh19 = IpaAs::<pallas::Affine, Bgh19>::estimate_cost(&queries);
+
+        // Both send a single commitment before the inner product argument, but
+        // Bgh19 also sends an evaluation and squeezes an extra challenge.
+        assert_eq!(bdfg21.num_commitment, bgh19.num_commitment);
+        assert_eq!(bdfg21.num_evaluation, 0);
+        assert_eq!(bgh19.num_evaluation, 3);
+        assert_eq!(bdfg21.num_squeeze + 1, bgh19.num_squeeze);
+        assert!(bdfg21.num_absorption() < bgh19.num_absorption());
+        assert!(bdfg21.num_scalar_mul < bgh19.num_scalar_mul);
+    }
+
+    #[cfg(feature = "loader_halo2")]
+    mod halo2 {
+        use super::{create_instance, As, Instance};
+        use crate::{
+            loader::{self, native::NativeLoader},
+            pcs::{
+                ipa::{IpaProvingKey, IpaSuccinctVerifyingKey},
+                PolynomialCommitmentScheme, Query,
+            },
+            system::{self, halo2::test::MainGateWithRangeConfig},
+            util::{arithmetic::Field, msm::Msm, Itertools},
+        };
+        use halo2_curves::pasta::pallas;
+        use halo2_proofs::{
+            circuit::{floor_planner::V1, Layouter, Value},
+            dev::MockProver,
+            plonk::{Circuit, ConstraintSystem, Error},
+        };
+        use halo2_wrong_ecc::{
+            integer::rns::Rns,
+            maingate::{RangeInstructions, RegionCtx},
+        };
+        use rand::rngs::OsRng;
+        use std::rc::Rc;
+
+        const LIMBS: usize = 4;
+        const BITS: usize = 68;
+
+        type BaseFieldEccChip = halo2_wrong_ecc::BaseFieldEccChip<pallas::Affine, LIMBS, BITS>;
+        type Halo2Loader<'a> = loader::halo2::Halo2Loader<'a, pallas::Affine, BaseFieldEccChip>;
+        type PoseidonTranscript<L, S> =
+            system::halo2::transcript::halo2::PoseidonTranscript<pallas::Affine, L, S, 5, 4, 8, 60>;
+
+        struct Bdfg21Circuit {
+            svk: IpaSuccinctVerifyingKey<pallas::Affine>,
+            queries: Vec<Query<pallas::Scalar>>,
+            commitments: Vec<Value<pallas::Affine>>,
+            z: Value<pallas::Scalar>,
+            evals: Vec<Value<pallas::Scalar>>,
+            proof: Value<Vec<u8>>,
+        }
+
+        impl Bdfg21Circuit {
+            fn new(
+                pk: &IpaProvingKey<pallas::Affine>,
+                instance: &Instance,
+                proof: Vec<u8>,
+            ) -> Self {
+                Self {
+                    svk: pk.svk(),
+                    queries: instance.queries.clone(),
+                    commitments: instance
+                        .commitments
+                        .iter()
+                        .copied()
+                        .map(Value::known)
+                        .collect(),
+                    z: Value::known(instance.z),
+                    evals: instance.evals.iter().copied().map(Value::known).collect(),
+                    proof: Value::known(proof),
+                }
+            }
+        }
+
+        impl Circuit<pallas::Scalar> for Bdfg21Circuit {
+            type Config = MainGateWithRangeConfig;
+            type FloorPlanner = V1;
+            #[cfg(feature = "halo2_circuit_params")]
+            type Params = ();
+
+            fn without_witnesses(&self) -> Self {
+                Self {
+                    svk: self.svk.clone(),
+                    queries: self.queries.clone(),
+                    commitments: vec![Value::unknown(); self.commitments.len()],
+                    z: Value::unknown(),
+                    evals: vec![Value::unknown(); self.evals.len()],
+                    proof: Value::unknown(),
+                }
+            }
+
+            fn configure(meta: &mut ConstraintSystem<pallas::Scalar>) -> Self::Config {
+                MainGateWithRangeConfig::configure(
+                    meta,
+                    vec![BITS / LIMBS],
+                    Rns::<pallas::Base, pallas::Scalar, LIMBS, BITS>::construct()
+                        .overflow_lengths(),
+                )
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<pallas::Scalar>,
+            ) -> Result<(), Error> {
+                config.range_chip().load_table(&mut layouter)?;
+
+                layouter.assign_region(
+                    || "",
+                    |region| {
+                        let loader = Halo2Loader::new(config.ecc_chip(), RegionCtx::new(region, 0));
+
+                        let commitments = self
+                            .commitments
+                            .iter()
+                            .map(|commitment| loader.assign_ec_point(*commitment))
+                            .collect_vec();
+                        let z = loader.assign_scalar(self.z);
+                        let queries = self
+                            .queries
+                            .iter()
+                            .zip(self.evals.iter())
+                            .map(|(query, eval)| {
+                                query.clone().with_evaluation(loader.assign_scalar(*eval))
+                            })
+                            .collect_vec();
+
+                        let mut transcript = PoseidonTranscript::<Rc<Halo2Loader>, _>::new(
+                            &loader,
+                            self.proof.as_ref().map(Vec::as_slice),
+                        );
+                        let proof = As::read_proof(&self.svk, &self.queries, &mut transcript)
+                            .map_err(|_| Error::Synthesis)?;
+                        As::verify(
+                            &self.svk,
+                            &commitments.iter().map(Msm::base).collect_vec(),
+                            &z,
+                            &queries,
+                            &proof,
+                        )
+                        .map_err(|_| Error::Synthesis)?;
+
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        #[test]
+        fn test_ipa_bdfg21_halo2_loader() {
+            const K: u32 = 20;
+            let mut rng = OsRng;
+
+            let pk = IpaProvingKey::<pallas::Affine>::rand(2, true, &mut rng);
+            let mut transcript = PoseidonTranscript::<NativeLoader, _>::new(Vec::new());
+            let mut instance = create_instance(&pk, &mut transcript, &mut rng);
+            let proof = transcript.finalize();
+
+            let circuit = Bdfg21Circuit::new(&pk, &instance, proof.clone());
+            MockProver::run(K, &circuit, vec![Vec::new()])
+                .unwrap()
+                .assert_satisfied();
+
+            instance.evals[3] += pallas::Scalar::ONE;
+            let circuit = Bdfg21Circuit::new(&pk, &instance, proof);
+            let prover = MockProver::run(K, &circuit, vec![Vec::new()]);
+            assert!(!matches!(prover.map(|prover| prover.verify()), Ok(Ok(()))));
+        }
+    }
+}
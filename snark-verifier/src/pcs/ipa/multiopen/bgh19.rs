@@ -1,4 +1,5 @@
 use crate::{
+    cost::{Cost, CostEstimation},
     loader::{LoadedScalar, Loader, ScalarLoader},
     pcs::{
         ipa::{Ipa, IpaAccumulator, IpaAs, IpaProof, IpaSuccinctVerifyingKey, Round},
@@ -397,3 +398,59 @@ where
         self.r_eval_coeff.as_mut().unwrap().evaluate();
     }
 }
+
+impl<C> CostEstimation<C> for IpaAs<C, Bgh19>
+where
+    C: CurveAffine,
+    C::Scalar: PrimeField + Ord,
+{
+    type Input = Vec<Query<C::Scalar>>;
+
+    /// Estimate the cost of multi-opening `queries`, excluding the succinct
+    /// check of the inner product argument, which costs the same as the one
+    /// of [`Bdfg21`](crate::pcs::ipa::Bdfg21) on the same domain.
+    fn estimate_cost(queries: &Vec<Query<C::Scalar>>) -> Cost {
+        let sets = query_sets(queries);
+        let num_set = sets.len();
+        let num_superset = sets
+            .iter()
+            .flat_map(|set| set.shifts.iter())
+            .sorted()
+            .dedup()
+            .count();
+        let max_num_shift = sets.iter().map(|set| set.shifts.len()).max().unwrap_or(2);
+        let max_num_poly = sets.iter().map(|set| set.polys.len()).max().unwrap_or(1);
+        let num_shift = sets.iter().map(|set| set.shifts.len()).sum::<usize>();
+        let num_poly = sets.iter().map(|set| set.polys.len()).sum::<usize>();
+        let num_eval = sets
+            .iter()
+            .map(|set| set.polys.len() * set.shifts.len())
+            .sum::<usize>();
+        // Denominators of barycentric weights and `f_eval_coeff` in first
+        // batch inversion, and denominators of `r_eval_coeff` in second one,
+        // each takes 3 multiplications in batch inversion and 1 to evaluate.
+        let num_denom = (num_shift + num_set) + num_set;
+
+        let coeffs_cost = Cost {
+            num_scalar_add: num_superset + 2 * num_shift,
+            num_scalar_mul: max_num_shift.max(2) + num_superset + 5 * num_shift + 4 * num_denom
+                - num_set,
+            num_inversion: 2,
+            ..Default::default()
+        };
+        let msm_cost = Cost {
+            num_scalar_add: num_eval + 2 * num_set,
+            num_scalar_mul: num_eval + 4 * num_poly + (max_num_poly - 1) + 5 * num_set + 1,
+            ..Default::default()
+        };
+
+        coeffs_cost
+            + msm_cost
+            + Cost {
+                num_commitment: 1,
+                num_evaluation: num_set,
+                num_squeeze: 4,
+                ..Default::default()
+            }
+    }
+}
@@ -7,14 +7,16 @@ mod accumulation;
 mod accumulator;
 mod decider;
 mod multiopen;
+mod zeromorph;
 
 pub use accumulation::{KzgAs, KzgAsProvingKey, KzgAsVerifyingKey};
-pub use accumulator::{KzgAccumulator, LimbsEncoding};
+pub use accumulator::{CompressedEncoding, HashedEncoding, KzgAccumulator, LimbsEncoding};
 pub use decider::KzgDecidingKey;
 pub use multiopen::{Bdfg21, Bdfg21Proof, Gwc19, Gwc19Proof};
+pub use zeromorph::{Zeromorph, ZeromorphProof, ZeromorphProvingKey};
 
 #[cfg(feature = "loader_halo2")]
-pub use accumulator::LimbsEncodingInstructions;
+pub use accumulator::{CompressedEncodingInstructions, LimbsEncodingInstructions};
 
 /// KZG succinct verifying key.
 #[derive(Clone, Copy, Debug)]
@@ -1,4 +1,10 @@
-use crate::{loader::Loader, util::arithmetic::CurveAffine};
+use crate::{
+    loader::{LoadedScalar, Loader},
+    util::{
+        arithmetic::{CurveAffine, Field, FromUniformBytes, PrimeField},
+        hash::Poseidon,
+    },
+};
 use std::fmt::Debug;
 
 /// KZG accumulator, containing lhs G1 and rhs G1 of pairing.
@@ -30,18 +36,153 @@ where
 /// Since in circuit everything are in scalar field, but `Accumulator` might contain base field elements, so we split them into limbs.
 /// The const generic `LIMBS` and `BITS` respectively represents how many limbs
 /// a base field element are split into and how many bits each limbs could have.
+///
+/// Besides the default `LimbsEncoding<4, 68>` used by `halo2_wrong_ecc`,
+/// `LimbsEncoding<3, 88>` takes 12 instances instead of 16 for bn254 and
+/// matches the default of `halo2_ecc`.
 #[derive(Clone, Debug)]
 pub struct LimbsEncoding<const LIMBS: usize, const BITS: usize>;
 
+/// `AccumulatorEncoding` that encodes `Accumulator` into limbs of x
+/// coordinates and signs of y coordinates.
+///
+/// Each elliptic curve point is encoded into `LIMBS` limbs of its x coordinate
+/// followed by its sign, which is `1` if y coordinate is odd otherwise `0`, so
+/// it takes `2 * (LIMBS + 1)` instances instead of `4 * LIMBS`. The cost is a
+/// square root to decompress each point when decoding.
+#[derive(Clone, Debug)]
+pub struct CompressedEncoding<const LIMBS: usize, const BITS: usize>;
+
+/// `AccumulatorEncoding` that encodes `Accumulator` into a single digest.
+///
+/// The digest is Poseidon hash of the limbs of `Accumulator` encoded by
+/// [`LimbsEncoding`], and it's the only instance. The limbs are expected to be
+/// appended to the proof instead (e.g. by [`HashedEncoding::encode`] and
+/// [`crate::util::transcript::TranscriptWrite::write_scalar`]), which are
+/// read from transcript right after the proof and checked against the digest
+/// when decoding. The const generics other than `LIMBS` and `BITS` are the
+/// parameters of Poseidon.
+#[derive(Clone, Debug)]
+pub struct HashedEncoding<
+    const LIMBS: usize,
+    const BITS: usize,
+    const T: usize,
+    const RATE: usize,
+    const R_F: usize,
+    const R_P: usize,
+>;
+
+impl<
+        const LIMBS: usize,
+        const BITS: usize,
+        const T: usize,
+        const RATE: usize,
+        const R_F: usize,
+        const R_P: usize,
+    > HashedEncoding<LIMBS, BITS, T, RATE, R_F, R_P>
+{
+    /// Hash limbs of `Accumulator` encoded by [`LimbsEncoding`] into digest.
+    pub fn hash<F, L>(limbs: &[L]) -> L
+    where
+        F: FromUniformBytes<64>,
+        L: LoadedScalar<F>,
+    {
+        assert!(!limbs.is_empty());
+
+        let mut hasher = Poseidon::<F, L, T, RATE>::new(limbs[0].loader(), R_F, R_P);
+        hasher.update(limbs);
+        hasher.squeeze()
+    }
+}
+
+/// Returns elliptic curve point with x coordinate `x` and y coordinate being
+/// odd if `is_odd`, or `None` if there is no such point.
+fn ec_point_from_compressed<C: CurveAffine>(x: C::Base, is_odd: bool) -> Option<C> {
+    let y_square = x.square() * x + C::a() * x + C::b();
+    let y = Option::<C::Base>::from(y_square.sqrt())?;
+    let y = if bool::from(y.is_odd()) == is_odd {
+        y
+    } else {
+        -y
+    };
+    Option::from(C::from_xy(x, y))
+}
+
+mod hashed {
+    use crate::{
+        loader::{LoadedScalar, Loader, ScalarLoader},
+        pcs::{
+            kzg::{HashedEncoding, KzgAccumulator, LimbsEncoding},
+            AccumulatorEncoding,
+        },
+        util::{
+            arithmetic::{CurveAffine, FromUniformBytes},
+            transcript::TranscriptRead,
+            Itertools,
+        },
+        Error,
+    };
+
+    impl<
+            C,
+            L,
+            const LIMBS: usize,
+            const BITS: usize,
+            const T: usize,
+            const RATE: usize,
+            const R_F: usize,
+            const R_P: usize,
+        > AccumulatorEncoding<C, L> for HashedEncoding<LIMBS, BITS, T, RATE, R_F, R_P>
+    where
+        C: CurveAffine,
+        C::Scalar: FromUniformBytes<64>,
+        L: Loader<C>,
+        LimbsEncoding<LIMBS, BITS>: AccumulatorEncoding<C, L, Accumulator = KzgAccumulator<C, L>>,
+    {
+        type Accumulator = KzgAccumulator<C, L>;
+
+        fn from_repr(_: &[&L::LoadedScalar]) -> Result<Self::Accumulator, Error> {
+            Err(Error::AssertionFailure(
+                "HashedEncoding requires limbs read from transcript".to_string(),
+            ))
+        }
+
+        fn read_repr<TR>(
+            digest: &[&L::LoadedScalar],
+            transcript: &mut TR,
+        ) -> Result<Self::Accumulator, Error>
+        where
+            TR: TranscriptRead<C, L>,
+        {
+            assert_eq!(digest.len(), 1);
+
+            let limbs = transcript.read_n_scalars(4 * LIMBS)?;
+            digest[0].loader().assert_eq(
+                "Digest of accumulator limbs",
+                digest[0],
+                &Self::hash(&limbs),
+            )?;
+
+            LimbsEncoding::<LIMBS, BITS>::from_repr(&limbs.iter().collect_vec())
+        }
+    }
+}
+
 mod native {
     use crate::{
         loader::native::NativeLoader,
         pcs::{
-            kzg::{KzgAccumulator, LimbsEncoding},
+            kzg::{
+                accumulator::ec_point_from_compressed, CompressedEncoding, HashedEncoding,
+                KzgAccumulator, LimbsEncoding,
+            },
             AccumulatorEncoding,
         },
         util::{
-            arithmetic::{fe_from_limbs, CurveAffine},
+            arithmetic::{
+                ec_point_to_coordinates, fe_from_limbs, fe_to_limbs, CurveAffine, Field,
+                FromUniformBytes, PrimeField,
+            },
             Itertools,
         },
         Error,
@@ -72,12 +213,113 @@ mod native {
                 .collect_vec()
                 .try_into()
                 .unwrap();
-            let accumulator = KzgAccumulator::new(
-                C::from_xy(lhs_x, lhs_y).unwrap(),
-                C::from_xy(rhs_x, rhs_y).unwrap(),
-            );
+            let [lhs, rhs] = [(lhs_x, lhs_y), (rhs_x, rhs_y)].map(|(x, y)| {
+                Option::<C>::from(C::from_xy(x, y)).ok_or_else(|| {
+                    Error::AssertionFailure(
+                        "Invalid elliptic curve point decoded from limbs".to_string(),
+                    )
+                })
+            });
 
-            Ok(accumulator)
+            Ok(KzgAccumulator::new(lhs?, rhs?))
+        }
+    }
+
+    impl<C, const LIMBS: usize, const BITS: usize> AccumulatorEncoding<C, NativeLoader>
+        for CompressedEncoding<LIMBS, BITS>
+    where
+        C: CurveAffine,
+    {
+        type Accumulator = KzgAccumulator<C, NativeLoader>;
+
+        fn from_repr(repr: &[&C::Scalar]) -> Result<Self::Accumulator, Error> {
+            assert_eq!(repr.len(), 2 * (LIMBS + 1));
+
+            let [lhs, rhs]: [_; 2] = repr
+                .chunks(LIMBS + 1)
+                .map(|repr| {
+                    let x = fe_from_limbs::<_, _, LIMBS, BITS>(
+                        repr[..LIMBS]
+                            .iter()
+                            .map(|limb| **limb)
+                            .collect_vec()
+                            .try_into()
+                            .unwrap(),
+                    );
+                    let is_odd = match *repr[LIMBS] {
+                        sign if sign == C::Scalar::ZERO => false,
+                        sign if sign == C::Scalar::ONE => true,
+                        _ => {
+                            return Err(Error::AssertionFailure(
+                                "Invalid sign of compressed elliptic curve point".to_string(),
+                            ))
+                        }
+                    };
+                    ec_point_from_compressed(x, is_odd).ok_or_else(|| {
+                        Error::AssertionFailure(
+                            "Invalid compressed elliptic curve point".to_string(),
+                        )
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?
+                .try_into()
+                .unwrap();
+
+            Ok(KzgAccumulator::new(lhs, rhs))
+        }
+    }
+
+    impl<const LIMBS: usize, const BITS: usize> LimbsEncoding<LIMBS, BITS> {
+        /// Encode [`KzgAccumulator`] into limbs.
+        pub fn encode<C: CurveAffine>(
+            accumulator: &KzgAccumulator<C, NativeLoader>,
+        ) -> Vec<C::Scalar> {
+            [accumulator.lhs, accumulator.rhs]
+                .iter()
+                .flat_map(ec_point_to_coordinates)
+                .flat_map(fe_to_limbs::<_, _, LIMBS, BITS>)
+                .collect()
+        }
+    }
+
+    impl<const LIMBS: usize, const BITS: usize> CompressedEncoding<LIMBS, BITS> {
+        /// Encode [`KzgAccumulator`] into limbs of x coordinates and signs of y
+        /// coordinates.
+        pub fn encode<C: CurveAffine>(
+            accumulator: &KzgAccumulator<C, NativeLoader>,
+        ) -> Vec<C::Scalar> {
+            [accumulator.lhs, accumulator.rhs]
+                .iter()
+                .flat_map(|ec_point| {
+                    let [x, y] = ec_point_to_coordinates(ec_point);
+                    fe_to_limbs::<_, _, LIMBS, BITS>(x)
+                        .into_iter()
+                        .chain(Some(C::Scalar::from(bool::from(y.is_odd()) as u64)))
+                })
+                .collect()
+        }
+    }
+
+    impl<
+            const LIMBS: usize,
+            const BITS: usize,
+            const T: usize,
+            const RATE: usize,
+            const R_F: usize,
+            const R_P: usize,
+        > HashedEncoding<LIMBS, BITS, T, RATE, R_F, R_P>
+    {
+        /// Encode [`KzgAccumulator`] into digest, and returns it with the limbs
+        /// to be appended to the proof.
+        pub fn encode<C>(
+            accumulator: &KzgAccumulator<C, NativeLoader>,
+        ) -> (C::Scalar, Vec<C::Scalar>)
+        where
+            C: CurveAffine,
+            C::Scalar: FromUniformBytes<64>,
+        {
+            let limbs = LimbsEncoding::<LIMBS, BITS>::encode(accumulator);
+            (Self::hash(&limbs), limbs)
         }
     }
 }
@@ -87,7 +329,7 @@ mod evm {
     use crate::{
         loader::evm::{EvmLoader, Scalar},
         pcs::{
-            kzg::{KzgAccumulator, LimbsEncoding},
+            kzg::{CompressedEncoding, KzgAccumulator, LimbsEncoding},
             AccumulatorEncoding,
         },
         util::{
@@ -125,21 +367,53 @@ mod evm {
             Ok(accumulator)
         }
     }
+
+    impl<C, const LIMBS: usize, const BITS: usize> AccumulatorEncoding<C, Rc<EvmLoader>>
+        for CompressedEncoding<LIMBS, BITS>
+    where
+        C: CurveAffine,
+        C::Scalar: PrimeField<Repr = [u8; 0x20]>,
+    {
+        type Accumulator = KzgAccumulator<C, Rc<EvmLoader>>;
+
+        fn from_repr(repr: &[&Scalar]) -> Result<Self::Accumulator, Error> {
+            assert_eq!(repr.len(), 2 * (LIMBS + 1));
+
+            let loader = repr[0].loader();
+
+            let [lhs, rhs]: [_; 2] = repr
+                .chunks(LIMBS + 1)
+                .map(|repr| {
+                    loader.ec_point_from_compressed_limbs::<LIMBS, BITS>(
+                        repr[..LIMBS].to_vec().try_into().unwrap(),
+                        repr[LIMBS],
+                    )
+                })
+                .collect_vec()
+                .try_into()
+                .unwrap();
+
+            Ok(KzgAccumulator::new(lhs, rhs))
+        }
+    }
 }
 
 #[cfg(feature = "loader_halo2")]
-pub use halo2::LimbsEncodingInstructions;
+pub use halo2::{CompressedEncodingInstructions, LimbsEncodingInstructions};
 
 #[cfg(feature = "loader_halo2")]
 mod halo2 {
     use crate::{
         loader::halo2::{EccInstructions, Halo2Loader, Scalar, Valuetools},
         pcs::{
-            kzg::{KzgAccumulator, LimbsEncoding},
+            kzg::{
+                accumulator::ec_point_from_compressed, CompressedEncoding, KzgAccumulator,
+                LimbsEncoding,
+            },
             AccumulatorEncoding,
         },
         util::{
-            arithmetic::{fe_from_limbs, CurveAffine},
+            arithmetic::{fe_from_limbs, CurveAffine, Field},
             Itertools,
         },
         Error,
@@ -149,7 +423,7 @@ mod halo2 {
 
     fn ec_point_from_limbs<C: CurveAffine, const LIMBS: usize, const BITS: usize>(
         limbs: &[Value<&C::Scalar>],
-    ) -> Value<C> {
+    ) -> Result<Value<C>, plonk::Error> {
         assert_eq!(limbs.len(), 2 * LIMBS);
 
         let [x, y] = [&limbs[..LIMBS], &limbs[LIMBS..]].map(|limbs| {
@@ -163,7 +437,32 @@ mod halo2 {
                 .map(|limbs| fe_from_limbs::<_, _, LIMBS, BITS>(limbs.try_into().unwrap()))
         });
 
-        x.zip(y).map(|(x, y)| C::from_xy(x, y).unwrap())
+        let ec_point = x.zip(y).map(|(x, y)| Option::<C>::from(C::from_xy(x, y)));
+        ec_point.error_if_known_and(Option::is_none)?;
+        Ok(ec_point.map(Option::unwrap))
+    }
+
+    fn ec_point_from_compressed_limbs<C: CurveAffine, const LIMBS: usize, const BITS: usize>(
+        limbs: &[Value<&C::Scalar>],
+    ) -> Result<Value<C>, plonk::Error> {
+        assert_eq!(limbs.len(), LIMBS + 1);
+
+        let x = limbs[..LIMBS]
+            .iter()
+            .cloned()
+            .fold_zipped(Vec::new(), |mut acc, limb| {
+                acc.push(*limb);
+                acc
+            })
+            .map(|limbs| fe_from_limbs::<_, _, LIMBS, BITS>(limbs.try_into().unwrap()));
+
+        let ec_point = x.zip(limbs[LIMBS]).map(|(x, sign)| match *sign {
+            sign if sign == C::Scalar::ZERO => ec_point_from_compressed(x, false),
+            sign if sign == C::Scalar::ONE => ec_point_from_compressed(x, true),
+            _ => None,
+        });
+        ec_point.error_if_known_and(Option::is_none)?;
+        Ok(ec_point.map(Option::unwrap))
     }
 
     /// Instructions to encode/decode a elliptic curve point into/from limbs.
@@ -185,6 +484,32 @@ mod halo2 {
         ) -> Result<Vec<Self::AssignedCell>, plonk::Error>;
     }
 
+    /// Instructions to encode/decode a elliptic curve point into/from limbs of
+    /// x coordinate and sign of y coordinate.
+    pub trait CompressedEncodingInstructions<
+        'a,
+        C: CurveAffine,
+        const LIMBS: usize,
+        const BITS: usize,
+    >: EccInstructions<'a, C>
+    {
+        /// Decode and assign an elliptic curve point from limbs of x coordinate
+        /// followed by sign of y coordinate.
+        fn assign_ec_point_from_compressed(
+            &self,
+            ctx: &mut Self::Context,
+            limbs: &[impl Deref<Target = Self::AssignedScalar>],
+        ) -> Result<Self::AssignedEcPoint, plonk::Error>;
+
+        /// Encode an elliptic curve point into limbs of x coordinate followed by
+        /// sign of y coordinate.
+        fn assign_ec_point_to_compressed(
+            &self,
+            ctx: &mut Self::Context,
+            ec_point: impl Deref<Target = Self::AssignedEcPoint>,
+        ) -> Result<Vec<Self::AssignedCell>, plonk::Error>;
+    }
+
     impl<'a, C, EccChip, const LIMBS: usize, const BITS: usize>
         AccumulatorEncoding<C, Rc<Halo2Loader<'a, C, EccChip>>> for LimbsEncoding<LIMBS, BITS>
     where
@@ -199,23 +524,108 @@ mod halo2 {
             let loader = limbs[0].loader();
 
             let [lhs, rhs] = [&limbs[..2 * LIMBS], &limbs[2 * LIMBS..]].map(|limbs| {
-                let assigned = loader
+                loader
                     .ecc_chip()
                     .assign_ec_point_from_limbs(
                         &mut loader.ctx_mut(),
                         &limbs.iter().map(|limb| limb.assigned()).collect_vec(),
                     )
-                    .unwrap();
-                loader.ec_point_from_assigned(assigned)
+                    .map(|assigned| loader.ec_point_from_assigned(assigned))
+                    .map_err(|_| {
+                        Error::AssertionFailure(
+                            "Invalid elliptic curve point decoded from limbs".to_string(),
+                        )
+                    })
             });
 
-            Ok(KzgAccumulator::new(lhs, rhs))
+            Ok(KzgAccumulator::new(lhs?, rhs?))
+        }
+    }
+
+    impl<'a, C, EccChip, const LIMBS: usize, const BITS: usize>
+        AccumulatorEncoding<C, Rc<Halo2Loader<'a, C, EccChip>>> for CompressedEncoding<LIMBS, BITS>
+    where
+        C: CurveAffine,
+        EccChip: CompressedEncodingInstructions<'a, C, LIMBS, BITS>,
+    {
+        type Accumulator = KzgAccumulator<C, Rc<Halo2Loader<'a, C, EccChip>>>;
+
+        fn from_repr(repr: &[&Scalar<'a, C, EccChip>]) -> Result<Self::Accumulator, Error> {
+            assert_eq!(repr.len(), 2 * (LIMBS + 1));
+
+            let loader = repr[0].loader();
+
+            let [lhs, rhs] = [&repr[..LIMBS + 1], &repr[LIMBS + 1..]].map(|repr| {
+                loader
+                    .ecc_chip()
+                    .assign_ec_point_from_compressed(
+                        &mut loader.ctx_mut(),
+                        &repr.iter().map(|limb| limb.assigned()).collect_vec(),
+                    )
+                    .map(|assigned| loader.ec_point_from_assigned(assigned))
+                    .map_err(|_| {
+                        Error::AssertionFailure(
+                            "Invalid compressed elliptic curve point".to_string(),
+                        )
+                    })
+            });
+
+            Ok(KzgAccumulator::new(lhs?, rhs?))
         }
     }
 
     mod halo2_wrong {
         use super::*;
-        use halo2_wrong_ecc::BaseFieldEccChip;
+        use crate::util::arithmetic::{powers, PrimeField};
+        use halo2_proofs::circuit::AssignedCell;
+        use halo2_wrong_ecc::{
+            integer::IntegerInstructions as _,
+            maingate::{MainGate, MainGateInstructions, RegionCtx, Term},
+            BaseFieldEccChip,
+        };
+
+        /// Decompose `limbs` of `BITS_IN` bits into bits and compose them into
+        /// `LIMBS_OUT` limbs of `BITS_OUT` bits, where the remaining high bits
+        /// are constrained to be zero.
+        fn relimb<
+            F: PrimeField,
+            const BITS_IN: usize,
+            const LIMBS_OUT: usize,
+            const BITS_OUT: usize,
+        >(
+            main_gate: &MainGate<F>,
+            ctx: &mut RegionCtx<'_, F>,
+            limbs: &[AssignedCell<F, F>],
+        ) -> Result<Vec<AssignedCell<F, F>>, plonk::Error> {
+            assert!(limbs.len() * BITS_IN >= LIMBS_OUT * BITS_OUT);
+
+            let bits = limbs
+                .iter()
+                .map(|limb| main_gate.to_bits(ctx, limb, BITS_IN))
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter()
+                .flatten()
+                .collect_vec();
+            let (bits, high_bits) = bits.split_at(LIMBS_OUT * BITS_OUT);
+            for bit in high_bits {
+                main_gate.assert_zero(ctx, bit)?;
+            }
+
+            let powers_of_two = powers(F::from(2)).take(BITS_OUT).collect_vec();
+            bits.chunks(BITS_OUT)
+                .map(|bits| {
+                    main_gate.compose(
+                        ctx,
+                        &bits
+                            .iter()
+                            .zip(powers_of_two.iter())
+                            .map(|(bit, power_of_two)| Term::Assigned(bit, *power_of_two))
+                            .collect_vec(),
+                        F::ZERO,
+                    )
+                })
+                .collect()
+        }
 
         impl<'a, C: CurveAffine, const LIMBS: usize, const BITS: usize>
             LimbsEncodingInstructions<'a, C, LIMBS, BITS> for BaseFieldEccChip<C, LIMBS, BITS>
@@ -231,7 +641,7 @@ mod halo2 {
                     ctx,
                     ec_point_from_limbs::<_, LIMBS, BITS>(
                         &limbs.iter().map(|limb| limb.value()).collect_vec(),
-                    ),
+                    )?,
                 )?;
 
                 for (src, dst) in limbs.iter().zip_eq(
@@ -258,5 +668,365 @@ mod halo2 {
                     .collect())
             }
         }
+
+        /// Encoding of [`LimbsEncoding<3, 88>`] with the default chip of 4 limbs
+        /// of 68 bits, by re-limbing coordinates through bit decomposition.
+        impl<'a, C: CurveAffine> LimbsEncodingInstructions<'a, C, 3, 88> for BaseFieldEccChip<C, 4, 68> {
+            fn assign_ec_point_from_limbs(
+                &self,
+                ctx: &mut Self::Context,
+                limbs: &[impl Deref<Target = Self::AssignedScalar>],
+            ) -> Result<Self::AssignedEcPoint, plonk::Error> {
+                assert_eq!(limbs.len(), 2 * 3);
+
+                let ec_point = self.assign_point(
+                    ctx,
+                    ec_point_from_limbs::<_, 3, 88>(
+                        &limbs.iter().map(|limb| limb.value()).collect_vec(),
+                    )?,
+                )?;
+
+                let relimbed = LimbsEncodingInstructions::<C, 3, 88>::assign_ec_point_to_limbs(
+                    self, ctx, &ec_point,
+                )?;
+                for (src, dst) in limbs.iter().zip_eq(relimbed.iter()) {
+                    ctx.constrain_equal(src.cell(), dst.cell())?;
+                }
+
+                Ok(ec_point)
+            }
+
+            fn assign_ec_point_to_limbs(
+                &self,
+                ctx: &mut Self::Context,
+                ec_point: impl Deref<Target = Self::AssignedEcPoint>,
+            ) -> Result<Vec<Self::AssignedCell>, plonk::Error> {
+                let main_gate = self.main_gate();
+                Ok([ec_point.x(), ec_point.y()]
+                    .into_iter()
+                    .map(|coordinate| {
+                        relimb::<_, 68, 3, 88>(
+                            &main_gate,
+                            ctx,
+                            &coordinate
+                                .limbs()
+                                .iter()
+                                .map(|limb| limb.as_ref().clone())
+                                .collect_vec(),
+                        )
+                    })
+                    .collect::<Result<Vec<_>, _>>()?
+                    .into_iter()
+                    .flatten()
+                    .collect())
+            }
+        }
+
+        impl<'a, C: CurveAffine, const LIMBS: usize, const BITS: usize>
+            CompressedEncodingInstructions<'a, C, LIMBS, BITS>
+            for BaseFieldEccChip<C, LIMBS, BITS>
+        {
+            fn assign_ec_point_from_compressed(
+                &self,
+                ctx: &mut Self::Context,
+                limbs: &[impl Deref<Target = Self::AssignedScalar>],
+            ) -> Result<Self::AssignedEcPoint, plonk::Error> {
+                assert_eq!(limbs.len(), LIMBS + 1);
+
+                let ec_point = self.assign_point(
+                    ctx,
+                    ec_point_from_compressed_limbs::<_, LIMBS, BITS>(
+                        &limbs.iter().map(|limb| limb.value()).collect_vec(),
+                    )?,
+                )?;
+
+                let compressed = self.assign_ec_point_to_compressed(ctx, &ec_point)?;
+                for (src, dst) in limbs.iter().zip_eq(compressed.iter()) {
+                    ctx.constrain_equal(src.cell(), dst.cell())?;
+                }
+
+                Ok(ec_point)
+            }
+
+            fn assign_ec_point_to_compressed(
+                &self,
+                ctx: &mut Self::Context,
+                ec_point: impl Deref<Target = Self::AssignedEcPoint>,
+            ) -> Result<Vec<Self::AssignedCell>, plonk::Error> {
+                // Both coordinates need to be canonical to make the encoding unique.
+                self.integer_chip().assert_in_field(ctx, ec_point.x())?;
+                self.integer_chip().assert_in_field(ctx, ec_point.y())?;
+                let sign = self
+                    .main_gate()
+                    .to_bits(ctx, ec_point.y().limbs()[0].as_ref(), BITS)?
+                    .swap_remove(0);
+
+                Ok(ec_point
+                    .x()
+                    .limbs()
+                    .iter()
+                    .map(|limb| limb.as_ref())
+                    .cloned()
+                    .chain(Some(sign))
+                    .collect())
+            }
+        }
+    }
+
+    #[cfg(feature = "halo2_lib")]
+    mod halo2_lib {
+        use super::*;
+        use halo2_base::{
+            gates::{GateInstructions, RangeInstructions},
+            utils::PrimeField,
+        };
+        use halo2_ecc::{ecc::BaseFieldEccChip, fields::FieldChip};
+
+        impl<'a, C, const LIMBS: usize, const BITS: usize>
+            LimbsEncodingInstructions<'a, C, LIMBS, BITS> for BaseFieldEccChip<C>
+        where
+            C: CurveAffine,
+            C::Scalar: PrimeField,
+            C::Base: PrimeField,
+        {
+            fn assign_ec_point_from_limbs(
+                &self,
+                ctx: &mut Self::Context,
+                limbs: &[impl Deref<Target = Self::AssignedScalar>],
+            ) -> Result<Self::AssignedEcPoint, plonk::Error> {
+                assert_eq!(limbs.len(), 2 * LIMBS);
+                assert_eq!(self.field_chip.num_limbs, LIMBS);
+                assert_eq!(self.field_chip.limb_bits, BITS);
+
+                let ec_point = EccInstructions::assign_point(
+                    self,
+                    ctx,
+                    ec_point_from_limbs::<_, LIMBS, BITS>(
+                        &limbs.iter().map(|limb| limb.value()).collect_vec(),
+                    )?,
+                )?;
+
+                for (src, dst) in limbs.iter().zip_eq(
+                    iter::empty()
+                        .chain(ec_point.x().truncation.limbs.iter())
+                        .chain(ec_point.y().truncation.limbs.iter()),
+                ) {
+                    ctx.region.constrain_equal(src.cell(), dst.cell())?;
+                }
+
+                Ok(ec_point)
+            }
+
+            fn assign_ec_point_to_limbs(
+                &self,
+                _: &mut Self::Context,
+                ec_point: impl Deref<Target = Self::AssignedEcPoint>,
+            ) -> Result<Vec<Self::AssignedCell>, plonk::Error> {
+                Ok(iter::empty()
+                    .chain(ec_point.x().truncation.limbs.iter())
+                    .chain(ec_point.y().truncation.limbs.iter())
+                    .cloned()
+                    .collect())
+            }
+        }
+
+        fn assign_ec_point_to_compressed<'a, C, const BITS: usize>(
+            chip: &BaseFieldEccChip<C>,
+            ctx: &mut <BaseFieldEccChip<C> as EccInstructions<'a, C>>::Context,
+            ec_point: &<BaseFieldEccChip<C> as EccInstructions<'a, C>>::AssignedEcPoint,
+        ) -> Vec<<BaseFieldEccChip<C> as EccInstructions<'a, C>>::AssignedCell>
+        where
+            C: CurveAffine,
+            C::Scalar: PrimeField,
+            C::Base: PrimeField,
+        {
+            // Both coordinates need to be canonical to make the encoding unique.
+            chip.field_chip.enforce_less_than(ctx, ec_point.x());
+            chip.field_chip.enforce_less_than(ctx, ec_point.y());
+            let sign = chip
+                .field_chip
+                .range()
+                .gate()
+                .num_to_bits(ctx, &ec_point.y().truncation.limbs[0], BITS)
+                .swap_remove(0);
+
+            ec_point
+                .x()
+                .truncation
+                .limbs
+                .iter()
+                .cloned()
+                .chain(Some(sign))
+                .collect()
+        }
+
+        impl<'a, C, const LIMBS: usize, const BITS: usize>
+            CompressedEncodingInstructions<'a, C, LIMBS, BITS> for BaseFieldEccChip<C>
+        where
+            C: CurveAffine,
+            C::Scalar: PrimeField,
+            C::Base: PrimeField,
+        {
+            fn assign_ec_point_from_compressed(
+                &self,
+                ctx: &mut Self::Context,
+                limbs: &[impl Deref<Target = Self::AssignedScalar>],
+            ) -> Result<Self::AssignedEcPoint, plonk::Error> {
+                assert_eq!(limbs.len(), LIMBS + 1);
+                assert_eq!(self.field_chip.num_limbs, LIMBS);
+                assert_eq!(self.field_chip.limb_bits, BITS);
+
+                let ec_point = EccInstructions::assign_point(
+                    self,
+                    ctx,
+                    ec_point_from_compressed_limbs::<_, LIMBS, BITS>(
+                        &limbs.iter().map(|limb| limb.value()).collect_vec(),
+                    )?,
+                )?;
+
+                let compressed = assign_ec_point_to_compressed::<_, BITS>(self, ctx, &ec_point);
+                for (src, dst) in limbs.iter().zip_eq(compressed.iter()) {
+                    ctx.region.constrain_equal(src.cell(), dst.cell())?;
+                }
+
+                Ok(ec_point)
+            }
+
+            fn assign_ec_point_to_compressed(
+                &self,
+                ctx: &mut Self::Context,
+                ec_point: impl Deref<Target = Self::AssignedEcPoint>,
+            ) -> Result<Vec<Self::AssignedCell>, plonk::Error> {
+                Ok(assign_ec_point_to_compressed::<_, BITS>(
+                    self, ctx, &ec_point,
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        loader::native::NativeLoader,
+        pcs::{
+            kzg::{CompressedEncoding, HashedEncoding, KzgAccumulator, LimbsEncoding},
+            AccumulatorEncoding,
+        },
+        util::{
+            arithmetic::{fe_to_limbs, CurveAffine, Field},
+            Itertools,
+        },
+    };
+    use halo2_curves::bn256::{Fq, Fr, G1Affine};
+    use rand::rngs::OsRng;
+
+    fn random_accumulator() -> KzgAccumulator<G1Affine, NativeLoader> {
+        let [lhs, rhs] = [(); 2].map(|_| (G1Affine::generator() * Fr::random(OsRng)).into());
+        KzgAccumulator::new(lhs, rhs)
+    }
+
+    fn assert_decoded<AE>(repr: &[Fr], accumulator: &KzgAccumulator<G1Affine, NativeLoader>)
+    where
+        AE: AccumulatorEncoding<
+            G1Affine,
+            NativeLoader,
+            Accumulator = KzgAccumulator<G1Affine, NativeLoader>,
+        >,
+    {
+        let decoded = AE::from_repr(&repr.iter().collect_vec()).unwrap();
+        assert_eq!(decoded.lhs, accumulator.lhs);
+        assert_eq!(decoded.rhs, accumulator.rhs);
+    }
+
+    #[test]
+    fn test_limbs_encoding() {
+        for _ in 0..10 {
+            let accumulator = random_accumulator();
+            let repr = LimbsEncoding::<3, 88>::encode(&accumulator);
+            assert_eq!(repr.len(), 12);
+            assert_decoded::<LimbsEncoding<3, 88>>(&repr, &accumulator);
+        }
+    }
+
+    #[test]
+    fn test_compressed_encoding() {
+        for _ in 0..10 {
+            let accumulator = random_accumulator();
+            let repr = CompressedEncoding::<3, 88>::encode(&accumulator);
+            assert_eq!(repr.len(), 8);
+            assert_decoded::<CompressedEncoding<3, 88>>(&repr, &accumulator);
+
+            let mut invalid = repr.clone();
+            invalid[3] = Fr::from(2);
+            assert!(CompressedEncoding::<3, 88>::from_repr(&invalid.iter().collect_vec()).is_err());
+        }
+
+        let off_curve_x = (0u64..)
+            .map(Fq::from)
+            .find(|x| bool::from((x.square() * x + G1Affine::b()).sqrt().is_none()))
+            .unwrap();
+        let invalid = fe_to_limbs::<_, Fr, 3, 88>(off_curve_x)
+            .into_iter()
+            .chain([Fr::ZERO])
+            .chain(CompressedEncoding::<3, 88>::encode(&random_accumulator()).split_off(4))
+            .collect_vec();
+        assert!(CompressedEncoding::<3, 88>::from_repr(&invalid.iter().collect_vec()).is_err());
+    }
+
+    #[test]
+    fn test_limbs_encoding_off_curve() {
+        let mut repr = LimbsEncoding::<3, 88>::encode(&random_accumulator());
+        repr[0] += Fr::ONE;
+        assert!(LimbsEncoding::<3, 88>::from_repr(&repr.iter().collect_vec()).is_err());
+    }
+
+    #[test]
+    fn test_hashed_encoding() {
+        type Hashed = HashedEncoding<3, 88, 5, 4, 8, 60>;
+
+        let accumulator = random_accumulator();
+        let (digest, limbs) = Hashed::encode(&accumulator);
+        assert_eq!(limbs, LimbsEncoding::<3, 88>::encode(&accumulator));
+        assert_eq!(digest, Hashed::hash(&limbs));
+        assert_decoded::<LimbsEncoding<3, 88>>(&limbs, &accumulator);
+    }
+
+    #[cfg(feature = "system_halo2")]
+    #[test]
+    fn test_hashed_encoding_transcript() {
+        use crate::{
+            system::halo2::transcript::halo2::PoseidonTranscript, util::transcript::TranscriptWrite,
+        };
+
+        type Hashed = HashedEncoding<4, 68, 5, 4, 8, 60>;
+        type Transcript<S> = PoseidonTranscript<G1Affine, NativeLoader, S, 5, 4, 8, 60>;
+
+        let read_repr = |digest: Fr, proof: &[u8]| {
+            <Hashed as AccumulatorEncoding<G1Affine, NativeLoader>>::read_repr(
+                &[&digest],
+                &mut Transcript::new(proof),
+            )
+        };
+
+        let accumulator = random_accumulator();
+        let (digest, limbs) = Hashed::encode(&accumulator);
+        let proof = {
+            let mut transcript = Transcript::new(Vec::new());
+            for limb in limbs {
+                transcript.write_scalar(limb).unwrap();
+            }
+            transcript.finalize()
+        };
+
+        let decoded = read_repr(digest, &proof).unwrap();
+        assert_eq!(decoded.lhs, accumulator.lhs);
+        assert_eq!(decoded.rhs, accumulator.rhs);
+
+        let mut tampered = proof.clone();
+        tampered[0] ^= 1;
+        assert!(read_repr(digest, &tampered).is_err());
+        assert!(read_repr(digest + Fr::ONE, &proof).is_err());
+        assert!(read_repr(digest, &proof[..proof.len() - 0x20]).is_err());
     }
 }
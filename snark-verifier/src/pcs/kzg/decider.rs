@@ -94,7 +94,7 @@ mod native {
 mod evm {
     use crate::{
         loader::{
-            evm::{loader::Value, EvmLoader, U256},
+            evm::{loader::Value, EvmLoader, MemoryPtr, U256},
             LoadedScalar,
         },
         pcs::{
@@ -157,7 +157,11 @@ mod evm {
 
                 let hash_ptr = loader.keccak256(lhs[0].ptr(), lhs.len() * 0x80);
                 let challenge_ptr = loader.allocate(0x20);
-                let code = format!("mstore({challenge_ptr}, mod(mload({hash_ptr}), f_q))");
+                let code = format!(
+                    "mstore({}, mod(mload({}), f_q))",
+                    MemoryPtr(challenge_ptr),
+                    MemoryPtr(hash_ptr)
+                );
                 loader.code_mut().runtime_append(code);
                 let challenge = loader.scalar(Value::Memory(challenge_ptr));
 
@@ -179,3 +183,261 @@ mod evm {
         }
     }
 }
+
+#[cfg(all(feature = "loader_halo2", feature = "system_halo2"))]
+mod halo2 {
+    use crate::{
+        loader::{
+            halo2::{Halo2Loader, PairingInstructions, Scalar},
+            LoadedEcPoint, LoadedScalar,
+        },
+        pcs::{
+            kzg::{KzgAccumulator, KzgAs, KzgDecidingKey},
+            AccumulationDecider,
+        },
+        system::halo2::transcript::halo2::NativeEncoding,
+        util::{
+            arithmetic::{FromUniformBytes, MultiMillerLoop, PrimeField},
+            hash::Poseidon,
+            msm::Msm,
+            Itertools,
+        },
+        Error,
+    };
+    use std::{fmt::Debug, rc::Rc};
+
+    // Parameters of Poseidon to squeeze challenge for batching accumulators,
+    // which are the same as the default ones of `PoseidonTranscript`.
+    const T: usize = 5;
+    const RATE: usize = 4;
+    const R_F: usize = 8;
+    const R_P: usize = 60;
+
+    impl<'a, M, MOS, EccChip>
+        AccumulationDecider<M::G1Affine, Rc<Halo2Loader<'a, M::G1Affine, EccChip>>>
+        for KzgAs<M, MOS>
+    where
+        M: MultiMillerLoop,
+        M::Scalar: PrimeField + FromUniformBytes<64>,
+        MOS: Clone + Debug,
+        EccChip: PairingInstructions<'a, M> + NativeEncoding<'a, M::G1Affine>,
+    {
+        type DecidingKey = KzgDecidingKey<M>;
+
+        fn decide(
+            dk: &Self::DecidingKey,
+            KzgAccumulator { lhs, rhs }: KzgAccumulator<
+                M::G1Affine,
+                Rc<Halo2Loader<'a, M::G1Affine, EccChip>>,
+            >,
+        ) -> Result<(), Error> {
+            let loader = lhs.loader();
+            let [lhs, rhs] = [&lhs, &rhs].map(|ec_point| ec_point.assigned());
+            loader
+                .ecc_chip()
+                .assert_pairing_check(&mut loader.ctx_mut(), &[(lhs, dk.g2), (rhs, -dk.s_g2)])
+                .map_err(|_| Error::AssertionFailure("e(lhs, g2)·e(rhs, -s_g2) == O".to_string()))
+        }
+
+        fn decide_all(
+            dk: &Self::DecidingKey,
+            mut accumulators: Vec<
+                KzgAccumulator<M::G1Affine, Rc<Halo2Loader<'a, M::G1Affine, EccChip>>>,
+            >,
+        ) -> Result<(), Error> {
+            assert!(!accumulators.is_empty());
+
+            // Pairing check is much more expensive than accumulation in circuit,
+            // so accumulators are batched into one by powers of a challenge
+            // squeezed from their native encodings, then checked once.
+            let accumulator = if accumulators.len() == 1 {
+                accumulators.pop().unwrap()
+            } else {
+                let loader = accumulators[0].lhs.loader();
+                let mut hasher =
+                    Poseidon::<M::Scalar, Scalar<'a, M::G1Affine, EccChip>, T, RATE>::new(
+                        loader, R_F, R_P,
+                    );
+                for ec_point in accumulators
+                    .iter()
+                    .flat_map(|accumulator| [&accumulator.lhs, &accumulator.rhs])
+                {
+                    let assigned = ec_point.assigned().clone();
+                    let encoded = loader
+                        .ecc_chip()
+                        .encode(&mut loader.ctx_mut(), &assigned)
+                        .map_err(|_| {
+                            Error::AssertionFailure(
+                                "Invalid elliptic curve point of accumulator".to_string(),
+                            )
+                        })?;
+                    hasher.update(
+                        &encoded
+                            .into_iter()
+                            .map(|encoded| loader.scalar_from_assigned(encoded))
+                            .collect_vec(),
+                    );
+                }
+                let challenge = hasher.squeeze();
+
+                let powers_of_challenge = challenge.powers(accumulators.len());
+                let (lhs, rhs) = accumulators
+                    .iter()
+                    .map(|KzgAccumulator { lhs, rhs }| (lhs, rhs))
+                    .unzip::<_, _, Vec<_>, Vec<_>>();
+                let [lhs, rhs] = [lhs, rhs].map(|ec_points| {
+                    ec_points
+                        .into_iter()
+                        .zip(powers_of_challenge.iter())
+                        .map(|(ec_point, power_of_challenge)| {
+                            Msm::<M::G1Affine, Rc<Halo2Loader<'a, M::G1Affine, EccChip>>>::base(
+                                ec_point,
+                            ) * power_of_challenge
+                        })
+                        .sum::<Msm<_, _>>()
+                        .evaluate(None)
+                });
+
+                KzgAccumulator::new(lhs, rhs)
+            };
+
+            <Self as AccumulationDecider<_, Rc<Halo2Loader<'a, M::G1Affine, EccChip>>>>::decide(
+                dk,
+                accumulator,
+            )
+        }
+    }
+}
+
+#[cfg(all(test, feature = "halo2_lib", feature = "system_halo2"))]
+mod test {
+    use crate::{
+        loader::{
+            halo2::{
+                halo2_base::{utils::modulus, SKIP_FIRST_PASS},
+                halo2_ecc::{
+                    ecc::EccChip,
+                    fields::fp::{FpConfig, FpStrategy},
+                },
+                Halo2Loader,
+            },
+            Loader,
+        },
+        pcs::{
+            kzg::{Bdfg21, KzgAccumulator, KzgAs, KzgDecidingKey},
+            AccumulationDecider,
+        },
+    };
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        halo2curves::bn256::{Bn256, Fq, Fr, G1Affine},
+        plonk::{self, Circuit, ConstraintSystem},
+        poly::kzg::commitment::ParamsKZG,
+    };
+    use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+
+    const K: u32 = 20;
+    const LOOKUP_BITS: usize = 19;
+    const LIMBS: usize = 3;
+    const BITS: usize = 90;
+
+    type As = KzgAs<Bn256, Bdfg21>;
+
+    #[derive(Clone)]
+    struct DeciderCircuit {
+        dk: KzgDecidingKey<Bn256>,
+        lhs: Value<G1Affine>,
+        rhs: Value<G1Affine>,
+    }
+
+    impl Circuit<Fr> for DeciderCircuit {
+        type Config = FpConfig<Fr, Fq>;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "halo2_circuit_params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                dk: self.dk,
+                lhs: Value::unknown(),
+                rhs: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            FpConfig::configure(
+                meta,
+                FpStrategy::Simple,
+                &[8],
+                &[1],
+                1,
+                LOOKUP_BITS,
+                BITS,
+                LIMBS,
+                modulus::<Fq>(),
+                0,
+                K as usize,
+            )
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), plonk::Error> {
+            config.range.load_lookup_table(&mut layouter)?;
+
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let ctx = config.new_context(region);
+
+                    let loader = Halo2Loader::new(EccChip::construct(config.clone()), ctx);
+                    let accumulator = KzgAccumulator::new(
+                        loader.assign_ec_point(self.lhs),
+                        loader.assign_ec_point(self.rhs),
+                    );
+
+                    loader.start_cost_metering("Pairing check");
+                    <As as AccumulationDecider<_, _>>::decide(&self.dk, accumulator)
+                        .map_err(|_| plonk::Error::Synthesis)?;
+                    loader.end_cost_metering();
+                    loader.print_row_metering();
+
+                    config.range.finalize(&mut loader.ctx_mut());
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn circuit(valid: bool) -> DeciderCircuit {
+        let params = ParamsKZG::<Bn256>::setup(1, ChaCha20Rng::from_seed(Default::default()));
+        let dk = (params.get_g()[0], params.g2(), params.s_g2()).into();
+        let [g1, s_g1] = [params.get_g()[0], params.get_g()[1]];
+        let lhs = if valid { s_g1 } else { g1 };
+        DeciderCircuit {
+            dk,
+            lhs: Value::known(lhs),
+            rhs: Value::known(g1),
+        }
+    }
+
+    #[test]
+    #[ignore = "cause it takes several minutes to run"]
+    fn test_kzg_decider_halo2_loader() {
+        MockProver::run(K, &circuit(true), Vec::new())
+            .unwrap()
+            .assert_satisfied();
+        assert!(MockProver::run(K, &circuit(false), Vec::new())
+            .unwrap()
+            .verify()
+            .is_err());
+    }
+}
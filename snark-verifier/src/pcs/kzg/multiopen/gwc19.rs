@@ -166,10 +166,19 @@ where
     type Input = Vec<Query<M::Scalar>>;
 
     fn estimate_cost(queries: &Vec<Query<M::Scalar>>) -> Cost {
-        let num_w = query_sets(queries).len();
+        let sets = query_sets(queries);
+        let num_w = sets.len();
+        let num_query = queries.len();
+        let max_num_poly = sets.iter().map(|set| set.polys.len()).max().unwrap_or(1);
         Cost {
             num_commitment: num_w,
             num_msm: num_w,
+            num_scalar_add: 2 * num_query + num_w,
+            num_scalar_mul: num_w.saturating_sub(1)
+                + (max_num_poly - 1)
+                + 3 * num_query
+                + 3 * num_w,
+            num_squeeze: 2,
             ..Default::default()
         }
     }
@@ -0,0 +1,465 @@
+//! Multilinear polynomial commitment scheme [Zeromorph] on top of KZG.
+//!
+//! A multilinear polynomial $f$ of $n$ variables is committed as the univariate
+//! polynomial $\hat{f}(X) = \sum_{i} f(i_0, ..., i_{n-1}) X^i$, where $i_k$ is
+//! the $k$-th bit of $i$.
+//!
+//! The degree check of quotients $\hat{q}_k$ is batched into $\hat{q}$ by
+//! shifting each of them to degree $2^n - 1$, which only holds when the SRS
+//! has exactly $2^n$ powers on G1, so the SRS size must be $2^{num\_var}$ for
+//! each `num_var` to verify. There is no separate degree check with an extra
+//! G2 element, so proving with a larger SRS is unsound.
+//!
+//! [Zeromorph]: https://eprint.iacr.org/2023/917
+
+use crate::{
+    cost::{Cost, CostEstimation},
+    loader::{LoadedScalar, Loader, ScalarLoader},
+    pcs::{
+        kzg::{KzgAccumulator, KzgAs, KzgSuccinctVerifyingKey},
+        MultilinearPolynomialCommitmentScheme, MultilinearQuery,
+    },
+    util::{
+        arithmetic::{powers, CurveAffine, Field, MultiMillerLoop, PrimeField},
+        msm::{multi_scalar_multiplication, Msm},
+        transcript::{TranscriptRead, TranscriptWrite},
+        Itertools,
+    },
+    Error,
+};
+use std::iter;
+
+/// Verifier of non-hiding [Zeromorph](https://eprint.iacr.org/2023/917) with
+/// all queried polynomials evaluated at the same point.
+///
+/// The trusted-setup should have exactly $2^{num\_var}$ powers on G1, see the
+/// module documentation.
+#[derive(Clone, Debug)]
+pub struct Zeromorph;
+
+impl<M, L> MultilinearPolynomialCommitmentScheme<M::G1Affine, L> for KzgAs<M, Zeromorph>
+where
+    M: MultiMillerLoop,
+    M::Scalar: PrimeField,
+    L: Loader<M::G1Affine>,
+{
+    type VerifyingKey = KzgSuccinctVerifyingKey<M::G1Affine>;
+    type Proof = ZeromorphProof<M::G1Affine, L>;
+    type Output = KzgAccumulator<M::G1Affine, L>;
+
+    fn read_proof<T>(
+        _: &Self::VerifyingKey,
+        num_var: usize,
+        _: &[MultilinearQuery],
+        transcript: &mut T,
+    ) -> Result<Self::Proof, Error>
+    where
+        T: TranscriptRead<M::G1Affine, L>,
+    {
+        ZeromorphProof::read(num_var, transcript)
+    }
+
+    fn verify(
+        svk: &Self::VerifyingKey,
+        commitments: &[Msm<M::G1Affine, L>],
+        point: &[L::LoadedScalar],
+        queries: &[MultilinearQuery<L::LoadedScalar>],
+        proof: &Self::Proof,
+    ) -> Result<Self::Output, Error> {
+        let num_var = point.len();
+        if proof.q_hats.len() != num_var {
+            return Err(Error::InvalidProtocol(format!(
+                "Zeromorph proof for {} variables but point has {num_var}",
+                proof.q_hats.len()
+            )));
+        }
+
+        let loader = proof.x.loader();
+        let powers_of_rho = proof.rho.powers(queries.len());
+        let (f, v) = {
+            let f = queries
+                .iter()
+                .zip(powers_of_rho.iter())
+                .map(|(query, power_of_rho)| commitments[query.poly].clone() * power_of_rho)
+                .sum::<Msm<_, _>>();
+            let v = loader.sum_products(
+                &queries
+                    .iter()
+                    .zip(powers_of_rho.iter())
+                    .map(|(query, power_of_rho)| (power_of_rho, &query.eval))
+                    .collect_vec(),
+            );
+            (f, v)
+        };
+
+        let (squares_of_x, offsets_of_x, phis) = x_terms(&proof.x, num_var);
+        let powers_of_y = proof.y.powers(num_var);
+        let q_hat_scalars = (0..num_var).map(|k| {
+            let z_scalar = loader.sum_products_with_coeff(&[
+                (M::Scalar::ONE, &squares_of_x[k], &phis[k + 1]),
+                (-M::Scalar::ONE, &point[k], &phis[k]),
+            ]);
+            -loader.sum_products(&[(&powers_of_y[k], &offsets_of_x[k]), (&proof.z, &z_scalar)])
+        });
+
+        let lhs = Msm::base(&proof.q_hat)
+            + (f - Msm::constant(v * &phis[0])) * &proof.z
+            + proof
+                .q_hats
+                .iter()
+                .zip(q_hat_scalars)
+                .map(|(q_hat, scalar)| Msm::base(q_hat) * &scalar)
+                .sum::<Msm<_, _>>()
+            + Msm::base(&proof.pi) * &proof.x;
+
+        Ok(KzgAccumulator::new(
+            lhs.evaluate(Some(svk.g)),
+            proof.pi.clone(),
+        ))
+    }
+}
+
+/// Returns $x^{2^k}$, $x^{2^n - 2^k}$ and
+/// $\Phi_{n-k}(x^{2^k}) = \prod_{i=k}^{n-1} (1 + x^{2^i})$ for $k$ in $0..=n$,
+/// which are computed without any inversion.
+fn x_terms<F, T>(x: &T, num_var: usize) -> (Vec<T>, Vec<T>, Vec<T>)
+where
+    F: PrimeField,
+    T: LoadedScalar<F>,
+{
+    let one = x.loader().load_one();
+    let squares_of_x = iter::successors(Some(x.clone()), |x| Some(x.square()))
+        .take(num_var + 1)
+        .collect_vec();
+    let mut offsets_of_x = vec![one.clone(); num_var + 1];
+    let mut phis = vec![one.clone(); num_var + 1];
+    for k in (0..num_var).rev() {
+        offsets_of_x[k] = offsets_of_x[k + 1].clone() * &squares_of_x[k];
+        phis[k] = phis[k + 1].clone() * &(squares_of_x[k].clone() + &one);
+    }
+    (squares_of_x, offsets_of_x, phis)
+}
+
+/// Structured proof of [`Zeromorph`].
+#[derive(Clone, Debug)]
+pub struct ZeromorphProof<C, L>
+where
+    C: CurveAffine,
+    L: Loader<C>,
+{
+    rho: L::LoadedScalar,
+    q_hats: Vec<L::LoadedEcPoint>,
+    y: L::LoadedScalar,
+    q_hat: L::LoadedEcPoint,
+    x: L::LoadedScalar,
+    z: L::LoadedScalar,
+    pi: L::LoadedEcPoint,
+}
+
+impl<C, L> ZeromorphProof<C, L>
+where
+    C: CurveAffine,
+    L: Loader<C>,
+{
+    fn read<T>(num_var: usize, transcript: &mut T) -> Result<Self, Error>
+    where
+        T: TranscriptRead<C, L>,
+    {
+        let rho = transcript.squeeze_challenge();
+        let q_hats = transcript.read_n_ec_points(num_var)?;
+        let y = transcript.squeeze_challenge();
+        let q_hat = transcript.read_ec_point()?;
+        let x = transcript.squeeze_challenge();
+        let z = transcript.squeeze_challenge();
+        let pi = transcript.read_ec_point()?;
+        Ok(ZeromorphProof {
+            rho,
+            q_hats,
+            y,
+            q_hat,
+            x,
+            z,
+            pi,
+        })
+    }
+}
+
+/// Zeromorph proving key, which is the powers of trusted-setup secret on G1.
+///
+/// It supports only polynomials of exactly [`ZeromorphProvingKey::num_var`]
+/// variables, since the degree check relies on the SRS size.
+#[derive(Clone, Debug)]
+pub struct ZeromorphProvingKey<C: CurveAffine> {
+    /// $[s^i]_1$ for $i$ in $0..2^n$.
+    pub g: Vec<C>,
+}
+
+impl<C: CurveAffine> ZeromorphProvingKey<C> {
+    /// Initialize a [`ZeromorphProvingKey`].
+    pub fn new(g: Vec<C>) -> Self {
+        assert!(g.len().is_power_of_two());
+        Self { g }
+    }
+
+    /// Returns number of variables supported.
+    pub fn num_var(&self) -> usize {
+        self.g.len().trailing_zeros() as usize
+    }
+
+    /// Returns [`KzgSuccinctVerifyingKey`].
+    pub fn svk(&self) -> KzgSuccinctVerifyingKey<C> {
+        KzgSuccinctVerifyingKey::new(self.g[0])
+    }
+
+    /// Commit to a multilinear polynomial given its evaluations on the boolean
+    /// hypercube.
+    pub fn commit(&self, evals: &[C::Scalar]) -> C {
+        assert!(evals.len() <= self.g.len());
+        multi_scalar_multiplication(evals, &self.g[..evals.len()]).into()
+    }
+}
+
+impl<C: CurveAffine> ZeromorphProvingKey<C> {
+    #[cfg(test)]
+    pub(crate) fn setup<M, R>(
+        num_var: usize,
+        mut rng: R,
+    ) -> (Self, crate::pcs::kzg::KzgDecidingKey<M>)
+    where
+        M: MultiMillerLoop<G1Affine = C, Scalar = C::Scalar>,
+        R: rand::Rng,
+    {
+        use crate::util::arithmetic::{Curve, PrimeCurveAffine};
+
+        let s = C::Scalar::random(&mut rng);
+        let g = powers(s)
+            .take(1 << num_var)
+            .map(|power_of_s| (C::generator() * power_of_s).to_affine())
+            .collect_vec();
+        let g2 = M::G2Affine::generator();
+        let s_g2 = (g2 * s).to_affine();
+        (Self::new(g), (g[0], g2, s_g2).into())
+    }
+}
+
+impl Zeromorph {
+    /// Create a [`Zeromorph`] proof of multilinear polynomials `polys` given as
+    /// evaluations on the boolean hypercube, all evaluated at `point`. The
+    /// evaluations are expected to have been written to transcript already.
+    pub fn create_proof<C, T>(
+        pk: &ZeromorphProvingKey<C>,
+        polys: &[&[C::Scalar]],
+        point: &[C::Scalar],
+        transcript: &mut T,
+    ) -> Result<(), Error>
+    where
+        C: CurveAffine,
+        T: TranscriptWrite<C>,
+    {
+        let num_var = point.len();
+        let n = 1 << num_var;
+        assert_eq!(num_var, pk.num_var());
+        assert!(polys.iter().all(|poly| poly.len() == n));
+
+        let rho = transcript.squeeze_challenge();
+        let f = polys.iter().zip(powers(rho)).fold(
+            vec![C::Scalar::ZERO; n],
+            |mut f, (poly, power_of_rho)| {
+                f.iter_mut()
+                    .zip(poly.iter())
+                    .for_each(|(f, eval)| *f += power_of_rho * eval);
+                f
+            },
+        );
+
+        let (qs, v) = {
+            let mut qs = vec![Vec::new(); num_var];
+            let mut remainder = f.clone();
+            for k in (0..num_var).rev() {
+                let (lo, hi) = remainder.split_at(1 << k);
+                qs[k] = hi
+                    .iter()
+                    .zip(lo.iter())
+                    .map(|(hi, lo)| *hi - lo)
+                    .collect_vec();
+                remainder = lo
+                    .iter()
+                    .zip(qs[k].iter())
+                    .map(|(lo, q)| *lo + point[k] * q)
+                    .collect_vec();
+            }
+            (qs, remainder[0])
+        };
+        for q in qs.iter() {
+            transcript.write_ec_point(pk.commit(q))?;
+        }
+
+        let y = transcript.squeeze_challenge();
+        let powers_of_y = powers(y).take(num_var).collect_vec();
+        let q_hat = qs.iter().zip(powers_of_y.iter()).enumerate().fold(
+            vec![C::Scalar::ZERO; n],
+            |mut q_hat, (k, (q, power_of_y))| {
+                q_hat[n - (1 << k)..]
+                    .iter_mut()
+                    .zip(q.iter())
+                    .for_each(|(q_hat, q)| *q_hat += *power_of_y * q);
+                q_hat
+            },
+        );
+        transcript.write_ec_point(pk.commit(&q_hat))?;
+
+        let x = transcript.squeeze_challenge();
+        let z = transcript.squeeze_challenge();
+        let (squares_of_x, offsets_of_x, phis) = x_terms(&x, num_var);
+
+        // ζ_x + z·Z_x, which vanishes at x.
+        let mut h = q_hat;
+        h.iter_mut().zip(f.iter()).for_each(|(h, f)| *h += z * f);
+        h[0] -= z * v * phis[0];
+        for (k, q) in qs.iter().enumerate() {
+            let scalar = powers_of_y[k] * offsets_of_x[k]
+                + z * (squares_of_x[k] * phis[k + 1] - point[k] * phis[k]);
+            h.iter_mut()
+                .zip(q.iter())
+                .for_each(|(h, q)| *h -= scalar * q);
+        }
+        let pi = {
+            let mut quotient = vec![C::Scalar::ZERO; n - 1];
+            let mut carry = C::Scalar::ZERO;
+            for i in (1..n).rev() {
+                carry = h[i] + x * carry;
+                quotient[i - 1] = carry;
+            }
+            debug_assert_eq!(h[0] + x * carry, C::Scalar::ZERO);
+            pk.commit(&quotient)
+        };
+        transcript.write_ec_point(pi)?;
+
+        Ok(())
+    }
+}
+
+impl<M> CostEstimation<M::G1Affine> for KzgAs<M, Zeromorph>
+where
+    M: MultiMillerLoop,
+    M::Scalar: PrimeField,
+{
+    type Input = (usize, Vec<MultilinearQuery>);
+
+    fn estimate_cost((num_var, queries): &(usize, Vec<MultilinearQuery>)) -> Cost {
+        let num_query = queries.len();
+        Cost {
+            num_commitment: num_var + 2,
+            num_msm: num_query + num_var + 3,
+            num_scalar_add: num_query + 3 * num_var,
+            num_scalar_mul: 2 * num_query + 9 * num_var + 2,
+            num_squeeze: 4,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(all(test, feature = "system_halo2"))]
+mod test {
+    use crate::{
+        loader::native::NativeLoader,
+        pcs::{
+            kzg::{KzgAs, Zeromorph, ZeromorphProvingKey},
+            AccumulationDecider, MultilinearPolynomialCommitmentScheme, MultilinearQuery,
+        },
+        util::{
+            arithmetic::Field,
+            msm::Msm,
+            transcript::{TranscriptRead, TranscriptWrite},
+            Itertools,
+        },
+    };
+    use halo2_curves::bn256::{Bn256, Fr};
+    use halo2_proofs::transcript::{
+        Blake2bRead, Blake2bWrite, TranscriptReadBuffer, TranscriptWriterBuffer,
+    };
+    use rand::rngs::OsRng;
+    use std::iter;
+
+    type Pcs = KzgAs<Bn256, Zeromorph>;
+
+    fn evaluate(poly: &[Fr], point: &[Fr]) -> Fr {
+        point.iter().rev().fold(poly.to_vec(), |poly, x_k| {
+            let (lo, hi) = poly.split_at(poly.len() / 2);
+            lo.iter()
+                .zip(hi.iter())
+                .map(|(lo, hi)| *lo + *x_k * (*hi - lo))
+                .collect()
+        })[0]
+    }
+
+    #[test]
+    fn test_zeromorph() {
+        let num_var = 5;
+        let (pk, dk) = ZeromorphProvingKey::setup::<Bn256, _>(num_var, OsRng);
+
+        for num_poly in [1, 3] {
+            let polys = iter::repeat_with(|| {
+                iter::repeat_with(|| Fr::random(OsRng))
+                    .take(1 << num_var)
+                    .collect_vec()
+            })
+            .take(num_poly)
+            .collect_vec();
+            let point = iter::repeat_with(|| Fr::random(OsRng))
+                .take(num_var)
+                .collect_vec();
+            let commitments = polys.iter().map(|poly| pk.commit(poly)).collect_vec();
+            let evals = polys
+                .iter()
+                .map(|poly| evaluate(poly, &point))
+                .collect_vec();
+
+            let proof = {
+                let mut transcript = Blake2bWrite::init(Vec::new());
+                for eval in evals.iter() {
+                    transcript.write_scalar(*eval).unwrap();
+                }
+                Zeromorph::create_proof(
+                    &pk,
+                    &polys.iter().map(Vec::as_slice).collect_vec(),
+                    &point,
+                    &mut transcript,
+                )
+                .unwrap();
+                transcript.finalize()
+            };
+
+            let queries = (0..num_poly).map(MultilinearQuery::new).collect_vec();
+            for tampered in [false, true] {
+                let mut transcript = Blake2bRead::init(proof.as_slice());
+                let mut evals = transcript.read_n_scalars(num_poly).unwrap();
+                if tampered {
+                    evals[0] += Fr::ONE;
+                }
+                let proof =
+                    <Pcs as MultilinearPolynomialCommitmentScheme<_, NativeLoader>>::read_proof(
+                        &pk.svk(),
+                        num_var,
+                        &queries,
+                        &mut transcript,
+                    )
+                    .unwrap();
+                let accumulator = <Pcs as MultilinearPolynomialCommitmentScheme<_, _>>::verify(
+                    &pk.svk(),
+                    &commitments.iter().map(Msm::base).collect_vec(),
+                    &point,
+                    &queries
+                        .iter()
+                        .cloned()
+                        .zip(evals)
+                        .map(|(query, eval)| query.with_evaluation(eval))
+                        .collect_vec(),
+                    &proof,
+                )
+                .unwrap();
+                assert_eq!(Pcs::decide(&dk, accumulator).is_ok(), !tampered);
+            }
+        }
+    }
+}
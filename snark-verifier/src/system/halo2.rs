@@ -6,8 +6,8 @@ use crate::{
         Itertools,
     },
     verifier::plonk::protocol::{
-        CommonPolynomial, Expression, InstanceCommittingKey, PlonkProtocol, Query,
-        QuotientPolynomial,
+        CommonPolynomial, Expression, InstanceCommittingKey, LinearizationStrategy, PlonkProtocol,
+        Query, QuotientPolynomial,
     },
 };
 use halo2_proofs::{
@@ -16,7 +16,7 @@ use halo2_proofs::{
     transcript::{EncodedChallenge, Transcript},
 };
 use num_integer::Integer;
-use std::{io, iter, mem::size_of};
+use std::{collections::BTreeSet, io, iter, mem::size_of};
 
 pub mod strategy;
 pub mod transcript;
@@ -33,6 +33,7 @@ pub struct Config {
     num_proof: usize,
     num_instance: Vec<usize>,
     accumulator_indices: Option<Vec<(usize, usize)>>,
+    linearization: Option<LinearizationStrategy>,
 }
 
 impl Config {
@@ -89,6 +90,18 @@ impl Config {
         self.accumulator_indices = accumulator_indices;
         self
     }
+
+    /// Set `linearization`
+    ///
+    /// Note that prover of [`halo2_proofs`] always sends all evaluations and
+    /// opens the quotient polynomial, so its proofs can only be verified with
+    /// `linearization` set to `None`. A linearized protocol requires a prover
+    /// that sends [`PlonkProtocol::evaluations`] and opens
+    /// [`PlonkProtocol::queries`] accordingly.
+    pub fn with_linearization(mut self, linearization: Option<LinearizationStrategy>) -> Self {
+        self.linearization = linearization;
+        self
+    }
 }
 
 /// Convert a [`VerifyingKey`] of [`halo2_proofs`] into [`PlonkProtocol`].
@@ -109,6 +122,7 @@ where
         num_proof,
         num_instance,
         accumulator_indices,
+        linearization,
     } = config;
 
     let k = params.k() as usize;
@@ -124,7 +138,7 @@ where
 
     let polynomials = &Polynomials::new(cs, zk, query_instance, num_instance, num_proof);
 
-    let evaluations = iter::empty()
+    let evaluations: Vec<_> = iter::empty()
         .chain((0..num_proof).flat_map(move |t| polynomials.instance_queries(t)))
         .chain((0..num_proof).flat_map(move |t| polynomials.advice_queries(t)))
         .chain(polynomials.fixed_queries())
@@ -134,7 +148,7 @@ where
         .chain((0..num_proof).flat_map(move |t| polynomials.lookup_queries::<true>(t)))
         .collect();
 
-    let queries = (0..num_proof)
+    let queries: Vec<_> = (0..num_proof)
         .flat_map(|t| {
             iter::empty()
                 .chain(polynomials.instance_queries(t))
@@ -148,6 +162,18 @@ where
         .chain(polynomials.random_query())
         .collect();
 
+    let quotient = polynomials.quotient();
+    let (evaluations, queries) = match linearization {
+        Some(linearization) => linearize(
+            linearization,
+            &quotient.numerator,
+            polynomials.quotient_query(),
+            evaluations,
+            queries,
+        ),
+        None => (evaluations, queries),
+    };
+
     let transcript_initial_state = transcript_initial_state::<C>(vk);
 
     let instance_committing_key = query_instance.then(|| {
@@ -173,14 +199,61 @@ where
         num_challenge: polynomials.num_challenge(),
         evaluations,
         queries,
-        quotient: polynomials.quotient(),
+        quotient,
         transcript_initial_state: Some(transcript_initial_state),
         instance_committing_key,
-        linearization: None,
+        linearization,
         accumulator_indices,
     }
 }
 
+/// Returns `evaluations` and `queries` with queries of rotation current that
+/// could be linearized removed, and query of linearization polynomial added if
+/// it requires an extra evaluation.
+///
+/// Queries are linearized greedily in order of `evaluations`, as long as the
+/// numerator stays linear in linearized ones.
+fn linearize<F: PrimeField>(
+    linearization: LinearizationStrategy,
+    numerator: &Expression<F>,
+    quotient_query: Query,
+    evaluations: Vec<Query>,
+    queries: Vec<Query>,
+) -> (Vec<Query>, Vec<Query>) {
+    let used_query = numerator.used_query();
+    let linearized = evaluations
+        .iter()
+        .filter(|query| query.rotation == Rotation::cur() && used_query.contains(query))
+        .fold(BTreeSet::new(), |mut linearized, query| {
+            linearized.insert(*query);
+            if numerator.degree_in(&linearized) > 1 {
+                linearized.remove(query);
+            }
+            linearized
+        });
+
+    let mut evaluations = evaluations
+        .into_iter()
+        .filter(|query| !linearized.contains(query))
+        .collect_vec();
+    let mut queries = queries
+        .into_iter()
+        .filter(|query| !linearized.contains(query))
+        .collect_vec();
+
+    if let LinearizationStrategy::WithoutConstant = linearization {
+        let linearization_query = Query::new(quotient_query.poly + 1, Rotation::cur());
+        evaluations.push(linearization_query);
+        let idx = queries
+            .iter()
+            .position(|query| *query == quotient_query)
+            .unwrap();
+        queries.insert(idx + 1, linearization_query);
+    }
+
+    (evaluations, queries)
+}
+
 impl From<poly::Rotation> for Rotation {
     fn from(rotation: poly::Rotation) -> Rotation {
         Rotation(rotation.0)
@@ -737,11 +810,7 @@ impl<'a, F: PrimeField> Polynomials<'a, F> {
             })
             .collect_vec();
         let numerator = Expression::DistributePowers(constraints, self.alpha().into());
-        QuotientPolynomial {
-            num_chunk: self.degree - 1,
-            chunk_degree: 1,
-            numerator,
-        }
+        QuotientPolynomial::new(1, self.degree - 1, numerator)
     }
 
     fn accumulator_indices(
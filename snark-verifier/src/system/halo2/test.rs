@@ -13,7 +13,7 @@ use std::{fs, io::Cursor};
 
 mod circuit;
 mod ipa;
-mod kzg;
+pub(crate) mod kzg;
 
 pub use circuit::{
     maingate::{MainGateWithRange, MainGateWithRangeConfig},
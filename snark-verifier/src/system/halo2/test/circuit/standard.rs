@@ -61,15 +61,30 @@ impl StandardPlonkConfig {
 }
 
 #[derive(Clone, Default)]
-pub struct StandardPlonk<F>(F);
+pub struct StandardPlonk<F> {
+    instance: F,
+    without_constant: bool,
+}
 
 impl<F: PrimeField> StandardPlonk<F> {
     pub fn rand<R: RngCore>(mut rng: R) -> Self {
-        Self(F::from(rng.next_u32() as u64))
+        Self {
+            instance: F::from(rng.next_u32() as u64),
+            without_constant: false,
+        }
+    }
+
+    /// Same as `rand`, but leaves the `constant` column unassigned, so its
+    /// commitment is the identity.
+    pub fn rand_without_constant<R: RngCore>(rng: R) -> Self {
+        Self {
+            without_constant: true,
+            ..Self::rand(rng)
+        }
     }
 
     pub fn instances(&self) -> Vec<Vec<F>> {
-        vec![vec![self.0]]
+        vec![vec![self.instance]]
     }
 }
 
@@ -80,7 +95,10 @@ impl<F: PrimeField> Circuit<F> for StandardPlonk<F> {
     type Params = ();
 
     fn without_witnesses(&self) -> Self {
-        Self::default()
+        Self {
+            without_constant: self.without_constant,
+            ..Self::default()
+        }
     }
 
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
@@ -96,21 +114,31 @@ impl<F: PrimeField> Circuit<F> for StandardPlonk<F> {
         layouter.assign_region(
             || "",
             |mut region| {
-                region.assign_advice(|| "", config.a, 0, || Value::known(self.0))?;
+                region.assign_advice(|| "", config.a, 0, || Value::known(self.instance))?;
                 region.assign_fixed(|| "", config.q_a, 0, || Value::known(-F::ONE))?;
 
-                region.assign_advice(|| "", config.a, 1, || Value::known(-F::from(5)))?;
-                for (column, idx) in [
-                    config.q_a,
-                    config.q_b,
-                    config.q_c,
-                    config.q_ab,
-                    config.constant,
-                ]
-                .iter()
-                .zip(1..)
-                {
-                    region.assign_fixed(|| "", *column, 1, || Value::known(F::from(idx)))?;
+                if self.without_constant {
+                    region.assign_advice(|| "", config.a, 1, || Value::known(F::ZERO))?;
+                    for (column, idx) in [config.q_a, config.q_b, config.q_c, config.q_ab]
+                        .iter()
+                        .zip(1..)
+                    {
+                        region.assign_fixed(|| "", *column, 1, || Value::known(F::from(idx)))?;
+                    }
+                } else {
+                    region.assign_advice(|| "", config.a, 1, || Value::known(-F::from(5)))?;
+                    for (column, idx) in [
+                        config.q_a,
+                        config.q_b,
+                        config.q_c,
+                        config.q_ab,
+                        config.constant,
+                    ]
+                    .iter()
+                    .zip(1..)
+                    {
+                        region.assign_fixed(|| "", *column, 1, || Value::known(F::from(idx)))?;
+                    }
                 }
 
                 let a = region.assign_advice(|| "", config.a, 2, || Value::known(F::ONE))?;
@@ -24,7 +24,15 @@ macro_rules! halo2_kzg_evm_verify {
         use halo2_proofs::poly::commitment::ParamsProver;
         use std::rc::Rc;
         use $crate::{
-            loader::evm::{compile_yul, deploy_and_call, encode_calldata, EvmLoader},
+            cost::CostEstimation,
+            loader::{
+                evm::{
+                    compile_yul, deploy_and_call, deploy_and_call_with_error_messages,
+                    deploy_and_call_with_output, encode_abi_calldata, encode_calldata,
+                    estimate_gas, modulus, EvmLoader, EvmLoaderConfig, U256,
+                },
+                native::NativeLoader,
+            },
             system::halo2::{
                 test::kzg::{BITS, LIMBS},
                 transcript::evm::EvmTranscript,
@@ -33,8 +41,7 @@ macro_rules! halo2_kzg_evm_verify {
             verifier::SnarkVerifier,
         };
 
-        let loader = EvmLoader::new::<Fq, Fr>();
-        let deployment_code = {
+        let gen_deployment_code = |loader: Rc<EvmLoader>| {
             let vk = ($params.get_g()[0].into(), $params.g2(), $params.s_g2()).into();
             let protocol = $protocol.loaded(&loader);
             let mut transcript = EvmTranscript::<_, Rc<EvmLoader>, _, _>::new(&loader);
@@ -50,16 +57,147 @@ macro_rules! halo2_kzg_evm_verify {
 
             compile_yul(&loader.yul_code())
         };
+        let deployment_code = gen_deployment_code(EvmLoader::new::<Fq, Fr>());
+
+        // Verifier with ABI should return `true` for valid proof, `false` for
+        // invalid proof, and revert for malformed calldata.
+        {
+            let deployment_code = gen_deployment_code(EvmLoader::new_with_abi::<Fq, Fr>());
+            let verify = |calldata: Vec<u8>| {
+                deploy_and_call_with_output(deployment_code.clone(), calldata)
+                    .map(|(_, output)| U256::from_be_slice(&output))
+            };
+
+            let calldata = encode_abi_calldata($instances, &$proof);
+            assert_eq!(verify(calldata.clone()).unwrap(), U256::from(1));
+
+            let mut proof = $proof.clone();
+            proof[0] = proof[0].wrapping_add(1);
+            let invalid_calldata = encode_abi_calldata($instances, &proof);
+            assert_eq!(verify(invalid_calldata).unwrap(), U256::from(0));
 
-        let calldata = encode_calldata($instances, &$proof);
+            let mut wrong_selector = calldata.clone();
+            wrong_selector[0] ^= 1;
+            assert!(verify(wrong_selector).is_err());
+            let mut truncated = calldata;
+            truncated.truncate(truncated.len() - 0x20);
+            assert!(verify(truncated).is_err());
+        }
+
+        let vk = ($params.get_g()[0].into(), $params.g2(), $params.s_g2()).into();
+        let calldata =
+            encode_calldata::<_, $plonk_verifier>(&vk, $protocol, $instances, &$proof).unwrap();
         let gas_cost = deploy_and_call(deployment_code.clone(), calldata.clone()).unwrap();
         println!("Total gas cost: {}", gas_cost);
+        println!(
+            "Estimated gas cost: {}",
+            estimate_gas(<$plonk_verifier>::estimate_cost($protocol))
+        );
 
-        let mut calldata = calldata;
-        calldata[0] = calldata[0].wrapping_add(1);
-        assert!(deploy_and_call(deployment_code, calldata)
-            .unwrap_err()
-            .starts_with("Contract call transaction reverts"))
+        // Reusing memory of dead values should save both gas and code size.
+        {
+            let deployment_code_without_reuse =
+                gen_deployment_code(EvmLoader::new_with_config::<Fq, Fr>(EvmLoaderConfig {
+                    reuse_memory: false,
+                    ..Default::default()
+                }));
+            let gas_cost_without_reuse =
+                deploy_and_call(deployment_code_without_reuse.clone(), calldata.clone()).unwrap();
+            println!("Total gas cost without reusing memory: {}", gas_cost_without_reuse);
+            assert!(gas_cost < gas_cost_without_reuse);
+            assert!(deployment_code.len() < deployment_code_without_reuse.len());
+        }
+
+        {
+            let mut calldata = calldata.clone();
+            calldata[0] = calldata[0].wrapping_add(1);
+            assert!(deploy_and_call(deployment_code.clone(), calldata)
+                .unwrap_err()
+                .starts_with("Contract call transaction reverts"));
+        }
+
+        // Verifier in debug mode should revert with error code of the first
+        // failing check, which is decoded into message naming the element.
+        {
+            let loader = EvmLoader::new_with_config::<Fq, Fr>(EvmLoaderConfig {
+                debug: true,
+                ..Default::default()
+            });
+            let deployment_code = gen_deployment_code(loader.clone());
+            let error_messages = loader.error_messages();
+            let verify = |calldata: Vec<u8>| {
+                deploy_and_call_with_error_messages(
+                    deployment_code.clone(),
+                    calldata,
+                    &error_messages,
+                )
+            };
+
+            assert!(verify(calldata.clone()).is_ok());
+
+            let mut extended = calldata.clone();
+            extended.push(0);
+            assert!(verify(extended)
+                .unwrap_err()
+                .contains("Unexpected calldata length"));
+
+            let proof_offset = 0x20 * $instances.iter().map(Vec::len).sum::<usize>();
+            let mut invalid_ec_point = calldata.clone();
+            invalid_ec_point[proof_offset + 0x3f] ^= 1;
+            assert!(verify(invalid_ec_point).unwrap_err().ends_with(&format!(
+                "Invalid elliptic curve point at proof offset 0x0 (calldata offset {proof_offset:#x})"
+            )));
+        }
+
+        // Non-canonical encoding of first evaluation, which is the same field
+        // element after reduction, should be rejected by both verifiers.
+        {
+            let proof_offset = 0x20 * $instances.iter().map(Vec::len).sum::<usize>();
+            let offset = proof_offset
+                + 0x40
+                    * ($protocol.num_witness.iter().sum::<usize>()
+                        + $protocol.quotient.num_chunk());
+            let mut calldata = calldata;
+            let value = U256::from_be_slice(&calldata[offset..offset + 0x20]) + modulus::<Fr>();
+            calldata[offset..offset + 0x20].copy_from_slice(&value.to_be_bytes::<32>());
+
+            assert!(encode_calldata::<_, $plonk_verifier>(
+                &vk,
+                $protocol,
+                $instances,
+                &calldata[proof_offset..]
+            )
+            .is_err());
+            let mut transcript =
+                EvmTranscript::<_, NativeLoader, _, _>::new(&calldata[proof_offset..]);
+            assert!(
+                <$plonk_verifier>::read_proof(&vk, $protocol, $instances, &mut transcript).is_err()
+            );
+            assert!(deploy_and_call(deployment_code, calldata)
+                .unwrap_err()
+                .starts_with("Contract call transaction reverts"));
+        }
+
+        // Invalid elliptic curve point or trailing bytes in proof should also be
+        // rejected when encoding calldata.
+        {
+            let mut invalid_ec_point = $proof.clone();
+            invalid_ec_point[0x3f] ^= 1;
+            assert!(encode_calldata::<_, $plonk_verifier>(
+                &vk,
+                $protocol,
+                $instances,
+                &invalid_ec_point
+            )
+            .is_err());
+
+            let mut extended = $proof.clone();
+            extended.push(0);
+            assert!(
+                encode_calldata::<_, $plonk_verifier>(&vk, $protocol, $instances, &extended)
+                    .is_err()
+            );
+        }
     }};
 }
 
@@ -116,6 +254,12 @@ test!(
     halo2_kzg_config!(true, 1),
     StandardPlonk::rand(ChaCha20Rng::from_seed(Default::default()))
 );
+test!(
+    zk_standard_plonk_rand_without_constant,
+    9,
+    halo2_kzg_config!(true, 1),
+    StandardPlonk::rand_without_constant(ChaCha20Rng::from_seed(Default::default()))
+);
 test!(
     zk_main_gate_with_range_with_mock_kzg_accumulator,
     9,
@@ -138,3 +282,740 @@ test!(
     halo2_kzg_config!(true, 1, (0..4 * LIMBS).map(|idx| (0, idx)).collect()),
     kzg::halo2::Accumulation::two_snark_with_accumulator()
 );
+
+#[test]
+fn test_evm_transcript_identity_ec_point() {
+    use crate::{
+        loader::evm::{compile_yul, deploy_and_call, EvmLoader},
+        util::{arithmetic::PrimeCurveAffine, transcript::TranscriptRead},
+    };
+    use halo2_curves::bn256::{Fq, Fr};
+    use halo2_proofs::transcript::TranscriptWrite;
+    use std::rc::Rc;
+
+    let proof = {
+        let mut transcript = EvmTranscript::<G1Affine, NativeLoader, _, _>::new(Vec::new());
+        transcript.write_point(G1Affine::identity()).unwrap();
+        transcript.finalize()
+    };
+    assert_eq!(proof, vec![0; 0x40]);
+
+    let mut transcript = EvmTranscript::<G1Affine, NativeLoader, _, _>::new(proof.as_slice());
+    assert_eq!(
+        TranscriptRead::read_ec_point(&mut transcript).unwrap(),
+        G1Affine::identity()
+    );
+
+    let deployment_code = {
+        let loader = EvmLoader::new::<Fq, Fr>();
+        let mut transcript = EvmTranscript::<G1Affine, Rc<EvmLoader>, _, _>::new(&loader);
+        TranscriptRead::read_ec_point(&mut transcript).unwrap();
+        compile_yul(&loader.yul_code())
+    };
+    assert!(deploy_and_call(deployment_code.clone(), proof).is_ok());
+
+    let mut calldata = vec![0; 0x40];
+    calldata[0x3f] = 1;
+    assert!(deploy_and_call(deployment_code, calldata).is_err());
+}
+
+#[test]
+fn test_evm_transcript_compressed_ec_point() {
+    use crate::{
+        loader::evm::{compile_yul, deploy_and_call, fe_to_u256, EvmLoader, MemoryPtr},
+        util::{
+            arithmetic::{ec_point_to_coordinates, PrimeCurveAffine},
+            transcript::TranscriptRead,
+        },
+    };
+    use halo2_curves::{
+        bn256::{Fq, Fr},
+        group::{Curve, Group},
+    };
+    use halo2_proofs::transcript::TranscriptWrite;
+    use std::rc::Rc;
+
+    let mut rng = ChaCha20Rng::from_seed(Default::default());
+    let ec_points = [G1Affine::identity(), G1Affine::generator()]
+        .into_iter()
+        .chain(
+            std::iter::repeat_with(|| halo2_curves::bn256::G1::random(&mut rng).to_affine())
+                .take(8),
+        )
+        .collect::<Vec<_>>();
+
+    let proof = {
+        let mut transcript =
+            EvmTranscript::<G1Affine, NativeLoader, _, _>::new_with_compression(Vec::new(), true);
+        for ec_point in ec_points.iter() {
+            transcript.write_point(*ec_point).unwrap();
+        }
+        transcript.finalize()
+    };
+    assert_eq!(proof.len(), 0x20 * ec_points.len());
+    assert_eq!(proof[..0x20], [&[0x40][..], &[0; 0x1f]].concat());
+
+    let mut transcript =
+        EvmTranscript::<G1Affine, NativeLoader, _, _>::new_with_compression(proof.as_slice(), true);
+    for ec_point in ec_points.iter() {
+        assert_eq!(
+            TranscriptRead::read_ec_point(&mut transcript).unwrap(),
+            *ec_point
+        );
+    }
+
+    let deployment_code = {
+        let loader = EvmLoader::new::<Fq, Fr>();
+        let mut transcript =
+            EvmTranscript::<G1Affine, Rc<EvmLoader>, _, _>::new_with_compression(&loader, true);
+        let loaded = (0..ec_points.len())
+            .map(|_| TranscriptRead::read_ec_point(&mut transcript).unwrap())
+            .collect::<Vec<_>>();
+        for (loaded, ec_point) in loaded.iter().zip(ec_points.iter()) {
+            let [x, y] = ec_point_to_coordinates(ec_point).map(fe_to_u256);
+            let (x_ptr, y_ptr) = (MemoryPtr(loaded.ptr()), MemoryPtr(loaded.ptr() + 0x20));
+            loader.code_mut().runtime_append(format!(
+                "success := and(and(eq(mload({x_ptr}), {x}), eq(mload({y_ptr}), {y})), success)"
+            ));
+        }
+        compile_yul(&loader.yul_code())
+    };
+    assert!(deploy_and_call(deployment_code.clone(), proof.clone()).is_ok());
+
+    // Identity with sign bit set is non-canonical.
+    let mut calldata = proof.clone();
+    calldata[0] |= 0x80;
+    assert!(deploy_and_call(deployment_code.clone(), calldata.clone()).is_err());
+    let mut transcript = EvmTranscript::<G1Affine, NativeLoader, _, _>::new_with_compression(
+        calldata.as_slice(),
+        true,
+    );
+    assert!(TranscriptRead::read_ec_point(&mut transcript).is_err());
+
+    // Flipping sign bit of a non-identity point decodes to its negation.
+    let mut calldata = proof;
+    calldata[0x20] ^= 0x80;
+    assert!(deploy_and_call(deployment_code, calldata).is_err());
+}
+
+#[test]
+fn test_evm_compressed_and_hashed_accumulator_encoding() {
+    use crate::{
+        loader::{
+            evm::{compile_yul, deploy_and_call, encode_calldata_unchecked, EvmLoader},
+            EcPointLoader,
+        },
+        pcs::{
+            kzg::{CompressedEncoding, HashedEncoding, KzgAccumulator},
+            AccumulatorEncoding,
+        },
+        util::arithmetic::Field,
+    };
+    use halo2_curves::{
+        bn256::{Fq, Fr, G1},
+        group::{Curve, Group},
+    };
+    use halo2_proofs::transcript::TranscriptWrite;
+    use std::rc::Rc;
+
+    type Compressed = CompressedEncoding<LIMBS, BITS>;
+    type Hashed = HashedEncoding<LIMBS, BITS, 5, 4, 8, 60>;
+
+    // Verifier that decodes accumulator and checks it against the expected one.
+    fn verifier<AE>(
+        num_instance: usize,
+        accumulator: &KzgAccumulator<G1Affine, NativeLoader>,
+    ) -> Vec<u8>
+    where
+        AE: AccumulatorEncoding<
+            G1Affine,
+            Rc<EvmLoader>,
+            Accumulator = KzgAccumulator<G1Affine, Rc<EvmLoader>>,
+        >,
+    {
+        let loader = EvmLoader::new::<Fq, Fr>();
+        let mut transcript = EvmTranscript::<G1Affine, Rc<EvmLoader>, _, _>::new(&loader);
+        let instances = transcript.load_instances(vec![num_instance]);
+        let decoded =
+            AE::read_repr(&instances[0].iter().collect::<Vec<_>>(), &mut transcript).unwrap();
+        for (decoded, expected) in [
+            (decoded.lhs, accumulator.lhs),
+            (decoded.rhs, accumulator.rhs),
+        ] {
+            EcPointLoader::<G1Affine>::ec_point_assert_eq(
+                &loader,
+                "Decoded accumulator",
+                &decoded,
+                &loader.ec_point_load_const(&expected),
+            )
+            .unwrap();
+        }
+        compile_yul(&loader.yul_code())
+    }
+
+    let mut rng = ChaCha20Rng::from_seed(Default::default());
+    let accumulator = KzgAccumulator::<G1Affine, NativeLoader>::new(
+        G1::random(&mut rng).to_affine(),
+        G1::random(&mut rng).to_affine(),
+    );
+
+    let instances = Compressed::encode(&accumulator);
+    let deployment_code = verifier::<Compressed>(instances.len(), &accumulator);
+    let calldata = encode_calldata_unchecked(&[instances.clone()], &[]);
+    assert!(deploy_and_call(deployment_code.clone(), calldata).is_ok());
+
+    // Flipping sign decodes to the negation.
+    let mut tampered = instances.clone();
+    tampered[LIMBS] = Fr::ONE - tampered[LIMBS];
+    let calldata = encode_calldata_unchecked(&[tampered], &[]);
+    assert!(deploy_and_call(deployment_code.clone(), calldata).is_err());
+
+    let mut tampered = instances;
+    tampered[LIMBS] = Fr::from(2);
+    let calldata = encode_calldata_unchecked(&[tampered], &[]);
+    assert!(deploy_and_call(deployment_code, calldata).is_err());
+
+    let (digest, limbs) = Hashed::encode(&accumulator);
+    let proof = {
+        let mut transcript = EvmTranscript::<G1Affine, NativeLoader, _, _>::new(Vec::new());
+        for limb in limbs {
+            transcript.write_scalar(limb).unwrap();
+        }
+        transcript.finalize()
+    };
+    let deployment_code = verifier::<Hashed>(1, &accumulator);
+    let calldata = encode_calldata_unchecked(&[vec![digest]], &proof);
+    assert!(deploy_and_call(deployment_code.clone(), calldata).is_ok());
+
+    let mut tampered = proof.clone();
+    *tampered.last_mut().unwrap() ^= 1;
+    let calldata = encode_calldata_unchecked(&[vec![digest]], &tampered);
+    assert!(deploy_and_call(deployment_code.clone(), calldata).is_err());
+
+    let calldata = encode_calldata_unchecked(&[vec![digest + Fr::ONE]], &proof);
+    assert!(deploy_and_call(deployment_code, calldata).is_err());
+}
+
+#[test]
+fn test_shplonk_zk_standard_plonk_rand_compressed() {
+    use crate::{
+        cost::CostEstimation,
+        loader::evm::{
+            compile_yul, deploy_and_call, encode_calldata_with_compression, estimate_gas_cost,
+            estimate_gas_cost_compressed, EvmLoader,
+        },
+        util::Itertools,
+        verifier::SnarkVerifier,
+    };
+    use halo2_curves::bn256::{Fq, Fr};
+    use halo2_proofs::{
+        plonk::{create_proof, verify_proof},
+        poly::{
+            commitment::ParamsProver,
+            kzg::{commitment::KZGCommitmentScheme, strategy::SingleStrategy},
+            VerificationStrategy,
+        },
+    };
+    use std::rc::Rc;
+
+    type Plonk = PlonkVerifier<KzgAs<Bn256, Bdfg21>, LimbsEncoding<LIMBS, BITS>>;
+
+    let (params, pk, protocol, circuits) = halo2_kzg_prepare!(
+        9,
+        halo2_kzg_config!(true, 1),
+        StandardPlonk::rand(ChaCha20Rng::from_seed(Default::default()))
+    );
+    let instances = circuits[0].instances();
+    let proof = {
+        let instances = instances.iter().map(Vec::as_slice).collect_vec();
+        let mut transcript =
+            EvmTranscript::<G1Affine, NativeLoader, _, _>::new_with_compression(Vec::new(), true);
+        create_proof::<KZGCommitmentScheme<_>, ProverSHPLONK<_>, ChallengeEvm<_>, _, _, _>(
+            &params,
+            &pk,
+            &circuits,
+            &[instances.as_slice()],
+            ChaCha20Rng::from_seed(Default::default()),
+            &mut transcript,
+        )
+        .unwrap();
+        transcript.finalize()
+    };
+
+    {
+        let instances = instances.iter().map(Vec::as_slice).collect_vec();
+        let mut transcript = EvmTranscript::<G1Affine, NativeLoader, _, _>::new_with_compression(
+            proof.as_slice(),
+            true,
+        );
+        let strategy = SingleStrategy::new(params.verifier_params());
+        let strategy = verify_proof::<_, VerifierSHPLONK<_>, _, _, _>(
+            params.verifier_params(),
+            pk.get_vk(),
+            strategy,
+            &[instances.as_slice()],
+            &mut transcript,
+        )
+        .unwrap();
+        assert!(VerificationStrategy::<_, VerifierSHPLONK<_>>::finalize(
+            strategy
+        ));
+    }
+
+    halo2_kzg_native_verify!(
+        Plonk,
+        params,
+        &protocol,
+        &instances,
+        &mut EvmTranscript::<_, NativeLoader, _, _>::new_with_compression(proof.as_slice(), true)
+    );
+
+    let deployment_code = {
+        let loader = EvmLoader::new::<Fq, Fr>();
+        let vk = (params.get_g()[0], params.g2(), params.s_g2()).into();
+        let protocol = protocol.loaded(&loader);
+        let mut transcript =
+            EvmTranscript::<_, Rc<EvmLoader>, _, _>::new_with_compression(&loader, true);
+        let instances =
+            transcript.load_instances(instances.iter().map(|instances| instances.len()).collect());
+        let proof = Plonk::read_proof(&vk, &protocol, &instances, &mut transcript).unwrap();
+        Plonk::verify(&vk, &protocol, &instances, &proof).unwrap();
+        compile_yul(&loader.yul_code())
+    };
+    let vk = (params.get_g()[0], params.g2(), params.s_g2()).into();
+    let calldata =
+        encode_calldata_with_compression::<_, Plonk>(&vk, &protocol, &instances, &proof, true)
+            .unwrap();
+    let gas_cost = deploy_and_call(deployment_code, calldata).unwrap();
+
+    let cost = Plonk::estimate_cost(&protocol);
+    assert_eq!(
+        proof.len() + 0x20 * instances.iter().map(Vec::len).sum::<usize>(),
+        cost.compressed_calldata_size()
+    );
+    println!("Total gas cost: {gas_cost}");
+    println!("Estimated gas cost: {:?}", estimate_gas_cost(&cost));
+    println!(
+        "Estimated gas cost with compression: {:?}",
+        estimate_gas_cost_compressed(&cost)
+    );
+}
+
+#[test]
+fn test_evm_transcript_public_input_digest() {
+    use crate::loader::evm::{
+        compile_yul, deploy_and_call, encode_calldata_with_preimage, fe_to_u256, EvmLoader,
+        MemoryPtr, PublicInputHash,
+    };
+    use halo2_curves::bn256::{Fq, Fr};
+    use std::rc::Rc;
+
+    let preimage = (0..100).collect::<Vec<u8>>();
+    for hash in [PublicInputHash::Keccak256, PublicInputHash::Sha256] {
+        let instances = vec![vec![Fr::from(1)], vec![Fr::from(2), hash.digest(&preimage)]];
+
+        let deployment_code = {
+            let loader = EvmLoader::new::<Fq, Fr>();
+            let mut transcript = EvmTranscript::<G1Affine, Rc<EvmLoader>, _, _>::new(&loader);
+            let loaded = transcript.load_instances_with_digest(
+                instances.iter().map(Vec::len).collect(),
+                preimage.len(),
+                hash,
+            );
+            for (loaded, instance) in loaded.iter().flatten().zip(instances.iter().flatten()) {
+                let (ptr, instance) = (MemoryPtr(loaded.ptr()), fe_to_u256(*instance));
+                loader.code_mut().runtime_append(format!(
+                    "success := and(eq(mload({ptr}), {instance}), success)"
+                ));
+            }
+            compile_yul(&loader.yul_code())
+        };
+
+        let calldata = encode_calldata_with_preimage(hash, &instances, &preimage, &[]);
+        assert_eq!(calldata.len(), 2 * 0x20 + preimage.len());
+        assert!(deploy_and_call(deployment_code.clone(), calldata.clone()).is_ok());
+
+        let mut calldata = calldata;
+        *calldata.last_mut().unwrap() ^= 1;
+        assert!(deploy_and_call(deployment_code, calldata).is_err());
+    }
+}
+
+#[test]
+#[should_panic(expected = "Public input hash is not supported by verifier with ABI")]
+fn test_evm_transcript_public_input_digest_with_abi() {
+    use crate::loader::evm::{EvmLoader, PublicInputHash};
+    use halo2_curves::bn256::{Fq, Fr};
+    use std::rc::Rc;
+
+    let loader = EvmLoader::new_with_abi::<Fq, Fr>();
+    let mut transcript = EvmTranscript::<G1Affine, Rc<EvmLoader>, _, _>::new(&loader);
+    transcript.load_instances_with_digest(vec![1], 0x20, PublicInputHash::Keccak256);
+}
+
+#[test]
+fn test_shplonk_zk_standard_plonk_rand_deferred_pairing() {
+    use crate::{
+        loader::evm::{
+            compile_yul, deploy_and_call, deploy_and_call_with_output, encode_calldata_unchecked,
+            encode_ec_points, EvmLoader,
+        },
+        pcs::{kzg::KzgAccumulator, AccumulationDecider},
+        util::Itertools,
+        verifier::{plonk::PlonkSuccinctVerifier, SnarkVerifier},
+    };
+    use halo2_curves::bn256::{Fq, Fr};
+    use halo2_proofs::poly::commitment::ParamsProver;
+    use std::rc::Rc;
+
+    type As = KzgAs<Bn256, Bdfg21>;
+    type Plonk = PlonkSuccinctVerifier<As, LimbsEncoding<LIMBS, BITS>>;
+
+    let (params, pk, protocol, circuits) = halo2_kzg_prepare!(
+        9,
+        halo2_kzg_config!(true, 1),
+        StandardPlonk::rand(ChaCha20Rng::from_seed(Default::default()))
+    );
+    let snark = halo2_kzg_create_snark!(
+        ProverSHPLONK<_>,
+        VerifierSHPLONK<_>,
+        EvmTranscript<G1Affine, _, _, _>,
+        EvmTranscript<G1Affine, _, _, _>,
+        ChallengeEvm<_>,
+        &params,
+        &pk,
+        &protocol,
+        &circuits
+    );
+    let svk = params.get_g()[0].into();
+
+    let accumulators = {
+        let mut transcript = EvmTranscript::<_, NativeLoader, _, _>::new(snark.proof.as_slice());
+        let proof =
+            Plonk::read_proof(&svk, &snark.protocol, &snark.instances, &mut transcript).unwrap();
+        Plonk::verify(&svk, &snark.protocol, &snark.instances, &proof).unwrap()
+    };
+
+    // Succinct verifier should return the same accumulators as native one.
+    let succinct_verifier = {
+        let loader = EvmLoader::new::<Fq, Fr>();
+        let protocol = snark.protocol.loaded(&loader);
+        let mut transcript = EvmTranscript::<_, Rc<EvmLoader>, _, _>::new(&loader);
+        let instances = transcript.load_instances(snark.instances.iter().map(Vec::len).collect());
+        let proof = Plonk::read_proof(&svk, &protocol, &instances, &mut transcript).unwrap();
+        let accumulators = Plonk::verify(&svk, &protocol, &instances, &proof).unwrap();
+        loader.return_ec_points(
+            &accumulators
+                .into_iter()
+                .flat_map(|KzgAccumulator { lhs, rhs }| [lhs, rhs])
+                .collect_vec(),
+        );
+        compile_yul(&loader.yul_code())
+    };
+    let calldata = encode_calldata_unchecked(&snark.instances, &snark.proof);
+    let (gas_cost, output) = deploy_and_call_with_output(succinct_verifier, calldata).unwrap();
+    println!("Total gas cost of succinct verifier: {gas_cost}");
+    assert_eq!(
+        output,
+        encode_ec_points(
+            &accumulators
+                .iter()
+                .flat_map(|KzgAccumulator { lhs, rhs }| [*lhs, *rhs])
+                .collect_vec()
+        )
+    );
+
+    // Decider should decide accumulators of several proofs with a single
+    // pairing.
+    let num_accumulator = 2 * accumulators.len();
+    let decider = {
+        let dk = (params.get_g()[0], params.g2(), params.s_g2()).into();
+        let loader = EvmLoader::new::<Fq, Fr>();
+        let accumulators = (0..num_accumulator)
+            .map(|idx| {
+                let [lhs, rhs] =
+                    [0, 0x40].map(|offset| loader.calldataload_ec_point(idx * 0x80 + offset));
+                KzgAccumulator::new(lhs, rhs)
+            })
+            .collect_vec();
+        <As as AccumulationDecider<_, Rc<EvmLoader>>>::decide_all(&dk, accumulators).unwrap();
+        compile_yul(&loader.yul_code())
+    };
+    let calldata = [output.clone(), output].concat();
+    let gas_cost = deploy_and_call(decider.clone(), calldata.clone()).unwrap();
+    println!("Total gas cost of decider: {gas_cost}");
+
+    let mut invalid_calldata = calldata;
+    invalid_calldata[..0x80].rotate_left(0x40);
+    assert!(deploy_and_call(decider, invalid_calldata).is_err());
+}
+
+#[test]
+fn test_shplonk_zk_multi_protocol() {
+    use crate::{
+        loader::evm::{compile_yul, deploy_and_call, encode_calldata_unchecked, EvmLoader},
+        pcs::{kzg::KzgDecidingKey, AccumulationDecider},
+        util::Itertools,
+        verifier::{plonk::PlonkSuccinctVerifier, SnarkVerifier},
+    };
+    use halo2_curves::bn256::{Fq, Fr};
+    use halo2_proofs::poly::commitment::ParamsProver;
+    use std::rc::Rc;
+
+    type As = KzgAs<Bn256, Bdfg21>;
+    type Plonk = PlonkSuccinctVerifier<As, LimbsEncoding<LIMBS, BITS>>;
+
+    let (params, snarks) = {
+        let (params, pk, protocol, circuits) = halo2_kzg_prepare!(
+            9,
+            halo2_kzg_config!(true, 1),
+            StandardPlonk::rand(ChaCha20Rng::from_seed(Default::default()))
+        );
+        let standard_plonk = halo2_kzg_create_snark!(
+            ProverSHPLONK<_>,
+            VerifierSHPLONK<_>,
+            EvmTranscript<G1Affine, _, _, _>,
+            EvmTranscript<G1Affine, _, _, _>,
+            ChallengeEvm<_>,
+            &params,
+            &pk,
+            &protocol,
+            &circuits
+        );
+        let (params, pk, protocol, circuits) = halo2_kzg_prepare!(
+            9,
+            halo2_kzg_config!(true, 1, (0..4 * LIMBS).map(|idx| (0, idx)).collect()),
+            main_gate_with_range_with_mock_kzg_accumulator::<Bn256>()
+        );
+        let main_gate_with_range = halo2_kzg_create_snark!(
+            ProverSHPLONK<_>,
+            VerifierSHPLONK<_>,
+            EvmTranscript<G1Affine, _, _, _>,
+            EvmTranscript<G1Affine, _, _, _>,
+            ChallengeEvm<_>,
+            &params,
+            &pk,
+            &protocol,
+            &circuits
+        );
+        (params, [standard_plonk, main_gate_with_range])
+    };
+
+    let deployment_code = {
+        let dk: KzgDecidingKey<Bn256> = (params.get_g()[0], params.g2(), params.s_g2()).into();
+        let loader = EvmLoader::new::<Fq, Fr>();
+        let accumulators = snarks
+            .iter()
+            .flat_map(|snark| {
+                let protocol = snark.protocol.loaded(&loader);
+                let mut transcript = EvmTranscript::<_, Rc<EvmLoader>, _, _>::new(&loader);
+                let instances = transcript.load_instances(protocol.num_instance.clone());
+                let proof =
+                    Plonk::read_proof(dk.as_ref(), &protocol, &instances, &mut transcript).unwrap();
+                Plonk::verify(dk.as_ref(), &protocol, &instances, &proof).unwrap()
+            })
+            .collect_vec();
+        assert_eq!(accumulators.len(), 3);
+        <As as AccumulationDecider<_, Rc<EvmLoader>>>::decide_all(&dk, accumulators).unwrap();
+        compile_yul(&loader.yul_code())
+    };
+
+    let calldata = snarks
+        .iter()
+        .flat_map(|snark| encode_calldata_unchecked(&snark.instances, &snark.proof))
+        .collect_vec();
+    let gas_cost = deploy_and_call(deployment_code.clone(), calldata.clone()).unwrap();
+    println!("Total gas cost: {gas_cost}");
+
+    // Tampering proof of the last protocol should be rejected.
+    let mut invalid_calldata = calldata;
+    *invalid_calldata.last_mut().unwrap() ^= 1;
+    assert!(deploy_and_call(deployment_code, invalid_calldata).is_err());
+}
+
+#[cfg(feature = "loader_halo2")]
+#[test]
+#[ignore = "cause it takes several minutes to run"]
+fn test_plonk_public_input_digest_sha256() {
+    use crate::{
+        loader::evm::{
+            compile_yul, deploy_and_call, encode_calldata_with_preimage, EvmLoader, PublicInputHash,
+        },
+        verifier::SnarkVerifier,
+    };
+    use halo2_curves::bn256::{Fq, Fr};
+    use halo2_proofs::poly::commitment::ParamsProver;
+    use std::rc::Rc;
+
+    type Plonk = PlonkVerifier<KzgAs<Bn256, Gwc19>, LimbsEncoding<LIMBS, BITS>>;
+
+    let hash = PublicInputHash::Sha256;
+    let inputs = vec![Fr::from(1), -Fr::from(1)];
+    let (params, pk, protocol, circuits) = halo2_kzg_prepare!(
+        18,
+        halo2_kzg_config!(true, 1),
+        kzg::halo2::PublicInputDigest::new(hash, inputs.clone())
+    );
+    let snark = halo2_kzg_create_snark!(
+        ProverGWC<_>,
+        VerifierGWC<_>,
+        EvmTranscript<G1Affine, _, _, _>,
+        EvmTranscript<G1Affine, _, _, _>,
+        ChallengeEvm<_>,
+        &params,
+        &pk,
+        &protocol,
+        &circuits
+    );
+    let preimage = circuits[0].preimage();
+
+    // Verifier takes preimage of the digest computed in circuit as calldata.
+    let deployment_code = {
+        let dk = (params.get_g()[0], params.g2(), params.s_g2()).into();
+        let loader = EvmLoader::new::<Fq, Fr>();
+        let protocol = snark.protocol.loaded(&loader);
+        let mut transcript = EvmTranscript::<_, Rc<EvmLoader>, _, _>::new(&loader);
+        let instances = transcript.load_instances_with_digest(vec![1], preimage.len(), hash);
+        let proof = Plonk::read_proof(&dk, &protocol, &instances, &mut transcript).unwrap();
+        Plonk::verify(&dk, &protocol, &instances, &proof).unwrap();
+        compile_yul(&loader.yul_code())
+    };
+
+    let calldata = encode_calldata_with_preimage(hash, &snark.instances, &preimage, &snark.proof);
+    assert!(deploy_and_call(deployment_code.clone(), calldata).is_ok());
+
+    let mut preimage = preimage;
+    *preimage.last_mut().unwrap() ^= 1;
+    let calldata = encode_calldata_with_preimage(hash, &snark.instances, &preimage, &snark.proof);
+    assert!(deploy_and_call(deployment_code, calldata).is_err());
+}
+
+#[test]
+fn test_evm_loader_diff_traces() {
+    use crate::{
+        loader::{evm::EvmLoader, ScalarLoader},
+        util::{
+            arithmetic::PrimeCurveAffine,
+            transcript::{
+                diff_traces, TraceLoader, TraceOp, TracingTranscript, Transcript, TranscriptRead,
+            },
+        },
+    };
+    use halo2_curves::bn256::{Fq, Fr};
+    use halo2_proofs::transcript::TranscriptWrite;
+    use std::rc::Rc;
+
+    fn read<L, T>(transcript: &mut TracingTranscript<G1Affine, T>, constant: u64)
+    where
+        L: TraceLoader<G1Affine>,
+        T: TranscriptRead<G1Affine, L>,
+    {
+        transcript.set_label("proof");
+        TranscriptRead::<G1Affine, L>::read_scalar(transcript).unwrap();
+        TranscriptRead::<G1Affine, L>::read_ec_point(transcript).unwrap();
+        let constant =
+            Transcript::<G1Affine, L>::loader(transcript).load_const(&Fr::from(constant));
+        Transcript::<G1Affine, L>::common_scalar(transcript, &constant).unwrap();
+        Transcript::<G1Affine, L>::squeeze_challenge(transcript);
+    }
+
+    let proof = {
+        let mut transcript = EvmTranscript::<G1Affine, NativeLoader, _, _>::new(Vec::new());
+        transcript.write_scalar(Fr::from(1)).unwrap();
+        transcript.write_point(G1Affine::generator()).unwrap();
+        transcript.finalize()
+    };
+    let native_trace = {
+        let mut transcript = TracingTranscript::new(
+            EvmTranscript::<G1Affine, NativeLoader, _, _>::new(proof.as_slice()),
+        );
+        read::<NativeLoader, _>(&mut transcript, 2);
+        transcript.into_parts().1
+    };
+    let evm_trace = |constant: u64| {
+        let loader = EvmLoader::new::<Fq, Fr>();
+        let mut transcript =
+            TracingTranscript::new(EvmTranscript::<G1Affine, Rc<EvmLoader>, _, _>::new(&loader));
+        read::<Rc<EvmLoader>, _>(&mut transcript, constant);
+        transcript.into_parts().1
+    };
+
+    // Only constants are known when generating verifier
+    let trace = evm_trace(2);
+    assert_eq!(
+        trace
+            .iter()
+            .map(|entry| entry.values.clone())
+            .collect::<Vec<_>>(),
+        vec![
+            vec![None],
+            vec![None; 2],
+            vec![Some(Fr::from(2))],
+            vec![None]
+        ]
+    );
+    assert_eq!(diff_traces(&native_trace, &trace), None);
+
+    let divergence = diff_traces(&native_trace, &evm_trace(3)).unwrap();
+    assert_eq!(divergence.index, 2);
+    assert_eq!(divergence.lhs.unwrap().op, TraceOp::CommonScalar);
+
+    let divergence = diff_traces(&native_trace, &trace[..3]).unwrap();
+    assert_eq!(divergence.index, 3);
+    assert_eq!(divergence.rhs, None);
+}
+
+#[test]
+fn test_evm_loader_hyperplonk_zeromorph() {
+    use crate::{
+        loader::evm::{compile_yul, deploy_and_call, encode_calldata_unchecked, EvmLoader},
+        pcs::kzg::Zeromorph,
+        verifier::{
+            hyperplonk::{
+                test::{create_proof, rand_vec, setup, Tamper},
+                HyperPlonkVerifier,
+            },
+            SnarkVerifier,
+        },
+    };
+    use halo2_curves::bn256::{Fq, Fr};
+    use std::rc::Rc;
+
+    type Verifier = HyperPlonkVerifier<KzgAs<Bn256, Zeromorph>, LimbsEncoding<LIMBS, BITS>>;
+
+    let (pk, dk, protocol, q) = setup();
+    let instances = vec![rand_vec(2)];
+
+    let deployment_code = {
+        let loader = EvmLoader::new::<Fq, Fr>();
+        let protocol = protocol.loaded(&loader);
+        let mut transcript = EvmTranscript::<G1Affine, Rc<EvmLoader>, _, _>::new(&loader);
+        let instances = transcript.load_instances(vec![2]);
+        let proof = Verifier::read_proof(&dk, &protocol, &instances, &mut transcript).unwrap();
+        Verifier::verify(&dk, &protocol, &instances, &proof).unwrap();
+        compile_yul(&loader.yul_code())
+    };
+
+    // Verifier in EVM should agree with the native one
+    let verify = |tamper: Option<Tamper>| {
+        let proof = {
+            let mut transcript = EvmTranscript::<G1Affine, NativeLoader, _, _>::new(Vec::new());
+            create_proof(&pk, &protocol, &q, &instances[0], tamper, &mut transcript);
+            transcript.finalize()
+        };
+
+        let mut transcript = EvmTranscript::<G1Affine, NativeLoader, _, _>::new(proof.as_slice());
+        let native = Verifier::read_proof(&dk, &protocol, &instances, &mut transcript)
+            .and_then(|proof| Verifier::verify(&dk, &protocol, &instances, &proof));
+        let evm = deploy_and_call(
+            deployment_code.clone(),
+            encode_calldata_unchecked(&instances, &proof),
+        );
+        assert_eq!(native.is_ok(), evm.is_ok());
+        evm.is_ok()
+    };
+    assert!(verify(None));
+    for tamper in [Tamper::Witness, Tamper::Evaluation, Tamper::Opening] {
+        assert!(!verify(Some(tamper)));
+    }
+}
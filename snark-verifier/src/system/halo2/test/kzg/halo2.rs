@@ -1,15 +1,21 @@
 use crate::{
+    cost::CostEstimation,
     loader::{
         self,
-        halo2::test::{Snark, SnarkWitness},
+        halo2::{
+            test::{Snark, SnarkWitness},
+            RowCost,
+        },
         native::NativeLoader,
+        EcPointLoader, Loader, ScalarLoader,
     },
     pcs::{
         kzg::{
-            Bdfg21, KzgAccumulator, KzgAs, KzgAsProvingKey, KzgAsVerifyingKey,
-            KzgSuccinctVerifyingKey, LimbsEncoding, LimbsEncodingInstructions,
+            Bdfg21, CompressedEncoding, Gwc19, HashedEncoding, KzgAccumulator, KzgAs,
+            KzgAsProvingKey, KzgAsVerifyingKey, KzgDecidingKey, KzgSuccinctVerifyingKey,
+            LimbsEncoding, LimbsEncodingInstructions, Zeromorph, ZeromorphProvingKey,
         },
-        AccumulationScheme, AccumulationSchemeProver,
+        AccumulationDecider, AccumulationScheme, AccumulationSchemeProver, AccumulatorEncoding,
     },
     system::{
         self,
@@ -24,10 +30,16 @@ use crate::{
             transcript::halo2::ChallengeScalar,
         },
     },
-    util::{arithmetic::fe_to_limbs, Itertools},
-    verifier::{self, SnarkVerifier},
+    util::{
+        arithmetic::{fe_to_limbs, root_of_unity, Curve, Domain, Field, Group},
+        transcript::{
+            diff_traces, TraceEntry, TraceOp, TracingTranscript, TranscriptRead, TranscriptWrite,
+        },
+        Itertools,
+    },
+    verifier::{self, hyperplonk::HyperPlonkProtocol, plonk::PlonkProtocol, SnarkVerifier},
 };
-use halo2_curves::bn256::{Bn256, Fq, Fr, G1Affine};
+use halo2_curves::bn256::{Bn256, Fq, Fr, G1Affine, G1, G2};
 use halo2_proofs::{
     circuit::{floor_planner::V1, Layouter, Value},
     plonk::{Circuit, ConstraintSystem, Error},
@@ -46,8 +58,11 @@ use halo2_wrong_ecc::{
     maingate::{MainGateInstructions, RangeInstructions, RegionCtx},
 };
 use paste::paste;
-use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
-use std::rc::Rc;
+use rand_chacha::{
+    rand_core::{RngCore, SeedableRng},
+    ChaCha20Rng,
+};
+use std::{cell::RefCell, rc::Rc};
 
 const T: usize = 5;
 const RATE: usize = 4;
@@ -92,9 +107,11 @@ pub fn accumulate<'a>(
             let instances = assign_instances(&snark.instances);
             let mut transcript =
                 PoseidonTranscript::<Rc<Halo2Loader>, _>::new(loader, snark.proof());
+            loader.start_cost_metering("Read proof");
             let proof =
                 PlonkSuccinctVerifier::read_proof(svk, &protocol, &instances, &mut transcript)
                     .unwrap();
+            loader.end_cost_metering();
             PlonkSuccinctVerifier::verify(svk, &protocol, &instances, &proof).unwrap()
         })
         .collect_vec();
@@ -307,9 +324,11 @@ impl Circuit<Fr> for Accumulation {
                 let accumulator_limbs = [accumulator.lhs, accumulator.rhs]
                     .iter()
                     .map(|ec_point| {
-                        loader
-                            .ecc_chip()
-                            .assign_ec_point_to_limbs(&mut loader.ctx_mut(), ec_point.assigned())
+                        LimbsEncodingInstructions::<_, LIMBS, BITS>::assign_ec_point_to_limbs(
+                            &*loader.ecc_chip(),
+                            &mut loader.ctx_mut(),
+                            ec_point.assigned(),
+                        )
                     })
                     .collect::<Result<Vec<_>, Error>>()?
                     .into_iter()
@@ -330,6 +349,128 @@ impl Circuit<Fr> for Accumulation {
     }
 }
 
+/// [`Accumulation`] that asserts rows estimated by calibrated [`RowCost`] are
+/// close to the rows it actually takes.
+struct RowCostEstimation(Accumulation);
+
+impl Circuit<Fr> for RowCostEstimation {
+    type Config = MainGateWithRangeConfig;
+    type FloorPlanner = V1;
+    #[cfg(feature = "halo2_circuit_params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self(self.0.without_witnesses())
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        Accumulation::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        config.range_chip().load_table(&mut layouter)?;
+
+        let row_cost = layouter.assign_region(
+            || "",
+            |region| {
+                let loader = Halo2Loader::new(config.ecc_chip(), RegionCtx::new(region, 0));
+                Ok(RowCost::calibrate(&loader).with_poseidon::<_, _, T, RATE>(&loader, R_F, R_P))
+            },
+        )?;
+
+        layouter.assign_region(
+            || "",
+            |region| {
+                let loader = Halo2Loader::new(config.ecc_chip(), RegionCtx::new(region, 0));
+                accumulate(
+                    &self.0.svk,
+                    &loader,
+                    &self.0.snarks,
+                    &self.0.as_vk,
+                    self.0.as_proof(),
+                );
+
+                let rows = loader.ctx().offset();
+                let estimated_rows = self
+                    .0
+                    .snarks
+                    .iter()
+                    .map(|snark| {
+                        row_cost
+                            .estimate_rows(&PlonkSuccinctVerifier::estimate_cost(&snark.protocol))
+                    })
+                    .sum::<usize>();
+                println!("Total row cost: {rows}");
+                println!("Estimated row cost: {estimated_rows}");
+                assert!(rows.abs_diff(estimated_rows) * 5 <= rows);
+
+                loader.print_row_metering();
+                let batch_inverts = loader
+                    .row_meterings()
+                    .into_iter()
+                    .filter_map(|(identifier, rows)| {
+                        let num = identifier
+                            .strip_prefix("batch_invert of ")?
+                            .strip_suffix(" scalars")?
+                            .parse::<usize>()
+                            .unwrap();
+                        Some((num, rows))
+                    })
+                    .collect_vec();
+                assert!(!batch_inverts.is_empty());
+                // Montgomery's trick takes 3 multiplications per extra scalar
+                // on top of a single inversion
+                for (num, rows) in batch_inverts {
+                    println!(
+                        "Rows of inverting {num} scalars separately: {}",
+                        num * row_cost.inversion
+                    );
+                    assert_eq!(
+                        rows,
+                        3 * (num - 1) * row_cost.scalar_mul + row_cost.inversion
+                    );
+                }
+
+                Ok(())
+            },
+        )
+    }
+}
+
+#[test]
+fn test_row_cost_estimation() {
+    use halo2_proofs::dev::MockProver;
+
+    const K: u32 = 20;
+
+    let (params, snark) = {
+        let (params, pk, protocol, circuits) = halo2_kzg_prepare!(
+            9,
+            halo2_kzg_config!(true, 1),
+            StandardPlonk::<_>::rand(ChaCha20Rng::from_seed(Default::default()))
+        );
+        let snark = halo2_kzg_create_snark!(
+            ProverSHPLONK<_>,
+            VerifierSHPLONK<_>,
+            PoseidonTranscript<_, _>,
+            PoseidonTranscript<_, _>,
+            ChallengeScalar<_>,
+            &params,
+            &pk,
+            &protocol,
+            &circuits
+        );
+        (params, snark)
+    };
+    let circuit = RowCostEstimation(Accumulation::new(&params, [snark]));
+    // Only synthesis is needed for the assertion in `RowCostEstimation`
+    MockProver::run(K, &circuit, circuit.0.instances()).unwrap();
+}
+
 macro_rules! test {
     (@ $(#[$attr:meta],)* $name:ident, $k:expr, $config:expr, $create_circuit:expr) => {
         paste! {
@@ -383,3 +524,1032 @@ test!(
     halo2_kzg_config!(true, 1, Accumulation::accumulator_indices()),
     Accumulation::two_snark_with_accumulator()
 );
+
+/// Asserts witness to be equal to constant on both sides of
+/// [`ScalarLoader::assert_eq`], where the constant is assigned only when
+/// being asserted.
+struct AssertEqConstant {
+    witness: Fr,
+    constant: Fr,
+}
+
+impl Circuit<Fr> for AssertEqConstant {
+    type Config = MainGateWithRangeConfig;
+    type FloorPlanner = V1;
+    #[cfg(feature = "halo2_circuit_params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            witness: Fr::zero(),
+            constant: self.constant,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        Accumulation::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        config.range_chip().load_table(&mut layouter)?;
+
+        layouter.assign_region(
+            || "",
+            |region| {
+                let loader = Halo2Loader::new(config.ecc_chip(), RegionCtx::new(region, 0));
+                let witness = loader.assign_scalar(Value::known(self.witness));
+                for (lhs, rhs) in [
+                    (witness.clone(), loader.load_const(&self.constant)),
+                    (loader.load_const(&self.constant), witness),
+                ] {
+                    loader
+                        .assert_eq("", &lhs, &rhs)
+                        .map_err(|_| Error::Synthesis)?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+#[test]
+fn test_halo2_loader_assert_eq_constant() {
+    use halo2_proofs::dev::MockProver;
+
+    const K: u32 = 18;
+
+    let mut rng = ChaCha20Rng::from_seed(Default::default());
+    let constant = Fr::random(&mut rng);
+    MockProver::run(
+        K,
+        &AssertEqConstant {
+            witness: constant,
+            constant,
+        },
+        vec![Vec::new()],
+    )
+    .unwrap()
+    .assert_satisfied();
+
+    let prover = MockProver::run(
+        K,
+        &AssertEqConstant {
+            witness: constant + Fr::one(),
+            constant,
+        },
+        vec![Vec::new()],
+    );
+    assert!(!matches!(prover.map(|prover| prover.verify()), Ok(Ok(()))));
+}
+
+type Gwc19SuccinctVerifier = verifier::plonk::PlonkSuccinctVerifier<KzgAs<Bn256, Gwc19>>;
+
+/// Verifies proof whose witness commitment could be identity, and constrains
+/// the accumulator to be the one computed natively.
+struct IdentityCommitment {
+    svk: Svk,
+    protocol: PlonkProtocol<G1Affine>,
+    proof: Value<Vec<u8>>,
+    accumulator: KzgAccumulator<G1Affine, NativeLoader>,
+}
+
+impl IdentityCommitment {
+    fn new(b: &[Fr]) -> Self {
+        use verifier::plonk::{
+            test::{commit, create_proof, interpolate, protocol, setup, K},
+            LinearizationStrategy,
+        };
+
+        let (g1s, dk) = setup();
+        let domain = Domain::new(K, root_of_unity(K));
+        let q = (0..domain.n)
+            .map(|i| if i % 2 == 0 { Fr::one() } else { Fr::zero() })
+            .collect_vec();
+        let protocol = protocol(
+            commit(&g1s, &interpolate(&domain, &q)),
+            LinearizationStrategy::WithoutConstant,
+        );
+        let proof = {
+            let mut transcript = PoseidonTranscript::<NativeLoader, _>::new(Vec::new());
+            create_proof(&g1s, &protocol, &q, b, true, &mut transcript);
+            transcript.finalize()
+        };
+
+        let svk = Svk::new(g1s[0]);
+        let mut transcript = PoseidonTranscript::<NativeLoader, _>::new(proof.as_slice());
+        let snark_proof =
+            Gwc19SuccinctVerifier::read_proof(&svk, &protocol, &[], &mut transcript).unwrap();
+        let accumulator = Gwc19SuccinctVerifier::verify(&svk, &protocol, &[], &snark_proof)
+            .unwrap()
+            .pop()
+            .unwrap();
+        assert!(KzgAs::<Bn256, Gwc19>::decide(&dk, accumulator.clone()).is_ok());
+
+        Self {
+            svk,
+            protocol,
+            proof: Value::known(proof),
+            accumulator,
+        }
+    }
+}
+
+impl Circuit<Fr> for IdentityCommitment {
+    type Config = MainGateWithRangeConfig;
+    type FloorPlanner = V1;
+    #[cfg(feature = "halo2_circuit_params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            svk: self.svk,
+            protocol: self.protocol.clone(),
+            proof: Value::unknown(),
+            accumulator: self.accumulator.clone(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        Accumulation::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        config.range_chip().load_table(&mut layouter)?;
+
+        layouter.assign_region(
+            || "",
+            |region| {
+                let loader = Halo2Loader::new(config.ecc_chip(), RegionCtx::new(region, 0));
+                let protocol = self.protocol.loaded(&loader);
+                let mut transcript = PoseidonTranscript::<Rc<Halo2Loader>, _>::new(
+                    &loader,
+                    self.proof.as_ref().map(Vec::as_slice),
+                );
+                let proof =
+                    Gwc19SuccinctVerifier::read_proof(&self.svk, &protocol, &[], &mut transcript)
+                        .unwrap();
+                let KzgAccumulator { lhs, rhs } =
+                    Gwc19SuccinctVerifier::verify(&self.svk, &protocol, &[], &proof)
+                        .unwrap()
+                        .pop()
+                        .unwrap();
+
+                for (ec_point, expected) in
+                    [(lhs, self.accumulator.lhs), (rhs, self.accumulator.rhs)]
+                {
+                    loader
+                        .ec_point_assert_eq("", &ec_point, &loader.ec_point_load_const(&expected))
+                        .map_err(|_| Error::Synthesis)?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+#[test]
+fn test_halo2_loader_identity_commitment() {
+    use halo2_proofs::dev::MockProver;
+
+    const K: u32 = 20;
+
+    let mut rng = ChaCha20Rng::from_seed(Default::default());
+    let n = 1 << verifier::plonk::test::K;
+    // Commitment of `b` in proof is identity when it's all zero
+    for b in [
+        vec![Fr::zero(); n],
+        (0..n).map(|_| Fr::random(&mut rng)).collect_vec(),
+    ] {
+        let circuit = IdentityCommitment::new(&b);
+        MockProver::run(K, &circuit, vec![Vec::new()])
+            .unwrap()
+            .assert_satisfied();
+    }
+}
+
+type Compressed = CompressedEncoding<LIMBS, BITS>;
+type Hashed = HashedEncoding<LIMBS, BITS, T, RATE, R_F, R_P>;
+
+/// Decodes accumulators encoded by [`CompressedEncoding`] and
+/// [`HashedEncoding`], and constrains them to be the one encoded natively.
+#[derive(Clone)]
+struct AccumulatorEncodings {
+    accumulator: KzgAccumulator<G1Affine, NativeLoader>,
+    compressed: Vec<Value<Fr>>,
+    digest: Value<Fr>,
+    limbs: Value<Vec<u8>>,
+}
+
+impl AccumulatorEncodings {
+    fn new(seed: u8) -> Self {
+        let mut rng = ChaCha20Rng::from_seed([seed; 32]);
+        let accumulator = KzgAccumulator::new(
+            G1::random(&mut rng).to_affine(),
+            G1::random(&mut rng).to_affine(),
+        );
+        let (digest, limbs) = Hashed::encode(&accumulator);
+        let limbs = {
+            let mut transcript = PoseidonTranscript::<NativeLoader, _>::new(Vec::new());
+            for limb in limbs {
+                transcript.write_scalar(limb).unwrap();
+            }
+            transcript.finalize()
+        };
+
+        Self {
+            compressed: Compressed::encode(&accumulator)
+                .into_iter()
+                .map(Value::known)
+                .collect(),
+            digest: Value::known(digest),
+            limbs: Value::known(limbs),
+            accumulator,
+        }
+    }
+}
+
+impl Circuit<Fr> for AccumulatorEncodings {
+    type Config = MainGateWithRangeConfig;
+    type FloorPlanner = V1;
+    #[cfg(feature = "halo2_circuit_params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            accumulator: self.accumulator.clone(),
+            compressed: vec![Value::unknown(); self.compressed.len()],
+            digest: Value::unknown(),
+            limbs: Value::unknown(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        Accumulation::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        config.range_chip().load_table(&mut layouter)?;
+
+        layouter.assign_region(
+            || "",
+            |region| {
+                let loader = Halo2Loader::new(config.ecc_chip(), RegionCtx::new(region, 0));
+                let compressed = self
+                    .compressed
+                    .iter()
+                    .map(|value| loader.assign_scalar(*value))
+                    .collect_vec();
+                let digest = loader.assign_scalar(self.digest);
+                let mut transcript = PoseidonTranscript::<Rc<Halo2Loader>, _>::new(
+                    &loader,
+                    self.limbs.as_ref().map(Vec::as_slice),
+                );
+
+                let accumulators = [
+                    <Compressed as AccumulatorEncoding<G1Affine, Rc<Halo2Loader>>>::from_repr(
+                        &compressed.iter().collect_vec(),
+                    ),
+                    <Hashed as AccumulatorEncoding<G1Affine, Rc<Halo2Loader>>>::read_repr(
+                        &[&digest],
+                        &mut transcript,
+                    ),
+                ];
+                for accumulator in accumulators {
+                    let KzgAccumulator { lhs, rhs } = accumulator.map_err(|_| Error::Synthesis)?;
+                    for (ec_point, expected) in
+                        [(lhs, self.accumulator.lhs), (rhs, self.accumulator.rhs)]
+                    {
+                        loader
+                            .ec_point_assert_eq(
+                                "",
+                                &ec_point,
+                                &loader.ec_point_load_const(&expected),
+                            )
+                            .map_err(|_| Error::Synthesis)?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+#[test]
+fn test_halo2_loader_accumulator_encodings() {
+    use halo2_proofs::dev::MockProver;
+
+    const K: u32 = 18;
+
+    let circuit = AccumulatorEncodings::new(0);
+    MockProver::run(K, &circuit, vec![Vec::new()])
+        .unwrap()
+        .assert_satisfied();
+
+    let assert_unsatisfied = |circuit: &AccumulatorEncodings| {
+        let prover = MockProver::run(K, circuit, vec![Vec::new()]);
+        assert!(!matches!(prover.map(|prover| prover.verify()), Ok(Ok(()))));
+    };
+
+    // Flipping sign decodes to the negation
+    let mut tampered = circuit.clone();
+    tampered.compressed[LIMBS] = tampered.compressed[LIMBS].map(|sign| Fr::one() - sign);
+    assert_unsatisfied(&tampered);
+
+    // Sign other than 0 or 1 is rejected
+    let mut tampered = circuit.clone();
+    tampered.compressed[LIMBS] = Value::known(Fr::from(2));
+    assert_unsatisfied(&tampered);
+
+    // Limbs in proof don't match the digest
+    let mut tampered = circuit.clone();
+    tampered.digest = tampered.digest.map(|digest| digest + Fr::one());
+    assert_unsatisfied(&tampered);
+
+    // Limbs of another accumulator
+    let mut tampered = circuit;
+    tampered.limbs = AccumulatorEncodings::new(1).limbs;
+    assert_unsatisfied(&tampered);
+}
+
+type PoseidonT3Transcript<L, S> =
+    system::halo2::transcript::halo2::PoseidonTranscript<G1Affine, L, S, 3, 2, 8, 57>;
+type Poseidon2T3Transcript<L, S> =
+    system::halo2::transcript::halo2::Poseidon2Transcript<G1Affine, L, S, 3, 2, 8, 56>;
+
+const TRANSCRIPT_ROUNDS: usize = 4;
+
+fn write_transcript(
+    transcript: &mut impl TranscriptWrite<G1Affine>,
+    mut rng: impl RngCore,
+) -> Vec<Fr> {
+    (0..TRANSCRIPT_ROUNDS)
+        .map(|_| {
+            transcript.write_scalar(Fr::random(&mut rng)).unwrap();
+            transcript
+                .write_ec_point(G1::random(&mut rng).to_affine())
+                .unwrap();
+            transcript.squeeze_challenge()
+        })
+        .collect()
+}
+
+fn read_transcript<'a>(
+    loader: &Rc<Halo2Loader<'a>>,
+    transcript: &mut impl TranscriptRead<G1Affine, Rc<Halo2Loader<'a>>>,
+    challenges: &[Fr],
+) -> Result<usize, Error> {
+    let start = loader.ctx().offset();
+    for challenge in challenges {
+        transcript.read_scalar().map_err(|_| Error::Synthesis)?;
+        transcript.read_ec_point().map_err(|_| Error::Synthesis)?;
+        let squeezed = transcript.squeeze_challenge();
+        loader
+            .assert_eq("", &squeezed, &loader.load_const(challenge))
+            .map_err(|_| Error::Synthesis)?;
+    }
+    Ok(loader.ctx().offset() - start)
+}
+
+/// Reads the same kind of proof with [`PoseidonTranscript`] using Poseidon
+/// and Poseidon2 hasher, and constrains squeezed challenges to be the ones
+/// computed natively.
+#[derive(Clone)]
+struct PoseidonTranscripts {
+    proofs: [Value<Vec<u8>>; 2],
+    challenges: [Vec<Fr>; 2],
+}
+
+impl PoseidonTranscripts {
+    fn new(seed: u8) -> Self {
+        let mut rng = ChaCha20Rng::from_seed([seed; 32]);
+        let mut poseidon = PoseidonT3Transcript::<NativeLoader, _>::new(Vec::new());
+        let mut poseidon2 = Poseidon2T3Transcript::<NativeLoader, _>::new(Vec::new());
+        let challenges = [
+            write_transcript(&mut poseidon, &mut rng),
+            write_transcript(&mut poseidon2, &mut rng),
+        ];
+        Self {
+            proofs: [
+                Value::known(poseidon.finalize()),
+                Value::known(poseidon2.finalize()),
+            ],
+            challenges,
+        }
+    }
+}
+
+impl Circuit<Fr> for PoseidonTranscripts {
+    type Config = MainGateWithRangeConfig;
+    type FloorPlanner = V1;
+    #[cfg(feature = "halo2_circuit_params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            proofs: [Value::unknown(), Value::unknown()],
+            challenges: self.challenges.clone(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        Accumulation::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        config.range_chip().load_table(&mut layouter)?;
+
+        layouter.assign_region(
+            || "",
+            |region| {
+                let loader = Halo2Loader::new(config.ecc_chip(), RegionCtx::new(region, 0));
+                let [poseidon, poseidon2] = &self.proofs;
+
+                let mut transcript = PoseidonT3Transcript::<Rc<Halo2Loader>, _>::new(
+                    &loader,
+                    poseidon.as_ref().map(Vec::as_slice),
+                );
+                let poseidon_rows =
+                    read_transcript(&loader, &mut transcript, &self.challenges[0])?;
+
+                let mut transcript = Poseidon2T3Transcript::<Rc<Halo2Loader>, _>::new(
+                    &loader,
+                    poseidon2.as_ref().map(Vec::as_slice),
+                );
+                let poseidon2_rows =
+                    read_transcript(&loader, &mut transcript, &self.challenges[1])?;
+
+                println!(
+                    "Rows of {TRANSCRIPT_ROUNDS} rounds of transcript with Poseidon: {poseidon_rows}"
+                );
+                println!(
+                    "Rows of {TRANSCRIPT_ROUNDS} rounds of transcript with Poseidon2: {poseidon2_rows}"
+                );
+                Ok(())
+            },
+        )
+    }
+}
+
+#[test]
+fn test_halo2_loader_poseidon2_transcript() {
+    use halo2_proofs::dev::MockProver;
+
+    const K: u32 = 18;
+
+    let circuit = PoseidonTranscripts::new(0);
+    MockProver::run(K, &circuit, vec![Vec::new()])
+        .unwrap()
+        .assert_satisfied();
+
+    // Challenges of another proof don't match
+    let mut circuit = circuit;
+    circuit.proofs = PoseidonTranscripts::new(1).proofs;
+    let prover = MockProver::run(K, &circuit, vec![Vec::new()]);
+    assert!(!matches!(prover.map(|prover| prover.verify()), Ok(Ok(()))));
+}
+
+fn read_rounds<L, T>(transcript: &mut T) -> Result<(), crate::Error>
+where
+    L: Loader<G1Affine>,
+    T: TranscriptRead<G1Affine, L>,
+{
+    for _ in 0..TRANSCRIPT_ROUNDS {
+        transcript.read_scalar()?;
+        transcript.read_ec_point()?;
+        let one = transcript.loader().load_one();
+        transcript.common_scalar(&one)?;
+        transcript.squeeze_challenge();
+    }
+    Ok(())
+}
+
+/// Reads proof in circuit with and without [`TracingTranscript`], and keeps
+/// the recorded trace and rows used by both.
+struct TracedTranscript {
+    proof: Value<Vec<u8>>,
+    trace: RefCell<Vec<TraceEntry<Fr>>>,
+    rows: RefCell<(usize, usize)>,
+}
+
+impl TracedTranscript {
+    fn new(proof: Vec<u8>) -> Self {
+        Self {
+            proof: Value::known(proof),
+            trace: Default::default(),
+            rows: Default::default(),
+        }
+    }
+}
+
+impl Circuit<Fr> for TracedTranscript {
+    type Config = MainGateWithRangeConfig;
+    type FloorPlanner = V1;
+    #[cfg(feature = "halo2_circuit_params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            proof: Value::unknown(),
+            trace: Default::default(),
+            rows: Default::default(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        Accumulation::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        config.range_chip().load_table(&mut layouter)?;
+
+        layouter.assign_region(
+            || "",
+            |region| {
+                let loader = Halo2Loader::new(config.ecc_chip(), RegionCtx::new(region, 0));
+
+                let start = loader.ctx().offset();
+                let mut transcript = PoseidonTranscript::<Rc<Halo2Loader>, _>::new(
+                    &loader,
+                    self.proof.as_ref().map(Vec::as_slice),
+                );
+                read_rounds::<Rc<Halo2Loader>, _>(&mut transcript).map_err(|_| Error::Synthesis)?;
+                let rows = loader.ctx().offset() - start;
+
+                let start = loader.ctx().offset();
+                let inner = PoseidonTranscript::<Rc<Halo2Loader>, _>::new(
+                    &loader,
+                    self.proof.as_ref().map(Vec::as_slice),
+                );
+                let mut transcript = TracingTranscript::<G1Affine, _>::new(inner);
+                transcript.set_label("proof");
+                read_rounds::<Rc<Halo2Loader>, _>(&mut transcript).map_err(|_| Error::Synthesis)?;
+                let traced_rows = loader.ctx().offset() - start;
+
+                *self.rows.borrow_mut() = (rows, traced_rows);
+                *self.trace.borrow_mut() = transcript.into_parts().1;
+                Ok(())
+            },
+        )
+    }
+}
+
+#[test]
+fn test_halo2_loader_diff_traces() {
+    use halo2_proofs::dev::MockProver;
+
+    const K: u32 = 18;
+
+    let native_trace = |proof: &[u8]| {
+        let inner = PoseidonTranscript::<NativeLoader, _>::new(proof);
+        let mut transcript = TracingTranscript::<G1Affine, _>::new(inner);
+        transcript.set_label("proof");
+        read_rounds::<NativeLoader, _>(&mut transcript).unwrap();
+        transcript.into_parts().1
+    };
+    let halo2_trace = |proof: &[u8]| {
+        let circuit = TracedTranscript::new(proof.to_vec());
+        MockProver::run(K, &circuit, vec![Vec::new()])
+            .unwrap()
+            .assert_satisfied();
+        // Tracing doesn't assign anything
+        let (rows, traced_rows) = *circuit.rows.borrow();
+        assert_eq!(rows, traced_rows);
+        circuit.trace.into_inner()
+    };
+
+    let proof = {
+        let mut transcript = PoseidonTranscript::<NativeLoader, _>::new(Vec::new());
+        write_transcript(&mut transcript, ChaCha20Rng::from_seed(Default::default()));
+        transcript.finalize()
+    };
+    let trace = halo2_trace(&proof);
+    assert_eq!(trace.len(), 4 * TRANSCRIPT_ROUNDS);
+    assert!(trace
+        .iter()
+        .all(|entry| entry.values.iter().all(Option::is_some)));
+    assert_eq!(diff_traces(&native_trace(&proof), &trace), None);
+
+    // Scalar of the second round is changed
+    let mut tampered = proof;
+    tampered[64] ^= 1;
+    let divergence = diff_traces(&native_trace(&tampered), &trace).unwrap();
+    assert_eq!(divergence.index, 4);
+    assert_eq!(divergence.lhs.unwrap().op, TraceOp::ReadScalar);
+}
+
+/// Circuit exposing digest of its public inputs computed by
+/// [`loader::halo2::Halo2Loader::assign_public_inputs_with_digest`] as the only
+/// instance.
+#[cfg(feature = "loader_evm")]
+#[derive(Clone)]
+pub struct PublicInputDigest {
+    hash: loader::evm::PublicInputHash,
+    inputs: Vec<Fr>,
+}
+
+#[cfg(feature = "loader_evm")]
+impl PublicInputDigest {
+    pub fn new(hash: loader::evm::PublicInputHash, inputs: Vec<Fr>) -> Self {
+        Self { hash, inputs }
+    }
+
+    pub fn preimage(&self) -> Vec<u8> {
+        use crate::util::arithmetic::PrimeField;
+
+        self.inputs
+            .iter()
+            .flat_map(|input| input.to_repr().into_iter().rev())
+            .collect()
+    }
+
+    pub fn instances(&self) -> Vec<Vec<Fr>> {
+        vec![vec![self.hash.digest(&self.preimage())]]
+    }
+}
+
+#[cfg(feature = "loader_evm")]
+impl Circuit<Fr> for PublicInputDigest {
+    type Config = MainGateWithRangeConfig;
+    type FloorPlanner = V1;
+    #[cfg(feature = "halo2_circuit_params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(self.hash, vec![Fr::zero(); self.inputs.len()])
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        MainGateWithRangeConfig::configure(meta, vec![8], vec![4, 7])
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        config.range_chip().load_table(&mut layouter)?;
+
+        let digest = layouter.assign_region(
+            || "",
+            |region| {
+                let loader = Halo2Loader::new(config.ecc_chip(), RegionCtx::new(region, 0));
+                let (_, digest) = loader
+                    .assign_public_inputs_with_digest(
+                        self.hash,
+                        self.inputs.iter().copied().map(Value::known),
+                    )
+                    .map_err(|_| Error::Synthesis)?;
+                println!("Total row cost: {}", loader.ctx().offset());
+                Ok(digest.into_assigned())
+            },
+        )?;
+
+        config
+            .main_gate()
+            .expose_public(layouter.namespace(|| ""), digest, 0)
+    }
+}
+
+#[cfg(feature = "loader_evm")]
+#[test]
+fn test_halo2_loader_public_input_digest_sha256() {
+    use halo2_proofs::dev::MockProver;
+
+    const K: u32 = 17;
+
+    let mut rng = ChaCha20Rng::from_seed(Default::default());
+    // Input `-1` has all bits of the canonical representation tested
+    for input in [-Fr::one(), Fr::random(&mut rng)] {
+        let circuit = PublicInputDigest::new(loader::evm::PublicInputHash::Sha256, vec![input]);
+        let instances = circuit.instances();
+        MockProver::run(K, &circuit, instances.clone())
+            .unwrap()
+            .assert_satisfied();
+
+        let wrong_instances = vec![vec![instances[0][0] + Fr::one()]];
+        assert!(MockProver::run(K, &circuit, wrong_instances)
+            .unwrap()
+            .verify()
+            .is_err());
+    }
+}
+
+#[cfg(feature = "loader_evm")]
+#[test]
+#[ignore = "cause it takes several minutes to run"]
+fn test_halo2_loader_public_input_digest_keccak256() {
+    use halo2_proofs::dev::MockProver;
+
+    const K: u32 = 19;
+
+    let mut rng = ChaCha20Rng::from_seed(Default::default());
+    let circuit = PublicInputDigest::new(
+        loader::evm::PublicInputHash::Keccak256,
+        vec![-Fr::one(), Fr::random(&mut rng)],
+    );
+    let instances = circuit.instances();
+    MockProver::run(K, &circuit, instances.clone())
+        .unwrap()
+        .assert_satisfied();
+
+    let wrong_instances = vec![vec![instances[0][0] + Fr::one()]];
+    assert!(MockProver::run(K, &circuit, wrong_instances)
+        .unwrap()
+        .verify()
+        .is_err());
+}
+
+/// Decides accumulators by pairing check in circuit, where multiple ones are
+/// batched into one by [`AccumulationDecider::decide_all`] before the check.
+#[derive(Clone)]
+struct KzgDecider {
+    dk: KzgDecidingKey<Bn256>,
+    accumulators: Vec<[Value<G1Affine>; 2]>,
+}
+
+impl KzgDecider {
+    fn new(num_accumulator: usize) -> Self {
+        let mut rng = ChaCha20Rng::from_seed(Default::default());
+        let s = Fr::random(&mut rng);
+        let dk = (
+            G1::generator().to_affine(),
+            G2::generator().to_affine(),
+            (G2::generator() * s).to_affine(),
+        )
+            .into();
+        let accumulators = (0..num_accumulator)
+            .map(|_| {
+                let rhs = G1::random(&mut rng);
+                [rhs * s, rhs].map(|ec_point| Value::known(ec_point.to_affine()))
+            })
+            .collect();
+        Self { dk, accumulators }
+    }
+
+    fn tamper(mut self, idx: usize, offset: G1) -> Self {
+        let lhs = &mut self.accumulators[idx][0];
+        *lhs = lhs.map(|lhs| (G1::from(lhs) + offset).to_affine());
+        self
+    }
+}
+
+impl Circuit<Fr> for KzgDecider {
+    type Config = MainGateWithRangeConfig;
+    type FloorPlanner = V1;
+    #[cfg(feature = "halo2_circuit_params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            dk: self.dk,
+            accumulators: vec![[Value::unknown(); 2]; self.accumulators.len()],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        Accumulation::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        config.range_chip().load_table(&mut layouter)?;
+
+        layouter.assign_region(
+            || "",
+            |region| {
+                let loader = Halo2Loader::new(config.ecc_chip(), RegionCtx::new(region, 0));
+                let accumulators = self
+                    .accumulators
+                    .iter()
+                    .map(|[lhs, rhs]| {
+                        KzgAccumulator::new(
+                            loader.assign_ec_point(*lhs),
+                            loader.assign_ec_point(*rhs),
+                        )
+                    })
+                    .collect_vec();
+
+                loader.start_cost_metering(&format!(
+                    "Decide {} accumulators",
+                    self.accumulators.len()
+                ));
+                <As as AccumulationDecider<G1Affine, Rc<Halo2Loader>>>::decide_all(
+                    &self.dk,
+                    accumulators,
+                )
+                .map_err(|_| Error::Synthesis)?;
+                loader.end_cost_metering();
+                loader.print_row_metering();
+                Ok(())
+            },
+        )
+    }
+}
+
+#[test]
+#[ignore = "cause it takes several minutes to run"]
+fn test_halo2_loader_kzg_decider() {
+    use halo2_proofs::dev::MockProver;
+
+    const K: u32 = 21;
+
+    MockProver::run(K, &KzgDecider::new(2), vec![Vec::new()])
+        .unwrap()
+        .assert_satisfied();
+
+    let assert_unsatisfied = |circuit: &KzgDecider| {
+        let prover = MockProver::run(K, circuit, vec![Vec::new()]);
+        assert!(!matches!(prover.map(|prover| prover.verify()), Ok(Ok(()))));
+    };
+
+    // Invalid accumulator decided alone
+    assert_unsatisfied(&KzgDecider::new(1).tamper(0, G1::generator()));
+
+    // Invalid accumulators whose errors cancel out in plain product of pairings
+    assert_unsatisfied(
+        &KzgDecider::new(2)
+            .tamper(0, G1::generator())
+            .tamper(1, -G1::generator()),
+    );
+}
+
+type ZeromorphSuccinctVerifier = verifier::hyperplonk::HyperPlonkSuccinctVerifier<
+    KzgAs<Bn256, Zeromorph>,
+    LimbsEncoding<LIMBS, BITS>,
+>;
+
+/// Verifies HyperPlonk proof with [`Zeromorph`], and constrains the
+/// accumulator to be the one computed natively if the proof passes native
+/// succinct verification.
+struct HyperPlonkZeromorph {
+    svk: Svk,
+    protocol: HyperPlonkProtocol<G1Affine>,
+    instances: Vec<Value<Fr>>,
+    proof: Value<Vec<u8>>,
+    accumulator: Option<KzgAccumulator<G1Affine, NativeLoader>>,
+}
+
+impl HyperPlonkZeromorph {
+    fn new(
+        pk: &ZeromorphProvingKey<G1Affine>,
+        protocol: &HyperPlonkProtocol<G1Affine>,
+        q: &[Fr],
+        tamper: Option<verifier::hyperplonk::test::Tamper>,
+    ) -> Self {
+        use verifier::hyperplonk::test::{create_proof, rand_vec};
+
+        let instances = rand_vec(2);
+        let proof = {
+            let mut transcript = PoseidonTranscript::<NativeLoader, _>::new(Vec::new());
+            create_proof(pk, protocol, q, &instances, tamper, &mut transcript);
+            transcript.finalize()
+        };
+
+        let svk = pk.svk();
+        let accumulator = {
+            let instances = vec![instances.clone()];
+            let mut transcript = PoseidonTranscript::<NativeLoader, _>::new(proof.as_slice());
+            ZeromorphSuccinctVerifier::read_proof(&svk, protocol, &instances, &mut transcript)
+                .and_then(|proof| {
+                    ZeromorphSuccinctVerifier::verify(&svk, protocol, &instances, &proof)
+                })
+                .ok()
+                .and_then(|mut accumulators| accumulators.pop())
+        };
+
+        Self {
+            svk,
+            protocol: protocol.clone(),
+            instances: instances.into_iter().map(Value::known).collect(),
+            proof: Value::known(proof),
+            accumulator,
+        }
+    }
+}
+
+impl Circuit<Fr> for HyperPlonkZeromorph {
+    type Config = MainGateWithRangeConfig;
+    type FloorPlanner = V1;
+    #[cfg(feature = "halo2_circuit_params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            svk: self.svk,
+            protocol: self.protocol.clone(),
+            instances: vec![Value::unknown(); self.instances.len()],
+            proof: Value::unknown(),
+            accumulator: self.accumulator.clone(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        Accumulation::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        config.range_chip().load_table(&mut layouter)?;
+
+        layouter.assign_region(
+            || "",
+            |region| {
+                let loader = Halo2Loader::new(config.ecc_chip(), RegionCtx::new(region, 0));
+                let protocol = self.protocol.loaded(&loader);
+                let instances = vec![self
+                    .instances
+                    .iter()
+                    .map(|instance| loader.assign_scalar(*instance))
+                    .collect_vec()];
+                let mut transcript = PoseidonTranscript::<Rc<Halo2Loader>, _>::new(
+                    &loader,
+                    self.proof.as_ref().map(Vec::as_slice),
+                );
+                let proof = ZeromorphSuccinctVerifier::read_proof(
+                    &self.svk,
+                    &protocol,
+                    &instances,
+                    &mut transcript,
+                )
+                .map_err(|_| Error::Synthesis)?;
+                let KzgAccumulator { lhs, rhs } =
+                    ZeromorphSuccinctVerifier::verify(&self.svk, &protocol, &instances, &proof)
+                        .map_err(|_| Error::Synthesis)?
+                        .pop()
+                        .unwrap();
+
+                if let Some(accumulator) = &self.accumulator {
+                    for (ec_point, expected) in [(lhs, accumulator.lhs), (rhs, accumulator.rhs)] {
+                        loader
+                            .ec_point_assert_eq(
+                                "",
+                                &ec_point,
+                                &loader.ec_point_load_const(&expected),
+                            )
+                            .map_err(|_| Error::Synthesis)?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+#[test]
+fn test_halo2_loader_hyperplonk_zeromorph() {
+    use halo2_proofs::dev::MockProver;
+    use verifier::hyperplonk::test::{setup, Tamper};
+
+    const K: u32 = 20;
+
+    let (pk, dk, protocol, q) = setup();
+
+    let circuit = HyperPlonkZeromorph::new(&pk, &protocol, &q, None);
+    assert!(KzgAs::<Bn256, Zeromorph>::decide(&dk, circuit.accumulator.clone().unwrap()).is_ok());
+    MockProver::run(K, &circuit, vec![Vec::new()])
+        .unwrap()
+        .assert_satisfied();
+
+    // Accumulator with invalid opening proof is computed as is, and only
+    // rejected when being decided
+    let circuit = HyperPlonkZeromorph::new(&pk, &protocol, &q, Some(Tamper::Opening));
+    assert!(KzgAs::<Bn256, Zeromorph>::decide(&dk, circuit.accumulator.clone().unwrap()).is_err());
+    MockProver::run(K, &circuit, vec![Vec::new()])
+        .unwrap()
+        .assert_satisfied();
+
+    // Invalid witness or evaluation fails sumcheck in circuit, where there is
+    // no native accumulator to constrain
+    for tamper in [Tamper::Witness, Tamper::Evaluation] {
+        let circuit = HyperPlonkZeromorph::new(&pk, &protocol, &q, Some(tamper));
+        assert!(circuit.accumulator.is_none());
+        let prover = MockProver::run(K, &circuit, vec![Vec::new()]);
+        assert!(!matches!(prover.map(|prover| prover.verify()), Ok(Ok(()))));
+    }
+}
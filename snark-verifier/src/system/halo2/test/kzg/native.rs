@@ -1,11 +1,15 @@
 use crate::{
+    loader::native::NativeLoader,
     pcs::kzg::{Bdfg21, Gwc19, KzgAs, LimbsEncoding},
-    system::halo2::test::{
-        kzg::{
-            halo2_kzg_config, halo2_kzg_create_snark, halo2_kzg_native_verify, halo2_kzg_prepare,
-            main_gate_with_range_with_mock_kzg_accumulator, BITS, LIMBS,
+    system::halo2::{
+        test::{
+            kzg::{
+                halo2_kzg_config, halo2_kzg_create_snark, halo2_kzg_native_verify,
+                halo2_kzg_prepare, main_gate_with_range_with_mock_kzg_accumulator, BITS, LIMBS,
+            },
+            StandardPlonk,
         },
-        StandardPlonk,
+        transcript::halo2::{ChallengeScalar, Poseidon2Transcript},
     },
     verifier::plonk::PlonkVerifier,
 };
@@ -60,9 +64,44 @@ test!(
     halo2_kzg_config!(true, 2),
     StandardPlonk::rand(ChaCha20Rng::from_seed(Default::default()))
 );
+test!(
+    zk_standard_plonk_rand_without_constant,
+    9,
+    halo2_kzg_config!(true, 2),
+    StandardPlonk::rand_without_constant(ChaCha20Rng::from_seed(Default::default()))
+);
 test!(
     zk_main_gate_with_range_with_mock_kzg_accumulator,
     9,
     halo2_kzg_config!(true, 2, (0..4 * LIMBS).map(|idx| (0, idx)).collect()),
     main_gate_with_range_with_mock_kzg_accumulator::<Bn256>()
 );
+
+#[test]
+fn test_shplonk_zk_standard_plonk_rand_poseidon2() {
+    type Transcript<S> = Poseidon2Transcript<G1Affine, NativeLoader, S, 3, 2, 8, 56>;
+
+    let (params, pk, protocol, circuits) = halo2_kzg_prepare!(
+        9,
+        halo2_kzg_config!(true, 2),
+        StandardPlonk::rand(ChaCha20Rng::from_seed(Default::default()))
+    );
+    let snark = halo2_kzg_create_snark!(
+        ProverSHPLONK<_>,
+        VerifierSHPLONK<_>,
+        Transcript<_>,
+        Transcript<_>,
+        ChallengeScalar<_>,
+        &params,
+        &pk,
+        &protocol,
+        &circuits
+    );
+    halo2_kzg_native_verify!(
+        PlonkVerifier<KzgAs<Bn256, Bdfg21>, LimbsEncoding<LIMBS, BITS>>,
+        params,
+        &snark.protocol,
+        &snark.instances,
+        &mut Transcript::new(snark.proof.as_slice())
+    );
+}
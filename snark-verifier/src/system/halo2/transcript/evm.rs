@@ -2,14 +2,20 @@
 
 use crate::{
     loader::{
-        evm::{loader::Value, u256_to_fe, util::MemoryChunk, EcPoint, EvmLoader, Scalar, U256},
+        evm::{
+            loader::Value, u256_to_fe, util::MemoryChunk, EcPoint, EvmLoader, MemoryPtr,
+            PublicInputHash, Scalar, U256,
+        },
         native::{self, NativeLoader},
         Loader,
     },
     util::{
-        arithmetic::{Coordinates, CurveAffine, PrimeField},
+        arithmetic::{
+            ec_point_from_coordinates, ec_point_to_coordinates, Coordinates, CurveAffine, Field,
+            PrimeCurveAffine, PrimeField,
+        },
         hash::{Digest, Keccak256},
-        transcript::{Transcript, TranscriptRead},
+        transcript::{TraceLoader, Transcript, TranscriptRead, TranscriptWrite},
         Itertools,
     },
     Error,
@@ -23,14 +29,45 @@ use std::{
 };
 
 /// Transcript for verifier on EVM using keccak256 as hasher.
+///
+/// Elliptic curve points in proof are encoded as 64 bytes uncompressed `x`
+/// and `y` by default, or as 32 bytes compressed `x` when it's initialized to
+/// be compressed, where the most significant bit indicates `y` is odd and the
+/// second most significant bit indicates it's identity. In both cases points
+/// are absorbed into transcript as uncompressed.
 #[derive(Debug)]
 pub struct EvmTranscript<C: CurveAffine, L: Loader<C>, S, B> {
     loader: L,
     stream: S,
     buf: B,
+    compressed: bool,
     _marker: PhantomData<C>,
 }
 
+/// Values of [`EvmLoader`] are only known at runtime except constants, so
+/// [`TracingTranscript`](crate::util::transcript::TracingTranscript) with it
+/// records unknown for all the others, and only divergence in sequence of
+/// transcript operations or in constants can be found.
+impl<C> TraceLoader<C> for Rc<EvmLoader>
+where
+    C: CurveAffine,
+    C::Scalar: PrimeField<Repr = [u8; 0x20]>,
+{
+    fn trace_scalar(&self, scalar: &Scalar) -> Option<C::Scalar> {
+        match scalar.value() {
+            Value::Constant(constant) => Some(u256_to_fe(constant)),
+            _ => None,
+        }
+    }
+
+    fn trace_ec_point(&self, ec_point: &EcPoint) -> Vec<Option<C::Scalar>> {
+        match ec_point.value() {
+            Value::Constant((x, y)) => vec![Some(u256_to_fe(x)), Some(u256_to_fe(y))],
+            _ => vec![None; 2],
+        }
+    }
+}
+
 impl<C> EvmTranscript<C, Rc<EvmLoader>, usize, MemoryChunk>
 where
     C: CurveAffine,
@@ -39,21 +76,39 @@ where
     /// Initialize [`EvmTranscript`] given [`Rc<EvmLoader>`] and pre-allocate an
     /// u256 for `transcript_initial_state`.
     pub fn new(loader: &Rc<EvmLoader>) -> Self {
+        Self::new_with_compression(loader, false)
+    }
+
+    /// Initialize [`EvmTranscript`] like [`EvmTranscript::new`], but reads
+    /// elliptic curve points in proof as compressed if `compressed` is `true`.
+    ///
+    /// If a proof has been read by another transcript with the same loader,
+    /// it reads instances and proof right after that one in calldata, so
+    /// proofs of several protocols could be verified by the same verifier.
+    pub fn new_with_compression(loader: &Rc<EvmLoader>, compressed: bool) -> Self {
+        // The reserved word for `transcript_initial_state` is only included in
+        // `buf` when something is absorbed, then `buf` is never empty again.
         let ptr = loader.allocate(0x20);
-        assert_eq!(ptr, 0);
-        let mut buf = MemoryChunk::new(ptr);
-        buf.extend(0x20);
         Self {
             loader: loader.clone(),
-            stream: 0,
-            buf,
+            stream: loader.next_instance_calldata_offset(),
+            buf: MemoryChunk::new(ptr),
+            compressed,
             _marker: PhantomData,
         }
     }
 
+    fn extend_buf(&mut self, ptr: usize, len: usize) {
+        if self.buf.len() == 0 {
+            self.buf.extend(0x20);
+        }
+        assert_eq!(self.buf.end(), ptr);
+        self.buf.extend(len);
+    }
+
     /// Load `num_instance` instances from calldata to memory.
     pub fn load_instances(&mut self, num_instance: Vec<usize>) -> Vec<Vec<Scalar>> {
-        num_instance
+        let instances = num_instance
             .into_iter()
             .map(|len| {
                 iter::repeat_with(|| {
@@ -64,7 +119,34 @@ where
                 .take(len)
                 .collect_vec()
             })
-            .collect()
+            .collect();
+        self.stream = self.loader.proof_calldata_offset(self.stream);
+        instances
+    }
+
+    /// Load `num_instance` instances from calldata to memory like
+    /// [`EvmTranscript::load_instances`], except the last one, which is
+    /// computed as digest of the following `preimage_len` bytes of public
+    /// inputs in calldata hashed with `hash`.
+    pub fn load_instances_with_digest(
+        &mut self,
+        mut num_instance: Vec<usize>,
+        preimage_len: usize,
+        hash: PublicInputHash,
+    ) -> Vec<Vec<Scalar>> {
+        let num_last = num_instance
+            .last_mut()
+            .filter(|num_last| **num_last > 0)
+            .expect("Digest should be the last instance");
+        *num_last -= 1;
+
+        let mut instances = self.load_instances(num_instance);
+        let digest = self
+            .loader
+            .calldataload_public_input_digest(self.stream, preimage_len, hash);
+        self.stream += preimage_len;
+        instances.last_mut().unwrap().push(digest);
+        instances
     }
 }
 
@@ -78,10 +160,13 @@ where
     }
 
     fn squeeze_challenge(&mut self) -> Scalar {
+        if self.buf.len() == 0 {
+            self.buf.extend(0x20);
+        }
         let len = if self.buf.len() == 0x20 {
             assert_eq!(self.loader.ptr(), self.buf.end());
             let buf_end = self.buf.end();
-            let code = format!("mstore8({buf_end}, 1)");
+            let code = format!("mstore8({}, 1)", MemoryPtr(buf_end));
             self.loader.code_mut().runtime_append(code);
             0x21
         } else {
@@ -93,10 +178,13 @@ where
         let dup_hash_ptr = self.loader.allocate(0x20);
         let code = format!(
             "{{
-            let hash := mload({hash_ptr:#x})
-            mstore({challenge_ptr:#x}, mod(hash, f_q))
-            mstore({dup_hash_ptr:#x}, hash)
-        }}"
+            let hash := mload({})
+            mstore({}, mod(hash, f_q))
+            mstore({}, hash)
+        }}",
+            MemoryPtr(hash_ptr),
+            MemoryPtr(challenge_ptr),
+            MemoryPtr(dup_hash_ptr)
         );
         self.loader.code_mut().runtime_append(code);
 
@@ -108,8 +196,7 @@ where
 
     fn common_ec_point(&mut self, ec_point: &EcPoint) -> Result<(), Error> {
         if let Value::Memory(ptr) = ec_point.value() {
-            assert_eq!(self.buf.end(), ptr);
-            self.buf.extend(0x40);
+            self.extend_buf(ptr, 0x40);
         } else {
             unreachable!()
         }
@@ -118,13 +205,11 @@ where
 
     fn common_scalar(&mut self, scalar: &Scalar) -> Result<(), Error> {
         match scalar.value() {
-            Value::Constant(_) if self.buf.ptr() == 0 => {
+            Value::Constant(_) if self.buf.len() == 0 => {
                 self.loader.copy_scalar(scalar, self.buf.ptr());
-            }
-            Value::Memory(ptr) => {
-                assert_eq!(self.buf.end(), ptr);
                 self.buf.extend(0x20);
             }
+            Value::Memory(ptr) => self.extend_buf(ptr, 0x20),
             _ => unreachable!(),
         }
         Ok(())
@@ -144,8 +229,15 @@ where
     }
 
     fn read_ec_point(&mut self) -> Result<EcPoint, Error> {
-        let ec_point = self.loader.calldataload_ec_point(self.stream);
-        self.stream += 0x40;
+        let ec_point = if self.compressed {
+            let ec_point = self.loader.calldataload_ec_point_compressed(self.stream);
+            self.stream += 0x20;
+            ec_point
+        } else {
+            let ec_point = self.loader.calldataload_ec_point(self.stream);
+            self.stream += 0x40;
+            ec_point
+        };
         self.common_ec_point(&ec_point)?;
         Ok(ec_point)
     }
@@ -158,10 +250,18 @@ where
     /// Initialize [`EvmTranscript`] given readable or writeable stream for
     /// verifying or proving with [`NativeLoader`].
     pub fn new(stream: S) -> Self {
+        Self::new_with_compression(stream, false)
+    }
+
+    /// Initialize [`EvmTranscript`] like [`EvmTranscript::new`], but reads or
+    /// writes elliptic curve points in proof as compressed if `compressed` is
+    /// `true`.
+    pub fn new_with_compression(stream: S, compressed: bool) -> Self {
         Self {
             loader: NativeLoader,
             stream,
             buf: Vec::new(),
+            compressed,
             _marker: PhantomData,
         }
     }
@@ -193,15 +293,7 @@ where
     }
 
     fn common_ec_point(&mut self, ec_point: &C) -> Result<(), Error> {
-        let coordinates =
-            Option::<Coordinates<C>>::from(ec_point.coordinates()).ok_or_else(|| {
-                Error::Transcript(
-                    io::ErrorKind::Other,
-                    "Invalid elliptic curve point".to_string(),
-                )
-            })?;
-
-        [coordinates.x(), coordinates.y()].map(|coordinate| {
+        ec_point_to_coordinates(ec_point).map(|coordinate| {
             self.buf
                 .extend(coordinate.to_repr().as_ref().iter().rev().cloned());
         });
@@ -239,29 +331,52 @@ where
     }
 
     fn read_ec_point(&mut self) -> Result<C, Error> {
-        let [mut x, mut y] = [<C::Base as PrimeField>::Repr::default(); 2];
-        for repr in [&mut x, &mut y] {
+        let ec_point = if self.compressed {
+            let mut compressed = <C::Base as PrimeField>::Repr::default();
             self.stream
-                .read_exact(repr.as_mut())
+                .read_exact(compressed.as_mut())
                 .map_err(|err| Error::Transcript(err.kind(), err.to_string()))?;
-            repr.as_mut().reverse();
-        }
-        let x = Option::from(<C::Base as PrimeField>::from_repr(x));
-        let y = Option::from(<C::Base as PrimeField>::from_repr(y));
-        let ec_point = x
-            .zip(y)
-            .and_then(|(x, y)| Option::from(C::from_xy(x, y)))
-            .ok_or_else(|| {
-                Error::Transcript(
-                    io::ErrorKind::Other,
-                    "Invalid elliptic curve point encoding in proof".to_string(),
-                )
-            })?;
+            ec_point_from_compressed(compressed)
+        } else {
+            let [mut x, mut y] = [<C::Base as PrimeField>::Repr::default(); 2];
+            for repr in [&mut x, &mut y] {
+                self.stream
+                    .read_exact(repr.as_mut())
+                    .map_err(|err| Error::Transcript(err.kind(), err.to_string()))?;
+                repr.as_mut().reverse();
+            }
+            let x = Option::from(<C::Base as PrimeField>::from_repr(x));
+            let y = Option::from(<C::Base as PrimeField>::from_repr(y));
+            x.zip(y).and_then(|(x, y)| ec_point_from_coordinates(x, y))
+        };
+        let ec_point = ec_point.ok_or_else(|| {
+            Error::Transcript(
+                io::ErrorKind::Other,
+                "Invalid elliptic curve point encoding in proof".to_string(),
+            )
+        })?;
         self.common_ec_point(&ec_point)?;
         Ok(ec_point)
     }
 }
 
+impl<C, W> TranscriptWrite<C> for EvmTranscript<C, NativeLoader, W, Vec<u8>>
+where
+    C: CurveAffine,
+    C::Scalar: PrimeField<Repr = [u8; 0x20]>,
+    W: Write,
+{
+    fn write_scalar(&mut self, scalar: C::Scalar) -> Result<(), Error> {
+        halo2_proofs::transcript::TranscriptWrite::<C, ChallengeEvm<C>>::write_scalar(self, scalar)
+            .map_err(|err| Error::Transcript(err.kind(), err.to_string()))
+    }
+
+    fn write_ec_point(&mut self, ec_point: C) -> Result<(), Error> {
+        halo2_proofs::transcript::TranscriptWrite::<C, ChallengeEvm<C>>::write_point(self, ec_point)
+            .map_err(|err| Error::Transcript(err.kind(), err.to_string()))
+    }
+}
+
 impl<C, S> EvmTranscript<C, NativeLoader, S, Vec<u8>>
 where
     C: CurveAffine,
@@ -278,6 +393,56 @@ where
     }
 }
 
+const COMPRESSED_Y_IS_ODD: u8 = 0x80;
+const COMPRESSED_IDENTITY: u8 = 0x40;
+
+/// Returns compressed big-endian encoding of `ec_point` as described in
+/// [`EvmTranscript`].
+fn ec_point_to_compressed<C: CurveAffine>(ec_point: &C) -> <C::Base as PrimeField>::Repr {
+    let mut compressed = <C::Base as PrimeField>::Repr::default();
+    match Option::<Coordinates<C>>::from(ec_point.coordinates()) {
+        Some(coordinates) => {
+            compressed = coordinates.x().to_repr();
+            compressed.as_mut().reverse();
+            if bool::from(coordinates.y().is_odd()) {
+                compressed.as_mut()[0] |= COMPRESSED_Y_IS_ODD;
+            }
+        }
+        None => compressed.as_mut()[0] = COMPRESSED_IDENTITY,
+    }
+    compressed
+}
+
+/// Returns elliptic curve point decoded from compressed big-endian encoding as
+/// described in [`EvmTranscript`], or `None` if it's invalid or non-canonical.
+fn ec_point_from_compressed<C: CurveAffine>(
+    mut compressed: <C::Base as PrimeField>::Repr,
+) -> Option<C> {
+    let flags = compressed.as_ref()[0] & (COMPRESSED_Y_IS_ODD | COMPRESSED_IDENTITY);
+    compressed.as_mut()[0] &= !flags;
+    if flags == COMPRESSED_IDENTITY {
+        return compressed
+            .as_ref()
+            .iter()
+            .all(|byte| *byte == 0)
+            .then(C::identity);
+    }
+    if flags & COMPRESSED_IDENTITY != 0 {
+        return None;
+    }
+
+    compressed.as_mut().reverse();
+    let x = Option::<C::Base>::from(C::Base::from_repr(compressed))?;
+    let y_square = x.square() * x + C::a() * x + C::b();
+    let y = Option::<C::Base>::from(y_square.sqrt())?;
+    let y = if bool::from(y.is_odd()) == (flags == COMPRESSED_Y_IS_ODD) {
+        y
+    } else {
+        -y
+    };
+    Option::from(C::from_xy(x, y))
+}
+
 /// [`EncodedChallenge`] implemented for verifier on EVM, which use input in
 /// big-endian as the challenge.
 #[derive(Debug)]
@@ -371,14 +536,12 @@ where
 {
     fn write_point(&mut self, ec_point: C) -> io::Result<()> {
         halo2_proofs::transcript::Transcript::<C, ChallengeEvm<C>>::common_point(self, ec_point)?;
-        let coords: Coordinates<C> = Option::from(ec_point.coordinates()).ok_or_else(|| {
-            io::Error::new(
-                io::ErrorKind::Other,
-                "Cannot write points at infinity to the transcript",
-            )
-        })?;
-        let mut x = coords.x().to_repr();
-        let mut y = coords.y().to_repr();
+        if self.compressed {
+            let compressed = ec_point_to_compressed(&ec_point);
+            return self.stream_mut().write_all(compressed.as_ref());
+        }
+        let [mut x, mut y] =
+            ec_point_to_coordinates(&ec_point).map(|coordinate| coordinate.to_repr());
         x.as_mut().reverse();
         y.as_mut().reverse();
         self.stream_mut().write_all(x.as_ref())?;
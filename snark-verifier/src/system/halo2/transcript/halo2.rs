@@ -2,22 +2,25 @@
 
 use crate::{
     loader::{
-        halo2::{EcPoint, EccInstructions, Halo2Loader, Scalar},
+        halo2::{EcPoint, EccInstructions, Halo2Loader, IntegerInstructions, Scalar},
         native::{self, NativeLoader},
         Loader, ScalarLoader,
     },
     util::{
-        arithmetic::{fe_to_fe, CurveAffine, FromUniformBytes, PrimeField},
-        hash::Poseidon,
-        transcript::{Transcript, TranscriptRead, TranscriptWrite},
+        arithmetic::{
+            ec_point_to_coordinates, fe_to_fe, CurveAffine, Field, FromUniformBytes,
+            PrimeCurveAffine, PrimeField,
+        },
+        hash::{Poseidon, Poseidon2, SpongeHasher},
+        transcript::{TraceLoader, Transcript, TranscriptRead, TranscriptWrite},
         Itertools,
     },
     Error,
 };
 use halo2_proofs::{circuit::Value, transcript::EncodedChallenge};
-use poseidon::Spec;
 use std::{
     io::{self, Read, Write},
+    marker::PhantomData,
     rc::Rc,
 };
 
@@ -32,9 +35,59 @@ where
         ctx: &mut Self::Context,
         ec_point: &Self::AssignedEcPoint,
     ) -> Result<Vec<Self::AssignedScalar>, Error>;
+
+    /// Returns witness values of [`NativeEncoding::encode`] without assigning
+    /// anything, which are unknown by default for chips not exposing them.
+    fn encode_value(&self, _: &Self::AssignedEcPoint) -> Option<Vec<Value<C::Scalar>>> {
+        None
+    }
 }
 
-/// Transcript for verifier in [`halo2_proofs`] circuit using poseidon hasher.
+/// Traces elliptic curve point as its [`NativeEncoding`], which is what
+/// [`PoseidonTranscript`] absorbs, so it matches the trace of [`NativeLoader`].
+///
+/// Nothing is assigned when tracing, so a traced circuit has exactly the same
+/// layout as the one without tracing.
+impl<'a, C, EccChip> TraceLoader<C> for Rc<Halo2Loader<'a, C, EccChip>>
+where
+    C: CurveAffine,
+    EccChip: NativeEncoding<'a, C>,
+{
+    fn trace_scalar(&self, scalar: &Scalar<'a, C, EccChip>) -> Option<C::Scalar> {
+        match scalar.maybe_const() {
+            Some(constant) => Some(constant),
+            None => trace_value(self.scalar_chip().integer_value(&scalar.assigned())),
+        }
+    }
+
+    fn trace_ec_point(&self, ec_point: &EcPoint<'a, C, EccChip>) -> Vec<Option<C::Scalar>> {
+        if let Some(constant) = ec_point.maybe_const() {
+            return TraceLoader::<C>::trace_ec_point(&native::LOADER, &constant);
+        }
+
+        let (assigned, non_identity) = ec_point.assigned_with_flag();
+        // Identity is encoded as `(0, 0)` same as `NativeLoader`
+        let is_identity = non_identity
+            .and_then(|non_identity| self.trace_scalar(&non_identity))
+            .map(|non_identity| non_identity == C::Scalar::ZERO);
+        if is_identity == Some(true) {
+            return TraceLoader::<C>::trace_ec_point(&native::LOADER, &C::identity());
+        }
+        match self.ecc_chip().encode_value(&assigned) {
+            Some(encoded) => encoded.into_iter().map(trace_value).collect(),
+            None => vec![None; 2],
+        }
+    }
+}
+
+fn trace_value<F>(value: Value<F>) -> Option<F> {
+    let mut traced = None;
+    value.map(|value| traced = Some(value));
+    traced
+}
+
+/// Transcript for verifier in [`halo2_proofs`] circuit using poseidon hasher
+/// by default, or any other [`SpongeHasher`] like [`Poseidon2`] given as `H`.
 /// Currently It assumes the elliptic curve scalar field is same as native
 /// field.
 #[derive(Debug)]
@@ -46,56 +99,110 @@ pub struct PoseidonTranscript<
     const RATE: usize,
     const R_F: usize,
     const R_P: usize,
+    H = Poseidon<
+        <C as CurveAffine>::ScalarExt,
+        <L as ScalarLoader<<C as CurveAffine>::ScalarExt>>::LoadedScalar,
+        T,
+        RATE,
+    >,
 > where
     C: CurveAffine,
     L: Loader<C>,
 {
     loader: L,
     stream: S,
-    buf: Poseidon<C::Scalar, <L as ScalarLoader<C::Scalar>>::LoadedScalar, T, RATE>,
+    buf: H,
+    _marker: PhantomData<C>,
 }
 
-impl<'a, C, R, EccChip, const T: usize, const RATE: usize, const R_F: usize, const R_P: usize>
-    PoseidonTranscript<C, Rc<Halo2Loader<'a, C, EccChip>>, Value<R>, T, RATE, R_F, R_P>
+/// [`PoseidonTranscript`] using [`Poseidon2`] hasher.
+pub type Poseidon2Transcript<
+    C,
+    L,
+    S,
+    const T: usize,
+    const RATE: usize,
+    const R_F: usize,
+    const R_P: usize,
+> = PoseidonTranscript<
+    C,
+    L,
+    S,
+    T,
+    RATE,
+    R_F,
+    R_P,
+    Poseidon2<
+        <C as CurveAffine>::ScalarExt,
+        <L as ScalarLoader<<C as CurveAffine>::ScalarExt>>::LoadedScalar,
+        T,
+        RATE,
+    >,
+>;
+
+impl<
+        'a,
+        C,
+        R,
+        EccChip,
+        const T: usize,
+        const RATE: usize,
+        const R_F: usize,
+        const R_P: usize,
+        H,
+    > PoseidonTranscript<C, Rc<Halo2Loader<'a, C, EccChip>>, Value<R>, T, RATE, R_F, R_P, H>
 where
     C: CurveAffine,
     C::Scalar: FromUniformBytes<64>,
     R: Read,
     EccChip: NativeEncoding<'a, C>,
+    H: SpongeHasher<C::Scalar, Scalar<'a, C, EccChip>>,
 {
     /// Initialize [`PoseidonTranscript`] given [`Rc<Halo2Loader>`].
     pub fn new(loader: &Rc<Halo2Loader<'a, C, EccChip>>, stream: Value<R>) -> Self {
-        let buf = Poseidon::new(loader, R_F, R_P);
+        let buf = H::new(loader, R_F, R_P);
         Self {
             loader: loader.clone(),
             stream,
             buf,
+            _marker: PhantomData,
         }
     }
 
-    /// Initialize [`PoseidonTranscript`] from a precomputed spec of round constants and MDS matrix because computing the constants is expensive.
+    /// Initialize [`PoseidonTranscript`] from a precomputed spec of round constants and MDS matrix (or internal matrix for [`Poseidon2`]) because computing the constants is expensive.
     pub fn from_spec(
         loader: &Rc<Halo2Loader<'a, C, EccChip>>,
         stream: Value<R>,
-        spec: Spec<C::Scalar, T, RATE>,
+        spec: H::Spec,
     ) -> Self {
-        let buf = Poseidon::from_spec(loader, spec);
+        let buf = H::from_spec(loader, spec);
         Self {
             loader: loader.clone(),
             stream,
             buf,
+            _marker: PhantomData,
         }
     }
 }
 
-impl<'a, C, R, EccChip, const T: usize, const RATE: usize, const R_F: usize, const R_P: usize>
-    Transcript<C, Rc<Halo2Loader<'a, C, EccChip>>>
-    for PoseidonTranscript<C, Rc<Halo2Loader<'a, C, EccChip>>, Value<R>, T, RATE, R_F, R_P>
+impl<
+        'a,
+        C,
+        R,
+        EccChip,
+        const T: usize,
+        const RATE: usize,
+        const R_F: usize,
+        const R_P: usize,
+        H,
+    > Transcript<C, Rc<Halo2Loader<'a, C, EccChip>>>
+    for PoseidonTranscript<C, Rc<Halo2Loader<'a, C, EccChip>>, Value<R>, T, RATE, R_F, R_P, H>
 where
     C: CurveAffine,
     C::Scalar: FromUniformBytes<64>,
     R: Read,
     EccChip: NativeEncoding<'a, C>,
+    H: SpongeHasher<C::Scalar, Scalar<'a, C, EccChip>>,
 {
     fn loader(&self) -> &Rc<Halo2Loader<'a, C, EccChip>> {
         &self.loader
@@ -111,35 +218,51 @@ where
     }
 
     fn common_ec_point(&mut self, ec_point: &EcPoint<'a, C, EccChip>) -> Result<(), Error> {
+        let (assigned, non_identity) = ec_point.assigned_with_flag();
         let encoded = self
             .loader
             .ecc_chip()
-            .encode(&mut self.loader.ctx_mut(), &ec_point.assigned())
-            .map(|encoded| {
-                encoded
-                    .into_iter()
-                    .map(|encoded| self.loader.scalar_from_assigned(encoded))
-                    .collect_vec()
-            })
+            .encode(&mut self.loader.ctx_mut(), &assigned)
             .map_err(|_| {
                 Error::Transcript(
                     io::ErrorKind::Other,
                     "Invalid elliptic curve point".to_string(),
                 )
             })?;
+        // Identity is encoded as `(0, 0)` same as `NativeLoader`
+        let encoded = encoded
+            .into_iter()
+            .map(|encoded| {
+                let encoded = self.loader.scalar_from_assigned(encoded);
+                match non_identity.as_ref() {
+                    Some(non_identity) => encoded * non_identity,
+                    None => encoded,
+                }
+            })
+            .collect_vec();
         self.buf.update(&encoded);
         Ok(())
     }
 }
 
-impl<'a, C, R, EccChip, const T: usize, const RATE: usize, const R_F: usize, const R_P: usize>
-    TranscriptRead<C, Rc<Halo2Loader<'a, C, EccChip>>>
-    for PoseidonTranscript<C, Rc<Halo2Loader<'a, C, EccChip>>, Value<R>, T, RATE, R_F, R_P>
+impl<
+        'a,
+        C,
+        R,
+        EccChip,
+        const T: usize,
+        const RATE: usize,
+        const R_F: usize,
+        const R_P: usize,
+        H,
+    > TranscriptRead<C, Rc<Halo2Loader<'a, C, EccChip>>>
+    for PoseidonTranscript<C, Rc<Halo2Loader<'a, C, EccChip>>, Value<R>, T, RATE, R_F, R_P, H>
 where
     C: CurveAffine,
     C::Scalar: FromUniformBytes<64>,
     R: Read,
     EccChip: NativeEncoding<'a, C>,
+    H: SpongeHasher<C::Scalar, Scalar<'a, C, EccChip>>,
 {
     fn read_scalar(&mut self) -> Result<Scalar<'a, C, EccChip>, Error> {
         let scalar = self.stream.as_mut().and_then(|stream| {
@@ -166,16 +289,24 @@ where
                 .map(Value::known)
                 .unwrap_or_else(Value::unknown)
         });
-        let ec_point = self.loader.assign_ec_point(ec_point);
+        let ec_point = self.loader.assign_ec_point_maybe_identity(ec_point);
         self.common_ec_point(&ec_point)?;
         Ok(ec_point)
     }
 }
 
-impl<C: CurveAffine, S, const T: usize, const RATE: usize, const R_F: usize, const R_P: usize>
-    PoseidonTranscript<C, NativeLoader, S, T, RATE, R_F, R_P>
+impl<
+        C: CurveAffine,
+        S,
+        const T: usize,
+        const RATE: usize,
+        const R_F: usize,
+        const R_P: usize,
+        H,
+    > PoseidonTranscript<C, NativeLoader, S, T, RATE, R_F, R_P, H>
 where
     C::Scalar: FromUniformBytes<64>,
+    H: SpongeHasher<C::Scalar, C::Scalar>,
 {
     /// Initialize [`PoseidonTranscript`] given readable or writeable stream for
     /// verifying or proving with [`NativeLoader`].
@@ -183,25 +314,35 @@ where
         Self {
             loader: NativeLoader,
             stream,
-            buf: Poseidon::new(&NativeLoader, R_F, R_P),
+            buf: H::new(&NativeLoader, R_F, R_P),
+            _marker: PhantomData,
         }
     }
 
-    /// Initialize [`PoseidonTranscript`] from a precomputed spec of round constants and MDS matrix because computing the constants is expensive.
-    pub fn from_spec(stream: S, spec: Spec<C::Scalar, T, RATE>) -> Self {
-        let buf = Poseidon::from_spec(&NativeLoader, spec);
+    /// Initialize [`PoseidonTranscript`] from a precomputed spec of round constants and MDS matrix (or internal matrix for [`Poseidon2`]) because computing the constants is expensive.
+    pub fn from_spec(stream: S, spec: H::Spec) -> Self {
+        let buf = H::from_spec(&NativeLoader, spec);
         Self {
             loader: NativeLoader,
             stream,
             buf,
+            _marker: PhantomData,
         }
     }
 }
 
-impl<C: CurveAffine, S, const T: usize, const RATE: usize, const R_F: usize, const R_P: usize>
-    Transcript<C, NativeLoader> for PoseidonTranscript<C, NativeLoader, S, T, RATE, R_F, R_P>
+impl<
+        C: CurveAffine,
+        S,
+        const T: usize,
+        const RATE: usize,
+        const R_F: usize,
+        const R_P: usize,
+        H,
+    > Transcript<C, NativeLoader> for PoseidonTranscript<C, NativeLoader, S, T, RATE, R_F, R_P, H>
 where
     C::Scalar: FromUniformBytes<64>,
+    H: SpongeHasher<C::Scalar, C::Scalar>,
 {
     fn loader(&self) -> &NativeLoader {
         &native::LOADER
@@ -217,29 +358,21 @@ where
     }
 
     fn common_ec_point(&mut self, ec_point: &C) -> Result<(), Error> {
-        let encoded: Vec<_> = Option::from(ec_point.coordinates().map(|coordinates| {
-            [coordinates.x(), coordinates.y()]
-                .into_iter()
-                .cloned()
-                .map(fe_to_fe)
-                .collect_vec()
-        }))
-        .ok_or_else(|| {
-            Error::Transcript(
-                io::ErrorKind::Other,
-                "Invalid elliptic curve point encoding in proof".to_string(),
-            )
-        })?;
+        let encoded = ec_point_to_coordinates(ec_point)
+            .into_iter()
+            .map(fe_to_fe)
+            .collect_vec();
         self.buf.update(&encoded);
         Ok(())
     }
 }
 
-impl<C, R, const T: usize, const RATE: usize, const R_F: usize, const R_P: usize>
-    TranscriptRead<C, NativeLoader> for PoseidonTranscript<C, NativeLoader, R, T, RATE, R_F, R_P>
+impl<C, R, const T: usize, const RATE: usize, const R_F: usize, const R_P: usize, H>
+    TranscriptRead<C, NativeLoader> for PoseidonTranscript<C, NativeLoader, R, T, RATE, R_F, R_P, H>
 where
     C: CurveAffine,
     C::Scalar: FromUniformBytes<64>,
+    H: SpongeHasher<C::Scalar, C::Scalar>,
     R: Read,
 {
     fn read_scalar(&mut self) -> Result<C::Scalar, Error> {
@@ -273,8 +406,8 @@ where
     }
 }
 
-impl<C, W, const T: usize, const RATE: usize, const R_F: usize, const R_P: usize>
-    PoseidonTranscript<C, NativeLoader, W, T, RATE, R_F, R_P>
+impl<C, W, const T: usize, const RATE: usize, const R_F: usize, const R_P: usize, H>
+    PoseidonTranscript<C, NativeLoader, W, T, RATE, R_F, R_P, H>
 where
     C: CurveAffine,
     W: Write,
@@ -290,11 +423,12 @@ where
     }
 }
 
-impl<C, W, const T: usize, const RATE: usize, const R_F: usize, const R_P: usize> TranscriptWrite<C>
-    for PoseidonTranscript<C, NativeLoader, W, T, RATE, R_F, R_P>
+impl<C, W, const T: usize, const RATE: usize, const R_F: usize, const R_P: usize, H>
+    TranscriptWrite<C> for PoseidonTranscript<C, NativeLoader, W, T, RATE, R_F, R_P, H>
 where
     C: CurveAffine,
     C::Scalar: FromUniformBytes<64>,
+    H: SpongeHasher<C::Scalar, C::Scalar>,
     W: Write,
 {
     fn write_scalar(&mut self, scalar: C::Scalar) -> Result<(), Error> {
@@ -338,11 +472,19 @@ impl<C: CurveAffine> EncodedChallenge<C> for ChallengeScalar<C> {
     }
 }
 
-impl<C: CurveAffine, S, const T: usize, const RATE: usize, const R_F: usize, const R_P: usize>
-    halo2_proofs::transcript::Transcript<C, ChallengeScalar<C>>
-    for PoseidonTranscript<C, NativeLoader, S, T, RATE, R_F, R_P>
+impl<
+        C: CurveAffine,
+        S,
+        const T: usize,
+        const RATE: usize,
+        const R_F: usize,
+        const R_P: usize,
+        H,
+    > halo2_proofs::transcript::Transcript<C, ChallengeScalar<C>>
+    for PoseidonTranscript<C, NativeLoader, S, T, RATE, R_F, R_P, H>
 where
     C::Scalar: FromUniformBytes<64>,
+    H: SpongeHasher<C::Scalar, C::Scalar>,
 {
     fn squeeze_challenge(&mut self) -> ChallengeScalar<C> {
         ChallengeScalar::new(&Transcript::squeeze_challenge(self))
@@ -365,12 +507,13 @@ where
     }
 }
 
-impl<C, R, const T: usize, const RATE: usize, const R_F: usize, const R_P: usize>
+impl<C, R, const T: usize, const RATE: usize, const R_F: usize, const R_P: usize, H>
     halo2_proofs::transcript::TranscriptRead<C, ChallengeScalar<C>>
-    for PoseidonTranscript<C, NativeLoader, R, T, RATE, R_F, R_P>
+    for PoseidonTranscript<C, NativeLoader, R, T, RATE, R_F, R_P, H>
 where
     C: CurveAffine,
     C::Scalar: FromUniformBytes<64>,
+    H: SpongeHasher<C::Scalar, C::Scalar>,
     R: Read,
 {
     fn read_point(&mut self) -> io::Result<C> {
@@ -390,12 +533,13 @@ where
     }
 }
 
-impl<C, R, const T: usize, const RATE: usize, const R_F: usize, const R_P: usize>
+impl<C, R, const T: usize, const RATE: usize, const R_F: usize, const R_P: usize, H>
     halo2_proofs::transcript::TranscriptReadBuffer<R, C, ChallengeScalar<C>>
-    for PoseidonTranscript<C, NativeLoader, R, T, RATE, R_F, R_P>
+    for PoseidonTranscript<C, NativeLoader, R, T, RATE, R_F, R_P, H>
 where
     C: CurveAffine,
     C::Scalar: FromUniformBytes<64>,
+    H: SpongeHasher<C::Scalar, C::Scalar>,
     R: Read,
 {
     fn init(reader: R) -> Self {
@@ -403,12 +547,13 @@ where
     }
 }
 
-impl<C, W, const T: usize, const RATE: usize, const R_F: usize, const R_P: usize>
+impl<C, W, const T: usize, const RATE: usize, const R_F: usize, const R_P: usize, H>
     halo2_proofs::transcript::TranscriptWrite<C, ChallengeScalar<C>>
-    for PoseidonTranscript<C, NativeLoader, W, T, RATE, R_F, R_P>
+    for PoseidonTranscript<C, NativeLoader, W, T, RATE, R_F, R_P, H>
 where
     C: CurveAffine,
     C::Scalar: FromUniformBytes<64>,
+    H: SpongeHasher<C::Scalar, C::Scalar>,
     W: Write,
 {
     fn write_point(&mut self, ec_point: C) -> io::Result<()> {
@@ -426,12 +571,13 @@ where
     }
 }
 
-impl<C, W, const T: usize, const RATE: usize, const R_F: usize, const R_P: usize>
+impl<C, W, const T: usize, const RATE: usize, const R_F: usize, const R_P: usize, H>
     halo2_proofs::transcript::TranscriptWriterBuffer<W, C, ChallengeScalar<C>>
-    for PoseidonTranscript<C, NativeLoader, W, T, RATE, R_F, R_P>
+    for PoseidonTranscript<C, NativeLoader, W, T, RATE, R_F, R_P, H>
 where
     C: CurveAffine,
     C::Scalar: FromUniformBytes<64>,
+    H: SpongeHasher<C::Scalar, C::Scalar>,
     W: Write,
 {
     fn init(writer: W) -> Self {
@@ -446,7 +592,7 @@ where
 mod halo2_wrong {
     use crate::system::halo2::transcript::halo2::NativeEncoding;
     use halo2_curves::CurveAffine;
-    use halo2_proofs::circuit::AssignedCell;
+    use halo2_proofs::circuit::{AssignedCell, Value};
     use halo2_wrong_ecc::BaseFieldEccChip;
 
     impl<'a, C: CurveAffine, const LIMBS: usize, const BITS: usize> NativeEncoding<'a, C>
@@ -462,5 +608,45 @@ mod halo2_wrong {
                 ec_point.y().native().clone(),
             ])
         }
+
+        fn encode_value(&self, ec_point: &Self::AssignedEcPoint) -> Option<Vec<Value<C::Scalar>>> {
+            Some(vec![
+                ec_point.x().native().value().copied(),
+                ec_point.y().native().value().copied(),
+            ])
+        }
+    }
+}
+
+#[cfg(feature = "halo2_lib")]
+mod halo2_lib {
+    use crate::system::halo2::transcript::halo2::NativeEncoding;
+    use halo2_base::utils::PrimeField;
+    use halo2_curves::CurveAffine;
+    use halo2_ecc::ecc::BaseFieldEccChip;
+    use halo2_proofs::circuit::Value;
+
+    impl<'a, C: CurveAffine> NativeEncoding<'a, C> for BaseFieldEccChip<C>
+    where
+        C::Scalar: PrimeField,
+        C::Base: PrimeField,
+    {
+        fn encode(
+            &self,
+            _: &mut Self::Context,
+            ec_point: &Self::AssignedEcPoint,
+        ) -> Result<Vec<Self::AssignedScalar>, crate::Error> {
+            Ok(vec![
+                ec_point.x().native.clone(),
+                ec_point.y().native.clone(),
+            ])
+        }
+
+        fn encode_value(&self, ec_point: &Self::AssignedEcPoint) -> Option<Vec<Value<C::Scalar>>> {
+            Some(vec![
+                ec_point.x().native.value().copied(),
+                ec_point.y().native.value().copied(),
+            ])
+        }
     }
 }
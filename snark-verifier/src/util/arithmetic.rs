@@ -297,6 +297,24 @@ pub fn fe_to_limbs<F1: PrimeField, F2: PrimeField, const LIMBS: usize, const BIT
         .unwrap()
 }
 
+/// Returns affine coordinates of an elliptic curve point, where identity is
+/// encoded as `(0, 0)` as EVM precompiles expect.
+pub fn ec_point_to_coordinates<C: CurveAffine>(ec_point: &C) -> [C::Base; 2] {
+    Option::<Coordinates<C>>::from(ec_point.coordinates())
+        .map(|coordinates| [*coordinates.x(), *coordinates.y()])
+        .unwrap_or([C::Base::ZERO; 2])
+}
+
+/// Returns elliptic curve point from affine coordinates, where `(0, 0)` is
+/// decoded as identity, or `None` if it's not on the curve.
+pub fn ec_point_from_coordinates<C: CurveAffine>(x: C::Base, y: C::Base) -> Option<C> {
+    if bool::from(x.is_zero() & y.is_zero()) {
+        Some(C::identity())
+    } else {
+        C::from_xy(x, y).into()
+    }
+}
+
 /// Returns iterator that yields scalar^0, scalar^1, scalar^2...
 pub fn powers<F: Field>(scalar: F) -> impl Iterator<Item = F> {
     iter::successors(Some(F::ONE), move |power| Some(scalar * power))
@@ -1,10 +1,35 @@
 //! Hash algorithms.
 
+use crate::{loader::LoadedScalar, util::arithmetic::PrimeField};
+
 #[cfg(feature = "loader_halo2")]
 mod poseidon;
+mod poseidon2;
 
 #[cfg(feature = "loader_halo2")]
 pub use crate::util::hash::poseidon::Poseidon;
+pub use crate::util::hash::poseidon2::{Poseidon2, Poseidon2Spec};
 
+#[cfg(feature = "loader_evm")]
+pub use sha2::Sha256;
 #[cfg(feature = "loader_evm")]
 pub use sha3::{Digest, Keccak256};
+
+/// Sponge hasher over [`LoadedScalar`], which buffers absorbed elements and
+/// permutes them only when squeezing.
+pub trait SpongeHasher<F: PrimeField, L: LoadedScalar<F>>: Sized {
+    /// Round constants and matrices of the permutation.
+    type Spec;
+
+    /// Initialize a hasher with `r_f` full rounds and `r_p` partial rounds.
+    fn new(loader: &L::Loader, r_f: usize, r_p: usize) -> Self;
+
+    /// Same as `new`, but uses the given `spec` instead of creating a new one.
+    fn from_spec(loader: &L::Loader, spec: Self::Spec) -> Self;
+
+    /// Store given `elements` into buffer.
+    fn update(&mut self, elements: &[L]);
+
+    /// Consume buffer and output a challenge.
+    fn squeeze(&mut self) -> L;
+}
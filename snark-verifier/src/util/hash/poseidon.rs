@@ -2,6 +2,7 @@ use crate::{
     loader::{LoadedScalar, ScalarLoader},
     util::{
         arithmetic::{FromUniformBytes, PrimeField},
+        hash::SpongeHasher,
         Itertools,
     },
 };
@@ -201,3 +202,25 @@ impl<F: FromUniformBytes<64>, L: LoadedScalar<F>, const T: usize, const RATE: us
         self.state.apply_mds(&mds);
     }
 }
+
+impl<F: FromUniformBytes<64>, L: LoadedScalar<F>, const T: usize, const RATE: usize>
+    SpongeHasher<F, L> for Poseidon<F, L, T, RATE>
+{
+    type Spec = Spec<F, T, RATE>;
+
+    fn new(loader: &L::Loader, r_f: usize, r_p: usize) -> Self {
+        Poseidon::new(loader, r_f, r_p)
+    }
+
+    fn from_spec(loader: &L::Loader, spec: Spec<F, T, RATE>) -> Self {
+        Poseidon::from_spec(loader, spec)
+    }
+
+    fn update(&mut self, elements: &[L]) {
+        Poseidon::update(self, elements)
+    }
+
+    fn squeeze(&mut self) -> L {
+        Poseidon::squeeze(self)
+    }
+}
@@ -0,0 +1,448 @@
+//! [Poseidon2](https://eprint.iacr.org/2023/323) hasher.
+//!
+//! The permutation follows the [reference implementation], where the external
+//! linear layer is `circ(2, 1, .., 1)` for `T` in `{2, 3}` (or built from `M4`
+//! for `T` multiple of `4`), and the internal one is `J + diag(d)` with `J`
+//! the all-ones matrix, so both only cost a sum and a multiplication by small
+//! constant per element.
+//!
+//! [reference implementation]: https://github.com/HorizenLabs/poseidon2
+
+use crate::{
+    loader::{LoadedScalar, ScalarLoader},
+    util::{
+        arithmetic::{fe_from_big, modulus, PrimeField},
+        hash::SpongeHasher,
+        Itertools,
+    },
+};
+use num_bigint::BigUint;
+use std::{array, iter, marker::PhantomData, mem};
+
+/// Round constants and internal matrix of Poseidon2 permutation with width
+/// `T`.
+#[derive(Clone, Debug)]
+pub struct Poseidon2Spec<F: PrimeField, const T: usize> {
+    r_f: usize,
+    r_p: usize,
+    round_constants: Vec<[F; T]>,
+    mat_internal_diag_m_1: [F; T],
+}
+
+impl<F: PrimeField, const T: usize> Poseidon2Spec<F, T> {
+    /// Generate round constants with the Grain LFSR as the reference
+    /// implementation does, for `x^5` S-box, `r_f` full rounds and `r_p`
+    /// partial rounds.
+    ///
+    /// Only `T` in `{2, 3}` is supported because the diagonal of internal
+    /// matrix of other widths is sampled rather than derived, which should be
+    /// given to [`Poseidon2Spec::from_parts`] instead.
+    pub fn new(r_f: usize, r_p: usize) -> Self {
+        let mat_internal_diag_m_1 = match T {
+            2 => [1, 2].as_slice(),
+            3 => [1, 1, 2].as_slice(),
+            _ => panic!("Internal matrix of Poseidon2 with width {T} should be given"),
+        }
+        .iter()
+        .map(|diag: &u64| F::from(*diag))
+        .collect_vec()
+        .try_into()
+        .unwrap();
+
+        let mut grain = Grain::<F>::new(T, r_f, r_p);
+        let round_constants = (0..r_f + r_p)
+            .map(|round| {
+                if (r_f / 2..r_f / 2 + r_p).contains(&round) {
+                    let mut constants = [F::ZERO; T];
+                    constants[0] = grain.next_field_element();
+                    constants
+                } else {
+                    [(); T].map(|_| grain.next_field_element())
+                }
+            })
+            .collect();
+
+        Self::from_parts(r_f, r_p, round_constants, mat_internal_diag_m_1)
+    }
+
+    /// Construct from given round constants and internal matrix, where
+    /// `round_constants` has `r_f + r_p` entries and only the first element of
+    /// entry of partial round is used, and `mat_internal_diag_m_1` is the
+    /// diagonal of internal matrix minus `1`.
+    pub fn from_parts(
+        r_f: usize,
+        r_p: usize,
+        round_constants: Vec<[F; T]>,
+        mat_internal_diag_m_1: [F; T],
+    ) -> Self {
+        assert!(T == 2 || T == 3 || T % 4 == 0);
+        assert!(r_f % 2 == 0);
+        assert_eq!(round_constants.len(), r_f + r_p);
+
+        Self {
+            r_f,
+            r_p,
+            round_constants,
+            mat_internal_diag_m_1,
+        }
+    }
+
+    /// Returns number of full rounds.
+    pub fn r_f(&self) -> usize {
+        self.r_f
+    }
+
+    /// Returns number of partial rounds.
+    pub fn r_p(&self) -> usize {
+        self.r_p
+    }
+
+    /// Returns round constants.
+    pub fn round_constants(&self) -> &[[F; T]] {
+        &self.round_constants
+    }
+
+    /// Returns diagonal of internal matrix minus `1`.
+    pub fn mat_internal_diag_m_1(&self) -> &[F; T] {
+        &self.mat_internal_diag_m_1
+    }
+
+    /// Compress `inputs` into a single element by permutation with
+    /// feed-forward, which is the compression mode for Merkle tree in the
+    /// paper.
+    ///
+    /// # Panic
+    ///
+    /// If `inputs` is empty or has more than `T` elements.
+    pub fn compress<L: LoadedScalar<F>>(&self, inputs: &[L]) -> L {
+        assert!(!inputs.is_empty() && inputs.len() <= T);
+
+        let loader = inputs[0].loader();
+        let mut state = State::new(array::from_fn(|idx| {
+            inputs
+                .get(idx)
+                .cloned()
+                .unwrap_or_else(|| loader.load_zero())
+        }));
+        permute(self, &mut state);
+        state.inner[0].clone() + &inputs[0]
+    }
+}
+
+/// Grain LFSR to generate pseudo-random field elements as round constants.
+struct Grain<F> {
+    state: Vec<bool>,
+    modulus: BigUint,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField> Grain<F> {
+    fn new(t: usize, r_f: usize, r_p: usize) -> Self {
+        // Field is prime field and S-box is `x^alpha`.
+        let state = [
+            (1, 2),
+            (0, 4),
+            (F::NUM_BITS as usize, 12),
+            (t, 12),
+            (r_f, 10),
+            (r_p, 10),
+        ]
+        .into_iter()
+        .flat_map(|(value, len)| (0..len).rev().map(move |idx| (value >> idx) & 1 == 1))
+        .chain(iter::repeat(true).take(30))
+        .collect();
+        let mut grain = Self {
+            state,
+            modulus: modulus::<F>(),
+            _marker: PhantomData,
+        };
+        for _ in 0..160 {
+            grain.next_bit_raw();
+        }
+        grain
+    }
+
+    fn next_bit_raw(&mut self) -> bool {
+        let bit = [62, 51, 38, 23, 13, 0]
+            .into_iter()
+            .fold(false, |acc, idx| acc ^ self.state[idx]);
+        self.state.remove(0);
+        self.state.push(bit);
+        bit
+    }
+
+    fn next_bit(&mut self) -> bool {
+        while !self.next_bit_raw() {
+            self.next_bit_raw();
+        }
+        self.next_bit_raw()
+    }
+
+    fn next_field_element(&mut self) -> F {
+        loop {
+            let value = (0..F::NUM_BITS).fold(BigUint::default(), |acc, _| {
+                (acc << 1usize) + BigUint::from(self.next_bit() as u8)
+            });
+            if value < self.modulus {
+                return fe_from_big(value);
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct State<F: PrimeField, L, const T: usize> {
+    inner: [L; T],
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField, L: LoadedScalar<F>, const T: usize> State<F, L, T> {
+    fn new(inner: [L; T]) -> Self {
+        Self {
+            inner,
+            _marker: PhantomData,
+        }
+    }
+
+    fn loader(&self) -> &L::Loader {
+        self.inner[0].loader()
+    }
+
+    fn power5(value: &L) -> L {
+        value
+            .loader()
+            .sum_products(&[(value, &value.square().square())])
+    }
+
+    fn sbox_full(&mut self) {
+        for state in self.inner.iter_mut() {
+            *state = Self::power5(state);
+        }
+    }
+
+    fn sbox_part(&mut self) {
+        self.inner[0] = Self::power5(&self.inner[0]);
+    }
+
+    fn absorb(&mut self, inputs: &[L]) {
+        assert!(inputs.len() < T);
+
+        self.inner
+            .iter_mut()
+            .skip(1)
+            .zip(inputs)
+            .for_each(|(state, input)| {
+                *state = state.loader().sum(&[state, input]);
+            });
+        if let Some(state) = self.inner.get_mut(1 + inputs.len()) {
+            *state = state.loader().sum_with_const(&[state], F::ONE);
+        }
+    }
+
+    /// Apply external matrix then add `constants`.
+    fn apply_external(&mut self, constants: &[F; T]) {
+        let inner = if T % 4 == 0 {
+            let m4 = [[5, 7, 1, 3], [4, 6, 1, 1], [1, 3, 5, 7], [1, 1, 4, 6]]
+                .map(|row| row.map(|coeff: u64| F::from(coeff)));
+            let chunks = self
+                .inner
+                .chunks(4)
+                .map(|chunk| {
+                    m4.iter()
+                        .map(|row| {
+                            self.loader().sum_with_coeff(
+                                &row.iter().cloned().zip(chunk.iter()).collect_vec(),
+                            )
+                        })
+                        .collect_vec()
+                })
+                .collect_vec();
+            if chunks.len() == 1 {
+                chunks[0]
+                    .iter()
+                    .zip(constants.iter())
+                    .map(|(state, constant)| self.loader().sum_with_const(&[state], *constant))
+                    .collect_vec()
+            } else {
+                let sums = (0..4)
+                    .map(|idx| {
+                        self.loader()
+                            .sum(&chunks.iter().map(|chunk| &chunk[idx]).collect_vec())
+                    })
+                    .collect_vec();
+                chunks
+                    .iter()
+                    .flatten()
+                    .zip(sums.iter().cycle())
+                    .zip(constants.iter())
+                    .map(|((state, sum), constant)| {
+                        self.loader().sum_with_const(&[state, sum], *constant)
+                    })
+                    .collect_vec()
+            }
+        } else {
+            let sum = self.loader().sum(&self.inner.iter().collect_vec());
+            self.inner
+                .iter()
+                .zip(constants.iter())
+                .map(|(state, constant)| self.loader().sum_with_const(&[state, &sum], *constant))
+                .collect_vec()
+        };
+        self.inner = inner.try_into().unwrap();
+    }
+
+    /// Apply internal matrix then add `constants`.
+    fn apply_internal(&mut self, mat_internal_diag_m_1: &[F; T], constants: &[F; T]) {
+        let sum = self.loader().sum(&self.inner.iter().collect_vec());
+        self.inner = self
+            .inner
+            .iter()
+            .zip(mat_internal_diag_m_1.iter())
+            .zip(constants.iter())
+            .map(|((state, diag), constant)| {
+                self.loader()
+                    .sum_with_coeff_and_const(&[(*diag, state), (F::ONE, &sum)], *constant)
+            })
+            .collect_vec()
+            .try_into()
+            .unwrap();
+    }
+}
+
+/// Poseidon2 hasher with configurable `RATE`.
+///
+/// It has the same sponge construction as [`Poseidon`](super::Poseidon),
+/// only the permutation is replaced.
+#[derive(Debug)]
+pub struct Poseidon2<F: PrimeField, L, const T: usize, const RATE: usize> {
+    spec: Poseidon2Spec<F, T>,
+    state: State<F, L, T>,
+    buf: Vec<L>,
+}
+
+impl<F: PrimeField, L: LoadedScalar<F>, const T: usize, const RATE: usize>
+    Poseidon2<F, L, T, RATE>
+{
+    /// Initialize a poseidon2 hasher.
+    pub fn new(loader: &L::Loader, r_f: usize, r_p: usize) -> Self {
+        Self::from_spec(loader, Poseidon2Spec::new(r_f, r_p))
+    }
+
+    /// Same as `new`, but uses the given `spec` instead of creating a new one.
+    pub fn from_spec(loader: &L::Loader, spec: Poseidon2Spec<F, T>) -> Self {
+        let mut state = [F::ZERO; T];
+        state[0] = F::from_u128(1 << 64);
+        Self {
+            spec,
+            state: State::new(state.map(|state| loader.load_const(&state))),
+            buf: Vec::new(),
+        }
+    }
+
+    /// Store given `elements` into buffer.
+    pub fn update(&mut self, elements: &[L]) {
+        self.buf.extend_from_slice(elements);
+    }
+
+    /// Consume buffer and perform permutation, then output second element of
+    /// state.
+    pub fn squeeze(&mut self) -> L {
+        let buf = mem::take(&mut self.buf);
+        let exact = buf.len() % RATE == 0;
+
+        for chunk in buf.chunks(RATE) {
+            self.permutation(chunk);
+        }
+        if exact {
+            self.permutation(&[]);
+        }
+
+        self.state.inner[1].clone()
+    }
+
+    fn permutation(&mut self, inputs: &[L]) {
+        self.state.absorb(inputs);
+        permute(&self.spec, &mut self.state);
+    }
+}
+
+impl<F: PrimeField, L: LoadedScalar<F>, const T: usize, const RATE: usize> SpongeHasher<F, L>
+    for Poseidon2<F, L, T, RATE>
+{
+    type Spec = Poseidon2Spec<F, T>;
+
+    fn new(loader: &L::Loader, r_f: usize, r_p: usize) -> Self {
+        Poseidon2::new(loader, r_f, r_p)
+    }
+
+    fn from_spec(loader: &L::Loader, spec: Poseidon2Spec<F, T>) -> Self {
+        Poseidon2::from_spec(loader, spec)
+    }
+
+    fn update(&mut self, elements: &[L]) {
+        Poseidon2::update(self, elements)
+    }
+
+    fn squeeze(&mut self) -> L {
+        Poseidon2::squeeze(self)
+    }
+}
+
+/// Poseidon2 permutation, where round constants are added right after the
+/// linear layer of previous round.
+fn permute<F: PrimeField, L: LoadedScalar<F>, const T: usize>(
+    spec: &Poseidon2Spec<F, T>,
+    state: &mut State<F, L, T>,
+) {
+    let r_f = spec.r_f / 2;
+    let mut constants = spec.round_constants.iter().chain(iter::once(&[F::ZERO; T]));
+
+    state.apply_external(constants.next().unwrap());
+    for _ in 0..r_f {
+        state.sbox_full();
+        state.apply_external(constants.next().unwrap());
+    }
+    for _ in 0..spec.r_p {
+        state.sbox_part();
+        state.apply_internal(&spec.mat_internal_diag_m_1, constants.next().unwrap());
+    }
+    for _ in 0..r_f {
+        state.sbox_full();
+        state.apply_external(constants.next().unwrap());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{permute, Poseidon2Spec, State};
+    use crate::util::arithmetic::{fe_from_big, PrimeField};
+    use halo2_curves::bn256::Fr;
+    use num_bigint::BigUint;
+
+    fn fe_from_hex<F: PrimeField>(hex: &str) -> F {
+        fe_from_big(BigUint::parse_bytes(hex.as_bytes(), 16).unwrap())
+    }
+
+    #[test]
+    fn test_poseidon2_permutation_bn256() {
+        // Test vector of reference implementation with `T = 3`, `R_F = 8` and
+        // `R_P = 56`.
+        let spec = Poseidon2Spec::<Fr, 3>::new(8, 56);
+        assert_eq!(
+            spec.round_constants()[0][0],
+            fe_from_hex("1d066a255517b7fd8bddd3a93f7804ef7f8fcde48bb4c37a59a09a1a97052816")
+        );
+
+        let mut state = State::<Fr, Fr, 3>::new([0, 1, 2].map(Fr::from));
+        permute(&spec, &mut state);
+        assert_eq!(
+            state.inner,
+            [
+                "0bb61d24daca55eebcb1929a82650f328134334da98ea4f847f760054f4a3033",
+                "303b6f7c86d043bfcbcc80214f26a30277a15d3f74ca654992defe7ff8d03570",
+                "1ed25194542b12eef8617361c3ba7c52e660b145994427cc86296242cf766ec8",
+            ]
+            .map(fe_from_hex::<Fr>)
+        );
+    }
+}
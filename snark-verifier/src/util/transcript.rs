@@ -5,6 +5,12 @@ use crate::{
     {util::arithmetic::CurveAffine, Error},
 };
 
+mod tracing;
+
+pub use tracing::{
+    diff_traces, TraceDivergence, TraceEntry, TraceLoader, TraceOp, TracingTranscript,
+};
+
 /// Common methods for prover and verifier.
 pub trait Transcript<C, L>
 where
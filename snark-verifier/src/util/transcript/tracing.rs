@@ -0,0 +1,289 @@
+//! Transcript wrapper recording everything it absorbs and squeezes, to find
+//! where native and in-circuit verifiers diverge.
+
+use crate::{
+    loader::{native::NativeLoader, Loader},
+    util::{
+        arithmetic::{ec_point_to_coordinates, fe_to_fe, CurveAffine},
+        transcript::{Transcript, TranscriptRead},
+        Itertools,
+    },
+    Error,
+};
+use std::fmt::{self, Debug, Display};
+
+/// [`Loader`] whose loaded values could be recorded by [`TracingTranscript`].
+pub trait TraceLoader<C: CurveAffine>: Loader<C> {
+    /// Returns value of `scalar`, or `None` if it's unknown.
+    fn trace_scalar(&self, scalar: &Self::LoadedScalar) -> Option<C::Scalar>;
+
+    /// Returns coordinates of `ec_point` reduced into scalar field, or `None`
+    /// if they are unknown.
+    fn trace_ec_point(&self, ec_point: &Self::LoadedEcPoint) -> Vec<Option<C::Scalar>>;
+}
+
+impl<C: CurveAffine> TraceLoader<C> for NativeLoader {
+    fn trace_scalar(&self, scalar: &C::Scalar) -> Option<C::Scalar> {
+        Some(*scalar)
+    }
+
+    fn trace_ec_point(&self, ec_point: &C) -> Vec<Option<C::Scalar>> {
+        ec_point_to_coordinates(ec_point)
+            .into_iter()
+            .map(|coordinate| Some(fe_to_fe(coordinate)))
+            .collect()
+    }
+}
+
+/// Operation of transcript recorded in [`TraceEntry`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TraceOp {
+    /// [`Transcript::common_scalar`].
+    CommonScalar,
+    /// [`Transcript::common_ec_point`].
+    CommonEcPoint,
+    /// [`TranscriptRead::read_scalar`].
+    ReadScalar,
+    /// [`TranscriptRead::read_ec_point`].
+    ReadEcPoint,
+    /// [`Transcript::squeeze_challenge`].
+    SqueezeChallenge,
+}
+
+/// Entry of trace recorded by [`TracingTranscript`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceEntry<F> {
+    /// Label set by [`TracingTranscript::set_label`] when recorded.
+    pub label: String,
+    /// Operation.
+    pub op: TraceOp,
+    /// Values absorbed or squeezed, where `None` means unknown.
+    pub values: Vec<Option<F>>,
+}
+
+impl<F: PartialEq> TraceEntry<F> {
+    /// Returns `true` if `self` and `other` are the same operation with values
+    /// equal when both known. Labels are not compared.
+    pub fn matches(&self, other: &Self) -> bool {
+        self.op == other.op
+            && self.values.len() == other.values.len()
+            && self
+                .values
+                .iter()
+                .zip(other.values.iter())
+                .all(|(lhs, rhs)| match (lhs, rhs) {
+                    (Some(lhs), Some(rhs)) => lhs == rhs,
+                    _ => true,
+                })
+    }
+}
+
+/// Transcript wrapper records every absorbed, read and squeezed value of
+/// inner transcript `T` with a label.
+#[derive(Debug)]
+pub struct TracingTranscript<C: CurveAffine, T> {
+    inner: T,
+    label: String,
+    trace: Vec<TraceEntry<C::Scalar>>,
+}
+
+impl<C: CurveAffine, T> TracingTranscript<C, T> {
+    /// Wrap `inner` transcript.
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            label: String::new(),
+            trace: Vec::new(),
+        }
+    }
+
+    /// Set label of entries recorded afterwards.
+    pub fn set_label(&mut self, label: impl Into<String>) {
+        self.label = label.into();
+    }
+
+    /// Returns recorded trace.
+    pub fn trace(&self) -> &[TraceEntry<C::Scalar>] {
+        &self.trace
+    }
+
+    /// Returns reference of inner transcript.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns inner transcript and recorded trace.
+    pub fn into_parts(self) -> (T, Vec<TraceEntry<C::Scalar>>) {
+        (self.inner, self.trace)
+    }
+
+    fn record(&mut self, op: TraceOp, values: Vec<Option<C::Scalar>>) {
+        self.trace.push(TraceEntry {
+            label: self.label.clone(),
+            op,
+            values,
+        });
+    }
+}
+
+impl<C, L, T> Transcript<C, L> for TracingTranscript<C, T>
+where
+    C: CurveAffine,
+    L: TraceLoader<C>,
+    T: Transcript<C, L>,
+{
+    fn loader(&self) -> &L {
+        self.inner.loader()
+    }
+
+    fn squeeze_challenge(&mut self) -> L::LoadedScalar {
+        let challenge = self.inner.squeeze_challenge();
+        let values = vec![self.inner.loader().trace_scalar(&challenge)];
+        self.record(TraceOp::SqueezeChallenge, values);
+        challenge
+    }
+
+    fn common_ec_point(&mut self, ec_point: &L::LoadedEcPoint) -> Result<(), Error> {
+        let values = self.inner.loader().trace_ec_point(ec_point);
+        self.record(TraceOp::CommonEcPoint, values);
+        self.inner.common_ec_point(ec_point)
+    }
+
+    fn common_scalar(&mut self, scalar: &L::LoadedScalar) -> Result<(), Error> {
+        let values = vec![self.inner.loader().trace_scalar(scalar)];
+        self.record(TraceOp::CommonScalar, values);
+        self.inner.common_scalar(scalar)
+    }
+}
+
+impl<C, L, T> TranscriptRead<C, L> for TracingTranscript<C, T>
+where
+    C: CurveAffine,
+    L: TraceLoader<C>,
+    T: TranscriptRead<C, L>,
+{
+    fn read_scalar(&mut self) -> Result<L::LoadedScalar, Error> {
+        let scalar = self.inner.read_scalar()?;
+        let values = vec![self.inner.loader().trace_scalar(&scalar)];
+        self.record(TraceOp::ReadScalar, values);
+        Ok(scalar)
+    }
+
+    fn read_ec_point(&mut self) -> Result<L::LoadedEcPoint, Error> {
+        let ec_point = self.inner.read_ec_point()?;
+        let values = self.inner.loader().trace_ec_point(&ec_point);
+        self.record(TraceOp::ReadEcPoint, values);
+        Ok(ec_point)
+    }
+}
+
+/// First divergence of two traces found by [`diff_traces`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceDivergence<F> {
+    /// Index of the first diverged entry.
+    pub index: usize,
+    /// Entry of `lhs`, or `None` if `lhs` is shorter.
+    pub lhs: Option<TraceEntry<F>>,
+    /// Entry of `rhs`, or `None` if `rhs` is shorter.
+    pub rhs: Option<TraceEntry<F>>,
+}
+
+impl<F: Debug> Display for TraceDivergence<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let entry = |entry: &Option<TraceEntry<F>>| match entry {
+            Some(entry) => format!(
+                "{:?} {:?} [{}]",
+                entry.label,
+                entry.op,
+                entry
+                    .values
+                    .iter()
+                    .map(|value| match value {
+                        Some(value) => format!("{value:?}"),
+                        None => "unknown".to_string(),
+                    })
+                    .join(", ")
+            ),
+            None => "end of trace".to_string(),
+        };
+        write!(
+            f,
+            "Traces diverge at entry {}: {} vs {}",
+            self.index,
+            entry(&self.lhs),
+            entry(&self.rhs)
+        )
+    }
+}
+
+/// Returns the first entry where `lhs` and `rhs` don't match (see
+/// [`TraceEntry::matches`]), or `None` if they match entirely.
+///
+/// Usually `lhs` is recorded with [`NativeLoader`] and `rhs` with
+/// [`Halo2Loader`](crate::loader::halo2::Halo2Loader) in circuit, where
+/// unknown values (e.g. in keygen) are skipped.
+pub fn diff_traces<F: Clone + PartialEq>(
+    lhs: &[TraceEntry<F>],
+    rhs: &[TraceEntry<F>],
+) -> Option<TraceDivergence<F>> {
+    (0..lhs.len().max(rhs.len()))
+        .find(|idx| match (lhs.get(*idx), rhs.get(*idx)) {
+            (Some(lhs), Some(rhs)) => !lhs.matches(rhs),
+            _ => true,
+        })
+        .map(|index| TraceDivergence {
+            index,
+            lhs: lhs.get(index).cloned(),
+            rhs: rhs.get(index).cloned(),
+        })
+}
+
+#[cfg(all(test, feature = "system_halo2"))]
+mod test {
+    use super::{diff_traces, TraceOp, TracingTranscript};
+    use crate::util::{
+        arithmetic::{Curve, Field, PrimeCurveAffine},
+        transcript::{Transcript, TranscriptRead, TranscriptWrite},
+    };
+    use halo2_curves::bn256::{Fr, G1Affine};
+    use halo2_proofs::transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+    };
+
+    fn proof(ec_point: G1Affine) -> Vec<u8> {
+        let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(Vec::new());
+        transcript.write_scalar(Fr::ONE).unwrap();
+        transcript.write_ec_point(ec_point).unwrap();
+        transcript.finalize()
+    }
+
+    fn trace(proof: &[u8]) -> Vec<super::TraceEntry<Fr>> {
+        let mut transcript =
+            TracingTranscript::<G1Affine, _>::new(
+                Blake2bRead::<_, G1Affine, Challenge255<_>>::init(proof),
+            );
+        transcript.set_label("proof");
+        transcript.read_scalar().unwrap();
+        transcript.read_ec_point().unwrap();
+        transcript.squeeze_challenge();
+        transcript.into_parts().1
+    }
+
+    #[test]
+    fn test_diff_traces() {
+        let generator = G1Affine::generator();
+        let lhs = trace(&proof(generator));
+        let rhs = trace(&proof((generator * Fr::from(2)).to_affine()));
+
+        assert_eq!(lhs.len(), 3);
+        assert_eq!(diff_traces(&lhs, &lhs), None);
+
+        let divergence = diff_traces(&lhs, &rhs).unwrap();
+        assert_eq!(divergence.index, 1);
+        assert_eq!(divergence.lhs.unwrap().op, TraceOp::ReadEcPoint);
+
+        let divergence = diff_traces(&lhs, &lhs[..2]).unwrap();
+        assert_eq!(divergence.index, 2);
+        assert_eq!(divergence.rhs, None);
+    }
+}
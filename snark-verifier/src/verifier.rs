@@ -7,6 +7,7 @@ use crate::{
 };
 use std::fmt::Debug;
 
+pub mod hyperplonk;
 pub mod plonk;
 
 /// (S)NARK verifier for verifying a (S)NARK.
@@ -0,0 +1,551 @@
+//! Verifiers for [HyperPlonk], currently there are
+//! [`HyperPlonkSuccinctVerifier`] and [`HyperPlonkVerifier`] implemented, both
+//! reduce constraints on the boolean hypercube into a single evaluation claim by
+//! sumcheck, then open the multilinear polynomials with
+//! [`MultilinearPolynomialCommitmentScheme`] that has accumulation scheme.
+//!
+//! With [`crate::pcs::kzg::Zeromorph`] the output is a
+//! [`crate::pcs::kzg::KzgAccumulator`], so the existing KZG accumulation,
+//! accumulator encodings and deciders can be reused as is.
+//!
+//! Only queries at [`crate::util::arithmetic::Rotation::cur`] are supported.
+//!
+//! [HyperPlonk]: https://eprint.iacr.org/2022/1355
+
+use crate::{
+    cost::{Cost, CostEstimation},
+    loader::{LoadedScalar, Loader, ScalarLoader},
+    pcs::{
+        AccumulationDecider, AccumulationScheme, AccumulatorEncoding,
+        MultilinearPolynomialCommitmentScheme, MultilinearQuery,
+    },
+    util::{arithmetic::CurveAffine, transcript::TranscriptRead},
+    verifier::{plonk::protocol::ExpressionDag, SnarkVerifier},
+    Error,
+};
+use std::{iter, marker::PhantomData};
+
+mod proof;
+mod protocol;
+mod sumcheck;
+
+pub use proof::HyperPlonkProof;
+pub use protocol::HyperPlonkProtocol;
+pub use sumcheck::SumcheckProof;
+
+/// Verifier that verifies the cheap part of HyperPlonk and ouput the
+/// accumulator.
+///
+/// Unlike [`crate::verifier::plonk::PlonkSuccinctVerifier`], the
+/// [`AccumulatorEncoding`] `AE` has no default, since `AS` is not a
+/// [`crate::pcs::PolynomialCommitmentScheme`]. With [`crate::pcs::kzg::Zeromorph`]
+/// it's usually [`crate::pcs::kzg::LimbsEncoding`].
+#[derive(Debug)]
+pub struct HyperPlonkSuccinctVerifier<AS, AE>(PhantomData<(AS, AE)>);
+
+impl<C, L, AS, AE> SnarkVerifier<C, L> for HyperPlonkSuccinctVerifier<AS, AE>
+where
+    C: CurveAffine,
+    L: Loader<C>,
+    AS: AccumulationScheme<C, L>
+        + MultilinearPolynomialCommitmentScheme<C, L, Output = AS::Accumulator>,
+    AE: AccumulatorEncoding<C, L, Accumulator = AS::Accumulator>,
+{
+    type VerifyingKey = <AS as MultilinearPolynomialCommitmentScheme<C, L>>::VerifyingKey;
+    type Protocol = HyperPlonkProtocol<C, L>;
+    type Proof = HyperPlonkProof<C, L, AS>;
+    type Output = Vec<AE::Accumulator>;
+
+    fn read_proof<T>(
+        svk: &Self::VerifyingKey,
+        protocol: &Self::Protocol,
+        instances: &[Vec<L::LoadedScalar>],
+        transcript: &mut T,
+    ) -> Result<Self::Proof, Error>
+    where
+        T: TranscriptRead<C, L>,
+    {
+        HyperPlonkProof::read::<T, AE>(svk, protocol, instances, transcript)
+    }
+
+    fn verify(
+        svk: &Self::VerifyingKey,
+        protocol: &Self::Protocol,
+        instances: &[Vec<L::LoadedScalar>],
+        proof: &Self::Proof,
+    ) -> Result<Self::Output, Error> {
+        let loader = proof.alpha.loader();
+        let evaluation = proof.sumcheck.verify(&loader.load_zero())?;
+        loader.assert_eq(
+            "HyperPlonk sumcheck evaluation",
+            &proof.expected_evaluation(protocol, instances)?,
+            &evaluation,
+        )?;
+
+        let commitments = proof.commitments(protocol);
+        let queries = proof.queries(protocol);
+        let accumulator = <AS as MultilinearPolynomialCommitmentScheme<C, L>>::verify(
+            svk,
+            &commitments,
+            proof.sumcheck.challenges(),
+            &queries,
+            &proof.pcs,
+        )?;
+
+        let accumulators = iter::empty()
+            .chain(Some(accumulator))
+            .chain(proof.old_accumulators.iter().cloned())
+            .collect();
+
+        Ok(accumulators)
+    }
+}
+
+/// Verifier that first verifies the cheap part of HyperPlonk, then decides
+/// accumulator and returns accept/reject as ouput.
+#[derive(Debug)]
+pub struct HyperPlonkVerifier<AS, AE>(PhantomData<(AS, AE)>);
+
+impl<C, L, AS, AE> SnarkVerifier<C, L> for HyperPlonkVerifier<AS, AE>
+where
+    C: CurveAffine,
+    L: Loader<C>,
+    AS: AccumulationDecider<C, L>
+        + MultilinearPolynomialCommitmentScheme<C, L, Output = AS::Accumulator>,
+    AS::DecidingKey: AsRef<<AS as MultilinearPolynomialCommitmentScheme<C, L>>::VerifyingKey>,
+    AE: AccumulatorEncoding<C, L, Accumulator = AS::Accumulator>,
+{
+    type VerifyingKey = AS::DecidingKey;
+    type Protocol = HyperPlonkProtocol<C, L>;
+    type Proof = HyperPlonkProof<C, L, AS>;
+    type Output = ();
+
+    fn read_proof<T>(
+        vk: &Self::VerifyingKey,
+        protocol: &Self::Protocol,
+        instances: &[Vec<L::LoadedScalar>],
+        transcript: &mut T,
+    ) -> Result<Self::Proof, Error>
+    where
+        T: TranscriptRead<C, L>,
+    {
+        HyperPlonkProof::read::<T, AE>(vk.as_ref(), protocol, instances, transcript)
+    }
+
+    fn verify(
+        vk: &Self::VerifyingKey,
+        protocol: &Self::Protocol,
+        instances: &[Vec<L::LoadedScalar>],
+        proof: &Self::Proof,
+    ) -> Result<Self::Output, Error> {
+        let accumulators =
+            HyperPlonkSuccinctVerifier::<AS, AE>::verify(vk.as_ref(), protocol, instances, proof)?;
+        AS::decide_all(vk, accumulators)
+    }
+}
+
+impl<C, L, AS, AE> CostEstimation<(C, L)> for HyperPlonkSuccinctVerifier<AS, AE>
+where
+    C: CurveAffine,
+    L: Loader<C>,
+    AS: AccumulationScheme<C, L>
+        + MultilinearPolynomialCommitmentScheme<C, L, Output = AS::Accumulator>
+        + CostEstimation<C, Input = (usize, Vec<MultilinearQuery>)>,
+{
+    type Input = HyperPlonkProtocol<C, L>;
+
+    fn estimate_cost(protocol: &HyperPlonkProtocol<C, L>) -> Cost {
+        let num_var = protocol.num_var;
+        let degree = protocol.degree();
+        let hyperplonk_cost = {
+            let num_accumulator = protocol.accumulator_indices.len();
+            let num_instance = protocol.num_instance.iter().sum();
+            let num_commitment = protocol.num_witness.iter().sum::<usize>();
+            let num_evaluation = num_var * (degree + 1) + protocol.evaluations().len();
+            let num_msm = protocol.preprocessed.len() + num_commitment + 2 * num_accumulator;
+            let num_squeeze = protocol.num_challenge.iter().sum::<usize>() + 1 + 2 * num_var;
+            Cost {
+                num_instance,
+                num_commitment,
+                num_evaluation,
+                num_msm,
+                num_squeeze,
+                ..Default::default()
+            }
+        };
+        let sumcheck_cost = {
+            // Each round checks the sum and interpolates at the challenge with
+            // prefix and suffix products.
+            Cost {
+                num_scalar_add: num_var * (degree + 3),
+                num_scalar_mul: num_var * (4 * (degree + 1)),
+                ..Default::default()
+            }
+        };
+        let constraint_cost = {
+            let num_lagrange = protocol.used_langrange().len();
+            let num_instance_eval = num_instance_eval(protocol);
+            let (num_add, num_mul) = protocol
+                .zero_check
+                .iter()
+                .chain(protocol.sum_check.iter())
+                .map(|expression| ExpressionDag::new(expression).num_operation())
+                .fold((0, 0), |acc, (num_add, num_mul)| {
+                    (acc.0 + num_add, acc.1 + num_mul)
+                });
+            let num_constraint = protocol.zero_check.len() + protocol.sum_check.len();
+            Cost {
+                num_scalar_add: 4 * num_var + num_instance_eval + num_add + num_constraint,
+                num_scalar_mul: (num_lagrange + num_instance_eval + 2) * num_var
+                    + num_instance_eval
+                    + num_mul
+                    + 2 * num_constraint,
+                ..Default::default()
+            }
+        };
+        let pcs_cost = {
+            let queries = HyperPlonkProof::<C, L, AS>::empty_queries(protocol);
+            AS::estimate_cost(&(num_var, queries))
+        };
+        hyperplonk_cost + sumcheck_cost + constraint_cost + pcs_cost
+    }
+}
+
+impl<C, L, AS, AE> CostEstimation<(C, L)> for HyperPlonkVerifier<AS, AE>
+where
+    C: CurveAffine,
+    L: Loader<C>,
+    AS: AccumulationScheme<C, L>
+        + MultilinearPolynomialCommitmentScheme<C, L, Output = AS::Accumulator>
+        + CostEstimation<C, Input = (usize, Vec<MultilinearQuery>)>,
+{
+    type Input = HyperPlonkProtocol<C, L>;
+
+    fn estimate_cost(protocol: &HyperPlonkProtocol<C, L>) -> Cost {
+        HyperPlonkSuccinctVerifier::<AS, AE>::estimate_cost(protocol)
+            + Cost {
+                num_pairing: 2,
+                ..Default::default()
+            }
+    }
+}
+
+fn num_instance_eval<C, L>(protocol: &HyperPlonkProtocol<C, L>) -> usize
+where
+    C: CurveAffine,
+    L: Loader<C>,
+{
+    let offset = protocol.preprocessed.len();
+    protocol
+        .used_query()
+        .into_iter()
+        .filter(|query| (offset..offset + protocol.num_instance.len()).contains(&query.poly))
+        .map(|query| protocol.num_instance[query.poly - offset])
+        .sum()
+}
+
+#[cfg(all(test, feature = "system_halo2"))]
+pub(crate) mod test {
+    use crate::{
+        pcs::kzg::{KzgAs, KzgDecidingKey, LimbsEncoding, Zeromorph, ZeromorphProvingKey},
+        system::halo2::test::kzg::{BITS, LIMBS},
+        util::{
+            arithmetic::Field,
+            transcript::{Transcript, TranscriptWrite},
+            Itertools,
+        },
+        verifier::{
+            hyperplonk::{protocol::lagrange_index, HyperPlonkProtocol, HyperPlonkVerifier},
+            plonk::protocol::{CommonPolynomial, Expression, Query},
+            SnarkVerifier,
+        },
+        Error,
+    };
+    use halo2_curves::bn256::{Bn256, Fr, G1Affine};
+    use halo2_proofs::transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+    };
+    use rand::rngs::OsRng;
+    use std::iter;
+
+    type Pcs = KzgAs<Bn256, Zeromorph>;
+    type Verifier = HyperPlonkVerifier<Pcs, LimbsEncoding<LIMBS, BITS>>;
+
+    const NUM_VAR: usize = 4;
+
+    pub(crate) fn rand_vec(n: usize) -> Vec<Fr> {
+        iter::repeat_with(|| Fr::random(OsRng)).take(n).collect()
+    }
+
+    /// Polynomials are `q`, `instance`, `a`, `b`, `c` and `d`, where `q`
+    /// selects between `a·b = c` and `a + b = c`, the first row of `a` is
+    /// the instance, and the sum of `d - γ·identity` is zero.
+    fn protocol(preprocessed: Vec<G1Affine>) -> HyperPlonkProtocol<G1Affine> {
+        let [q, instance, a, b, c, d] =
+            [0, 1, 2, 3, 4, 5].map(|poly| Expression::<Fr>::from(Query::new(poly, 0)));
+        let one = Expression::Constant(Fr::ONE);
+        let l_0 = Expression::<Fr>::from(CommonPolynomial::Lagrange(0));
+        let identity = Expression::<Fr>::from(CommonPolynomial::Identity);
+        let gamma = Expression::<Fr>::Challenge(0);
+        HyperPlonkProtocol {
+            num_var: NUM_VAR,
+            preprocessed,
+            num_instance: vec![2],
+            num_witness: vec![3, 1],
+            num_challenge: vec![1, 0],
+            zero_check: vec![
+                &q * &(&a * &b - &c) + (one - &q) * (&a + &b - &c),
+                l_0 * (&a - &instance),
+            ],
+            sum_check: vec![d - gamma * identity],
+            transcript_initial_state: None,
+            accumulator_indices: Vec::new(),
+        }
+    }
+
+    /// Returns Zeromorph proving key, deciding key, [`HyperPlonkProtocol`]
+    /// and the preprocessed selector `q`.
+    pub(crate) fn setup() -> (
+        ZeromorphProvingKey<G1Affine>,
+        KzgDecidingKey<Bn256>,
+        HyperPlonkProtocol<G1Affine>,
+        Vec<Fr>,
+    ) {
+        let (pk, dk) = ZeromorphProvingKey::setup::<Bn256, _>(NUM_VAR, OsRng);
+        let q = (0..1 << NUM_VAR)
+            .map(|i| if i % 3 == 0 { Fr::ZERO } else { Fr::ONE })
+            .collect_vec();
+        let protocol = protocol(vec![pk.commit(&q)]);
+        (pk, dk, protocol, q)
+    }
+
+    /// Ways to create an invalid proof which is read by verifier as the same
+    /// transcript, so it's rejected by the check named in each variant.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub(crate) enum Tamper {
+        /// Witness doesn't satisfy the gate, which fails the sumcheck round.
+        Witness,
+        /// Evaluation of `q` is off by one, which fails the final check of
+        /// sumcheck.
+        Evaluation,
+        /// Opening proof is for a `q` different from the committed one, which
+        /// fails the decider.
+        Opening,
+    }
+
+    impl Tamper {
+        /// Returns annotation of the failing check with [`crate::loader::native::NativeLoader`].
+        pub(crate) fn annotation(&self) -> &'static str {
+            match self {
+                Tamper::Witness => "Sumcheck round",
+                Tamper::Evaluation => "HyperPlonk sumcheck evaluation",
+                Tamper::Opening => "e(lhs, g2)·e(rhs, -s_g2) == O",
+            }
+        }
+    }
+
+    fn fold(tables: &mut [Vec<Fr>], r: Fr) {
+        for table in tables.iter_mut() {
+            *table = table
+                .chunks(2)
+                .map(|pair| pair[0] + r * (pair[1] - pair[0]))
+                .collect();
+        }
+    }
+
+    pub(crate) fn create_proof<T>(
+        pk: &ZeromorphProvingKey<G1Affine>,
+        protocol: &HyperPlonkProtocol<G1Affine>,
+        q: &[Fr],
+        instances: &[Fr],
+        tamper: Option<Tamper>,
+        transcript: &mut T,
+    ) where
+        T: TranscriptWrite<G1Affine>,
+    {
+        let n = 1 << NUM_VAR;
+        for instance in instances.iter() {
+            transcript.common_scalar(instance).unwrap();
+        }
+
+        let a = iter::empty()
+            .chain(instances.iter().take(1).cloned())
+            .chain(rand_vec(n - 1))
+            .collect_vec();
+        let b = rand_vec(n);
+        let mut c = (0..n)
+            .map(|i| {
+                if q[i] == Fr::ONE {
+                    a[i] * b[i]
+                } else {
+                    a[i] + b[i]
+                }
+            })
+            .collect_vec();
+        if tamper == Some(Tamper::Witness) {
+            c[1] += Fr::ONE;
+        }
+        for poly in [&a, &b, &c] {
+            transcript.write_ec_point(pk.commit(poly)).unwrap();
+        }
+        let gamma = transcript.squeeze_challenge();
+
+        let d = {
+            let mut d = rand_vec(n);
+            let sum = (0..n as u64).map(|i| gamma * Fr::from(i)).sum::<Fr>();
+            d[0] += sum - d.iter().sum::<Fr>();
+            d
+        };
+        transcript.write_ec_point(pk.commit(&d)).unwrap();
+
+        let alpha = transcript.squeeze_challenge();
+        let y = transcript.squeeze_n_challenges(NUM_VAR);
+
+        let instance = iter::empty()
+            .chain(instances.iter().cloned())
+            .chain(iter::repeat(Fr::ZERO))
+            .take(n)
+            .collect_vec();
+        let identity = (0..n as u64).map(Fr::from).collect_vec();
+        let lagranges = protocol.used_langrange().into_iter().collect_vec();
+        let eq = (0..n)
+            .map(|i| {
+                y.iter()
+                    .enumerate()
+                    .map(|(k, y_k)| {
+                        if (i >> k) & 1 == 1 {
+                            *y_k
+                        } else {
+                            Fr::ONE - y_k
+                        }
+                    })
+                    .product::<Fr>()
+            })
+            .collect_vec();
+        let mut tables = iter::empty()
+            .chain([q.to_vec(), instance, a, b, c, d, identity, eq])
+            .chain(lagranges.iter().map(|i| {
+                let mut lagrange = vec![Fr::ZERO; n];
+                lagrange[lagrange_index(NUM_VAR, *i)] = Fr::ONE;
+                lagrange
+            }))
+            .collect_vec();
+        let lagrange_offset = 8;
+
+        let powers_of_alpha = iter::successors(Some(Fr::ONE), |power| Some(alpha * power))
+            .take(protocol.zero_check.len() + protocol.sum_check.len())
+            .collect_vec();
+        let evaluate = |values: &[Fr]| {
+            let evaluate = |expression: &Expression<Fr>| {
+                expression.evaluate(
+                    &|scalar| scalar,
+                    &|poly| match poly {
+                        CommonPolynomial::Identity => values[6],
+                        CommonPolynomial::Lagrange(i) => {
+                            values
+                                [lagrange_offset + lagranges.iter().position(|j| *j == i).unwrap()]
+                        }
+                    },
+                    &|query| values[query.poly],
+                    &|_| gamma,
+                    &|a| -a,
+                    &|a, b| a + b,
+                    &|a, b| a * b,
+                    &|a, scalar| a * scalar,
+                )
+            };
+            let (zero_check_powers, sum_check_powers) =
+                powers_of_alpha.split_at(protocol.zero_check.len());
+            values[7]
+                * protocol
+                    .zero_check
+                    .iter()
+                    .zip(zero_check_powers)
+                    .map(|(expression, power)| evaluate(expression) * power)
+                    .sum::<Fr>()
+                + protocol
+                    .sum_check
+                    .iter()
+                    .zip(sum_check_powers)
+                    .map(|(expression, power)| evaluate(expression) * power)
+                    .sum::<Fr>()
+        };
+
+        let polys = tables.clone();
+        let degree = protocol.degree();
+        let mut r = Vec::with_capacity(NUM_VAR);
+        for _ in 0..NUM_VAR {
+            let half = tables[0].len() / 2;
+            for t in (0..=degree as u64).map(Fr::from) {
+                let eval = (0..half)
+                    .map(|i| {
+                        let values = tables
+                            .iter()
+                            .map(|table| table[2 * i] + t * (table[2 * i + 1] - table[2 * i]))
+                            .collect_vec();
+                        evaluate(&values)
+                    })
+                    .sum::<Fr>();
+                transcript.write_scalar(eval).unwrap();
+            }
+            let r_k = transcript.squeeze_challenge();
+            fold(&mut tables, r_k);
+            r.push(r_k);
+        }
+
+        // The first evaluation is of `q`, which is the only preprocessed
+        // polynomial.
+        let evaluations = protocol.evaluations();
+        let mut evals = evaluations
+            .iter()
+            .map(|poly| tables[*poly][0])
+            .collect_vec();
+        let mut opened = evaluations
+            .iter()
+            .map(|poly| polys[*poly].clone())
+            .collect_vec();
+        match tamper {
+            Some(Tamper::Evaluation) => evals[0] += Fr::ONE,
+            Some(Tamper::Opening) => opened[0][0] += Fr::ONE,
+            _ => {}
+        }
+        for eval in evals {
+            transcript.write_scalar(eval).unwrap();
+        }
+        Zeromorph::create_proof(
+            pk,
+            &opened.iter().map(Vec::as_slice).collect_vec(),
+            &r,
+            transcript,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_hyperplonk_zeromorph() {
+        let (pk, dk, protocol, q) = setup();
+        let instances = rand_vec(2);
+
+        let verify = |tamper: Option<Tamper>| {
+            let proof = {
+                let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(Vec::new());
+                create_proof(&pk, &protocol, &q, &instances, tamper, &mut transcript);
+                transcript.finalize()
+            };
+
+            let instances = vec![instances.clone()];
+            let mut transcript =
+                Blake2bRead::<_, G1Affine, Challenge255<_>>::init(proof.as_slice());
+            let proof = Verifier::read_proof(&dk, &protocol, &instances, &mut transcript)?;
+            Verifier::verify(&dk, &protocol, &instances, &proof)
+        };
+        assert!(verify(None).is_ok());
+
+        for tamper in [Tamper::Witness, Tamper::Evaluation, Tamper::Opening] {
+            assert!(matches!(
+                verify(Some(tamper)),
+                Err(Error::AssertionFailure(annotation)) if annotation == tamper.annotation()
+            ));
+        }
+    }
+}
@@ -0,0 +1,317 @@
+use crate::{
+    loader::{LoadedScalar, Loader, ScalarLoader},
+    pcs::{
+        AccumulationScheme, AccumulatorEncoding, MultilinearPolynomialCommitmentScheme,
+        MultilinearQuery,
+    },
+    util::{
+        arithmetic::{CurveAffine, Field},
+        msm::Msm,
+        transcript::TranscriptRead,
+        Itertools,
+    },
+    verifier::{
+        hyperplonk::{
+            protocol::{lagrange_index, HyperPlonkProtocol},
+            sumcheck::SumcheckProof,
+        },
+        plonk::protocol::{CommonPolynomial, Expression},
+    },
+    Error,
+};
+use std::{collections::BTreeMap, iter};
+
+/// Proof of HyperPlonk with [`MultilinearPolynomialCommitmentScheme`] that has
+/// [`AccumulationScheme`].
+#[derive(Clone, Debug)]
+pub struct HyperPlonkProof<C, L, AS>
+where
+    C: CurveAffine,
+    L: Loader<C>,
+    AS: AccumulationScheme<C, L>
+        + MultilinearPolynomialCommitmentScheme<C, L, Output = AS::Accumulator>,
+{
+    /// Commitments of witness polynomials read from transcript.
+    pub witnesses: Vec<L::LoadedEcPoint>,
+    /// Challenges squeezed from transcript.
+    pub challenges: Vec<L::LoadedScalar>,
+    /// Challenge to batch constraints squeezed from transcript.
+    pub alpha: L::LoadedScalar,
+    /// Point for zero check squeezed from transcript.
+    pub y: Vec<L::LoadedScalar>,
+    /// Proof of sumcheck.
+    pub sumcheck: SumcheckProof<C, L>,
+    /// Evaluations read from transcript.
+    pub evaluations: Vec<L::LoadedScalar>,
+    /// Proof of [`MultilinearPolynomialCommitmentScheme`].
+    pub pcs: <AS as MultilinearPolynomialCommitmentScheme<C, L>>::Proof,
+    /// Old [`AccumulationScheme::Accumulator`]s read from instances.
+    pub old_accumulators: Vec<AS::Accumulator>,
+}
+
+impl<C, L, AS> HyperPlonkProof<C, L, AS>
+where
+    C: CurveAffine,
+    L: Loader<C>,
+    AS: AccumulationScheme<C, L>
+        + MultilinearPolynomialCommitmentScheme<C, L, Output = AS::Accumulator>,
+{
+    /// Reads each part from transcript as [`HyperPlonkProof`].
+    pub fn read<T, AE>(
+        svk: &<AS as MultilinearPolynomialCommitmentScheme<C, L>>::VerifyingKey,
+        protocol: &HyperPlonkProtocol<C, L>,
+        instances: &[Vec<L::LoadedScalar>],
+        transcript: &mut T,
+    ) -> Result<Self, Error>
+    where
+        T: TranscriptRead<C, L>,
+        AE: AccumulatorEncoding<C, L, Accumulator = AS::Accumulator>,
+    {
+        protocol.validate()?;
+
+        if let Some(transcript_initial_state) = &protocol.transcript_initial_state {
+            transcript.common_scalar(transcript_initial_state)?;
+        }
+
+        if protocol.num_instance
+            != instances
+                .iter()
+                .map(|instances| instances.len())
+                .collect_vec()
+        {
+            return Err(Error::InvalidInstances);
+        }
+
+        for instances in instances.iter() {
+            for instance in instances.iter() {
+                transcript.common_scalar(instance)?;
+            }
+        }
+
+        let (witnesses, challenges) = {
+            let (witnesses, challenges) = protocol
+                .num_witness
+                .iter()
+                .zip(protocol.num_challenge.iter())
+                .map(|(&n, &m)| {
+                    Ok((
+                        transcript.read_n_ec_points(n)?,
+                        transcript.squeeze_n_challenges(m),
+                    ))
+                })
+                .collect::<Result<Vec<_>, Error>>()?
+                .into_iter()
+                .unzip::<_, _, Vec<_>, Vec<_>>();
+
+            (
+                witnesses.into_iter().flatten().collect_vec(),
+                challenges.into_iter().flatten().collect_vec(),
+            )
+        };
+
+        let alpha = transcript.squeeze_challenge();
+        let y = transcript.squeeze_n_challenges(protocol.num_var);
+
+        let sumcheck = SumcheckProof::read(protocol.num_var, protocol.degree(), transcript)?;
+        let evaluations = transcript.read_n_scalars(protocol.evaluations().len())?;
+
+        let pcs = <AS as MultilinearPolynomialCommitmentScheme<C, L>>::read_proof(
+            svk,
+            protocol.num_var,
+            &Self::empty_queries(protocol),
+            transcript,
+        )?;
+
+        let old_accumulators = protocol
+            .accumulator_indices
+            .iter()
+            .map(|accumulator_indices| {
+                AE::read_repr(
+                    &accumulator_indices
+                        .iter()
+                        .map(|&(i, j)| &instances[i][j])
+                        .collect_vec(),
+                    transcript,
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            witnesses,
+            challenges,
+            alpha,
+            y,
+            sumcheck,
+            evaluations,
+            pcs,
+            old_accumulators,
+        })
+    }
+
+    pub(super) fn empty_queries(protocol: &HyperPlonkProtocol<C, L>) -> Vec<MultilinearQuery> {
+        protocol
+            .evaluations()
+            .into_iter()
+            .map(MultilinearQuery::new)
+            .collect()
+    }
+
+    pub(super) fn queries(
+        &self,
+        protocol: &HyperPlonkProtocol<C, L>,
+    ) -> Vec<MultilinearQuery<L::LoadedScalar>> {
+        Self::empty_queries(protocol)
+            .into_iter()
+            .zip(self.evaluations.iter().cloned())
+            .map(|(query, eval)| query.with_evaluation(eval))
+            .collect()
+    }
+
+    pub(super) fn commitments<'a>(
+        &'a self,
+        protocol: &'a HyperPlonkProtocol<C, L>,
+    ) -> Vec<Msm<'a, C, L>> {
+        iter::empty()
+            .chain(protocol.preprocessed.iter().map(Msm::base))
+            .chain(iter::repeat_with(Default::default).take(protocol.num_instance.len()))
+            .chain(self.witnesses.iter().map(Msm::base))
+            .collect()
+    }
+
+    /// Returns the expected evaluation of the sumcheck polynomial at the point
+    /// reduced by sumcheck, which is
+    /// $\mathrm{eq}(y, r) \cdot \sum_i \alpha^i z_i(r) + \sum_j \alpha^{m+j} s_j(r)$
+    /// where $z_i$ are zero check constraints and $s_j$ are sum check
+    /// constraints.
+    pub(super) fn expected_evaluation(
+        &self,
+        protocol: &HyperPlonkProtocol<C, L>,
+        instances: &[Vec<L::LoadedScalar>],
+    ) -> Result<L::LoadedScalar, Error> {
+        let loader = self.alpha.loader();
+        let r = self.sumcheck.challenges();
+
+        let one_minus_r = r
+            .iter()
+            .map(|r_k| loader.sum_with_coeff_and_const(&[(-C::Scalar::ONE, r_k)], C::Scalar::ONE))
+            .collect_vec();
+        let lagrange = |i: usize| {
+            loader.product(
+                &r.iter()
+                    .zip(one_minus_r.iter())
+                    .enumerate()
+                    .map(|(k, (r_k, one_minus_r_k))| {
+                        if (i >> k) & 1 == 1 {
+                            r_k
+                        } else {
+                            one_minus_r_k
+                        }
+                    })
+                    .collect_vec(),
+            )
+        };
+        let identity = loader.sum_with_coeff(
+            &iter::successors(Some(C::Scalar::ONE), |power_of_two| {
+                Some(power_of_two.double())
+            })
+            .zip(r.iter())
+            .collect_vec(),
+        );
+        let lagranges = protocol
+            .used_langrange()
+            .into_iter()
+            .map(|i| (i, lagrange(lagrange_index(protocol.num_var, i))))
+            .collect::<BTreeMap<_, _>>();
+
+        let evaluations = {
+            let offset = protocol.preprocessed.len();
+            let instance_evals = protocol
+                .used_query()
+                .into_iter()
+                .filter(|query| {
+                    (offset..offset + protocol.num_instance.len()).contains(&query.poly)
+                })
+                .map(|query| {
+                    let instances = &instances[query.poly - offset];
+                    let lagranges = (0..instances.len()).map(&lagrange).collect_vec();
+                    let eval =
+                        loader.sum_products(&instances.iter().zip(lagranges.iter()).collect_vec());
+                    (query.poly, eval)
+                })
+                .collect_vec();
+            iter::empty()
+                .chain(instance_evals)
+                .chain(
+                    protocol
+                        .evaluations()
+                        .into_iter()
+                        .zip(self.evaluations.iter().cloned()),
+                )
+                .collect::<BTreeMap<_, _>>()
+        };
+
+        let evaluate =
+            |expression: &Expression<C::Scalar>| {
+                expression.evaluate(
+                    &|scalar| Ok(loader.load_const(&scalar)),
+                    &|poly| match poly {
+                        CommonPolynomial::Identity => Ok(identity.clone()),
+                        CommonPolynomial::Lagrange(i) => Ok(lagranges[&i].clone()),
+                    },
+                    &|query| {
+                        evaluations.get(&query.poly).cloned().ok_or_else(|| {
+                            Error::InvalidProtocol(format!("Missing query {query:?}"))
+                        })
+                    },
+                    &|index| {
+                        self.challenges.get(index).cloned().ok_or_else(|| {
+                            Error::InvalidProtocol(format!("Missing challenge {index}"))
+                        })
+                    },
+                    &|a| Ok(-a?),
+                    &|a, b| Ok(a? + b?),
+                    &|a, b| Ok(a? * b?),
+                    &|a, scalar| Ok(a? * loader.load_const(&scalar)),
+                )
+            };
+
+        let num_constraint = protocol.zero_check.len() + protocol.sum_check.len();
+        let powers_of_alpha = self.alpha.powers(num_constraint);
+        let (zero_check, sum_check) = {
+            let evals = protocol
+                .zero_check
+                .iter()
+                .chain(protocol.sum_check.iter())
+                .map(evaluate)
+                .collect::<Result<Vec<_>, Error>>()?;
+            let mut terms = evals
+                .iter()
+                .zip(powers_of_alpha.iter())
+                .map(|(eval, power_of_alpha)| (power_of_alpha, eval))
+                .collect_vec();
+            let sum_check = terms.split_off(protocol.zero_check.len());
+            (loader.sum_products(&terms), loader.sum_products(&sum_check))
+        };
+        let eq_y_r = {
+            let terms = self
+                .y
+                .iter()
+                .zip(r.iter())
+                .map(|(y_k, r_k)| {
+                    let y_k_r_k = y_k.clone() * r_k;
+                    loader.sum_with_coeff_and_const(
+                        &[
+                            (C::Scalar::ONE.double(), &y_k_r_k),
+                            (-C::Scalar::ONE, y_k),
+                            (-C::Scalar::ONE, r_k),
+                        ],
+                        C::Scalar::ONE,
+                    )
+                })
+                .collect_vec();
+            loader.product(&terms.iter().collect_vec())
+        };
+
+        Ok(eq_y_r * zero_check + sum_check)
+    }
+}
@@ -0,0 +1,188 @@
+use crate::{
+    loader::{native::NativeLoader, Loader},
+    util::arithmetic::{CurveAffine, Rotation},
+    verifier::plonk::protocol::{Expression, Query},
+    Error,
+};
+use std::{cmp::max, collections::BTreeSet};
+
+/// Protocol specifying configuration of a HyperPlonk.
+///
+/// Polynomials are indexed in the order of preprocessed, instance and then
+/// witness, which is the same as [`crate::verifier::plonk::PlonkProtocol`].
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "derive_serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HyperPlonkProtocol<C, L = NativeLoader>
+where
+    C: CurveAffine,
+    L: Loader<C>,
+{
+    /// Number of variables of multilinear polynomials.
+    pub num_var: usize,
+    #[cfg_attr(
+        feature = "derive_serde",
+        serde(bound(
+            serialize = "L::LoadedEcPoint: serde::Serialize",
+            deserialize = "L::LoadedEcPoint: serde::Deserialize<'de>"
+        ))
+    )]
+    /// Commitments of preprocessed polynomials.
+    pub preprocessed: Vec<L::LoadedEcPoint>,
+    /// Number of instances in each instance polynomial.
+    pub num_instance: Vec<usize>,
+    /// Number of witness polynomials in each phase.
+    pub num_witness: Vec<usize>,
+    /// Number of challenges to squeeze from transcript after each phase.
+    pub num_challenge: Vec<usize>,
+    #[cfg_attr(
+        feature = "derive_serde",
+        serde(bound(
+            serialize = "C::Scalar: serde::Serialize",
+            deserialize = "C::Scalar: serde::Deserialize<'de>"
+        ))
+    )]
+    /// Constraints that should vanish on the boolean hypercube.
+    pub zero_check: Vec<Expression<C::Scalar>>,
+    #[cfg_attr(
+        feature = "derive_serde",
+        serde(bound(
+            serialize = "C::Scalar: serde::Serialize",
+            deserialize = "C::Scalar: serde::Deserialize<'de>"
+        ))
+    )]
+    /// Constraints whose sum over the boolean hypercube should be zero, which
+    /// is useful for lookup or permutation argument like LogUp.
+    pub sum_check: Vec<Expression<C::Scalar>>,
+    #[cfg_attr(
+        feature = "derive_serde",
+        serde(bound(
+            serialize = "L::LoadedScalar: serde::Serialize",
+            deserialize = "L::LoadedScalar: serde::Deserialize<'de>"
+        ))
+    )]
+    /// Prover and verifier common initial state to write to transcript if any.
+    pub transcript_initial_state: Option<L::LoadedScalar>,
+    /// Indices (instance polynomial index, row) of encoded
+    /// [`crate::pcs::AccumulationScheme::Accumulator`]s.
+    pub accumulator_indices: Vec<Vec<(usize, usize)>>,
+}
+
+impl<C, L> HyperPlonkProtocol<C, L>
+where
+    C: CurveAffine,
+    L: Loader<C>,
+{
+    /// Returns degree of the polynomial to run sumcheck on, where the zero
+    /// check constraints are multiplied by an extra `eq` polynomial.
+    pub fn degree(&self) -> usize {
+        let zero_check_degree = self.zero_check.iter().map(Expression::degree).max();
+        let sum_check_degree = self.sum_check.iter().map(Expression::degree).max();
+        max(
+            zero_check_degree.map(|degree| degree + 1).unwrap_or(1),
+            sum_check_degree.unwrap_or(1),
+        )
+    }
+
+    /// Returns used queries in constraints, which should all be at
+    /// [`Rotation::cur`].
+    pub(super) fn used_query(&self) -> BTreeSet<Query> {
+        self.zero_check
+            .iter()
+            .chain(self.sum_check.iter())
+            .flat_map(Expression::used_query)
+            .collect()
+    }
+
+    /// Returns used lagrange polynomials in constraints.
+    pub(super) fn used_langrange(&self) -> BTreeSet<i32> {
+        self.zero_check
+            .iter()
+            .chain(self.sum_check.iter())
+            .flat_map(Expression::used_langrange)
+            .collect()
+    }
+
+    /// Returns indices of polynomials whose evaluations are read from
+    /// transcript, which are all used non-instance polynomials.
+    pub(super) fn evaluations(&self) -> Vec<usize> {
+        let instance_range =
+            self.preprocessed.len()..self.preprocessed.len() + self.num_instance.len();
+        self.used_query()
+            .into_iter()
+            .map(|query| query.poly)
+            .filter(|poly| !instance_range.contains(poly))
+            .collect()
+    }
+
+    pub(super) fn validate(&self) -> Result<(), Error> {
+        if self.zero_check.is_empty() && self.sum_check.is_empty() {
+            return Err(Error::InvalidProtocol("Missing constraints".to_string()));
+        }
+        let num_poly = self.preprocessed.len()
+            + self.num_instance.len()
+            + self.num_witness.iter().sum::<usize>();
+        let num_challenge = self.num_challenge.iter().sum::<usize>();
+        for query in self.used_query() {
+            if query.rotation != Rotation::cur() {
+                return Err(Error::InvalidProtocol(format!(
+                    "Unsupported rotation in query {query:?}"
+                )));
+            }
+            if query.poly >= num_poly {
+                return Err(Error::InvalidProtocol(format!("Missing query {query:?}")));
+            }
+        }
+        for expression in self.zero_check.iter().chain(self.sum_check.iter()) {
+            let max_challenge = expression.evaluate(
+                &|_| None,
+                &|_| None,
+                &|_| None,
+                &|index| Some(index),
+                &|a| a,
+                &|a, b| max(a, b),
+                &|a, b| max(a, b),
+                &|a, _| a,
+            );
+            if let Some(index) = max_challenge.filter(|index| *index >= num_challenge) {
+                return Err(Error::InvalidProtocol(format!("Missing challenge {index}")));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<C> HyperPlonkProtocol<C>
+where
+    C: CurveAffine,
+{
+    /// Loaded `HyperPlonkProtocol` with `preprocessed` and
+    /// `transcript_initial_state` loaded as constant.
+    pub fn loaded<L: Loader<C>>(&self, loader: &L) -> HyperPlonkProtocol<C, L> {
+        let preprocessed = self
+            .preprocessed
+            .iter()
+            .map(|preprocessed| loader.ec_point_load_const(preprocessed))
+            .collect();
+        let transcript_initial_state = self
+            .transcript_initial_state
+            .as_ref()
+            .map(|transcript_initial_state| loader.load_const(transcript_initial_state));
+        HyperPlonkProtocol {
+            num_var: self.num_var,
+            preprocessed,
+            num_instance: self.num_instance.clone(),
+            num_witness: self.num_witness.clone(),
+            num_challenge: self.num_challenge.clone(),
+            zero_check: self.zero_check.clone(),
+            sum_check: self.sum_check.clone(),
+            transcript_initial_state,
+            accumulator_indices: self.accumulator_indices.clone(),
+        }
+    }
+}
+
+/// Returns index on the boolean hypercube of `num_var` variables of the
+/// `i`-th lagrange polynomial, where negative `i` counts from the end.
+pub(super) fn lagrange_index(num_var: usize, i: i32) -> usize {
+    (i as i64).rem_euclid(1 << num_var) as usize
+}
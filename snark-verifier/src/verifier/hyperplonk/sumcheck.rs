@@ -0,0 +1,126 @@
+use crate::{
+    loader::{LoadedScalar, Loader, ScalarLoader},
+    util::{
+        arithmetic::{CurveAffine, Field, PrimeField},
+        transcript::TranscriptRead,
+        Itertools,
+    },
+    Error,
+};
+
+/// Proof of sumcheck on a multivariate polynomial, where each round sends
+/// evaluations of the univariate round polynomial on `0..=degree`.
+#[derive(Clone, Debug)]
+pub struct SumcheckProof<C, L>
+where
+    C: CurveAffine,
+    L: Loader<C>,
+{
+    rounds: Vec<Vec<L::LoadedScalar>>,
+    challenges: Vec<L::LoadedScalar>,
+}
+
+impl<C, L> SumcheckProof<C, L>
+where
+    C: CurveAffine,
+    L: Loader<C>,
+{
+    /// Read [`SumcheckProof`] of a `num_var`-variate polynomial with
+    /// individual degree `degree` from transcript.
+    pub fn read<T>(num_var: usize, degree: usize, transcript: &mut T) -> Result<Self, Error>
+    where
+        T: TranscriptRead<C, L>,
+    {
+        let (rounds, challenges) = (0..num_var)
+            .map(|_| {
+                Ok((
+                    transcript.read_n_scalars(degree + 1)?,
+                    transcript.squeeze_challenge(),
+                ))
+            })
+            .collect::<Result<Vec<_>, Error>>()?
+            .into_iter()
+            .unzip();
+        Ok(Self { rounds, challenges })
+    }
+
+    /// Returns challenges squeezed in each round, which is the point the
+    /// polynomial is reduced to be evaluated at.
+    pub fn challenges(&self) -> &[L::LoadedScalar] {
+        &self.challenges
+    }
+
+    /// Verify each round is consistent with the claimed `sum`, and returns
+    /// the claimed evaluation at [`SumcheckProof::challenges`].
+    pub fn verify(&self, sum: &L::LoadedScalar) -> Result<L::LoadedScalar, Error> {
+        let loader = sum.loader();
+        self.rounds.iter().zip(self.challenges.iter()).try_fold(
+            sum.clone(),
+            |sum, (evals, challenge)| {
+                loader.assert_eq("Sumcheck round", &(evals[0].clone() + &evals[1]), &sum)?;
+                Ok(interpolate(evals, challenge))
+            },
+        )
+    }
+}
+
+/// Evaluate the polynomial at `x` given its evaluations `evals` on
+/// `0..evals.len()` in Lagrange form.
+fn interpolate<F, T>(evals: &[T], x: &T) -> T
+where
+    F: PrimeField,
+    T: LoadedScalar<F>,
+{
+    let loader = x.loader();
+    let points = (0..evals.len() as u64).map(F::from).collect_vec();
+    let weights = points
+        .iter()
+        .enumerate()
+        .map(|(i, point_i)| {
+            let denom = points
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .fold(F::ONE, |denom, (_, point_j)| denom * (*point_i - point_j));
+            Field::invert(&denom).unwrap()
+        })
+        .collect_vec();
+    let x_minus_points = points
+        .iter()
+        .map(|point| loader.sum_with_const(&[x], -*point))
+        .collect_vec();
+    let products = {
+        let one = loader.load_one();
+        let prefixes = x_minus_points
+            .iter()
+            .scan(one.clone(), |product, x_minus_point| {
+                let prefix = product.clone();
+                *product *= x_minus_point;
+                Some(prefix)
+            })
+            .collect_vec();
+        let mut suffixes = x_minus_points
+            .iter()
+            .rev()
+            .scan(one, |product, x_minus_point| {
+                let suffix = product.clone();
+                *product *= x_minus_point;
+                Some(suffix)
+            })
+            .collect_vec();
+        suffixes.reverse();
+        prefixes
+            .into_iter()
+            .zip(suffixes)
+            .map(|(prefix, suffix)| prefix * suffix)
+            .collect_vec()
+    };
+    loader.sum_products_with_coeff(
+        &weights
+            .into_iter()
+            .zip(evals.iter())
+            .zip(products.iter())
+            .map(|((weight, eval), product)| (weight, eval, product))
+            .collect_vec(),
+    )
+}
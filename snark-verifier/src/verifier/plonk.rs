@@ -15,7 +15,7 @@ use crate::{
         AccumulationDecider, AccumulationScheme, AccumulatorEncoding, PolynomialCommitmentScheme,
         Query,
     },
-    util::{arithmetic::CurveAffine, transcript::TranscriptRead},
+    util::{arithmetic::CurveAffine, transcript::TranscriptRead, Itertools},
     verifier::{plonk::protocol::CommonPolynomialEvaluation, SnarkVerifier},
     Error,
 };
@@ -25,7 +25,7 @@ mod proof;
 pub(crate) mod protocol;
 
 pub use proof::PlonkProof;
-pub use protocol::PlonkProtocol;
+pub use protocol::{LinearizationStrategy, PlonkProtocol};
 
 /// Verifier that verifies the cheap part of PLONK and ouput the accumulator.
 #[derive(Debug)]
@@ -152,11 +152,44 @@ where
                 protocol.num_witness.iter().sum::<usize>() + protocol.quotient.num_chunk();
             let num_evaluation = protocol.evaluations.len();
             let num_msm = protocol.preprocessed.len() + num_commitment + 1 + 2 * num_accumulator;
+            let num_squeeze = protocol.num_challenge.iter().sum::<usize>() + 1;
             Cost {
                 num_instance,
                 num_commitment,
                 num_evaluation,
                 num_msm,
+                num_squeeze,
+                ..Default::default()
+            }
+        };
+        let common_poly_eval_cost = {
+            let num_lagrange = protocol.langranges().into_iter().sorted().dedup().count();
+            // Each denominator takes 3 multiplications in batch inversion and 1
+            // to evaluate the fraction.
+            let num_denom = num_lagrange + 1;
+            Cost {
+                num_scalar_add: 1 + num_lagrange,
+                num_scalar_mul: protocol.domain.k + 1 + num_lagrange + 4 * num_denom,
+                num_inversion: 1,
+                ..Default::default()
+            }
+        };
+        let quotient_cost = {
+            let num_instance_eval = protocol
+                .instance_committing_key
+                .is_none()
+                .then(|| protocol.num_instance.iter().sum::<usize>())
+                .unwrap_or_default();
+            let (num_add, num_mul) = protocol.quotient.numerator_dag().num_operation();
+            let num_chunk = protocol.quotient.num_chunk();
+            let num_chunk_degree_bit =
+                (usize::BITS - protocol.quotient.chunk_degree.leading_zeros()) as usize;
+            Cost {
+                num_scalar_add: num_instance_eval + num_add,
+                num_scalar_mul: num_instance_eval
+                    + num_mul
+                    + 2 * num_chunk_degree_bit
+                    + 2 * num_chunk,
                 ..Default::default()
             }
         };
@@ -164,7 +197,7 @@ where
             let queries = PlonkProof::<C, L, AS>::empty_queries(protocol);
             AS::estimate_cost(&queries)
         };
-        plonk_cost + pcs_cost
+        plonk_cost + common_poly_eval_cost + quotient_cost + pcs_cost
     }
 }
 
@@ -186,3 +219,260 @@ where
             }
     }
 }
+
+#[cfg(all(test, feature = "system_halo2"))]
+pub(crate) mod test {
+    use crate::{
+        pcs::kzg::{Gwc19, KzgAs, KzgDecidingKey},
+        util::{
+            arithmetic::{root_of_unity, Curve, Domain, Field, PrimeCurveAffine},
+            msm::multi_scalar_multiplication,
+            transcript::{Transcript, TranscriptWrite},
+            Itertools,
+        },
+        verifier::{
+            plonk::{
+                protocol::{Expression, Query, QuotientPolynomial},
+                LinearizationStrategy, PlonkProtocol, PlonkVerifier,
+            },
+            SnarkVerifier,
+        },
+    };
+    use halo2_curves::bn256::{Bn256, Fr, G1Affine, G2Affine};
+    use halo2_proofs::transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+    };
+    use rand::rngs::OsRng;
+    use std::iter;
+
+    type Verifier = PlonkVerifier<KzgAs<Bn256, Gwc19>>;
+
+    pub(crate) const K: usize = 3;
+    const N: usize = 1 << K;
+
+    pub(crate) fn rand_vec(n: usize) -> Vec<Fr> {
+        iter::repeat_with(|| Fr::random(OsRng)).take(n).collect()
+    }
+
+    fn evaluate(poly: &[Fr], x: Fr) -> Fr {
+        poly.iter()
+            .rev()
+            .fold(Fr::ZERO, |acc, coeff| acc * x + coeff)
+    }
+
+    pub(crate) fn interpolate(domain: &Domain<Fr>, evals: &[Fr]) -> Vec<Fr> {
+        (0..domain.n as u64)
+            .map(|i| {
+                let omega_inv_i = domain.gen_inv.pow_vartime([i]);
+                iter::successors(Some(Fr::ONE), |power| Some(omega_inv_i * power))
+                    .zip(evals)
+                    .map(|(power, eval)| power * eval)
+                    .sum::<Fr>()
+                    * domain.n_inv
+            })
+            .collect()
+    }
+
+    fn mul(lhs: &[Fr], rhs: &[Fr]) -> Vec<Fr> {
+        let mut output = vec![Fr::ZERO; lhs.len() + rhs.len() - 1];
+        for (i, lhs) in lhs.iter().enumerate() {
+            for (j, rhs) in rhs.iter().enumerate() {
+                output[i + j] += *lhs * rhs;
+            }
+        }
+        output
+    }
+
+    fn linear_combination(terms: &[(Fr, &[Fr])]) -> Vec<Fr> {
+        let mut output = vec![Fr::ZERO; terms.iter().map(|(_, poly)| poly.len()).max().unwrap()];
+        for (coeff, poly) in terms.iter() {
+            for (output, value) in output.iter_mut().zip(poly.iter()) {
+                *output += *coeff * value;
+            }
+        }
+        output
+    }
+
+    /// Returns `(poly - poly(x)) / (X - x)`.
+    fn div_linear(poly: &[Fr], x: Fr) -> Vec<Fr> {
+        let mut quotient = vec![Fr::ZERO; poly.len() - 1];
+        let mut acc = Fr::ZERO;
+        for (idx, coeff) in poly.iter().enumerate().skip(1).rev() {
+            acc = acc * x + coeff;
+            quotient[idx - 1] = acc;
+        }
+        quotient
+    }
+
+    /// Returns `poly / (X^n - 1)` with remainder dropped.
+    fn div_vanishing(poly: &[Fr]) -> Vec<Fr> {
+        let mut remainder = poly.to_vec();
+        let mut quotient = vec![Fr::ZERO; poly.len() - N];
+        for idx in (N..poly.len()).rev() {
+            quotient[idx - N] = remainder[idx];
+            remainder[idx - N] += remainder[idx];
+        }
+        quotient
+    }
+
+    pub(crate) fn setup() -> (Vec<G1Affine>, KzgDecidingKey<Bn256>) {
+        let s = Fr::random(OsRng);
+        let g1s = iter::successors(Some(Fr::ONE), |power| Some(s * power))
+            .take(N)
+            .map(|power| (G1Affine::generator() * power).to_affine())
+            .collect_vec();
+        let s_g2 = (G2Affine::generator() * s).to_affine();
+        let dk = (g1s[0], G2Affine::generator(), s_g2).into();
+        (g1s, dk)
+    }
+
+    pub(crate) fn commit(g1s: &[G1Affine], poly: &[Fr]) -> G1Affine {
+        multi_scalar_multiplication(poly, &g1s[..poly.len()]).to_affine()
+    }
+
+    /// Polynomials are `q`, `a`, `b` and `c`, where `q` selects the constraint
+    /// `a·b + 1 = c`. Since `a` and `c` are linearized, the constraint has a
+    /// constant term `q(z)` after linearization.
+    pub(crate) fn protocol(
+        q_commitment: G1Affine,
+        linearization: LinearizationStrategy,
+    ) -> PlonkProtocol<G1Affine> {
+        let [q_expr, a, b, c] =
+            [0, 1, 2, 3].map(|poly| Expression::<Fr>::from(Query::new(poly, 0)));
+        let numerator = &q_expr * &(&a * &b - &c + Expression::Constant(Fr::ONE));
+        let [q, b, quotient, linearization_poly] = [0, 2, 4, 5].map(|poly| Query::new(poly, 0));
+        let (evaluations, queries) = match linearization {
+            LinearizationStrategy::WithoutConstant => (
+                vec![q, b, linearization_poly],
+                vec![q, b, quotient, linearization_poly],
+            ),
+            LinearizationStrategy::MinusVanishingTimesQuotient => {
+                (vec![q, b], vec![q, b, quotient])
+            }
+        };
+        PlonkProtocol {
+            domain: Domain::new(K, root_of_unity(K)),
+            preprocessed: vec![q_commitment],
+            num_instance: Vec::new(),
+            num_witness: vec![3],
+            num_challenge: vec![0],
+            evaluations,
+            queries,
+            quotient: QuotientPolynomial::new(1, 2, numerator),
+            transcript_initial_state: None,
+            instance_committing_key: None,
+            linearization: Some(linearization),
+            accumulator_indices: Vec::new(),
+        }
+    }
+
+    /// Writes proof of `protocol` into `transcript` with given `b`, where the
+    /// constraint is broken if `valid` is `false`.
+    pub(crate) fn create_proof(
+        g1s: &[G1Affine],
+        protocol: &PlonkProtocol<G1Affine>,
+        q: &[Fr],
+        b: &[Fr],
+        valid: bool,
+        transcript: &mut impl TranscriptWrite<G1Affine>,
+    ) {
+        let domain = &protocol.domain;
+
+        let a = rand_vec(N);
+        let mut c = (0..N)
+            .map(|i| {
+                if q[i] == Fr::ONE {
+                    a[i] * b[i] + Fr::ONE
+                } else {
+                    Fr::random(OsRng)
+                }
+            })
+            .collect_vec();
+        if !valid {
+            c[0] += Fr::ONE;
+        }
+        let [q, a, b, c] = [q, &a, b, &c].map(|evals| interpolate(domain, evals));
+        for poly in [&a, &b, &c] {
+            transcript.write_ec_point(commit(g1s, poly)).unwrap();
+        }
+
+        let numerator = mul(
+            &q,
+            &linear_combination(&[
+                (Fr::ONE, &mul(&a, &b)[..]),
+                (-Fr::ONE, &c[..]),
+                (Fr::ONE, &[Fr::ONE][..]),
+            ]),
+        );
+        let quotient = div_vanishing(&numerator);
+        for chunk in quotient.chunks(N) {
+            transcript.write_ec_point(commit(g1s, chunk)).unwrap();
+        }
+
+        let z = transcript.squeeze_challenge();
+        let zn = z.pow_vartime([N as u64]);
+        let quotient = linear_combination(&[(Fr::ONE, &quotient[..N]), (zn, &quotient[N..])]);
+        let [q_eval, b_eval] = [&q, &b].map(|poly| evaluate(poly, z));
+        let linearization_poly =
+            linear_combination(&[(q_eval * b_eval, &a[..]), (-q_eval, &c[..])]);
+        let polys = match protocol.linearization.unwrap() {
+            LinearizationStrategy::WithoutConstant => {
+                for eval in [q_eval, b_eval, evaluate(&linearization_poly, z)] {
+                    transcript.write_scalar(eval).unwrap();
+                }
+                vec![q, b, quotient, linearization_poly]
+            }
+            LinearizationStrategy::MinusVanishingTimesQuotient => {
+                for eval in [q_eval, b_eval] {
+                    transcript.write_scalar(eval).unwrap();
+                }
+                let minus_vanishing_times_quotient = linear_combination(&[
+                    (Fr::ONE, &linearization_poly[..]),
+                    (Fr::ONE - zn, &quotient[..]),
+                ]);
+                vec![q, b, minus_vanishing_times_quotient]
+            }
+        };
+
+        // All queries are on `z`, so there is only one `W` for `Gwc19`.
+        let v = transcript.squeeze_challenge();
+        let combined = linear_combination(
+            &iter::successors(Some(Fr::ONE), |power| Some(v * power))
+                .zip(polys.iter().map(Vec::as_slice))
+                .collect_vec(),
+        );
+        transcript
+            .write_ec_point(commit(g1s, &div_linear(&combined, z)))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_linearization() {
+        let (g1s, dk) = setup();
+        let domain = Domain::new(K, root_of_unity(K));
+        let q = (0..N)
+            .map(|i| if i % 2 == 0 { Fr::ONE } else { Fr::ZERO })
+            .collect_vec();
+        let q_commitment = commit(&g1s, &interpolate(&domain, &q));
+
+        let verify = |protocol: &PlonkProtocol<G1Affine>, proof: Vec<u8>| {
+            let mut transcript =
+                Blake2bRead::<_, G1Affine, Challenge255<_>>::init(proof.as_slice());
+            let proof = Verifier::read_proof(&dk, protocol, &[], &mut transcript)?;
+            Verifier::verify(&dk, protocol, &[], &proof)
+        };
+        let prove = |protocol: &PlonkProtocol<G1Affine>, valid: bool| {
+            let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(Vec::new());
+            create_proof(&g1s, protocol, &q, &rand_vec(N), valid, &mut transcript);
+            transcript.finalize()
+        };
+        for linearization in [
+            LinearizationStrategy::WithoutConstant,
+            LinearizationStrategy::MinusVanishingTimesQuotient,
+        ] {
+            let protocol = protocol(q_commitment, linearization);
+            assert!(verify(&protocol, prove(&protocol, true)).is_ok());
+            assert!(verify(&protocol, prove(&protocol, false)).is_err());
+        }
+    }
+}
@@ -147,11 +147,12 @@ where
             .accumulator_indices
             .iter()
             .map(|accumulator_indices| {
-                AE::from_repr(
+                AE::read_repr(
                     &accumulator_indices
                         .iter()
                         .map(|&(i, j)| &instances[i][j])
                         .collect_vec(),
+                    transcript,
                 )
             })
             .collect::<Result<Vec<_>, _>>()?;
@@ -222,7 +223,7 @@ where
             .chain(self.witnesses.iter().map(Msm::base))
             .collect_vec();
 
-        let numerator = protocol.quotient.numerator.evaluate(
+        let numerator = protocol.quotient.numerator_dag().evaluate(
             &|scalar| Ok(Msm::constant(loader.load_const(&scalar))),
             &|poly| Ok(Msm::constant(common_poly_eval.get(poly).clone())),
             &|query| {
@@ -288,7 +289,7 @@ where
                 commitments.push(msm);
                 evaluations.insert(
                     quotient_query,
-                    constant.unwrap_or_else(|| loader.load_zero()),
+                    -constant.unwrap_or_else(|| loader.load_zero()),
                 );
             }
             None => {
@@ -8,7 +8,7 @@ use crate::{
 use num_traits::One;
 use std::{
     cmp::max,
-    collections::{BTreeMap, BTreeSet},
+    collections::{BTreeMap, BTreeSet, HashMap},
     fmt::Debug,
     iter::{self, Sum},
     ops::{Add, Mul, Neg, Sub},
@@ -188,7 +188,7 @@ mod halo2 {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "derive_serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CommonPolynomial {
     Identity,
@@ -291,12 +291,32 @@ pub struct QuotientPolynomial<F: Clone> {
     // calculation of the constraint system (e.g. halo2 has minimum degree 3).
     pub num_chunk: usize,
     pub numerator: Expression<F>,
+    numerator_dag: ExpressionDag<F>,
+}
+
+impl<F: Field> QuotientPolynomial<F> {
+    /// Returns [`QuotientPolynomial`] with [`ExpressionDag`] of `numerator`
+    /// built once, so it's not rebuilt for every proof to verify.
+    pub fn new(chunk_degree: usize, num_chunk: usize, numerator: Expression<F>) -> Self {
+        let numerator_dag = ExpressionDag::new(&numerator);
+        Self {
+            chunk_degree,
+            num_chunk,
+            numerator,
+            numerator_dag,
+        }
+    }
 }
 
 impl<F: Clone> QuotientPolynomial<F> {
     pub fn num_chunk(&self) -> usize {
         self.num_chunk
     }
+
+    /// Returns [`ExpressionDag`] of `numerator`.
+    pub fn numerator_dag(&self) -> &ExpressionDag<F> {
+        &self.numerator_dag
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -410,6 +430,21 @@ impl<F: Clone> Expression<F> {
         }
     }
 
+    /// Returns degree in given `queries`, where other queries, challenges and
+    /// common polynomials are considered as constant.
+    pub fn degree_in(&self, queries: &BTreeSet<Query>) -> usize {
+        self.evaluate(
+            &|_| 0,
+            &|_| 0,
+            &|query| queries.contains(&query) as usize,
+            &|_| 0,
+            &|a| a,
+            &|a, b| max(a, b),
+            &|a, b| a + b,
+            &|a, _| a,
+        )
+    }
+
     pub fn used_langrange(&self) -> BTreeSet<i32> {
         self.evaluate(
             &|_| None,
@@ -442,6 +477,77 @@ impl<F: Clone> Expression<F> {
     }
 }
 
+impl<F: Field> Expression<F> {
+    /// Returns an equivalent expression with constants folded, trivial
+    /// operations removed and common scalings factored out.
+    pub fn simplified(&self) -> Self {
+        match self {
+            Expression::Constant(_)
+            | Expression::CommonPolynomial(_)
+            | Expression::Polynomial(_)
+            | Expression::Challenge(_) => self.clone(),
+            Expression::Negated(a) => Self::scale(a.simplified(), -F::ONE),
+            Expression::Sum(a, b) => match (a.simplified(), b.simplified()) {
+                (Expression::Constant(a), Expression::Constant(b)) => Expression::Constant(a + b),
+                (Expression::Constant(zero), a) | (a, Expression::Constant(zero))
+                    if zero == F::ZERO =>
+                {
+                    a
+                }
+                (Expression::Scaled(a, lhs), Expression::Scaled(b, rhs)) if lhs == rhs => {
+                    Expression::Scaled(Expression::Sum(a, b).into(), lhs)
+                }
+                (Expression::Negated(a), Expression::Negated(b)) => {
+                    Expression::Negated(Expression::Sum(a, b).into())
+                }
+                (a, b) => Expression::Sum(a.into(), b.into()),
+            },
+            Expression::Product(a, b) => match (a.simplified(), b.simplified()) {
+                (Expression::Constant(a), Expression::Constant(b)) => Expression::Constant(a * b),
+                (Expression::Constant(scalar), a) | (a, Expression::Constant(scalar)) => {
+                    Self::scale(a, scalar)
+                }
+                (a, b) => {
+                    let ((a, lhs), (b, rhs)) = (Self::unscale(a), Self::unscale(b));
+                    Self::scale(Expression::Product(a.into(), b.into()), lhs * rhs)
+                }
+            },
+            Expression::Scaled(a, scalar) => Self::scale(a.simplified(), *scalar),
+            Expression::DistributePowers(exprs, scalar) => {
+                let mut exprs = exprs.iter().map(Self::simplified).collect_vec();
+                if exprs.len() == 1 {
+                    return exprs.pop().unwrap();
+                }
+                Expression::DistributePowers(exprs, scalar.simplified().into())
+            }
+        }
+    }
+
+    fn scale(expression: Self, scalar: F) -> Self {
+        if scalar == F::ZERO {
+            return Expression::Constant(F::ZERO);
+        }
+        if scalar == F::ONE {
+            return expression;
+        }
+        match expression {
+            Expression::Constant(constant) => Expression::Constant(constant * scalar),
+            Expression::Negated(a) => Self::scale(*a, -scalar),
+            Expression::Scaled(a, inner) => Self::scale(*a, inner * scalar),
+            a if scalar == -F::ONE => Expression::Negated(a.into()),
+            a => Expression::Scaled(a.into(), scalar),
+        }
+    }
+
+    fn unscale(expression: Self) -> (Self, F) {
+        match expression {
+            Expression::Negated(a) => (*a, -F::ONE),
+            Expression::Scaled(a, scalar) => (*a, scalar),
+            a => (a, F::ONE),
+        }
+    }
+}
+
 impl<F: Clone> From<Query> for Expression<F> {
     fn from(query: Query) -> Self {
         Self::Polynomial(query)
@@ -515,6 +621,148 @@ impl<F: Field> One for Expression<F> {
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "derive_serde", derive(serde::Serialize, serde::Deserialize))]
+enum Node {
+    Constant(usize),
+    CommonPolynomial(CommonPolynomial),
+    Polynomial(Query),
+    Challenge(usize),
+    Negated(usize),
+    Sum(usize, usize),
+    Product(usize, usize),
+    Scaled(usize, usize),
+}
+
+/// [`Expression`] simplified by [`Expression::simplified`] and hash-consed
+/// into a DAG, so each common sub-expression is only evaluated once.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "derive_serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExpressionDag<F> {
+    constants: Vec<F>,
+    nodes: Vec<Node>,
+    root: usize,
+}
+
+impl<F: Field> ExpressionDag<F> {
+    /// Build [`ExpressionDag`] from an [`Expression`].
+    pub fn new(expression: &Expression<F>) -> Self {
+        let mut builder = ExpressionDagBuilder::default();
+        let root = builder.insert(&expression.simplified());
+        Self {
+            constants: builder.constants,
+            nodes: builder.nodes,
+            root,
+        }
+    }
+
+    /// Returns number of distinct nodes.
+    pub fn num_node(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns number of additions (including negations) and multiplications
+    /// (including scalings) to evaluate the DAG.
+    pub fn num_operation(&self) -> (usize, usize) {
+        self.nodes
+            .iter()
+            .fold((0, 0), |(num_add, num_mul), node| match node {
+                Node::Negated(_) | Node::Sum(_, _) => (num_add + 1, num_mul),
+                Node::Product(_, _) | Node::Scaled(_, _) => (num_add, num_mul + 1),
+                _ => (num_add, num_mul),
+            })
+    }
+
+    /// Evaluate the DAG with the same semantic of [`Expression::evaluate`],
+    /// but each node is evaluated only once.
+    pub fn evaluate<T: Clone>(
+        &self,
+        constant: &impl Fn(F) -> T,
+        common_poly: &impl Fn(CommonPolynomial) -> T,
+        poly: &impl Fn(Query) -> T,
+        challenge: &impl Fn(usize) -> T,
+        negated: &impl Fn(T) -> T,
+        sum: &impl Fn(T, T) -> T,
+        product: &impl Fn(T, T) -> T,
+        scaled: &impl Fn(T, F) -> T,
+    ) -> T {
+        let mut values: Vec<T> = Vec::with_capacity(self.nodes.len());
+        for node in self.nodes.iter() {
+            let value = match node {
+                Node::Constant(idx) => constant(self.constants[*idx]),
+                Node::CommonPolynomial(poly) => common_poly(*poly),
+                Node::Polynomial(query) => poly(*query),
+                Node::Challenge(idx) => challenge(*idx),
+                Node::Negated(a) => negated(values[*a].clone()),
+                Node::Sum(a, b) => sum(values[*a].clone(), values[*b].clone()),
+                Node::Product(a, b) => product(values[*a].clone(), values[*b].clone()),
+                Node::Scaled(a, idx) => scaled(values[*a].clone(), self.constants[*idx]),
+            };
+            values.push(value);
+        }
+        values.swap_remove(self.root)
+    }
+}
+
+#[derive(Default)]
+struct ExpressionDagBuilder<F> {
+    constants: Vec<F>,
+    nodes: Vec<Node>,
+    indices: HashMap<Node, usize>,
+}
+
+impl<F: Field> ExpressionDagBuilder<F> {
+    fn constant(&mut self, constant: F) -> usize {
+        match self.constants.iter().position(|exist| *exist == constant) {
+            Some(idx) => idx,
+            None => {
+                self.constants.push(constant);
+                self.constants.len() - 1
+            }
+        }
+    }
+
+    fn node(&mut self, node: Node) -> usize {
+        // Sum and product are commutative, so operands are sorted to share more.
+        let node = match node {
+            Node::Sum(a, b) if a > b => Node::Sum(b, a),
+            Node::Product(a, b) if a > b => Node::Product(b, a),
+            node => node,
+        };
+        if let Some(idx) = self.indices.get(&node) {
+            return *idx;
+        }
+        self.nodes.push(node.clone());
+        self.indices.insert(node, self.nodes.len() - 1);
+        self.nodes.len() - 1
+    }
+
+    fn insert(&mut self, expression: &Expression<F>) -> usize {
+        let node = match expression {
+            Expression::Constant(constant) => Node::Constant(self.constant(*constant)),
+            Expression::CommonPolynomial(poly) => Node::CommonPolynomial(*poly),
+            Expression::Polynomial(query) => Node::Polynomial(*query),
+            Expression::Challenge(idx) => Node::Challenge(*idx),
+            Expression::Negated(a) => Node::Negated(self.insert(a)),
+            Expression::Sum(a, b) => Node::Sum(self.insert(a), self.insert(b)),
+            Expression::Product(a, b) => Node::Product(self.insert(a), self.insert(b)),
+            Expression::Scaled(a, scalar) => Node::Scaled(self.insert(a), self.constant(*scalar)),
+            Expression::DistributePowers(exprs, scalar) => {
+                assert!(!exprs.is_empty());
+                let scalar = self.insert(scalar);
+                let mut exprs = exprs.iter();
+                let first = self.insert(exprs.next().unwrap());
+                return exprs.fold(first, |acc, expr| {
+                    let acc = self.node(Node::Product(acc, scalar));
+                    let expr = self.insert(expr);
+                    self.node(Node::Sum(acc, expr))
+                });
+            }
+        };
+        self.node(node)
+    }
+}
+
 fn merge_left_right<T: Ord>(a: Option<BTreeSet<T>>, b: Option<BTreeSet<T>>) -> Option<BTreeSet<T>> {
     match (a, b) {
         (Some(a), None) | (None, Some(a)) => Some(a),
@@ -545,3 +793,172 @@ pub struct InstanceCommittingKey<C> {
     pub bases: Vec<C>,
     pub constant: Option<C>,
 }
+
+#[cfg(all(test, feature = "system_halo2"))]
+mod test {
+    use crate::{
+        system::halo2::test::{
+            kzg::{
+                halo2_kzg_config, halo2_kzg_prepare, main_gate_with_range_with_mock_kzg_accumulator,
+            },
+            StandardPlonk,
+        },
+        util::arithmetic::Field,
+        verifier::plonk::protocol::{CommonPolynomial, Expression, ExpressionDag, Query},
+    };
+    use halo2_curves::bn256::{Bn256, Fr};
+    use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+
+    fn numerators() -> Vec<Expression<Fr>> {
+        let (_, _, standard_plonk, _) = halo2_kzg_prepare!(
+            9,
+            halo2_kzg_config!(true, 1),
+            StandardPlonk::rand(ChaCha20Rng::from_seed(Default::default()))
+        );
+        let (_, _, main_gate_with_range, _) = halo2_kzg_prepare!(
+            9,
+            halo2_kzg_config!(true, 2),
+            main_gate_with_range_with_mock_kzg_accumulator::<Bn256>()
+        );
+        vec![
+            standard_plonk.quotient.numerator,
+            main_gate_with_range.quotient.numerator,
+        ]
+    }
+
+    fn num_node(expression: &Expression<Fr>) -> usize {
+        expression.evaluate(
+            &|_| 1,
+            &|_| 1,
+            &|_| 1,
+            &|_| 1,
+            &|a| a + 1,
+            &|a, b| a + b + 1,
+            &|a, b| a + b + 1,
+            &|a, _| a + 1,
+        )
+    }
+
+    #[test]
+    fn test_expression_dag() {
+        let mut rng = ChaCha20Rng::from_seed(Default::default());
+        let [identity, lagrange_seed, query_seed, challenge_seed] =
+            [(); 4].map(|_| Fr::random(&mut rng));
+        let hash = |seed: Fr, idx: u64| seed * Fr::from(idx + 1).square();
+
+        let constant = |scalar| scalar;
+        let common_poly = |poly| match poly {
+            CommonPolynomial::Identity => identity,
+            CommonPolynomial::Lagrange(i) => hash(lagrange_seed, i as u64),
+        };
+        let poly = |query: Query| {
+            hash(
+                query_seed,
+                ((query.poly as u64) << 8) | query.rotation.0 as u8 as u64,
+            )
+        };
+        let challenge = |idx: usize| hash(challenge_seed, idx as u64);
+
+        for numerator in numerators() {
+            let evaluate_naive = numerator.evaluate(
+                &constant,
+                &common_poly,
+                &poly,
+                &challenge,
+                &|a| -a,
+                &|a, b| a + b,
+                &|a, b| a * b,
+                &|a, scalar| a * scalar,
+            );
+            let dag = ExpressionDag::new(&numerator);
+            let evaluate_dag = dag.evaluate(
+                &constant,
+                &common_poly,
+                &poly,
+                &challenge,
+                &|a| -a,
+                &|a, b| a + b,
+                &|a, b| a * b,
+                &|a, scalar| a * scalar,
+            );
+
+            assert_eq!(evaluate_naive, evaluate_dag);
+            assert!(dag.num_node() < num_node(&numerator));
+        }
+    }
+
+    #[cfg(feature = "loader_evm")]
+    #[test]
+    fn test_expression_dag_evm_gas() {
+        use crate::{
+            loader::evm::{compile_yul, deploy_and_call, EvmLoader},
+            util::Itertools,
+        };
+        use halo2_curves::bn256::Fq;
+        use std::collections::BTreeMap;
+
+        for numerator in numerators() {
+            let queries = numerator.used_query().into_iter().collect_vec();
+            let num_challenge = numerator.evaluate(
+                &|_| 0,
+                &|_| 0,
+                &|_| 0,
+                &|idx| idx + 1,
+                &|a| a,
+                &|a, b| a.max(b),
+                &|a, b| a.max(b),
+                &|a, _| a,
+            );
+            let lagranges = numerator.used_langrange().into_iter().collect_vec();
+            let num_scalar = 1 + lagranges.len() + queries.len() + num_challenge;
+            let calldata = vec![1; 0x20 * num_scalar];
+
+            let [naive, dag] = [false, true].map(|use_dag| {
+                let loader = EvmLoader::new::<Fq, Fr>();
+                let mut scalars = (0..num_scalar).map(|idx| loader.calldataload_scalar(0x20 * idx));
+                let identity = scalars.next().unwrap();
+                let lagranges: BTreeMap<_, _> =
+                    lagranges.iter().copied().zip(&mut scalars).collect();
+                let queries: BTreeMap<_, _> = queries.iter().copied().zip(&mut scalars).collect();
+                let challenges = scalars.collect_vec();
+
+                let constant = |scalar: Fr| loader.load_const(&scalar);
+                let common_poly = |poly| match poly {
+                    CommonPolynomial::Identity => identity.clone(),
+                    CommonPolynomial::Lagrange(i) => lagranges[&i].clone(),
+                };
+                let poly = |query| queries[&query].clone();
+                let challenge = |idx: usize| challenges[idx].clone();
+                let scaled = |a, scalar: Fr| a * loader.load_const(&scalar);
+                if use_dag {
+                    ExpressionDag::new(&numerator).evaluate(
+                        &constant,
+                        &common_poly,
+                        &poly,
+                        &challenge,
+                        &|a| -a,
+                        &|a, b| a + b,
+                        &|a, b| a * b,
+                        &scaled,
+                    );
+                } else {
+                    numerator.evaluate(
+                        &constant,
+                        &common_poly,
+                        &poly,
+                        &challenge,
+                        &|a| -a,
+                        &|a, b| a + b,
+                        &|a, b| a * b,
+                        &scaled,
+                    );
+                }
+
+                let deployment_code = compile_yul(&loader.yul_code());
+                deploy_and_call(deployment_code, calldata.clone()).unwrap()
+            });
+
+            assert!(dag < naive);
+        }
+    }
+}